@@ -0,0 +1,83 @@
+//! Conversiones entre los tipos de `darwinx-core` y los mensajes proto
+
+use crate::common;
+use darwinx_core::types::timeframe::TimeFrameError;
+
+impl From<darwinx_core::TimeUnit> for common::TimeUnit {
+    fn from(unit: darwinx_core::TimeUnit) -> Self {
+        match unit {
+            darwinx_core::TimeUnit::Minute => common::TimeUnit::Minute,
+            darwinx_core::TimeUnit::Hour => common::TimeUnit::Hour,
+            darwinx_core::TimeUnit::Day => common::TimeUnit::Day,
+            darwinx_core::TimeUnit::Week => common::TimeUnit::Week,
+            darwinx_core::TimeUnit::Month => common::TimeUnit::Month,
+            // Unidades añadidas en core sin equivalente en el proto
+            _ => common::TimeUnit::Unspecified,
+        }
+    }
+}
+
+impl TryFrom<common::TimeUnit> for darwinx_core::TimeUnit {
+    type Error = TimeFrameError;
+
+    fn try_from(unit: common::TimeUnit) -> Result<Self, Self::Error> {
+        match unit {
+            common::TimeUnit::Minute => Ok(darwinx_core::TimeUnit::Minute),
+            common::TimeUnit::Hour => Ok(darwinx_core::TimeUnit::Hour),
+            common::TimeUnit::Day => Ok(darwinx_core::TimeUnit::Day),
+            common::TimeUnit::Week => Ok(darwinx_core::TimeUnit::Week),
+            common::TimeUnit::Month => Ok(darwinx_core::TimeUnit::Month),
+            common::TimeUnit::Unspecified => Err(TimeFrameError::Invalid(unit.as_str_name().to_string())),
+        }
+    }
+}
+
+impl From<darwinx_core::TimeFrame> for common::TimeFrame {
+    fn from(timeframe: darwinx_core::TimeFrame) -> Self {
+        Self {
+            value: timeframe.to_string(),
+            count: timeframe.count(),
+            unit: common::TimeUnit::from(timeframe.unit()) as i32,
+        }
+    }
+}
+
+impl TryFrom<common::TimeFrame> for darwinx_core::TimeFrame {
+    type Error = TimeFrameError;
+
+    /// Usa `count` y `unit`; si la unidad no está especificada, parsea `value`
+    fn try_from(timeframe: common::TimeFrame) -> Result<Self, Self::Error> {
+        match common::TimeUnit::try_from(timeframe.unit) {
+            Ok(common::TimeUnit::Unspecified) | Err(_) => timeframe.value.parse(),
+            Ok(unit) => darwinx_core::TimeFrame::new(timeframe.count, unit.try_into()?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use darwinx_core::{TimeFrame, TimeUnit};
+
+    #[test]
+    fn test_timeframe_round_trip() {
+        let mut timeframes = TimeFrame::all();
+        timeframes.push(TimeFrame::new(6, TimeUnit::Hour).unwrap());
+        timeframes.push(TimeFrame::new(3, TimeUnit::Month).unwrap());
+
+        for timeframe in timeframes {
+            let proto = common::TimeFrame::from(timeframe);
+            assert_eq!(proto.value, timeframe.to_string());
+            assert_eq!(TimeFrame::try_from(proto).unwrap(), timeframe);
+        }
+    }
+
+    #[test]
+    fn test_timeframe_from_proto_value() {
+        let proto = common::TimeFrame { value: "5m".to_string(), count: 0, unit: 0 };
+        assert_eq!(TimeFrame::try_from(proto).unwrap(), TimeFrame::M5);
+
+        let invalid = common::TimeFrame { value: String::new(), count: 0, unit: common::TimeUnit::Hour as i32 };
+        assert!(TimeFrame::try_from(invalid).is_err());
+    }
+}
@@ -44,6 +44,14 @@ pub struct Trade {
     pub quantity: f64,
     #[prost(double, tag = "7")]
     pub pnl: f64,
+    /// Razón de salida: "Signal", "StopLoss", "TakeProfit", "PartialTakeProfit", "TrailingStop",
+    /// "BreakEven", "ChandelierExit", "MaxBars", "SessionClose", "Rebalance", "Liquidation"
+    /// o "End of data"
+    #[prost(string, tag = "8")]
+    pub exit_reason: ::prost::alloc::string::String,
+    /// Posición a la que pertenece el trade (lotes de pyramiding y cierres parciales)
+    #[prost(uint64, tag = "9")]
+    pub position_id: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BacktestResult {
@@ -15,12 +15,17 @@ pub struct Candle {
     #[prost(double, tag = "6")]
     pub volume: f64,
 }
-/// TimeFrame
+/// TimeFrame: N unidades de tiempo (ej: 3m, 6h, 3d, 2w, 1mo)
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TimeFrame {
-    /// "1m", "5m", "1h", etc
+    /// "1m", "5m", "1h", "6h", "1mo", etc
     #[prost(string, tag = "1")]
     pub value: ::prost::alloc::string::String,
+    /// Número de unidades (ej: 6 en "6h")
+    #[prost(uint32, tag = "2")]
+    pub count: u32,
+    #[prost(enumeration = "TimeUnit", tag = "3")]
+    pub unit: i32,
 }
 /// Signal
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
@@ -88,3 +93,41 @@ pub struct Order {
     #[prost(int64, tag = "6")]
     pub timestamp: i64,
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TimeUnit {
+    Unspecified = 0,
+    Minute = 1,
+    Hour = 2,
+    Day = 3,
+    Week = 4,
+    Month = 5,
+}
+impl TimeUnit {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "TIME_UNIT_UNSPECIFIED",
+            Self::Minute => "TIME_UNIT_MINUTE",
+            Self::Hour => "TIME_UNIT_HOUR",
+            Self::Day => "TIME_UNIT_DAY",
+            Self::Week => "TIME_UNIT_WEEK",
+            Self::Month => "TIME_UNIT_MONTH",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "TIME_UNIT_UNSPECIFIED" => Some(Self::Unspecified),
+            "TIME_UNIT_MINUTE" => Some(Self::Minute),
+            "TIME_UNIT_HOUR" => Some(Self::Hour),
+            "TIME_UNIT_DAY" => Some(Self::Day),
+            "TIME_UNIT_WEEK" => Some(Self::Week),
+            "TIME_UNIT_MONTH" => Some(Self::Month),
+            _ => None,
+        }
+    }
+}
@@ -25,6 +25,9 @@ pub mod optimizer;
 #[path = "generated/darwinx.live.rs"]
 pub mod live;
 
+// Conversiones con los tipos de darwinx-core
+mod conversions;
+
 // Re-exports de tipos comunes para facilitar el uso
 pub use common::*;
 
@@ -1,6 +1,12 @@
 //! Configuración del Backtest Engine
 
+use std::sync::Arc;
+use darwinx_core::BarType;
 use serde::{Deserialize, Serialize};
+use crate::costs::CostModel;
+use crate::margin::MarginConfig;
+use crate::positions::{CloseOrder, PyramidingConfig, ScaleOutLevel};
+use crate::sizing::PositionSizing;
 
 /// Configuración para ejecutar un backtest
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,7 +17,7 @@ pub struct BacktestConfig {
     pub commission_rate: f64,
     /// Slippage en basis points (ej: 5 = 0.05%)
     pub slippage_bps: f64,
-    /// Máximo número de posiciones simultáneas
+    /// Máximo número de posiciones simultáneas (el motor event-driven solo admite 1)
     pub max_positions: usize,
     /// Riesgo por trade como porcentaje del balance (ej: 0.02 = 2%)
     pub risk_per_trade: f64,
@@ -21,6 +27,77 @@ pub struct BacktestConfig {
     pub take_profit_percent: Option<f64>,
     /// Porcentaje del balance a usar por posición (ej: 0.5 = 50%, 0.95 = 95%)
     pub position_size_percent: f64,
+    /// Costo diario de préstamo/funding de posiciones cortas sobre su valor (ej: 0.0001 = 0.01%/día)
+    #[serde(default)]
+    pub short_borrow_rate: f64,
+    /// Modelo de tamaño de posición (por defecto fracción fija de `position_size_percent`)
+    #[serde(default)]
+    pub position_sizing: PositionSizing,
+    /// Trailing stop como porcentaje desde el extremo favorable desde la entrada (None = deshabilitado)
+    #[serde(default)]
+    pub trailing_stop_percent: Option<f64>,
+    /// Trailing stop a N ATRs del cierre, que solo se acerca al precio (None = deshabilitado)
+    #[serde(default)]
+    pub trailing_stop_atr_multiple: Option<f64>,
+    /// Beneficio (como porcentaje de la entrada) que mueve el stop al precio de entrada (None = deshabilitado)
+    #[serde(default)]
+    pub break_even_trigger_percent: Option<f64>,
+    /// Chandelier exit: N ATRs desde el máximo/mínimo de las últimas `chandelier_period` velas (None = deshabilitado)
+    #[serde(default)]
+    pub chandelier_atr_multiple: Option<f64>,
+    /// Velas del máximo/mínimo del chandelier exit
+    #[serde(default = "default_chandelier_period")]
+    pub chandelier_period: usize,
+    /// Periodo del ATR de los stops basados en ATR
+    #[serde(default = "default_exit_atr_period")]
+    pub exit_atr_period: usize,
+    /// Máximo de velas en un trade; se cierra al cierre de la vela N tras la entrada (None = sin límite)
+    #[serde(default)]
+    pub max_bars_in_trade: Option<usize>,
+    /// Cierre de sesión en minutos desde medianoche UTC (ej: 1260 = 21:00); las posiciones
+    /// se cierran en la última vela antes del cierre y no se abren en ella (None = deshabilitado)
+    #[serde(default)]
+    pub session_close_minute: Option<u32>,
+    /// Añadidos a una posición abierta cuando se repite la señal de entrada (solo motor masivo)
+    #[serde(default)]
+    pub pyramiding: PyramidingConfig,
+    /// Tomas de beneficio parciales, en orden de beneficio creciente (solo motor masivo)
+    #[serde(default)]
+    pub scale_out: Vec<ScaleOutLevel>,
+    /// Orden en que los cierres parciales toman los lotes de una posición
+    #[serde(default)]
+    pub close_order: CloseOrder,
+    /// Modelo de comisiones, slippage y funding (por defecto `commission_rate` y `slippage_bps`)
+    #[serde(default)]
+    pub cost_model: CostModel,
+    /// Apalancamiento, margen y liquidaciones (None = sin apalancamiento ni liquidaciones)
+    ///
+    /// Solo lo simula el motor masivo; el event-driven rechaza la configuración.
+    #[serde(default)]
+    pub margin: Option<MarginConfig>,
+    /// Tipo de barra de las velas (informativo; se registra en los metadatos del resultado)
+    #[serde(default)]
+    pub bar_type: Option<BarType>,
+    /// Timestamp desde el que se opera (None = desde la primera vela)
+    ///
+    /// Las velas anteriores solo calientan los indicadores: no abren posiciones
+    /// ni entran en la curva de equity, las métricas ni los metadatos.
+    #[serde(default)]
+    pub trading_start: Option<i64>,
+    /// Timestamps ordenados de las velas sintéticas (generadas al reparar huecos)
+    ///
+    /// En ellas no se abren posiciones. No se serializa: el número de velas
+    /// sintéticas se registra en el informe de calidad de datos.
+    #[serde(skip)]
+    pub synthetic_bars: Option<Arc<[i64]>>,
+}
+
+fn default_chandelier_period() -> usize {
+    22
+}
+
+fn default_exit_atr_period() -> usize {
+    14
 }
 
 impl Default for BacktestConfig {
@@ -34,6 +111,24 @@ impl Default for BacktestConfig {
             stop_loss_percent: None, // Deshabilitado por defecto
             take_profit_percent: None, // Deshabilitado por defecto
             position_size_percent: 0.5, // 50% del balance por defecto
+            short_borrow_rate: 0.0,     // Sin costo de préstamo por defecto
+            position_sizing: PositionSizing::default(),
+            trailing_stop_percent: None,
+            trailing_stop_atr_multiple: None,
+            break_even_trigger_percent: None,
+            chandelier_atr_multiple: None,
+            chandelier_period: default_chandelier_period(),
+            exit_atr_period: default_exit_atr_period(),
+            max_bars_in_trade: None,
+            session_close_minute: None,
+            pyramiding: PyramidingConfig::default(),
+            scale_out: Vec::new(),
+            close_order: CloseOrder::default(),
+            cost_model: CostModel::default(),
+            margin: None,
+            bar_type: None,
+            trading_start: None,
+            synthetic_bars: None,
         }
     }
 }
@@ -56,6 +151,24 @@ impl BacktestConfig {
             stop_loss_percent: None,
             take_profit_percent: None,
             position_size_percent: 0.5,
+            short_borrow_rate: 0.0,
+            position_sizing: PositionSizing::default(),
+            trailing_stop_percent: None,
+            trailing_stop_atr_multiple: None,
+            break_even_trigger_percent: None,
+            chandelier_atr_multiple: None,
+            chandelier_period: default_chandelier_period(),
+            exit_atr_period: default_exit_atr_period(),
+            max_bars_in_trade: None,
+            session_close_minute: None,
+            pyramiding: PyramidingConfig::default(),
+            scale_out: Vec::new(),
+            close_order: CloseOrder::default(),
+            cost_model: CostModel::default(),
+            margin: None,
+            bar_type: None,
+            trading_start: None,
+            synthetic_bars: None,
         }
     }
 
@@ -78,6 +191,24 @@ impl BacktestConfig {
             stop_loss_percent,
             take_profit_percent,
             position_size_percent: 0.5,
+            short_borrow_rate: 0.0,
+            position_sizing: PositionSizing::default(),
+            trailing_stop_percent: None,
+            trailing_stop_atr_multiple: None,
+            break_even_trigger_percent: None,
+            chandelier_atr_multiple: None,
+            chandelier_period: default_chandelier_period(),
+            exit_atr_period: default_exit_atr_period(),
+            max_bars_in_trade: None,
+            session_close_minute: None,
+            pyramiding: PyramidingConfig::default(),
+            scale_out: Vec::new(),
+            close_order: CloseOrder::default(),
+            cost_model: CostModel::default(),
+            margin: None,
+            bar_type: None,
+            trading_start: None,
+            synthetic_bars: None,
         }
     }
 
@@ -101,9 +232,142 @@ impl BacktestConfig {
             stop_loss_percent,
             take_profit_percent,
             position_size_percent,
+            short_borrow_rate: 0.0,
+            position_sizing: PositionSizing::default(),
+            trailing_stop_percent: None,
+            trailing_stop_atr_multiple: None,
+            break_even_trigger_percent: None,
+            chandelier_atr_multiple: None,
+            chandelier_period: default_chandelier_period(),
+            exit_atr_period: default_exit_atr_period(),
+            max_bars_in_trade: None,
+            session_close_minute: None,
+            pyramiding: PyramidingConfig::default(),
+            scale_out: Vec::new(),
+            close_order: CloseOrder::default(),
+            cost_model: CostModel::default(),
+            margin: None,
+            bar_type: None,
+            trading_start: None,
+            synthetic_bars: None,
         }
     }
 
+    /// Define el costo diario de préstamo/funding para posiciones cortas
+    pub fn with_short_borrow_rate(mut self, short_borrow_rate: f64) -> Self {
+        self.short_borrow_rate = short_borrow_rate;
+        self
+    }
+
+    /// Define el modelo de tamaño de posición
+    pub fn with_position_sizing(mut self, position_sizing: PositionSizing) -> Self {
+        self.position_sizing = position_sizing;
+        self
+    }
+
+    /// Activa un trailing stop porcentual desde el extremo favorable
+    pub fn with_trailing_stop(mut self, percent: f64) -> Self {
+        self.trailing_stop_percent = Some(percent);
+        self
+    }
+
+    /// Activa un trailing stop a `multiple` ATRs del cierre
+    pub fn with_atr_trailing_stop(mut self, multiple: f64) -> Self {
+        self.trailing_stop_atr_multiple = Some(multiple);
+        self
+    }
+
+    /// Mueve el stop a la entrada tras un beneficio de `trigger_percent`
+    pub fn with_break_even(mut self, trigger_percent: f64) -> Self {
+        self.break_even_trigger_percent = Some(trigger_percent);
+        self
+    }
+
+    /// Activa el chandelier exit sobre las últimas `period` velas
+    pub fn with_chandelier_exit(mut self, period: usize, multiple: f64) -> Self {
+        self.chandelier_period = period;
+        self.chandelier_atr_multiple = Some(multiple);
+        self
+    }
+
+    /// Define el periodo del ATR de los stops basados en ATR
+    pub fn with_exit_atr_period(mut self, period: usize) -> Self {
+        self.exit_atr_period = period;
+        self
+    }
+
+    /// Cierra los trades que superan `bars` velas
+    pub fn with_max_bars_in_trade(mut self, bars: usize) -> Self {
+        self.max_bars_in_trade = Some(bars);
+        self
+    }
+
+    /// Cierra las posiciones antes del cierre de sesión (minutos desde medianoche UTC)
+    pub fn with_session_close(mut self, minute_of_day: u32) -> Self {
+        self.session_close_minute = Some(minute_of_day);
+        self
+    }
+
+    /// Permite añadir hasta `max_adds` lotes a una posición, cada uno `size_decay` veces el anterior
+    pub fn with_pyramiding(mut self, max_adds: usize, size_decay: f64) -> Self {
+        self.pyramiding = PyramidingConfig { max_adds, size_decay };
+        self
+    }
+
+    /// Define las tomas de beneficio parciales (se ordenan por beneficio)
+    pub fn with_scale_out(mut self, mut levels: Vec<ScaleOutLevel>) -> Self {
+        levels.sort_by(|a, b| a.profit_percent.total_cmp(&b.profit_percent));
+        self.scale_out = levels;
+        self
+    }
+
+    /// Define el orden de cierre de los lotes en los cierres parciales
+    pub fn with_close_order(mut self, close_order: CloseOrder) -> Self {
+        self.close_order = close_order;
+        self
+    }
+
+    /// Define el modelo de costes de ejecución
+    pub fn with_cost_model(mut self, cost_model: CostModel) -> Self {
+        self.cost_model = cost_model;
+        self
+    }
+
+    /// Activa el apalancamiento con margen y liquidaciones
+    pub fn with_margin(mut self, margin: MarginConfig) -> Self {
+        self.margin = Some(margin);
+        self
+    }
+
+    /// Registra el tipo de barra de las velas (temporales, de volumen, Renko...)
+    pub fn with_bar_type(mut self, bar_type: BarType) -> Self {
+        self.bar_type = Some(bar_type);
+        self
+    }
+
+    /// Opera solo desde `timestamp`; las velas anteriores sirven de warmup
+    pub fn with_trading_start(mut self, timestamp: i64) -> Self {
+        self.trading_start = Some(timestamp);
+        self
+    }
+
+    /// Marca velas sintéticas en las que no se abren posiciones
+    pub fn with_synthetic_bars(mut self, mut timestamps: Vec<i64>) -> Self {
+        timestamps.sort_unstable();
+        self.synthetic_bars = Some(timestamps.into());
+        self
+    }
+
+    /// Indica si la vela de `timestamp` es sintética
+    pub fn is_synthetic_bar(&self, timestamp: i64) -> bool {
+        self.synthetic_bars.as_ref().is_some_and(|bars| bars.binary_search(&timestamp).is_ok())
+    }
+
+    /// Capital que bloquea una posición de valor `notional` (todo su valor sin apalancamiento)
+    pub fn initial_margin(&self, notional: f64) -> f64 {
+        self.margin.map_or(notional, |margin| margin.initial_margin(notional))
+    }
+
     /// Calcula la comisión para un trade
     pub fn calculate_commission(&self, trade_value: f64) -> f64 {
         trade_value * self.commission_rate
@@ -113,5 +377,11 @@ impl BacktestConfig {
     pub fn calculate_slippage(&self, price: f64) -> f64 {
         price * (self.slippage_bps / 10000.0)
     }
+
+    /// Calcula el costo de préstamo de una posición corta mantenida `duration_ms` milisegundos
+    pub fn calculate_borrow_cost(&self, position_value: f64, duration_ms: i64) -> f64 {
+        let days = duration_ms.max(0) as f64 / (1000.0 * 60.0 * 60.0 * 24.0);
+        position_value * self.short_borrow_rate * days
+    }
 }
 
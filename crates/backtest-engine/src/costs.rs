@@ -0,0 +1,341 @@
+//! Modelos de costes de ejecución
+//!
+//! `CostModel` describe las comisiones (planas, maker/taker o por tiers de
+//! volumen), el slippage (fijo, según la participación en el volumen de la vela
+//! o según el ATR) y los pagos de funding de perpetuos. Por defecto reproduce los
+//! costes planos de `BacktestConfig` (`commission_rate` y `slippage_bps`).
+//!
+//! Los motores masivo, event-driven y de cartera calculan sus costes con
+//! `FeeLedger` y `SlippageSeries`, así que un mismo fill cuesta lo mismo en todos.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::sync::Arc;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use darwinx_core::PositionSide;
+use crate::config::BacktestConfig;
+use crate::error::BacktestError;
+use crate::sizing::atr_series;
+use crate::types::ExitReason;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Lado del libro de órdenes de un fill
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Liquidity {
+    /// Orden limitada que descansa en el libro
+    Maker,
+    /// Orden que cruza el spread (mercado o stop)
+    Taker,
+}
+
+impl Liquidity {
+    /// Liquidez de una salida: los take profit son órdenes limitadas, el resto cruzan el spread
+    pub fn for_exit(reason: ExitReason) -> Self {
+        match reason {
+            ExitReason::TakeProfit | ExitReason::PartialTakeProfit => Liquidity::Maker,
+            _ => Liquidity::Taker,
+        }
+    }
+}
+
+/// Tier de comisiones de un exchange
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeeTier {
+    /// Volumen negociado (en valor) en la ventana a partir del cual aplica el tier
+    pub min_volume: f64,
+    pub maker_rate: f64,
+    pub taker_rate: f64,
+}
+
+/// Modelo de comisiones
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum FeeModel {
+    /// `commission_rate` para todos los fills
+    #[default]
+    Flat,
+    /// Comisión distinta para órdenes maker y taker
+    MakerTaker { maker_rate: f64, taker_rate: f64 },
+    /// Tiers según el volumen negociado en los últimos `window_days` días
+    ///
+    /// Se aplica el tier de mayor `min_volume` alcanzado; sin ninguno alcanzado, el de menor `min_volume`.
+    Tiered {
+        tiers: Vec<FeeTier>,
+        #[serde(default = "default_tier_window_days")]
+        window_days: u32,
+    },
+}
+
+fn default_tier_window_days() -> u32 {
+    30
+}
+
+impl FeeModel {
+    /// Tiers ordenados por volumen mínimo
+    pub fn tiered(mut tiers: Vec<FeeTier>, window_days: u32) -> Self {
+        tiers.sort_by(|a, b| a.min_volume.total_cmp(&b.min_volume));
+        FeeModel::Tiered { tiers, window_days }
+    }
+}
+
+/// Modelo de slippage
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum SlippageModel {
+    /// `slippage_bps` sobre el precio
+    #[default]
+    Fixed,
+    /// `base_bps + impact_bps * sqrt(tamaño / volumen de la vela)`, con tope opcional
+    ///
+    /// `impact_bps` es el impacto de una orden del tamaño de todo el volumen de la vela.
+    VolumeImpact {
+        base_bps: f64,
+        impact_bps: f64,
+        #[serde(default)]
+        max_bps: Option<f64>,
+    },
+    /// `multiple` ATRs de `period` velas por unidad
+    Atr { multiple: f64, period: usize },
+}
+
+/// Tasa de funding de un perpetuo en un instante
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FundingRate {
+    pub timestamp: i64,
+    /// Tasa por pago (ej: 0.0001 = 0.01%); positiva = los largos pagan a los cortos
+    pub rate: f64,
+}
+
+/// Serie de tasas de funding ordenada por timestamp
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct FundingRates {
+    rates: Vec<FundingRate>,
+}
+
+impl FundingRates {
+    pub fn new(mut rates: Vec<FundingRate>) -> Self {
+        rates.sort_by_key(|r| r.timestamp);
+        Self { rates }
+    }
+
+    /// Lee las columnas `timestamp` (ms) y `funding_rate` de un DataFrame
+    pub fn from_dataframe(df: &DataFrame) -> Result<Self, BacktestError> {
+        let column = |name: &str| {
+            df.column(name)
+                .and_then(|c| c.cast(if name == "timestamp" { &DataType::Int64 } else { &DataType::Float64 }))
+                .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get {}: {}", name, e)))
+        };
+        let timestamps = column("timestamp")?;
+        let rates = column("funding_rate")?;
+        let timestamps = timestamps.i64()
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast timestamp: {}", e)))?;
+        let rates = rates.f64()
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast funding_rate: {}", e)))?;
+
+        Ok(Self::new(
+            timestamps
+                .iter()
+                .zip(rates.iter())
+                .filter_map(|(timestamp, rate)| Some(FundingRate { timestamp: timestamp?, rate: rate? }))
+                .collect(),
+        ))
+    }
+
+    /// Carga la serie de un CSV con columnas `timestamp,funding_rate`
+    pub fn load_csv(path: &str) -> Result<Self, BacktestError> {
+        let schema = Schema::from_iter([
+            Field::new("timestamp".into(), DataType::Int64),
+            Field::new("funding_rate".into(), DataType::Float64),
+        ]);
+        let df = CsvReadOptions::default()
+            .with_has_header(true)
+            .with_schema_overwrite(Some(Arc::new(schema)))
+            .try_into_reader_with_file_path(Some(path.into()))
+            .and_then(|reader| reader.finish())
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to read funding CSV {}: {}", path, e)))?;
+        Self::from_dataframe(&df)
+    }
+
+    /// Carga la serie de un Parquet con columnas `timestamp` y `funding_rate`
+    pub fn load_parquet(path: &str) -> Result<Self, BacktestError> {
+        let mut file = File::open(path)
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to open funding Parquet {}: {}", path, e)))?;
+        let df = ParquetReader::new(&mut file)
+            .finish()
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to read funding Parquet {}: {}", path, e)))?;
+        Self::from_dataframe(&df)
+    }
+
+    pub fn rates(&self) -> &[FundingRate] {
+        &self.rates
+    }
+
+    pub fn len(&self) -> usize {
+        self.rates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rates.is_empty()
+    }
+
+    /// Suma de las tasas con timestamp en `(from, to]`
+    pub fn cumulative_rate(&self, from: i64, to: i64) -> f64 {
+        let start = self.rates.partition_point(|r| r.timestamp <= from);
+        let end = self.rates.partition_point(|r| r.timestamp <= to);
+        self.rates[start..end.max(start)].iter().map(|r| r.rate).sum()
+    }
+}
+
+/// Modelo de costes de ejecución de un backtest
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct CostModel {
+    #[serde(default)]
+    pub fees: FeeModel,
+    #[serde(default)]
+    pub slippage: SlippageModel,
+    /// Tasas de funding de perpetuos (None = sin funding)
+    #[serde(default)]
+    pub funding: Option<FundingRates>,
+}
+
+impl CostModel {
+    /// Define el modelo de comisiones
+    pub fn with_fees(mut self, fees: FeeModel) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Define el modelo de slippage
+    pub fn with_slippage(mut self, slippage: SlippageModel) -> Self {
+        self.slippage = slippage;
+        self
+    }
+
+    /// Aplica pagos de funding con las tasas dadas
+    pub fn with_funding(mut self, funding: FundingRates) -> Self {
+        self.funding = Some(funding);
+        self
+    }
+
+    /// Funding pagado por una posición de valor de entrada `notional` entre dos instantes
+    ///
+    /// Cuenta los pagos con timestamp en `(entry_timestamp, exit_timestamp]`. Positivo
+    /// es un coste y negativo un cobro (los cortos cobran cuando la tasa es positiva).
+    pub fn funding_payment(&self, side: PositionSide, notional: f64, entry_timestamp: i64, exit_timestamp: i64) -> f64 {
+        let Some(funding) = &self.funding else {
+            return 0.0;
+        };
+        let payment = notional * funding.cumulative_rate(entry_timestamp, exit_timestamp);
+        match side {
+            PositionSide::Long => payment,
+            PositionSide::Short => -payment,
+        }
+    }
+}
+
+/// Volumen negociado de una cuenta, para las comisiones por tiers
+#[derive(Debug, Default)]
+pub(crate) struct FeeLedger {
+    /// Fills dentro de la ventana del tier: (timestamp, valor)
+    fills: VecDeque<(i64, f64)>,
+    window_volume: f64,
+}
+
+impl FeeLedger {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tasa de comisión de un fill en `timestamp` con el volumen negociado hasta entonces
+    pub(crate) fn rate(&mut self, config: &BacktestConfig, timestamp: i64, liquidity: Liquidity) -> f64 {
+        let (maker_rate, taker_rate) = match &config.cost_model.fees {
+            FeeModel::Flat => return config.commission_rate,
+            FeeModel::MakerTaker { maker_rate, taker_rate } => (*maker_rate, *taker_rate),
+            FeeModel::Tiered { tiers, window_days } => {
+                let window_start = timestamp - i64::from(*window_days) * DAY_MS;
+                while let Some(&(fill_timestamp, notional)) = self.fills.front() {
+                    if fill_timestamp > window_start {
+                        break;
+                    }
+                    self.window_volume -= notional;
+                    self.fills.pop_front();
+                }
+                let by_volume = |a: &&FeeTier, b: &&FeeTier| a.min_volume.total_cmp(&b.min_volume);
+                let tier = tiers
+                    .iter()
+                    .filter(|tier| tier.min_volume <= self.window_volume)
+                    .max_by(by_volume)
+                    .or_else(|| tiers.iter().min_by(by_volume));
+                match tier {
+                    Some(tier) => (tier.maker_rate, tier.taker_rate),
+                    None => return config.commission_rate,
+                }
+            }
+        };
+        match liquidity {
+            Liquidity::Maker => maker_rate,
+            Liquidity::Taker => taker_rate,
+        }
+    }
+
+    /// Comisión de un fill sin registrarlo (para comprobar el balance antes de ejecutar)
+    pub(crate) fn quote(&mut self, config: &BacktestConfig, timestamp: i64, notional: f64, liquidity: Liquidity) -> f64 {
+        notional * self.rate(config, timestamp, liquidity)
+    }
+
+    /// Comisión de un fill ejecutado; su valor cuenta para el tier de los siguientes
+    pub(crate) fn charge(&mut self, config: &BacktestConfig, timestamp: i64, notional: f64, liquidity: Liquidity) -> f64 {
+        let commission = self.quote(config, timestamp, notional, liquidity);
+        if matches!(config.cost_model.fees, FeeModel::Tiered { .. }) {
+            self.fills.push_back((timestamp, notional));
+            self.window_volume += notional;
+        }
+        commission
+    }
+}
+
+/// Series de mercado de un dataset que usa el modelo de slippage
+pub(crate) struct SlippageSeries {
+    volumes: Vec<f64>,
+    atr: Option<Vec<Option<f64>>>,
+}
+
+impl SlippageSeries {
+    /// Calcula solo las series que usa el modelo configurado
+    pub(crate) fn new(highs: &[f64], lows: &[f64], closes: &[f64], volumes: &[f64], config: &BacktestConfig) -> Self {
+        match config.cost_model.slippage {
+            SlippageModel::Fixed => Self { volumes: Vec::new(), atr: None },
+            SlippageModel::VolumeImpact { .. } => Self { volumes: volumes.to_vec(), atr: None },
+            SlippageModel::Atr { period, .. } => Self {
+                volumes: Vec::new(),
+                atr: Some(atr_series(highs, lows, closes, period)),
+            },
+        }
+    }
+
+    /// Slippage por unidad de un fill de `size` unidades a `price` en la vela `index`
+    ///
+    /// El volumen es el de la vela del fill y el ATR el de la última vela cerrada:
+    /// `index` si el fill es al cierre, la anterior si es a la apertura. Sin ATR
+    /// disponible (warmup) se usa `slippage_bps`.
+    pub(crate) fn slippage(&self, index: usize, at_close: bool, price: f64, size: f64, config: &BacktestConfig) -> f64 {
+        match config.cost_model.slippage {
+            SlippageModel::Fixed => config.calculate_slippage(price),
+            SlippageModel::VolumeImpact { base_bps, impact_bps, max_bps } => {
+                let volume = self.volumes.get(index).copied().unwrap_or(0.0);
+                let participation = if volume > 0.0 { size / volume } else { 1.0 };
+                let bps = base_bps + impact_bps * participation.max(0.0).sqrt();
+                price * max_bps.map_or(bps, |max| bps.min(max)) / 10000.0
+            }
+            SlippageModel::Atr { multiple, .. } => {
+                let last_closed = if at_close { Some(index) } else { index.checked_sub(1) };
+                last_closed
+                    .and_then(|i| self.atr.as_ref().and_then(|atr| atr.get(i).copied().flatten()))
+                    .map_or_else(|| config.calculate_slippage(price), |atr| multiple * atr)
+            }
+        }
+    }
+}
@@ -1,22 +1,23 @@
 //! Motor Event-Driven para simulación realista
 //!
-//! Este módulo implementa el motor de backtest tick-by-tick para
-//! simulación más realista con order book, slippage y latencia.
-
-pub struct EventDrivenBacktestEngine;
-
-impl EventDrivenBacktestEngine {
-    /// Crea un nuevo motor event-driven
-    pub fn new() -> Self {
-        Self
-    }
-}
-
-impl Default for EventDrivenBacktestEngine {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+//! Este módulo implementa el motor de backtest vela a vela para
+//! simulación más realista que el motor vectorizado:
+//!
+//! - Órdenes market, limit y stop con cola de órdenes pendientes
+//! - Reglas de ejecución intrabar usando OHLC (gaps incluidos)
+//! - Ejecuciones parciales limitadas por el volumen de la vela
+//! - Latencia configurable en velas entre señal y ejecución
+//!
+//! Consume cualquier `DataProvider` y produce el mismo `BacktestResult`
+//! (incluyendo equity curve) que el motor Polars, de forma que ambos motores
+//! pueden contrastarse sobre el mismo `StrategyAST`.
 
-// TODO: Implementar motor event-driven completo
+pub mod orders;
+pub mod strategy;
+pub mod interpreter;
+pub mod engine;
 
+pub use orders::{OrderType, OrderIntent, OrderRequest, PendingOrder};
+pub use strategy::{BarStrategy, BarContext, BarHistory, CoreStrategyAdapter};
+pub use interpreter::AstInterpreter;
+pub use engine::{EventDrivenBacktestEngine, EventDrivenConfig};
@@ -0,0 +1,581 @@
+//! Motor event-driven: simulación vela a vela con cola de órdenes
+
+use serde::{Deserialize, Serialize};
+use darwinx_core::{Candle, OrderSide, PositionSide};
+use darwinx_generator::StrategyAST;
+use crate::config::BacktestConfig;
+use crate::costs::{FeeLedger, Liquidity, SlippageSeries};
+use crate::data_provider::DataProvider;
+use crate::error::BacktestError;
+use crate::metrics::apply_buy_and_hold_benchmark;
+use crate::sizing::{atr_series, PositionSizer, SizingContext};
+use crate::exits::{session_closes_after, ExitIndicators, ExitTracker};
+use crate::polars_engine::massive::PolarsVectorizedBacktestEngine;
+use crate::positions::Lot;
+use crate::types::{BacktestMetadata, BacktestResult, EquityPoint, ExitReason, Trade};
+use super::interpreter::AstInterpreter;
+use super::orders::{order_side_for, OrderIntent, OrderRequest, OrderType, PendingOrder};
+use super::strategy::{BarContext, BarHistory, BarStrategy};
+
+/// Tolerancia para considerar una cantidad como cero
+const QUANTITY_EPSILON: f64 = 1e-12;
+
+/// Configuración específica del motor event-driven
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventDrivenConfig {
+    /// Velas de latencia entre el envío de una orden y su primera ejecución posible
+    ///
+    /// Con 0, las órdenes de mercado se ejecutan al cierre de la vela que las generó
+    /// (igual que el motor de Polars). Con N > 0, a la apertura de la vela `i + N`.
+    pub latency_bars: usize,
+    /// Fracción máxima del volumen de cada vela que puede ejecutarse (None = sin límite)
+    ///
+    /// Si una orden supera este límite se ejecuta parcialmente y el resto
+    /// queda pendiente para las velas siguientes.
+    pub max_volume_participation: Option<f64>,
+    /// Velas que una orden permanece activa antes de cancelarse (None = sin expiración)
+    pub order_ttl_bars: Option<usize>,
+}
+
+/// Posición abierta durante la simulación
+#[derive(Debug, Clone)]
+struct OpenPosition {
+    /// Número de la posición en la simulación (`Trade.position_id`)
+    id: usize,
+    side: PositionSide,
+    size: f64,
+    entry_price: f64,
+    entry_timestamp: i64,
+    /// Comisiones de entrada aún no asignadas a un trade
+    entry_commission: f64,
+    /// Trailing stops, break-even y salidas por tiempo
+    exits: ExitTracker,
+}
+
+/// Estado mutable de una simulación
+struct SimulationState {
+    position: Option<OpenPosition>,
+    pending: Vec<PendingOrder>,
+    trades: Vec<Trade>,
+    /// Balance de la cuenta: P&L realizado menos comisiones de entrada
+    balance: f64,
+    next_order_id: u64,
+    /// Posiciones abiertas hasta el momento
+    positions_opened: usize,
+    /// Volumen ya ejecutado en la vela actual
+    volume_used: f64,
+    /// ATR conocido en el momento de la ejecución (solo si el modelo de tamaño lo pide)
+    atr: Option<f64>,
+    /// Vela actual y timestamp de la siguiente (para las salidas por sesión)
+    bar_index: usize,
+    next_timestamp: Option<i64>,
+    /// Órdenes ejecutadas al cierre de la vela actual (ya no a su apertura)
+    at_close: bool,
+    exit_indicators: ExitIndicators,
+    /// Volumen negociado para las comisiones por tiers
+    fees: FeeLedger,
+    slippage: SlippageSeries,
+}
+
+/// Motor de backtest event-driven
+///
+/// Recorre las velas una a una: ejecuta las órdenes pendientes con reglas
+/// intrabar sobre OHLC, comprueba stop loss / take profit, consulta a la
+/// estrategia al cierre de la vela y registra la equity mark-to-market.
+pub struct EventDrivenBacktestEngine {
+    config: EventDrivenConfig,
+    /// Se reutilizan el cierre de trades y las métricas del motor masivo
+    /// para que ambos motores sean comparables
+    polars: PolarsVectorizedBacktestEngine,
+}
+
+impl EventDrivenBacktestEngine {
+    /// Crea un nuevo motor event-driven
+    pub fn new() -> Self {
+        Self::with_config(EventDrivenConfig::default())
+    }
+
+    /// Crea un motor con configuración específica
+    pub fn with_config(config: EventDrivenConfig) -> Self {
+        Self {
+            config,
+            polars: PolarsVectorizedBacktestEngine::new(),
+        }
+    }
+
+    /// Configuración del motor
+    pub fn config(&self) -> &EventDrivenConfig {
+        &self.config
+    }
+
+    /// Ejecuta el backtest de un `StrategyAST` interpretado vela a vela
+    pub async fn run_ast(
+        &self,
+        strategy: StrategyAST,
+        data_provider: &dyn DataProvider,
+        config: &BacktestConfig,
+    ) -> Result<BacktestResult, BacktestError> {
+        let mut interpreter = AstInterpreter::new(strategy);
+        self.run_backtest(&mut interpreter, data_provider, config).await
+    }
+
+    /// Ejecuta el backtest de una estrategia sobre los datos del provider
+    ///
+    /// Simula una sola posición sin apalancamiento: rechaza `max_positions > 1`
+    /// y `margin` en lugar de ignorarlos.
+    pub async fn run_backtest(
+        &self,
+        strategy: &mut dyn BarStrategy,
+        data_provider: &dyn DataProvider,
+        config: &BacktestConfig,
+    ) -> Result<BacktestResult, BacktestError> {
+        Self::validate_config(config)?;
+        let candles = self.load_candles(data_provider).await?;
+        if candles.is_empty() {
+            return Err(BacktestError::DataError(anyhow::anyhow!(
+                "No candles provided for backtest"
+            )));
+        }
+
+        strategy.prepare(&candles)?;
+
+        let mut history = BarHistory::new(&candles);
+        let highs: Vec<f64> = candles.iter().map(|c| c.high).collect();
+        let lows: Vec<f64> = candles.iter().map(|c| c.low).collect();
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+        let volumes: Vec<f64> = candles.iter().map(|c| c.volume).collect();
+        let mut state = SimulationState {
+            position: None,
+            pending: Vec::new(),
+            trades: Vec::new(),
+            balance: config.initial_balance,
+            next_order_id: 0,
+            positions_opened: 0,
+            volume_used: 0.0,
+            atr: None,
+            bar_index: 0,
+            next_timestamp: None,
+            at_close: false,
+            exit_indicators: ExitIndicators::new(&highs, &lows, &closes, config),
+            fees: FeeLedger::new(),
+            slippage: SlippageSeries::new(&highs, &lows, &closes, &volumes, config),
+        };
+        let atr_values = config.position_sizing.atr_period()
+            .map(|period| atr_series(&highs, &lows, &closes, period));
+        let atr_at = |index: usize| atr_values.as_ref().and_then(|atr| atr[index]);
+        let mut equity_curve = Vec::with_capacity(candles.len());
+        let mut peak_equity = config.initial_balance;
+        // Las velas anteriores a `trading_start` solo calientan la estrategia: sus órdenes se descartan
+        let start = config.trading_start.map_or(0, |start| candles.partition_point(|c| c.timestamp < start));
+        if start == candles.len() {
+            return Err(BacktestError::DataError(anyhow::anyhow!("No candles after trading start")));
+        }
+
+        for (index, candle) in candles.iter().enumerate() {
+            if index < start {
+                history.advance_to(index);
+                let ctx = BarContext { index, candle, history: &history, position: None, pending: &state.pending };
+                strategy.on_bar(&ctx)?;
+                continue;
+            }
+
+            state.volume_used = 0.0;
+            state.bar_index = index;
+            state.next_timestamp = candles.get(index + 1).map(|c| c.timestamp);
+            state.at_close = false;
+            // Las órdenes pendientes se dimensionan con el ATR de la vela anterior
+            state.atr = index.checked_sub(1).and_then(atr_at);
+
+            // 1. Ejecutar órdenes pendientes activas durante la vela
+            self.process_pending_orders(&mut state, index, candle, config);
+
+            // 2. Stops, take profit y salidas por tiempo sobre la posición abierta
+            self.check_exit_levels(&mut state, candle, config);
+
+            // 3. La estrategia evalúa la vela cerrada y envía órdenes
+            history.advance_to(index);
+            let ctx = BarContext {
+                index,
+                candle,
+                history: &history,
+                position: state.position.as_ref().map(|p| p.side),
+                pending: &state.pending,
+            };
+            let requests = strategy.on_bar(&ctx)?;
+            state.atr = atr_at(index);
+            state.at_close = true;
+            self.submit_orders(&mut state, requests, index, candle, config);
+
+            // 4. Registrar equity mark-to-market al cierre (incluye las comisiones de entrada pagadas)
+            let equity = state.balance + Self::unrealized_pnl(&state, candle.close);
+            peak_equity = peak_equity.max(equity);
+            equity_curve.push(EquityPoint {
+                timestamp: candle.timestamp,
+                balance: equity,
+                drawdown: if peak_equity > 0.0 { (peak_equity - equity) / peak_equity } else { 0.0 },
+            });
+        }
+
+        // Cerrar posición abierta al final si existe (las órdenes pendientes se descartan)
+        let last = candles.last().expect("candles no vacío");
+        if let Some((side, size)) = state.position.as_ref().map(|p| (p.side, p.size)) {
+            let slippage = state.slippage.slippage(candles.len() - 1, true, last.close, size, config);
+            let exit_price = match side {
+                PositionSide::Long => last.close - slippage,
+                PositionSide::Short => last.close + slippage,
+            };
+            let reason = ExitReason::EndOfData;
+            self.close_position(&mut state, size, last.timestamp, exit_price, slippage * size, reason, Liquidity::Taker, config);
+        }
+
+        let mut metrics = self.polars.calculate_metrics_from_trades(&state.trades, &equity_curve, config)?;
+        metrics.entry_signals_count = strategy.entry_signals_count();
+        let closes: Vec<f64> = candles[start..].iter().map(|c| c.close).collect();
+        apply_buy_and_hold_benchmark(&mut metrics, &equity_curve, &closes);
+
+        Ok(BacktestResult {
+            strategy_name: strategy.name().to_string(),
+            metrics,
+            trades: state.trades,
+            equity_curve,
+            metadata: BacktestMetadata {
+                start_date: candles[start].timestamp,
+                end_date: last.timestamp,
+                total_candles: candles.len() - start,
+                initial_balance: config.initial_balance,
+                final_balance: state.balance,
+                config: config.clone(),
+            },
+        })
+    }
+
+    /// Rechaza los ajustes del motor masivo que esta simulación no modela
+    fn validate_config(config: &BacktestConfig) -> Result<(), BacktestError> {
+        if config.max_positions > 1 {
+            return Err(BacktestError::ConfigError(format!(
+                "Event-driven engine supports a single position (max_positions = {})",
+                config.max_positions
+            )));
+        }
+        if config.margin.is_some() {
+            return Err(BacktestError::ConfigError(
+                "Event-driven engine does not simulate margin or liquidations".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Lee todas las velas del timeframe principal del provider
+    async fn load_candles(&self, data_provider: &dyn DataProvider) -> Result<Vec<Candle>, BacktestError> {
+        let len = data_provider.len().await?;
+        let mut candles = Vec::with_capacity(len);
+        for index in 0..len {
+            let candle = data_provider.get_candle(index).await?.ok_or_else(|| {
+                BacktestError::DataError(anyhow::anyhow!("Missing candle at index {}", index))
+            })?;
+            candles.push(candle.clone());
+        }
+        Ok(candles)
+    }
+
+    /// P&L no realizado de la posición abierta al precio dado
+    fn unrealized_pnl(state: &SimulationState, price: f64) -> f64 {
+        match &state.position {
+            Some(p) if p.side == PositionSide::Long => (price - p.entry_price) * p.size,
+            Some(p) => (p.entry_price - price) * p.size,
+            None => 0.0,
+        }
+    }
+
+    /// Encola las órdenes de la estrategia; con latencia 0 las de mercado se ejecutan al cierre
+    fn submit_orders(
+        &self,
+        state: &mut SimulationState,
+        requests: Vec<OrderRequest>,
+        index: usize,
+        candle: &Candle,
+        config: &BacktestConfig,
+    ) {
+        for request in requests {
+            // Las señales de entrada de una vela sintética se ignoran, como en el motor masivo
+            if matches!(request.intent, OrderIntent::Open(_)) && config.is_synthetic_bar(candle.timestamp) {
+                continue;
+            }
+            let side = order_side_for(request.intent, state.position.as_ref().map(|p| p.side));
+            let Some(side) = side else {
+                continue; // Cerrar sin posición no tiene efecto
+            };
+
+            // Las órdenes limitadas/stop no pueden evaluarse sobre una vela ya cerrada
+            let delay = match request.order_type {
+                OrderType::Market => self.config.latency_bars,
+                _ => self.config.latency_bars.max(1),
+            };
+            let mut order = PendingOrder {
+                id: state.next_order_id,
+                intent: request.intent,
+                order_type: request.order_type,
+                side,
+                quantity: request.quantity,
+                filled: 0.0,
+                submitted_bar: index,
+                active_from_bar: index + delay,
+            };
+            state.next_order_id += 1;
+
+            if delay == 0 {
+                // Las órdenes ejecutadas al cierre mantienen el orden de envío
+                // (ej: cerrar y luego abrir en la misma vela)
+                if self.try_fill(state, &mut order, candle.close, candle, config) {
+                    continue;
+                }
+            }
+            state.pending.push(order);
+        }
+    }
+
+    /// Ejecuta (total o parcialmente) las órdenes pendientes activas en esta vela
+    fn process_pending_orders(
+        &self,
+        state: &mut SimulationState,
+        index: usize,
+        candle: &Candle,
+        config: &BacktestConfig,
+    ) {
+        let pending = std::mem::take(&mut state.pending);
+        for mut order in pending {
+            if self.config.order_ttl_bars.is_some_and(|ttl| index >= order.active_from_bar + ttl) {
+                continue; // Orden expirada
+            }
+            // Las entradas pendientes no se ejecutan sobre precios sintéticos
+            let synthetic_entry = matches!(order.intent, OrderIntent::Open(_)) && config.is_synthetic_bar(candle.timestamp);
+            if index < order.active_from_bar || synthetic_entry {
+                state.pending.push(order);
+                continue;
+            }
+
+            let done = match order.intrabar_fill_price(candle) {
+                Some(price) => self.try_fill(state, &mut order, price, candle, config),
+                None => false,
+            };
+            if !done {
+                state.pending.push(order);
+            }
+        }
+    }
+
+    /// Intenta ejecutar una orden al precio dado; retorna true si la orden terminó
+    /// (ejecutada por completo o cancelada)
+    fn try_fill(
+        &self,
+        state: &mut SimulationState,
+        order: &mut PendingOrder,
+        price: f64,
+        candle: &Candle,
+        config: &BacktestConfig,
+    ) -> bool {
+        // Slippage por unidad y precio de ejecución de un fill de `size` unidades
+        let (has_slippage, order_side) = (order.has_slippage(), order.side);
+        let fill_price_for = |state: &SimulationState, size: f64| {
+            let slippage = if has_slippage {
+                state.slippage.slippage(state.bar_index, state.at_close, price, size, config)
+            } else {
+                0.0
+            };
+            match order_side {
+                OrderSide::Buy => (slippage, price + slippage),
+                OrderSide::Sell => (slippage, price - slippage),
+            }
+        };
+        let liquidity = match order.order_type {
+            OrderType::Limit(_) => Liquidity::Maker,
+            _ => Liquidity::Taker,
+        };
+
+        match order.intent {
+            OrderIntent::Open(side) => {
+                // No se puede abrir en un lado mientras hay posición en el contrario
+                if state.position.as_ref().is_some_and(|p| p.side != side) {
+                    return false;
+                }
+                // En la última vela de la sesión no se abren posiciones: se cancela la orden
+                if session_closes_after(candle.timestamp, state.next_timestamp, config) {
+                    return true;
+                }
+                // Tamaño según el modelo de la configuración (igual que el motor masivo),
+                // fijado en la primera ejecución con el slippage de una orden mínima
+                let quantity = *order.quantity.get_or_insert_with(|| {
+                    config.position_sizing.position_size(&SizingContext {
+                        side,
+                        entry_price: fill_price_for(state, 0.0).1,
+                        balance: state.balance,
+                        atr: state.atr,
+                        closed_trades: &state.trades,
+                        config,
+                    })
+                });
+                if quantity <= QUANTITY_EPSILON {
+                    return true; // El modelo de tamaño no abre posición: se cancela la orden
+                }
+                let fill = self.fillable_quantity(state, quantity - order.filled, candle);
+                if fill <= QUANTITY_EPSILON {
+                    return false;
+                }
+
+                let (_, fill_price) = fill_price_for(state, fill);
+                let commission = state.fees.quote(config, candle.timestamp, fill_price * fill, liquidity);
+                if state.balance < fill_price * fill + commission {
+                    return true; // Balance insuficiente: se cancela la orden
+                }
+                let commission = state.fees.charge(config, candle.timestamp, fill_price * fill, liquidity);
+                state.balance -= commission;
+                state.volume_used += fill;
+                order.filled += fill;
+
+                match &mut state.position {
+                    Some(position) => {
+                        let total = position.size + fill;
+                        position.entry_price = (position.entry_price * position.size + fill_price * fill) / total;
+                        position.size = total;
+                        position.entry_commission += commission;
+                    }
+                    None => {
+                        // Al cierre los stops por ATR usan la vela actual; a la apertura, la anterior
+                        let mut exits = ExitTracker::new(side, fill_price, state.bar_index);
+                        let last_closed = if state.at_close { Some(state.bar_index) } else { state.bar_index.checked_sub(1) };
+                        if let Some(last_closed) = last_closed {
+                            exits.refresh_indicator_stops(last_closed, &state.exit_indicators, config);
+                        }
+                        state.position = Some(OpenPosition {
+                            id: state.positions_opened,
+                            side,
+                            size: fill,
+                            entry_price: fill_price,
+                            entry_timestamp: candle.timestamp,
+                            entry_commission: commission,
+                            exits,
+                        });
+                        state.positions_opened += 1;
+                    }
+                }
+            }
+            OrderIntent::Close => {
+                let Some(position) = state.position.as_ref() else {
+                    return true; // La posición ya se cerró (ej: stop loss)
+                };
+                if order_side_for(OrderIntent::Close, Some(position.side)) != Some(order.side) {
+                    return true; // La posición cambió de lado desde el envío
+                }
+                let quantity = *order.quantity.get_or_insert(position.size);
+                let remaining = (quantity - order.filled).min(position.size);
+                let fill = self.fillable_quantity(state, remaining, candle);
+                if fill <= QUANTITY_EPSILON {
+                    return false;
+                }
+
+                let (slippage, fill_price) = fill_price_for(state, fill);
+                state.volume_used += fill;
+                order.filled += fill;
+                let reason = ExitReason::Signal;
+                self.close_position(state, fill, candle.timestamp, fill_price, slippage * fill, reason, liquidity, config);
+            }
+        }
+
+        order.remaining().is_some_and(|r| r <= QUANTITY_EPSILON)
+    }
+
+    /// Cantidad ejecutable en la vela actual respetando la participación máxima de volumen
+    fn fillable_quantity(&self, state: &SimulationState, wanted: f64, candle: &Candle) -> f64 {
+        match self.config.max_volume_participation {
+            Some(participation) => {
+                let available = (candle.volume * participation - state.volume_used).max(0.0);
+                wanted.min(available)
+            }
+            None => wanted,
+        }
+    }
+
+    /// Cierra `size` unidades de la posición abierta y registra el trade
+    #[allow(clippy::too_many_arguments)]
+    fn close_position(
+        &self,
+        state: &mut SimulationState,
+        size: f64,
+        exit_timestamp: i64,
+        exit_price: f64,
+        slippage: f64,
+        exit_reason: ExitReason,
+        liquidity: Liquidity,
+        config: &BacktestConfig,
+    ) {
+        let Some(position) = state.position.as_mut() else {
+            return;
+        };
+
+        // Parte de las comisiones de entrada proporcional al tamaño cerrado
+        let commission = position.entry_commission * (size / position.size).min(1.0);
+        position.entry_commission -= commission;
+        let lot = Lot {
+            entry_timestamp: position.entry_timestamp,
+            entry_price: position.entry_price,
+            size,
+            commission,
+        };
+        let trade = self.polars.close_trade(
+            position.side,
+            lot,
+            exit_timestamp,
+            exit_price,
+            slippage,
+            exit_reason,
+            liquidity,
+            &mut state.fees,
+            config,
+        );
+
+        let trade = Trade { position_id: position.id, ..trade };
+        position.size -= size;
+        if position.size <= QUANTITY_EPSILON {
+            state.position = None;
+        }
+        state.balance += trade.pnl;
+        state.trades.push(trade);
+    }
+
+    /// Comprueba stops, take profit y salidas por tiempo (mismo orden que el motor masivo)
+    ///
+    /// Los niveles se ejecutan al precio exacto y sin slippage; las salidas por
+    /// tiempo, al cierre de la vela con slippage. Si la posición sigue abierta, sus
+    /// niveles se actualizan con la vela.
+    fn check_exit_levels(&self, state: &mut SimulationState, candle: &Candle, config: &BacktestConfig) {
+        let Some(position) = state.position.as_ref() else {
+            return;
+        };
+        let (side, entry_price, size) = (position.side, position.entry_price, position.size);
+
+        if let Some((price, reason)) = position.exits.check_levels(entry_price, candle.high, candle.low, config) {
+            self.close_position(state, size, candle.timestamp, price, 0.0, reason, Liquidity::for_exit(reason), config);
+            return;
+        }
+
+        if let Some(reason) = position.exits.check_timed(state.bar_index, candle.timestamp, state.next_timestamp, config) {
+            let slippage = state.slippage.slippage(state.bar_index, true, candle.close, size, config);
+            let price = match side {
+                PositionSide::Long => candle.close - slippage,
+                PositionSide::Short => candle.close + slippage,
+            };
+            self.close_position(state, size, candle.timestamp, price, slippage * size, reason, Liquidity::Taker, config);
+            return;
+        }
+
+        if let Some(position) = state.position.as_mut() {
+            position.exits.update(state.bar_index, entry_price, candle.high, candle.low, &state.exit_indicators, config);
+        }
+    }
+}
+
+impl Default for EventDrivenBacktestEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
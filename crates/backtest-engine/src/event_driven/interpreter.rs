@@ -0,0 +1,210 @@
+//! Intérprete de `StrategyAST` vela a vela
+//!
+//! Evalúa las mismas reglas que el motor masivo de Polars (incluida la
+//! semántica de cruces y las condiciones en timeframes superiores) para que
+//! ambos motores puedan contrastarse.
+
+use std::collections::HashMap;
+use darwinx_core::{Candle, PositionSide, TimeFrame};
+use darwinx_generator::StrategyAST;
+use darwinx_generator::ast::nodes::{Comparison, Condition, ConditionValue, LogicalOperator, RuleSet};
+use crate::error::BacktestError;
+use crate::polars_engine::massive::PolarsVectorizedBacktestEngine;
+use crate::polars_engine::multi_timeframe::{base_interval, condition_timeframe, HigherTimeframeBars};
+use super::orders::{OrderIntent, OrderRequest};
+use super::strategy::{BarContext, BarStrategy};
+
+/// Estrategia que interpreta un `StrategyAST` en el motor event-driven
+pub struct AstInterpreter {
+    ast: StrategyAST,
+    engine: PolarsVectorizedBacktestEngine,
+    closes: Vec<f64>,
+    /// Intervalo de las velas (solo si la estrategia usa timeframes superiores)
+    base_millis: Option<i64>,
+    /// Series de indicadores pre-calculadas, indexadas por nombre de columna
+    series: HashMap<String, Vec<f64>>,
+    entry_signals: usize,
+}
+
+impl AstInterpreter {
+    /// Crea un intérprete para la estrategia dada
+    pub fn new(ast: StrategyAST) -> Self {
+        Self {
+            ast,
+            engine: PolarsVectorizedBacktestEngine::new(),
+            closes: Vec::new(),
+            base_millis: None,
+            series: HashMap::new(),
+            entry_signals: 0,
+        }
+    }
+
+    /// Retorna la estrategia interpretada
+    pub fn ast(&self) -> &StrategyAST {
+        &self.ast
+    }
+
+    /// Valor de un operando en la vela `index` (None si no es válido, ej: warmup)
+    ///
+    /// `timeframe` selecciona la serie proyectada de un timeframe superior.
+    fn value_at(&self, value: &ConditionValue, timeframe: Option<TimeFrame>, index: usize) -> Option<f64> {
+        let raw = match (value, timeframe) {
+            (ConditionValue::Number(n), _) => *n,
+            (ConditionValue::Price, None) => *self.closes.get(index)?,
+            (ConditionValue::Price, Some(_)) => {
+                let name = PolarsVectorizedBacktestEngine::price_column_name(timeframe);
+                *self.series.get(&name)?.get(index)?
+            }
+            (ConditionValue::Indicator(ind), _) => {
+                let name = self.engine.condition_column_name(ind, timeframe);
+                *self.series.get(&name)?.get(index)?
+            }
+        };
+        (!raw.is_nan()).then_some(raw)
+    }
+
+    /// Evalúa una condición individual en la vela `index`
+    fn evaluate_condition(&self, condition: &Condition, index: usize) -> bool {
+        let indicator = ConditionValue::Indicator(condition.indicator.clone());
+        let timeframe = condition_timeframe(condition.timeframe, self.base_millis);
+        let (Some(current), Some(compare)) = (
+            self.value_at(&indicator, timeframe, index),
+            self.value_at(&condition.value, timeframe, index),
+        ) else {
+            return false;
+        };
+
+        match condition.comparison {
+            Comparison::GreaterThan => current > compare,
+            Comparison::LessThan => current < compare,
+            Comparison::Equals => current == compare,
+            Comparison::CrossesAbove | Comparison::CrossesBelow => {
+                let Some(prev_index) = index.checked_sub(1) else {
+                    return false;
+                };
+                let (Some(prev), Some(prev_compare)) = (
+                    self.value_at(&indicator, timeframe, prev_index),
+                    self.value_at(&condition.value, timeframe, prev_index),
+                ) else {
+                    return false;
+                };
+                if condition.comparison == Comparison::CrossesAbove {
+                    prev <= prev_compare && current > compare
+                } else {
+                    prev >= prev_compare && current < compare
+                }
+            }
+        }
+    }
+
+    /// Evalúa un conjunto de reglas (vacío = sin señal)
+    fn evaluate_rules(&self, rules: Option<&RuleSet>, index: usize) -> bool {
+        let Some(rules) = rules.filter(|r| !r.conditions.is_empty()) else {
+            return false;
+        };
+        let mut results = rules.conditions.iter().map(|c| self.evaluate_condition(c, index));
+        match rules.operator {
+            LogicalOperator::And => results.all(|r| r),
+            LogicalOperator::Or => results.any(|r| r),
+        }
+    }
+}
+
+impl BarStrategy for AstInterpreter {
+    fn name(&self) -> &str {
+        &self.ast.name
+    }
+
+    fn prepare(&mut self, candles: &[Candle]) -> Result<(), BacktestError> {
+        // Se reutiliza el cálculo de indicadores del motor masivo: cada valor en la
+        // vela i solo depende de velas <= i, por lo que no hay look-ahead
+        self.closes = candles.iter().map(|c| c.close).collect();
+        let highs: Vec<f64> = candles.iter().map(|c| c.high).collect();
+        let lows: Vec<f64> = candles.iter().map(|c| c.low).collect();
+        let volumes: Vec<f64> = candles.iter().map(|c| c.volume).collect();
+        let ohlcv = [self.closes.clone(), highs, lows, volumes];
+        let timestamps: Vec<i64> = candles.iter().map(|c| c.timestamp).collect();
+
+        self.base_millis = if self.ast.higher_timeframes().is_empty() {
+            None
+        } else {
+            base_interval(&timestamps)
+        };
+        self.series.clear();
+        self.entry_signals = 0;
+        let mut higher_bars: HashMap<TimeFrame, HigherTimeframeBars> = HashMap::new();
+        for condition in self.ast.all_conditions() {
+            let timeframe = condition_timeframe(condition.timeframe, self.base_millis);
+            // En timeframes superiores las series se calculan sobre las barras cerradas
+            let bars = match (timeframe, self.base_millis) {
+                (Some(timeframe), Some(base_millis)) => Some(&*higher_bars
+                    .entry(timeframe)
+                    .or_insert_with(|| HigherTimeframeBars::resample(&timestamps, &ohlcv, base_millis, timeframe))),
+                _ => None,
+            };
+            let [closes, highs, lows, volumes] = bars.map_or(&ohlcv, |b| &b.ohlcv);
+
+            if let Some(bars) = bars {
+                let name = PolarsVectorizedBacktestEngine::price_column_name(timeframe);
+                self.series.entry(name).or_insert_with(|| bars.align(closes));
+            }
+            let indicators = std::iter::once(&condition.indicator).chain(match &condition.value {
+                ConditionValue::Indicator(ind) => Some(ind),
+                _ => None,
+            });
+            for indicator in indicators {
+                let name = self.engine.condition_column_name(indicator, timeframe);
+                if !self.series.contains_key(&name) {
+                    let values = self.engine.calculate_indicator_values(indicator, closes, highs, lows, volumes)?;
+                    let values = match bars {
+                        Some(bars) => bars.align(&values),
+                        None => values,
+                    };
+                    self.series.insert(name, values);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_bar(&mut self, ctx: &BarContext<'_>) -> Result<Vec<OrderRequest>, BacktestError> {
+        let index = ctx.index;
+        let entry = self.evaluate_rules(Some(&self.ast.entry_rules), index);
+        let exit = self.evaluate_rules(Some(&self.ast.exit_rules), index);
+        let short_entry = self.evaluate_rules(self.ast.short_entry_rules.as_ref(), index);
+        let short_exit = self.evaluate_rules(self.ast.short_exit_rules.as_ref(), index);
+        self.entry_signals += usize::from(entry) + usize::from(short_entry);
+
+        let mut orders = Vec::new();
+
+        // Misma lógica que el motor masivo: primero la salida (una entrada en el
+        // lado contrario también cierra) y después la entrada si quedamos planos.
+        // Se parte de la posición esperada tras las órdenes en cola para no
+        // reenviar una orden que aún no se ha ejecutado (latencia o ejecución parcial).
+        let mut position = ctx.expected_position();
+        let should_exit = match position {
+            Some(PositionSide::Long) => exit || short_entry,
+            Some(PositionSide::Short) => short_exit || entry,
+            None => false,
+        };
+        if should_exit && ctx.position.is_some() && !ctx.has_pending(OrderIntent::Close) {
+            orders.push(OrderRequest::market_close());
+            position = None;
+        }
+
+        if position.is_none() {
+            match (entry, short_entry) {
+                (true, false) => orders.push(OrderRequest::market_open(PositionSide::Long)),
+                (false, true) => orders.push(OrderRequest::market_open(PositionSide::Short)),
+                _ => {}
+            }
+        }
+
+        Ok(orders)
+    }
+
+    fn entry_signals_count(&self) -> usize {
+        self.entry_signals
+    }
+}
@@ -0,0 +1,161 @@
+//! Órdenes del motor event-driven y reglas de ejecución intrabar
+
+use serde::{Deserialize, Serialize};
+use darwinx_core::{Candle, OrderSide, PositionSide};
+
+/// Tipo de orden
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum OrderType {
+    /// Se ejecuta al primer precio disponible
+    Market,
+    /// Se ejecuta al precio límite o mejor
+    Limit(f64),
+    /// Se activa al tocar el precio de disparo y se ejecuta como orden de mercado
+    Stop(f64),
+}
+
+/// Intención de la orden respecto a la posición
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderIntent {
+    /// Abrir (o ampliar) una posición en el lado indicado
+    Open(PositionSide),
+    /// Cerrar la posición abierta
+    Close,
+}
+
+/// Orden solicitada por una estrategia al cierre de una vela
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OrderRequest {
+    /// Intención de la orden
+    pub intent: OrderIntent,
+    /// Tipo de orden
+    pub order_type: OrderType,
+    /// Cantidad (None = tamaño según `BacktestConfig` al abrir, posición completa al cerrar)
+    pub quantity: Option<f64>,
+}
+
+impl OrderRequest {
+    /// Crea una orden con intención y tipo dados
+    pub fn new(intent: OrderIntent, order_type: OrderType) -> Self {
+        Self {
+            intent,
+            order_type,
+            quantity: None,
+        }
+    }
+
+    /// Orden de mercado para abrir posición
+    pub fn market_open(side: PositionSide) -> Self {
+        Self::new(OrderIntent::Open(side), OrderType::Market)
+    }
+
+    /// Orden de mercado para cerrar la posición
+    pub fn market_close() -> Self {
+        Self::new(OrderIntent::Close, OrderType::Market)
+    }
+
+    /// Orden limitada para abrir posición
+    pub fn limit_open(side: PositionSide, price: f64) -> Self {
+        Self::new(OrderIntent::Open(side), OrderType::Limit(price))
+    }
+
+    /// Orden stop para abrir posición
+    pub fn stop_open(side: PositionSide, price: f64) -> Self {
+        Self::new(OrderIntent::Open(side), OrderType::Stop(price))
+    }
+
+    /// Define una cantidad explícita
+    pub fn with_quantity(mut self, quantity: f64) -> Self {
+        self.quantity = Some(quantity);
+        self
+    }
+}
+
+/// Orden pendiente en la cola del motor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingOrder {
+    /// Identificador secuencial
+    pub id: u64,
+    /// Intención de la orden
+    pub intent: OrderIntent,
+    /// Tipo de orden
+    pub order_type: OrderType,
+    /// Lado de la orden (compra o venta)
+    pub side: OrderSide,
+    /// Cantidad total (None hasta la primera ejecución si se dimensiona automáticamente)
+    pub quantity: Option<f64>,
+    /// Cantidad ya ejecutada
+    pub filled: f64,
+    /// Vela en la que se envió la orden
+    pub submitted_bar: usize,
+    /// Primera vela en la que la orden puede ejecutarse (tras la latencia)
+    pub active_from_bar: usize,
+}
+
+impl PendingOrder {
+    /// Cantidad pendiente de ejecutar (None si aún no está dimensionada)
+    pub fn remaining(&self) -> Option<f64> {
+        self.quantity.map(|q| (q - self.filled).max(0.0))
+    }
+
+    /// Precio de ejecución dentro de la vela usando OHLC, o None si no se alcanza
+    ///
+    /// Si la vela abre más allá del precio de la orden (gap), se ejecuta a la apertura.
+    pub fn intrabar_fill_price(&self, candle: &Candle) -> Option<f64> {
+        match (self.order_type, self.side) {
+            (OrderType::Market, _) => Some(candle.open),
+            (OrderType::Limit(limit), OrderSide::Buy) => {
+                if candle.open <= limit {
+                    Some(candle.open)
+                } else if candle.low <= limit {
+                    Some(limit)
+                } else {
+                    None
+                }
+            }
+            (OrderType::Limit(limit), OrderSide::Sell) => {
+                if candle.open >= limit {
+                    Some(candle.open)
+                } else if candle.high >= limit {
+                    Some(limit)
+                } else {
+                    None
+                }
+            }
+            (OrderType::Stop(stop), OrderSide::Buy) => {
+                if candle.open >= stop {
+                    Some(candle.open)
+                } else if candle.high >= stop {
+                    Some(stop)
+                } else {
+                    None
+                }
+            }
+            (OrderType::Stop(stop), OrderSide::Sell) => {
+                if candle.open <= stop {
+                    Some(candle.open)
+                } else if candle.low <= stop {
+                    Some(stop)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Indica si la ejecución sufre slippage (las órdenes limitadas no lo sufren)
+    pub fn has_slippage(&self) -> bool {
+        !matches!(self.order_type, OrderType::Limit(_))
+    }
+}
+
+/// Lado de la orden necesario para ejecutar una intención sobre una posición
+pub(crate) fn order_side_for(intent: OrderIntent, position: Option<PositionSide>) -> Option<OrderSide> {
+    match (intent, position) {
+        (OrderIntent::Open(PositionSide::Long), _) => Some(OrderSide::Buy),
+        (OrderIntent::Open(PositionSide::Short), _) => Some(OrderSide::Sell),
+        (OrderIntent::Close, Some(PositionSide::Long)) => Some(OrderSide::Sell),
+        (OrderIntent::Close, Some(PositionSide::Short)) => Some(OrderSide::Buy),
+        (OrderIntent::Close, None) => None,
+    }
+}
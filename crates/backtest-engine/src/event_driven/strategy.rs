@@ -0,0 +1,198 @@
+//! Estrategias ejecutables por el motor event-driven
+
+use darwinx_core::{Candle, MarketData, PositionSide, Signal};
+use crate::error::BacktestError;
+use super::orders::{OrderIntent, OrderRequest, PendingOrder};
+
+/// Historial de velas visible para la estrategia (sin look-ahead)
+///
+/// Solo expone las velas hasta la vela actual inclusive.
+pub struct BarHistory {
+    closes: Vec<f64>,
+    highs: Vec<f64>,
+    lows: Vec<f64>,
+    volumes: Vec<f64>,
+    visible: usize,
+}
+
+impl BarHistory {
+    /// Crea un historial a partir de las velas del backtest (inicialmente vacío)
+    pub fn new(candles: &[Candle]) -> Self {
+        Self {
+            closes: candles.iter().map(|c| c.close).collect(),
+            highs: candles.iter().map(|c| c.high).collect(),
+            lows: candles.iter().map(|c| c.low).collect(),
+            volumes: candles.iter().map(|c| c.volume).collect(),
+            visible: 0,
+        }
+    }
+
+    /// Hace visible el historial hasta la vela `index` inclusive
+    pub(crate) fn advance_to(&mut self, index: usize) {
+        self.visible = (index + 1).min(self.closes.len());
+    }
+
+    fn last(values: &[f64], visible: usize, lookback: usize) -> &[f64] {
+        &values[visible.saturating_sub(lookback)..visible]
+    }
+}
+
+impl MarketData for BarHistory {
+    fn close(&self, lookback: usize) -> &[f64] {
+        Self::last(&self.closes, self.visible, lookback)
+    }
+
+    fn volume(&self, lookback: usize) -> &[f64] {
+        Self::last(&self.volumes, self.visible, lookback)
+    }
+
+    fn high(&self, lookback: usize) -> &[f64] {
+        Self::last(&self.highs, self.visible, lookback)
+    }
+
+    fn low(&self, lookback: usize) -> &[f64] {
+        Self::last(&self.lows, self.visible, lookback)
+    }
+
+    fn len(&self) -> usize {
+        self.visible
+    }
+}
+
+/// Contexto que recibe la estrategia al cierre de cada vela
+pub struct BarContext<'a> {
+    /// Índice de la vela actual
+    pub index: usize,
+    /// Vela actual (ya cerrada)
+    pub candle: &'a Candle,
+    /// Historial visible hasta la vela actual
+    pub history: &'a BarHistory,
+    /// Lado de la posición abierta, si existe
+    pub position: Option<PositionSide>,
+    /// Órdenes enviadas que aún no se han ejecutado por completo
+    pub pending: &'a [PendingOrder],
+}
+
+impl BarContext<'_> {
+    /// Indica si hay una orden pendiente con la intención dada
+    pub fn has_pending(&self, intent: OrderIntent) -> bool {
+        self.pending.iter().any(|order| order.intent == intent)
+    }
+
+    /// Lado de la posición que quedará abierta cuando se ejecuten las órdenes pendientes
+    ///
+    /// Evita que una estrategia vuelva a enviar la misma orden mientras la
+    /// anterior sigue en cola (latencia o ejecución parcial).
+    pub fn expected_position(&self) -> Option<PositionSide> {
+        let pending_open = self.pending.iter().find_map(|order| match order.intent {
+            OrderIntent::Open(side) => Some(side),
+            OrderIntent::Close => None,
+        });
+        match pending_open {
+            Some(side) => Some(side),
+            None if self.has_pending(OrderIntent::Close) => None,
+            None => self.position,
+        }
+    }
+}
+
+/// Estrategia evaluada vela a vela por el motor event-driven
+pub trait BarStrategy: Send {
+    /// Nombre de la estrategia
+    fn name(&self) -> &str;
+
+    /// Prepara la estrategia antes del backtest (ej: pre-calcular indicadores)
+    ///
+    /// Los valores pre-calculados en la vela `i` solo deben depender de velas `<= i`.
+    fn prepare(&mut self, _candles: &[Candle]) -> Result<(), BacktestError> {
+        Ok(())
+    }
+
+    /// Evalúa la vela cerrada y retorna las órdenes a enviar
+    fn on_bar(&mut self, ctx: &BarContext<'_>) -> Result<Vec<OrderRequest>, BacktestError>;
+
+    /// Número de señales de entrada generadas (para diagnóstico)
+    fn entry_signals_count(&self) -> usize {
+        0
+    }
+}
+
+/// Adaptador que ejecuta una `darwinx_core::Strategy` en el motor event-driven
+///
+/// `Signal::Buy` abre largo (cerrando un corto previo) y `Signal::Sell` cierra
+/// el largo; si se permiten cortos, `Signal::Sell` además abre corto.
+pub struct CoreStrategyAdapter<S: darwinx_core::Strategy> {
+    strategy: S,
+    allow_short: bool,
+    entry_signals: usize,
+}
+
+impl<S: darwinx_core::Strategy> CoreStrategyAdapter<S> {
+    /// Crea un adaptador que solo opera en largo
+    pub fn new(strategy: S) -> Self {
+        Self {
+            strategy,
+            allow_short: false,
+            entry_signals: 0,
+        }
+    }
+
+    /// Permite abrir posiciones cortas con `Signal::Sell`
+    pub fn with_short(mut self, allow_short: bool) -> Self {
+        self.allow_short = allow_short;
+        self
+    }
+
+    /// Retorna la estrategia envuelta
+    pub fn into_inner(self) -> S {
+        self.strategy
+    }
+}
+
+impl<S: darwinx_core::Strategy> BarStrategy for CoreStrategyAdapter<S> {
+    fn name(&self) -> &str {
+        self.strategy.name()
+    }
+
+    fn prepare(&mut self, _candles: &[Candle]) -> Result<(), BacktestError> {
+        self.strategy.reset();
+        self.entry_signals = 0;
+        Ok(())
+    }
+
+    fn on_bar(&mut self, ctx: &BarContext<'_>) -> Result<Vec<OrderRequest>, BacktestError> {
+        if ctx.history.len() < self.strategy.min_periods() {
+            return Ok(Vec::new());
+        }
+
+        let orders = match (self.strategy.evaluate(ctx.history), ctx.expected_position()) {
+            (Signal::Buy { .. }, None) => vec![OrderRequest::market_open(PositionSide::Long)],
+            (Signal::Buy { .. }, Some(PositionSide::Short)) => vec![
+                OrderRequest::market_close(),
+                OrderRequest::market_open(PositionSide::Long),
+            ],
+            (Signal::Sell { .. }, Some(PositionSide::Long)) if self.allow_short => vec![
+                OrderRequest::market_close(),
+                OrderRequest::market_open(PositionSide::Short),
+            ],
+            (Signal::Sell { .. }, Some(PositionSide::Long)) => vec![OrderRequest::market_close()],
+            (Signal::Sell { .. }, None) if self.allow_short => {
+                vec![OrderRequest::market_open(PositionSide::Short)]
+            }
+            _ => Vec::new(),
+        };
+        // No se reenvían órdenes que siguen en cola
+        let orders: Vec<OrderRequest> = orders.into_iter().filter(|o| !ctx.has_pending(o.intent)).collect();
+
+        self.entry_signals += orders
+            .iter()
+            .filter(|o| matches!(o.intent, OrderIntent::Open(_)))
+            .count();
+
+        Ok(orders)
+    }
+
+    fn entry_signals_count(&self) -> usize {
+        self.entry_signals
+    }
+}
@@ -0,0 +1,190 @@
+//! Reglas de salida de posiciones
+//!
+//! Stop loss, take profit, trailing stops, break-even, chandelier exit y salidas
+//! por tiempo, compartidas por el motor masivo y el event-driven para que ambos
+//! cierren las posiciones en la misma vela y al mismo precio.
+//!
+//! Los niveles de stop de una vela se calculan con la información disponible al
+//! cierre de la vela anterior, así que no hay sesgo de anticipación.
+
+use darwinx_core::PositionSide;
+use crate::config::BacktestConfig;
+use crate::sizing::atr_series;
+use crate::types::ExitReason;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Series que necesitan las reglas de salida basadas en ATR
+pub(crate) struct ExitIndicators {
+    atr: Option<Vec<Option<f64>>>,
+    /// Cierres (solo si hay reglas basadas en ATR)
+    closes: Vec<f64>,
+    /// Máximo de las últimas `chandelier_period` velas (incluida la actual)
+    highest: Vec<f64>,
+    /// Mínimo de las últimas `chandelier_period` velas (incluida la actual)
+    lowest: Vec<f64>,
+}
+
+impl ExitIndicators {
+    /// Calcula solo las series que usan las reglas configuradas
+    pub(crate) fn new(highs: &[f64], lows: &[f64], closes: &[f64], config: &BacktestConfig) -> Self {
+        let needs_atr = config.trailing_stop_atr_multiple.is_some() || config.chandelier_atr_multiple.is_some();
+        let atr = needs_atr.then(|| atr_series(highs, lows, closes, config.exit_atr_period));
+        let closes = if needs_atr { closes.to_vec() } else { Vec::new() };
+
+        let (highest, lowest) = if config.chandelier_atr_multiple.is_some() {
+            let period = config.chandelier_period.max(1);
+            let window = |i: usize| i + 1 - period.min(i + 1)..=i;
+            (
+                (0..highs.len()).map(|i| highs[window(i)].iter().copied().fold(f64::NEG_INFINITY, f64::max)).collect(),
+                (0..lows.len()).map(|i| lows[window(i)].iter().copied().fold(f64::INFINITY, f64::min)).collect(),
+            )
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        Self { atr, closes, highest, lowest }
+    }
+
+    fn atr(&self, index: usize) -> Option<f64> {
+        self.atr.as_ref().and_then(|atr| atr.get(index).copied().flatten())
+    }
+}
+
+/// Estado de las reglas de salida de una posición abierta
+#[derive(Debug, Clone)]
+pub(crate) struct ExitTracker {
+    side: PositionSide,
+    entry_index: usize,
+    /// Máximo (largo) o mínimo (corto) alcanzado desde la entrada
+    extreme: f64,
+    atr_stop: Option<f64>,
+    chandelier_stop: Option<f64>,
+    break_even: bool,
+}
+
+impl ExitTracker {
+    /// Estado al abrir la posición al precio `entry_price` en la vela `entry_index`
+    ///
+    /// El extremo parte del precio de entrada. Los stops basados en ATR quedan sin
+    /// definir hasta llamar a `refresh_indicator_stops` con la última vela cerrada.
+    pub(crate) fn new(side: PositionSide, entry_price: f64, entry_index: usize) -> Self {
+        Self {
+            side,
+            entry_index,
+            extreme: entry_price,
+            atr_stop: None,
+            chandelier_stop: None,
+            break_even: false,
+        }
+    }
+
+    /// Primer nivel tocado por la vela: take profit primero y después el stop más cercano
+    ///
+    /// Los niveles se ejecutan al precio exacto, sin slippage.
+    pub(crate) fn check_levels(&self, entry_price: f64, high: f64, low: f64, config: &BacktestConfig) -> Option<(f64, ExitReason)> {
+        let long = self.side == PositionSide::Long;
+        // Precio a `percent` de `base` en la dirección favorable (negativo = adversa)
+        let offset = |base: f64, percent: f64| if long { base * (1.0 + percent) } else { base * (1.0 - percent) };
+        let touched = |price: f64, favorable: bool| if long == favorable { high >= price } else { low <= price };
+
+        if let Some(tp) = config.take_profit_percent {
+            let price = offset(entry_price, tp);
+            if touched(price, true) {
+                return Some((price, ExitReason::TakeProfit));
+            }
+        }
+
+        // En empate gana la primera regla de la lista
+        let stops = [
+            (config.stop_loss_percent.map(|sl| offset(entry_price, -sl)), ExitReason::StopLoss),
+            (self.break_even.then_some(entry_price), ExitReason::BreakEven),
+            (config.trailing_stop_percent.map(|p| offset(self.extreme, -p)), ExitReason::TrailingStop),
+            (self.atr_stop, ExitReason::TrailingStop),
+            (self.chandelier_stop, ExitReason::ChandelierExit),
+        ];
+        stops
+            .into_iter()
+            .filter_map(|(price, reason)| price.map(|price| (price, reason)))
+            .fold(None, |best: Option<(f64, ExitReason)>, (price, reason)| match best {
+                Some((best_price, _)) if (long && price <= best_price) || (!long && price >= best_price) => best,
+                _ => Some((price, reason)),
+            })
+            .filter(|(price, _)| touched(*price, false))
+    }
+
+    /// Salida por tiempo al cierre de la vela `index`
+    pub(crate) fn check_timed(
+        &self,
+        index: usize,
+        timestamp: i64,
+        next_timestamp: Option<i64>,
+        config: &BacktestConfig,
+    ) -> Option<ExitReason> {
+        if config.max_bars_in_trade.is_some_and(|max| index.saturating_sub(self.entry_index) >= max) {
+            return Some(ExitReason::MaxBars);
+        }
+        session_closes_after(timestamp, next_timestamp, config).then_some(ExitReason::SessionClose)
+    }
+
+    /// Actualiza los niveles con la vela `index` ya cerrada (se usan desde la siguiente)
+    pub(crate) fn update(
+        &mut self,
+        index: usize,
+        entry_price: f64,
+        high: f64,
+        low: f64,
+        indicators: &ExitIndicators,
+        config: &BacktestConfig,
+    ) {
+        let long = self.side == PositionSide::Long;
+        self.extreme = if long { self.extreme.max(high) } else { self.extreme.min(low) };
+
+        if let Some(trigger) = config.break_even_trigger_percent {
+            let excursion = if long { self.extreme / entry_price - 1.0 } else { 1.0 - self.extreme / entry_price };
+            self.break_even |= excursion >= trigger;
+        }
+
+        self.refresh_indicator_stops(index, indicators, config);
+    }
+
+    /// Recalcula los stops basados en ATR con la vela `index` ya cerrada
+    pub(crate) fn refresh_indicator_stops(&mut self, index: usize, indicators: &ExitIndicators, config: &BacktestConfig) {
+        let long = self.side == PositionSide::Long;
+        let Some(atr) = indicators.atr(index) else {
+            return;
+        };
+        let close = indicators.closes[index];
+
+        if let Some(multiple) = config.trailing_stop_atr_multiple {
+            let level = if long { close - multiple * atr } else { close + multiple * atr };
+            // El trailing stop solo se acerca al precio
+            self.atr_stop = Some(match self.atr_stop {
+                Some(previous) if long => previous.max(level),
+                Some(previous) => previous.min(level),
+                None => level,
+            });
+        }
+
+        if let Some(multiple) = config.chandelier_atr_multiple {
+            self.chandelier_stop = Some(if long {
+                indicators.highest[index] - multiple * atr
+            } else {
+                indicators.lowest[index] + multiple * atr
+            });
+        }
+    }
+}
+
+/// Indica si el cierre de sesión cae entre la vela `timestamp` y la siguiente
+///
+/// En ese caso la vela es la última de la sesión: las posiciones se cierran a su
+/// cierre y no se abren nuevas.
+pub(crate) fn session_closes_after(timestamp: i64, next_timestamp: Option<i64>, config: &BacktestConfig) -> bool {
+    let (Some(minute), Some(next_timestamp)) = (config.session_close_minute, next_timestamp) else {
+        return false;
+    };
+    let offset = minute as i64 * 60 * 1000;
+    let next_close = (timestamp - offset).div_euclid(DAY_MS) * DAY_MS + offset + DAY_MS;
+    next_timestamp >= next_close
+}
@@ -2,7 +2,7 @@
 //!
 //! Este crate proporciona dos motores de backtest:
 //! 1. **Polars Engine**: Motor vectorizado para backtest masivo
-//! 2. **Event-Driven Engine**: Motor tick-by-tick para simulación realista
+//! 2. **Event-Driven Engine**: Motor vela a vela con órdenes para simulación realista
 //!
 //! # Principios de Diseño
 //!
@@ -18,13 +18,46 @@ pub mod metrics;
 pub mod polars_engine;
 pub mod event_driven;
 pub mod config;
+pub mod walk_forward;
+pub mod monte_carlo;
+pub mod sizing;
+pub mod exits;
+pub mod positions;
+pub mod portfolio;
+pub mod costs;
+pub mod margin;
+pub mod scoring;
 
 // Re-exports
 pub use error::BacktestError;
-pub use types::{BacktestResult, BacktestMetrics, Trade, EquityPoint, BacktestMetadata};
+pub use types::{BacktestResult, BacktestMetrics, Trade, EquityPoint, BacktestMetadata, ExitReason, ExitReasonStats};
 pub use data_provider::{DataProvider, SingleTimeFrameProvider, MultiTimeFrameProvider};
 pub use config::BacktestConfig;
+pub use sizing::{
+    PositionSizer, PositionSizing, SizingContext, FixedFractional, FixedQuantity,
+    VolatilityTarget, RiskPerTrade, KellyFraction,
+};
+pub use positions::{PyramidingConfig, ScaleOutLevel, CloseOrder};
+pub use costs::{CostModel, FeeModel, FeeTier, SlippageModel, FundingRate, FundingRates, Liquidity};
+pub use margin::{MarginConfig, MarginMode};
+pub use scoring::{
+    ScoreSpec, ScoreTerm, ScoreMetric, ScoreConstraint, Normalization, RankingMethod, RankedResult,
+};
 pub use polars_engine::PolarsBacktestEngine;
 pub use polars_engine::vectorized::{Strategy, BacktestEngine};
 pub use polars_engine::massive::PolarsVectorizedBacktestEngine;
-pub use event_driven::EventDrivenBacktestEngine;
+pub use polars_engine::indicator_cache::{IndicatorCache, IndicatorCacheStats, DEFAULT_INDICATOR_CACHE_BUDGET};
+pub use polars_engine::parallel::{ParallelConfig, BacktestProgress, ProgressCallback, MassiveBacktestStream, EquityCurveOutput};
+pub use event_driven::{EventDrivenBacktestEngine, EventDrivenConfig, AstInterpreter};
+pub use walk_forward::{
+    WalkForwardConfig, WalkForwardMode, WalkForwardWindow, WalkForwardReport,
+    WalkForwardWindowReport, WalkForwardStrategyResult, walk_forward_efficiency,
+};
+pub use monte_carlo::{
+    MonteCarloConfig, MonteCarloMethod, MonteCarloReport, MonteCarloMethodReport,
+    MonteCarloSummary, ConfidenceInterval, run_monte_carlo,
+};
+pub use portfolio::{
+    PortfolioConfig, PortfolioSleeve, PortfolioReport, PortfolioSleeveReport,
+    SymbolAttribution, CorrelationMatrix, RebalanceRule,
+};
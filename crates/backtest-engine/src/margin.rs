@@ -0,0 +1,188 @@
+//! Apalancamiento, margen y liquidaciones de perpetuos
+//!
+//! Con `BacktestConfig::margin` definido, el motor masivo exige solo el margen
+//! inicial (`valor / leverage`) para abrir posiciones y liquida las que tocan su
+//! precio de liquidación:
+//!
+//! - **Aislado**: cada posición respalda sus pérdidas solo con su margen inicial.
+//! - **Cruzado**: todo el balance de la cuenta respalda las posiciones abiertas,
+//!   que se liquidan juntas.
+//!
+//! La liquidación se ejecuta al precio de liquidación como orden taker y paga
+//! además `liquidation_fee_rate` sobre el valor liquidado. Los costes que se
+//! devengan durante el trade (funding, préstamo en corto) no mueven el precio de
+//! liquidación.
+
+use serde::{Deserialize, Serialize};
+use darwinx_core::PositionSide;
+
+/// Modo de margen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarginMode {
+    /// Margen aislado por posición
+    #[default]
+    Isolated,
+    /// Margen cruzado sobre el balance de la cuenta
+    Cross,
+}
+
+impl MarginMode {
+    /// Nombre usado en logs y persistencia
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarginMode::Isolated => "isolated",
+            MarginMode::Cross => "cross",
+        }
+    }
+}
+
+/// Configuración de margen y liquidación
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarginConfig {
+    /// Apalancamiento máximo: el margen inicial es el valor de la posición entre `leverage`
+    pub leverage: f64,
+    pub mode: MarginMode,
+    /// Margen de mantenimiento sobre el valor de la posición (ej: 0.005 = 0.5%)
+    pub maintenance_margin_rate: f64,
+    /// Comisión de liquidación sobre el valor liquidado (ej: 0.005 = 0.5%)
+    pub liquidation_fee_rate: f64,
+}
+
+impl Default for MarginConfig {
+    fn default() -> Self {
+        Self {
+            leverage: 1.0,
+            mode: MarginMode::Isolated,
+            maintenance_margin_rate: 0.005,
+            liquidation_fee_rate: 0.0,
+        }
+    }
+}
+
+impl MarginConfig {
+    /// Margen aislado con el apalancamiento dado
+    pub fn isolated(leverage: f64) -> Self {
+        Self { leverage, ..Self::default() }
+    }
+
+    /// Margen cruzado con el apalancamiento dado
+    pub fn cross(leverage: f64) -> Self {
+        Self { leverage, mode: MarginMode::Cross, ..Self::default() }
+    }
+
+    /// Define el margen de mantenimiento
+    pub fn with_maintenance_margin_rate(mut self, rate: f64) -> Self {
+        self.maintenance_margin_rate = rate;
+        self
+    }
+
+    /// Define la comisión de liquidación
+    pub fn with_liquidation_fee_rate(mut self, rate: f64) -> Self {
+        self.liquidation_fee_rate = rate;
+        self
+    }
+
+    /// Margen inicial de una posición de valor `notional`
+    pub fn initial_margin(&self, notional: f64) -> f64 {
+        notional / self.leverage.max(1.0)
+    }
+
+    /// Comisión de liquidación de un valor liquidado `notional`
+    pub fn liquidation_fee(&self, notional: f64) -> f64 {
+        notional * self.liquidation_fee_rate
+    }
+
+    /// Precio de liquidación de una posición aislada con precio medio `entry_price`
+    ///
+    /// Es el precio en el que el margen inicial más el P&L no realizado iguala el
+    /// margen de mantenimiento.
+    pub fn isolated_liquidation_price(&self, side: PositionSide, entry_price: f64) -> f64 {
+        let inverse_leverage = 1.0 / self.leverage.max(1.0);
+        let mmr = self.maintenance_margin_rate;
+        match side {
+            PositionSide::Long => entry_price * (1.0 - inverse_leverage) / (1.0 - mmr),
+            PositionSide::Short => entry_price * (1.0 + inverse_leverage) / (1.0 + mmr),
+        }
+    }
+
+    /// Precio de liquidación en margen cruzado de posiciones del mismo lado
+    ///
+    /// `balance` es el balance de la cuenta sin P&L no realizado, `size` el tamaño
+    /// total y `notional` su valor a precio de entrada. Devuelve `None` si el
+    /// balance cubre cualquier caída (largos que no pueden liquidarse).
+    pub fn cross_liquidation_price(&self, side: PositionSide, balance: f64, size: f64, notional: f64) -> Option<f64> {
+        if size <= 0.0 {
+            return None;
+        }
+        let mmr = self.maintenance_margin_rate;
+        let price = match side {
+            PositionSide::Long => (notional - balance) / (size * (1.0 - mmr)),
+            PositionSide::Short => (notional + balance) / (size * (1.0 + mmr)),
+        };
+        (price > 0.0).then_some(price)
+    }
+
+    /// Distancia relativa desde `price` hasta el precio de liquidación (0 = liquidada)
+    pub(crate) fn liquidation_distance(side: PositionSide, price: f64, liquidation_price: f64) -> f64 {
+        if price <= 0.0 {
+            return 0.0;
+        }
+        let distance = match side {
+            PositionSide::Long => (price - liquidation_price) / price,
+            PositionSide::Short => (liquidation_price - price) / price,
+        };
+        distance.max(0.0)
+    }
+
+    /// Indica si el precio de liquidación se tocó en una vela con extremos `high` y `low`
+    pub(crate) fn touched(side: PositionSide, liquidation_price: f64, high: f64, low: f64) -> bool {
+        match side {
+            PositionSide::Long => low <= liquidation_price,
+            PositionSide::Short => high >= liquidation_price,
+        }
+    }
+}
+
+/// Uso de margen acumulado vela a vela durante una simulación
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct MarginStats {
+    max_usage: f64,
+    usage_sum: f64,
+    bars_in_market: usize,
+    min_liquidation_distance: Option<f64>,
+}
+
+impl MarginStats {
+    /// Registra el margen usado sobre el equity al cierre de una vela con posiciones abiertas
+    pub(crate) fn record_usage(&mut self, used_margin: f64, equity: f64) {
+        if used_margin <= 0.0 {
+            return;
+        }
+        let usage = if equity > 0.0 { used_margin / equity } else { f64::INFINITY };
+        self.max_usage = self.max_usage.max(usage);
+        self.usage_sum += usage;
+        self.bars_in_market += 1;
+    }
+
+    /// Registra la distancia a la liquidación en el precio más adverso de una vela
+    pub(crate) fn record_distance(&mut self, distance: f64) {
+        self.min_liquidation_distance = Some(self.min_liquidation_distance.map_or(distance, |d| d.min(distance)));
+    }
+
+    /// Uso máximo de margen sobre el equity
+    pub(crate) fn max_usage(&self) -> f64 {
+        self.max_usage
+    }
+
+    /// Uso medio de margen en las velas con posiciones abiertas
+    pub(crate) fn average_usage(&self) -> f64 {
+        if self.bars_in_market > 0 { self.usage_sum / self.bars_in_market as f64 } else { 0.0 }
+    }
+
+    /// Menor distancia a la liquidación observada (None si nunca hubo posiciones con margen)
+    pub(crate) fn min_liquidation_distance(&self) -> Option<f64> {
+        self.min_liquidation_distance
+    }
+}
@@ -3,8 +3,14 @@
 pub mod returns;
 pub mod risk;
 pub mod statistics;
+pub mod significance;
+pub mod equity;
+pub mod benchmark;
 
 pub use returns::*;
 pub use risk::*;
 pub use statistics::*;
+pub use significance::*;
+pub use equity::*;
+pub use benchmark::*;
 
@@ -0,0 +1,110 @@
+//! Comparación contra el benchmark de comprar y mantener
+
+use crate::types::{BacktestMetrics, EquityPoint};
+
+const YEAR_MS: f64 = 365.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+
+/// Métricas de una serie de retornos frente a un benchmark
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BenchmarkComparison {
+    /// Retorno total compuesto del benchmark
+    pub benchmark_return: f64,
+    /// Alpha de Jensen anualizado
+    pub alpha: f64,
+    pub beta: f64,
+    /// Retorno activo medio sobre el tracking error (por vela, como el Sharpe)
+    pub information_ratio: f64,
+    /// Desviación típica del retorno activo por vela
+    pub tracking_error: f64,
+    /// Retorno medio en velas alcistas del benchmark sobre el del benchmark
+    pub up_capture: f64,
+    /// Retorno medio en velas bajistas del benchmark sobre el del benchmark
+    pub down_capture: f64,
+}
+
+/// Retornos por vela de comprar y mantener (un retorno menos que velas)
+pub fn calculate_buy_and_hold_returns(closes: &[f64]) -> Vec<f64> {
+    closes
+        .windows(2)
+        .map(|w| if w[0] > 0.0 { w[1] / w[0] - 1.0 } else { 0.0 })
+        .collect()
+}
+
+/// Compara retornos por vela de igual longitud con los del benchmark
+///
+/// `periods_per_year` anualiza el alpha. Con menos de dos observaciones o un
+/// benchmark constante la beta, el alpha y las capturas quedan a 0.
+pub fn calculate_benchmark_comparison(
+    returns: &[f64],
+    benchmark_returns: &[f64],
+    periods_per_year: f64,
+) -> BenchmarkComparison {
+    let n = returns.len().min(benchmark_returns.len());
+    let (returns, benchmark_returns) = (&returns[..n], &benchmark_returns[..n]);
+    let benchmark_return = benchmark_returns.iter().fold(1.0, |acc, r| acc * (1.0 + r)) - 1.0;
+    if n < 2 {
+        return BenchmarkComparison { benchmark_return, ..Default::default() };
+    }
+
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let mean_return = mean(returns);
+    let mean_benchmark = mean(benchmark_returns);
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (r, b) in returns.iter().zip(benchmark_returns) {
+        covariance += (r - mean_return) * (b - mean_benchmark);
+        variance += (b - mean_benchmark).powi(2);
+    }
+    let beta = if variance > 0.0 { covariance / variance } else { 0.0 };
+    let alpha = (mean_return - beta * mean_benchmark) * periods_per_year;
+
+    let active: Vec<f64> = returns.iter().zip(benchmark_returns).map(|(r, b)| r - b).collect();
+    let mean_active = mean(&active);
+    let tracking_error = (active.iter().map(|a| (a - mean_active).powi(2)).sum::<f64>() / n as f64).sqrt();
+    let information_ratio = if tracking_error > 0.0 { mean_active / tracking_error } else { 0.0 };
+
+    // Media de la estrategia sobre media del benchmark en las velas que cumplen `filter`
+    let capture = |filter: fn(f64) -> bool| {
+        let (sum, sum_benchmark) = returns
+            .iter()
+            .zip(benchmark_returns)
+            .filter(|(_, b)| filter(**b))
+            .fold((0.0, 0.0), |(s, sb), (r, b)| (s + r, sb + b));
+        if sum_benchmark != 0.0 { sum / sum_benchmark } else { 0.0 }
+    };
+
+    BenchmarkComparison {
+        benchmark_return,
+        alpha,
+        beta,
+        information_ratio,
+        tracking_error,
+        up_capture: capture(|b| b > 0.0),
+        down_capture: capture(|b| b < 0.0),
+    }
+}
+
+/// Completa las métricas de un backtest con la comparación contra comprar y mantener
+///
+/// `equity_curve` y `closes` corresponden a las mismas velas. El retorno en exceso
+/// es el `total_return` de las métricas menos el del benchmark.
+pub fn apply_buy_and_hold_benchmark(metrics: &mut BacktestMetrics, equity_curve: &[EquityPoint], closes: &[f64]) {
+    let returns: Vec<f64> = equity_curve
+        .windows(2)
+        .map(|w| if w[0].balance != 0.0 { (w[1].balance - w[0].balance) / w[0].balance } else { 0.0 })
+        .collect();
+    let span_ms = match (equity_curve.first(), equity_curve.last()) {
+        (Some(first), Some(last)) => (last.timestamp - first.timestamp) as f64,
+        _ => 0.0,
+    };
+    let periods_per_year = if span_ms > 0.0 { returns.len() as f64 * YEAR_MS / span_ms } else { 0.0 };
+
+    let comparison = calculate_benchmark_comparison(&returns, &calculate_buy_and_hold_returns(closes), periods_per_year);
+    metrics.benchmark_return = comparison.benchmark_return;
+    metrics.excess_return = metrics.total_return - comparison.benchmark_return;
+    metrics.alpha = comparison.alpha;
+    metrics.beta = comparison.beta;
+    metrics.information_ratio = comparison.information_ratio;
+    metrics.tracking_error = comparison.tracking_error;
+    metrics.up_capture = comparison.up_capture;
+    metrics.down_capture = comparison.down_capture;
+}
@@ -0,0 +1,102 @@
+//! Curva de equity mark-to-market
+
+use crate::config::BacktestConfig;
+use crate::types::{EquityPoint, Trade};
+
+/// Construye la curva de equity mark-to-market vela a vela
+///
+/// La equity de cada vela es el balance inicial más el P&L realizado de los
+/// trades cerrados hasta esa vela (menos sus comisiones de entrada) más el P&L no
+/// realizado de las posiciones abiertas al cierre. Un trade cuenta como abierto
+/// desde la vela de su entrada y como realizado en la vela de su salida. El costo
+/// de préstamo de los cortos solo se refleja al cerrar.
+///
+/// `trades` puede solaparse en el tiempo (varias posiciones o lotes abiertos).
+pub fn calculate_mark_to_market_equity(
+    trades: &[Trade],
+    timestamps: &[i64],
+    closes: &[f64],
+    config: &BacktestConfig,
+) -> Vec<EquityPoint> {
+    let mut curve = Vec::with_capacity(timestamps.len());
+    // Con varias posiciones los trades se registran al cerrarse: se recorren por entrada
+    let mut trades: Vec<&Trade> = trades.iter().collect();
+    trades.sort_by_key(|trade| trade.entry_timestamp);
+    let mut open: Vec<&Trade> = Vec::new();
+    let mut next_trade = 0;
+    let mut realized = config.initial_balance;
+    let mut peak = config.initial_balance;
+
+    for (&timestamp, &close) in timestamps.iter().zip(closes) {
+        while let Some(&trade) = trades.get(next_trade).filter(|t| t.entry_timestamp <= timestamp) {
+            realized -= trade.entry_commission;
+            open.push(trade);
+            next_trade += 1;
+        }
+        open.retain(|trade| {
+            let closed = trade.exit_timestamp <= timestamp;
+            if closed {
+                realized += trade.pnl;
+            }
+            !closed
+        });
+
+        let unrealized: f64 = open
+            .iter()
+            .map(|trade| {
+                let price_move = (close - trade.entry_price) * trade.size;
+                if trade.is_long { price_move } else { -price_move }
+            })
+            .sum();
+        let balance = realized + unrealized;
+        peak = peak.max(balance);
+        curve.push(EquityPoint {
+            timestamp,
+            balance,
+            drawdown: if peak > 0.0 { (peak - balance) / peak } else { 0.0 },
+        });
+    }
+
+    curve
+}
+
+/// Reduce la curva de equity a como máximo `max_points` puntos
+///
+/// Divide la curva en bloques y conserva de cada uno el mínimo y el máximo de
+/// equity (en orden temporal), de modo que los picos y valles que definen el
+/// drawdown sobreviven. El primer y el último punto se conservan siempre.
+pub fn downsample_equity_curve(curve: &[EquityPoint], max_points: usize) -> Vec<EquityPoint> {
+    if curve.len() <= max_points.max(2) {
+        return curve.to_vec();
+    }
+    let max_points = max_points.max(2);
+    if max_points < 4 {
+        return vec![curve[0].clone(), curve[curve.len() - 1].clone()];
+    }
+
+    let inner = &curve[1..curve.len() - 1];
+    let buckets = (max_points - 2) / 2;
+    let bucket_size = inner.len().div_ceil(buckets);
+
+    let mut sampled = Vec::with_capacity(max_points);
+    sampled.push(curve[0].clone());
+    for bucket in inner.chunks(bucket_size) {
+        let (mut min_index, mut max_index) = (0, 0);
+        for (i, point) in bucket.iter().enumerate() {
+            if point.balance < bucket[min_index].balance {
+                min_index = i;
+            }
+            if point.balance > bucket[max_index].balance {
+                max_index = i;
+            }
+        }
+        let (first, second) = (min_index.min(max_index), min_index.max(max_index));
+        sampled.push(bucket[first].clone());
+        if second != first {
+            sampled.push(bucket[second].clone());
+        }
+    }
+    sampled.push(curve[curve.len() - 1].clone());
+
+    sampled
+}
@@ -70,3 +70,26 @@ pub fn calculate_sortino_ratio(returns: &[f64], risk_free_rate: f64) -> f64 {
     excess_return / downside_std
 }
 
+/// Correlación de Pearson entre dos series de retornos de igual longitud
+///
+/// Retorna 0.0 si alguna serie es constante o tiene menos de dos observaciones.
+pub fn calculate_correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len().min(b.len());
+    if n < 2 {
+        return 0.0;
+    }
+
+    let mean_a = a[..n].iter().sum::<f64>() / n as f64;
+    let mean_b = b[..n].iter().sum::<f64>() / n as f64;
+    let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a[..n].iter().zip(&b[..n]) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a).powi(2);
+        variance_b += (y - mean_b).powi(2);
+    }
+
+    if variance_a == 0.0 || variance_b == 0.0 {
+        return 0.0;
+    }
+    covariance / (variance_a * variance_b).sqrt()
+}
@@ -0,0 +1,273 @@
+//! Significancia estadística del Sharpe y corrección por pruebas múltiples
+//!
+//! Al generar miles de estrategias aleatorias, la mejor tiene un Sharpe alto
+//! aunque ninguna tenga ventaja real. Estas métricas corrigen ese sesgo:
+//!
+//! - **PSR** (Probabilistic Sharpe Ratio): probabilidad de que el Sharpe real
+//!   supere un Sharpe de referencia, considerando el número de observaciones y
+//!   la asimetría/curtosis de los retornos (Bailey & López de Prado, 2012)
+//! - **DSR** (Deflated Sharpe Ratio): PSR contra el Sharpe máximo esperado entre
+//!   N intentos sin habilidad (Bailey & López de Prado, 2014)
+//! - **Reality Check / SPA**: p-valor por bootstrap estacionario de que la mejor
+//!   estrategia de un conjunto no supere al benchmark (White, 2000; Hansen, 2005)
+//!
+//! Los Sharpe de este módulo no están anualizados: se expresan en las mismas
+//! unidades que los retornos de los que se calculan.
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{ContinuousCDF, Normal};
+use crate::error::BacktestError;
+use crate::types::Trade;
+
+/// Constante de Euler-Mascheroni
+const EULER_MASCHERONI: f64 = 0.577_215_664_901_532_9;
+
+/// Calcula la asimetría (skewness) de los retornos
+pub fn calculate_skewness(returns: &[f64]) -> f64 {
+    let (mean, std_dev) = mean_and_std(returns);
+    if std_dev == 0.0 {
+        return 0.0;
+    }
+    returns.iter().map(|r| ((r - mean) / std_dev).powi(3)).sum::<f64>() / returns.len() as f64
+}
+
+/// Calcula la curtosis (no excesiva, normal = 3) de los retornos
+pub fn calculate_kurtosis(returns: &[f64]) -> f64 {
+    let (mean, std_dev) = mean_and_std(returns);
+    if std_dev == 0.0 {
+        return 3.0;
+    }
+    returns.iter().map(|r| ((r - mean) / std_dev).powi(4)).sum::<f64>() / returns.len() as f64
+}
+
+/// Calcula el Probabilistic Sharpe Ratio
+///
+/// Probabilidad de que el Sharpe real supere `benchmark_sharpe` dado el Sharpe
+/// observado sobre `observations` retornos con la asimetría y curtosis dadas.
+pub fn calculate_probabilistic_sharpe_ratio(
+    sharpe_ratio: f64,
+    benchmark_sharpe: f64,
+    observations: usize,
+    skewness: f64,
+    kurtosis: f64,
+) -> f64 {
+    if observations < 2 {
+        return 0.0;
+    }
+
+    let variance = 1.0 - skewness * sharpe_ratio + (kurtosis - 1.0) / 4.0 * sharpe_ratio.powi(2);
+    if variance <= 0.0 {
+        return 0.0;
+    }
+
+    let z = (sharpe_ratio - benchmark_sharpe) * ((observations - 1) as f64).sqrt() / variance.sqrt();
+    standard_normal().cdf(z)
+}
+
+/// Sharpe máximo esperado entre `trials` estrategias sin habilidad
+///
+/// `sharpe_variance` es la varianza de los Sharpe observados entre los intentos.
+pub fn calculate_expected_max_sharpe(trials: usize, sharpe_variance: f64) -> f64 {
+    if trials < 2 || sharpe_variance <= 0.0 {
+        return 0.0;
+    }
+
+    let normal = standard_normal();
+    let n = trials as f64;
+    sharpe_variance.sqrt()
+        * ((1.0 - EULER_MASCHERONI) * normal.inverse_cdf(1.0 - 1.0 / n)
+            + EULER_MASCHERONI * normal.inverse_cdf(1.0 - 1.0 / (n * std::f64::consts::E)))
+}
+
+/// Calcula el Deflated Sharpe Ratio
+///
+/// PSR usando como referencia el Sharpe máximo esperado entre `trials` intentos.
+pub fn calculate_deflated_sharpe_ratio(
+    sharpe_ratio: f64,
+    observations: usize,
+    skewness: f64,
+    kurtosis: f64,
+    trials: usize,
+    sharpe_variance: f64,
+) -> f64 {
+    let expected_max = calculate_expected_max_sharpe(trials, sharpe_variance);
+    calculate_probabilistic_sharpe_ratio(sharpe_ratio, expected_max, observations, skewness, kurtosis)
+}
+
+/// Retornos por período a partir del P&L realizado de los trades
+///
+/// Cada trade aporta su P&L al período en que cierra (el primer timestamp mayor
+/// o igual a su salida); el retorno del período es relativo al balance al inicio
+/// del mismo. Devuelve un retorno por timestamp, alineado entre estrategias.
+pub fn calculate_period_returns(trades: &[Trade], initial_balance: f64, timestamps: &[i64]) -> Vec<f64> {
+    let mut pnl_by_period = vec![0.0; timestamps.len()];
+    for trade in trades {
+        let index = timestamps.partition_point(|ts| *ts < trade.exit_timestamp);
+        if let Some(pnl) = pnl_by_period.get_mut(index) {
+            *pnl += trade.pnl;
+        }
+    }
+
+    let mut balance = initial_balance;
+    pnl_by_period
+        .into_iter()
+        .map(|pnl| {
+            let period_return = if balance > 0.0 { pnl / balance } else { 0.0 };
+            balance += pnl;
+            period_return
+        })
+        .collect()
+}
+
+/// Configuración del test Reality Check / SPA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealityCheckConfig {
+    /// Remuestreos bootstrap
+    pub bootstrap_iterations: usize,
+    /// Longitud media de bloque del bootstrap estacionario
+    pub mean_block_length: f64,
+    /// Estadístico studentizado con recentrado consistente (SPA de Hansen);
+    /// false = Reality Check de White
+    pub studentized: bool,
+    /// Semilla para resultados reproducibles (None = aleatoria)
+    pub seed: Option<u64>,
+}
+
+impl Default for RealityCheckConfig {
+    fn default() -> Self {
+        Self {
+            bootstrap_iterations: 1000,
+            mean_block_length: 10.0,
+            studentized: true,
+            seed: None,
+        }
+    }
+}
+
+/// Resultado del test Reality Check / SPA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealityCheckResult {
+    /// Estadístico observado (máximo sobre las estrategias)
+    pub statistic: f64,
+    /// Probabilidad de observar un estadístico igual o mayor si ninguna
+    /// estrategia supera al benchmark
+    pub p_value: f64,
+    /// Índice de la estrategia con mayor estadístico
+    pub best_index: usize,
+}
+
+/// Test Reality Check / SPA de la mejor estrategia contra un benchmark
+///
+/// `strategy_returns` contiene una serie de retornos por estrategia, todas
+/// alineadas con `benchmark_returns` (ver `calculate_period_returns`).
+pub fn calculate_reality_check(
+    strategy_returns: &[Vec<f64>],
+    benchmark_returns: &[f64],
+    config: &RealityCheckConfig,
+) -> Result<RealityCheckResult, BacktestError> {
+    let n = benchmark_returns.len();
+    if strategy_returns.is_empty() {
+        return Err(BacktestError::MetricsError("Reality Check requires at least one strategy".to_string()));
+    }
+    if n < 2 {
+        return Err(BacktestError::MetricsError("Reality Check requires at least 2 observations".to_string()));
+    }
+    if strategy_returns.iter().any(|r| r.len() != n) {
+        return Err(BacktestError::MetricsError(
+            "Strategy returns must be aligned with the benchmark returns".to_string(),
+        ));
+    }
+    if config.bootstrap_iterations == 0 || config.mean_block_length < 1.0 {
+        return Err(BacktestError::ConfigError(
+            "Reality Check needs bootstrap_iterations > 0 and mean_block_length >= 1".to_string(),
+        ));
+    }
+
+    // Diferenciales de retorno contra el benchmark
+    let differentials: Vec<Vec<f64>> = strategy_returns
+        .iter()
+        .map(|returns| returns.iter().zip(benchmark_returns).map(|(r, b)| r - b).collect())
+        .collect();
+    let root_n = (n as f64).sqrt();
+    let moments: Vec<(f64, f64)> = differentials.iter().map(|d| mean_and_std(d)).collect();
+    let scales: Vec<f64> = moments
+        .iter()
+        .map(|(_, std_dev)| if config.studentized { std_dev.max(f64::EPSILON) } else { 1.0 })
+        .collect();
+
+    // Recentrado: el SPA no recentra las estrategias claramente peores que el
+    // benchmark para que no inflen el p-valor
+    let threshold = (2.0 * (n as f64).ln().ln()).max(0.0).sqrt();
+    let centers: Vec<f64> = moments
+        .iter()
+        .zip(&scales)
+        .map(|((mean, _), scale)| {
+            if config.studentized && root_n * mean / scale < -threshold { 0.0 } else { *mean }
+        })
+        .collect();
+
+    let (best_index, best_statistic) = moments
+        .iter()
+        .zip(&scales)
+        .map(|((mean, _), scale)| root_n * mean / scale)
+        .enumerate()
+        .fold((0, f64::NEG_INFINITY), |best, (i, stat)| if stat > best.1 { (i, stat) } else { best });
+    let statistic = if config.studentized { best_statistic.max(0.0) } else { best_statistic };
+
+    let base_seed = config.seed.unwrap_or_else(|| rand::rng().random());
+    let restart_probability = 1.0 / config.mean_block_length;
+    let exceedances = (0..config.bootstrap_iterations)
+        .into_par_iter()
+        .filter(|b| {
+            let mut rng = StdRng::seed_from_u64(base_seed.wrapping_add(*b as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let indices = stationary_bootstrap_indices(n, restart_probability, &mut rng);
+            let bootstrap_statistic = differentials
+                .iter()
+                .zip(centers.iter().zip(&scales))
+                .map(|(d, (center, scale))| {
+                    let mean = indices.iter().map(|i| d[*i]).sum::<f64>() / n as f64;
+                    root_n * (mean - center) / scale
+                })
+                .fold(f64::NEG_INFINITY, f64::max);
+            let bootstrap_statistic = if config.studentized { bootstrap_statistic.max(0.0) } else { bootstrap_statistic };
+            bootstrap_statistic >= statistic
+        })
+        .count();
+
+    Ok(RealityCheckResult {
+        statistic,
+        p_value: exceedances as f64 / config.bootstrap_iterations as f64,
+        best_index,
+    })
+}
+
+/// Índices del bootstrap estacionario de Politis-Romano (bloques de longitud geométrica)
+fn stationary_bootstrap_indices(n: usize, restart_probability: f64, rng: &mut StdRng) -> Vec<usize> {
+    let mut indices = Vec::with_capacity(n);
+    let mut current = rng.random_range(0..n);
+    for _ in 0..n {
+        indices.push(current);
+        current = if rng.random_bool(restart_probability) {
+            rng.random_range(0..n)
+        } else {
+            (current + 1) % n
+        };
+    }
+    indices
+}
+
+fn mean_and_std(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    (mean, variance.sqrt())
+}
+
+fn standard_normal() -> Normal {
+    Normal::standard()
+}
@@ -1,6 +1,6 @@
 //! Estadísticas de trading
 
-use crate::types::Trade;
+use crate::types::{ExitReason, ExitReasonStats, Trade};
 
 /// Calcula el win rate
 pub fn calculate_win_rate(trades: &[Trade]) -> f64 {
@@ -158,3 +158,52 @@ pub fn calculate_total_loss(trades: &[Trade]) -> f64 {
     trades.iter().filter(|t| t.pnl < 0.0).map(|t| t.pnl.abs()).sum()
 }
 
+
+/// Cuenta los trades de un lado (true = long, false = short)
+pub fn count_trades_by_side(trades: &[Trade], is_long: bool) -> usize {
+    trades.iter().filter(|t| t.is_long == is_long).count()
+}
+
+/// Calcula el win rate de los trades de un lado
+pub fn calculate_win_rate_by_side(trades: &[Trade], is_long: bool) -> f64 {
+    let total = count_trades_by_side(trades, is_long);
+    if total == 0 {
+        return 0.0;
+    }
+
+    let winning = trades.iter().filter(|t| t.is_long == is_long && t.pnl > 0.0).count();
+    winning as f64 / total as f64
+}
+
+/// Calcula el P&L neto de los trades de un lado
+pub fn calculate_pnl_by_side(trades: &[Trade], is_long: bool) -> f64 {
+    trades.iter().filter(|t| t.is_long == is_long).map(|t| t.pnl).sum()
+}
+
+/// Cuenta los trades cerrados por una razón
+pub fn count_trades_by_exit_reason(trades: &[Trade], reason: ExitReason) -> usize {
+    trades.iter().filter(|t| t.exit_reason == reason).count()
+}
+
+/// Número de trades, P&L y win rate por razón de salida
+///
+/// Solo incluye las razones con algún trade, en el orden de `ExitReason::ALL`.
+pub fn calculate_exit_reason_breakdown(trades: &[Trade]) -> Vec<ExitReasonStats> {
+    ExitReason::ALL
+        .into_iter()
+        .filter_map(|reason| {
+            let (count, winning, pnl) = trades
+                .iter()
+                .filter(|t| t.exit_reason == reason)
+                .fold((0, 0, 0.0), |(count, winning, pnl), t| {
+                    (count + 1, winning + usize::from(t.pnl > 0.0), pnl + t.pnl)
+                });
+            (count > 0).then(|| ExitReasonStats {
+                reason,
+                trades: count,
+                pnl,
+                win_rate: winning as f64 / count as f64,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,360 @@
+//! Análisis de robustez Monte Carlo
+//!
+//! Re-simula la secuencia de trades de un `BacktestResult` miles de veces para
+//! estimar qué parte del resultado depende del orden y la suerte:
+//!
+//! - **Shuffle**: permuta el orden de los trades (mismo P&L total, distinto camino)
+//! - **Bootstrap**: remuestrea los trades con reemplazo
+//! - **SkipTrades**: descarta cada trade con cierta probabilidad (señales perdidas)
+//! - **CostPerturbation**: añade comisión/slippage aleatorio a cada trade
+//!
+//! Cada método produce intervalos de confianza del balance final, del máximo
+//! drawdown y del Sharpe, junto con la probabilidad de ruina (alcanzar un
+//! drawdown mayor o igual al umbral configurado).
+//!
+//! Las simulaciones usan P&L aditivo por trade, igual que los motores de backtest,
+//! y descuentan la comisión de entrada que el P&L del trade no incluye.
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::error::BacktestError;
+use crate::metrics::calculate_sharpe_ratio;
+use crate::types::{BacktestResult, Trade};
+
+/// Método de re-simulación
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MonteCarloMethod {
+    /// Permuta el orden de los trades
+    Shuffle,
+    /// Remuestrea los trades con reemplazo
+    Bootstrap,
+    /// Descarta trades al azar
+    SkipTrades,
+    /// Añade costos aleatorios a cada trade
+    CostPerturbation,
+}
+
+impl MonteCarloMethod {
+    /// Todos los métodos disponibles
+    pub const ALL: [MonteCarloMethod; 4] = [
+        MonteCarloMethod::Shuffle,
+        MonteCarloMethod::Bootstrap,
+        MonteCarloMethod::SkipTrades,
+        MonteCarloMethod::CostPerturbation,
+    ];
+
+    /// Nombre legible del método
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MonteCarloMethod::Shuffle => "shuffle",
+            MonteCarloMethod::Bootstrap => "bootstrap",
+            MonteCarloMethod::SkipTrades => "skip_trades",
+            MonteCarloMethod::CostPerturbation => "cost_perturbation",
+        }
+    }
+}
+
+/// Configuración del análisis Monte Carlo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloConfig {
+    /// Simulaciones por método
+    pub iterations: usize,
+    /// Semilla para resultados reproducibles (None = aleatoria)
+    pub seed: Option<u64>,
+    /// Métodos a ejecutar
+    pub methods: Vec<MonteCarloMethod>,
+    /// Probabilidad de descartar cada trade en `SkipTrades` (ej: 0.1 = 10%)
+    pub skip_probability: f64,
+    /// Costo extra máximo por trade como fracción del nocional de entrada+salida
+    /// en `CostPerturbation` (ej: 0.001 = hasta 0.1%)
+    pub cost_perturbation: f64,
+    /// Drawdown desde el máximo que se considera ruina (ej: 0.5 = 50%)
+    pub ruin_threshold: f64,
+    /// Nivel de confianza de los intervalos (ej: 0.90 = percentiles 5-95)
+    pub confidence: f64,
+}
+
+impl Default for MonteCarloConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 1000,
+            seed: None,
+            methods: MonteCarloMethod::ALL.to_vec(),
+            skip_probability: 0.1,
+            cost_perturbation: 0.001,
+            ruin_threshold: 0.5,
+            confidence: 0.90,
+        }
+    }
+}
+
+impl MonteCarloConfig {
+    /// Crea una configuración con el número de simulaciones dado
+    pub fn new(iterations: usize) -> Self {
+        Self {
+            iterations,
+            ..Default::default()
+        }
+    }
+
+    /// Fija la semilla
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Restringe los métodos a ejecutar
+    pub fn with_methods(mut self, methods: Vec<MonteCarloMethod>) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Cambia el umbral de ruina
+    pub fn with_ruin_threshold(mut self, ruin_threshold: f64) -> Self {
+        self.ruin_threshold = ruin_threshold;
+        self
+    }
+
+    fn validate(&self) -> Result<(), BacktestError> {
+        if self.iterations == 0 {
+            return Err(BacktestError::ConfigError("Monte Carlo iterations must be > 0".to_string()));
+        }
+        if self.methods.is_empty() {
+            return Err(BacktestError::ConfigError("At least one Monte Carlo method is required".to_string()));
+        }
+        if !(0.0..1.0).contains(&self.skip_probability) {
+            return Err(BacktestError::ConfigError("skip_probability must be in [0, 1)".to_string()));
+        }
+        if self.cost_perturbation < 0.0 {
+            return Err(BacktestError::ConfigError("cost_perturbation must be >= 0".to_string()));
+        }
+        if self.ruin_threshold <= 0.0 || self.ruin_threshold > 1.0 {
+            return Err(BacktestError::ConfigError("ruin_threshold must be in (0, 1]".to_string()));
+        }
+        if self.confidence <= 0.0 || self.confidence >= 1.0 {
+            return Err(BacktestError::ConfigError("confidence must be in (0, 1)".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Intervalo de confianza de una métrica simulada
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceInterval {
+    /// Percentil inferior
+    pub lower: f64,
+    /// Mediana
+    pub median: f64,
+    /// Percentil superior
+    pub upper: f64,
+    /// Media
+    pub mean: f64,
+}
+
+impl ConfidenceInterval {
+    fn from_samples(samples: &mut [f64], confidence: f64) -> Self {
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let tail = (1.0 - confidence) / 2.0;
+        Self {
+            lower: percentile(samples, tail),
+            median: percentile(samples, 0.5),
+            upper: percentile(samples, 1.0 - tail),
+            mean: samples.iter().sum::<f64>() / samples.len() as f64,
+        }
+    }
+}
+
+/// Resumen de un conjunto de simulaciones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloSummary {
+    /// Número de simulaciones
+    pub iterations: usize,
+    /// Balance final
+    pub final_balance: ConfidenceInterval,
+    /// Máximo drawdown (fracción del máximo de equity)
+    pub max_drawdown: ConfidenceInterval,
+    /// Sharpe por trade
+    pub sharpe_ratio: ConfidenceInterval,
+    /// Fracción de simulaciones que alcanzaron el umbral de ruina
+    pub probability_of_ruin: f64,
+}
+
+/// Resultado de un método de re-simulación
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloMethodReport {
+    /// Método ejecutado
+    pub method: MonteCarloMethod,
+    /// Resumen de sus simulaciones
+    pub summary: MonteCarloSummary,
+}
+
+/// Resultado completo del análisis Monte Carlo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloReport {
+    /// Nombre de la estrategia analizada
+    pub strategy_name: String,
+    /// Configuración usada
+    pub config: MonteCarloConfig,
+    /// Balance inicial
+    pub initial_balance: f64,
+    /// Balance final del backtest original
+    pub original_final_balance: f64,
+    /// Resultado por método
+    pub methods: Vec<MonteCarloMethodReport>,
+    /// Resumen de todas las simulaciones juntas
+    pub combined: MonteCarloSummary,
+}
+
+impl MonteCarloReport {
+    /// Resultado de un método concreto
+    pub fn method(&self, method: MonteCarloMethod) -> Option<&MonteCarloSummary> {
+        self.methods.iter().find(|m| m.method == method).map(|m| &m.summary)
+    }
+}
+
+/// Métricas de una simulación individual
+#[derive(Debug, Clone, Copy)]
+struct SimulationOutcome {
+    final_balance: f64,
+    max_drawdown: f64,
+    sharpe_ratio: f64,
+    ruined: bool,
+}
+
+/// Ejecuta el análisis Monte Carlo sobre los trades de un backtest
+///
+/// Requiere que el resultado conserve sus trades (ver `ParallelConfig::without_trades`).
+pub fn run_monte_carlo(result: &BacktestResult, config: &MonteCarloConfig) -> Result<MonteCarloReport, BacktestError> {
+    config.validate()?;
+    if result.trades.is_empty() {
+        return Err(BacktestError::MetricsError(format!(
+            "Strategy {} has no trades for Monte Carlo analysis",
+            result.strategy_name
+        )));
+    }
+
+    let initial_balance = result.metadata.initial_balance;
+    // P&L neto por trade: el P&L del trade solo descuenta la comisión de salida
+    let net_pnls: Vec<f64> = result.trades.iter().map(Trade::net_pnl).collect();
+    let base_seed = config.seed.unwrap_or_else(|| rand::rng().random());
+
+    let mut methods = Vec::with_capacity(config.methods.len());
+    let mut all_outcomes = Vec::with_capacity(config.methods.len() * config.iterations);
+    for (method_index, method) in config.methods.iter().enumerate() {
+        let outcomes: Vec<SimulationOutcome> = (0..config.iterations)
+            .into_par_iter()
+            .map(|i| {
+                // Una semilla por simulación mantiene el resultado reproducible en paralelo
+                let seed = base_seed
+                    .wrapping_add((method_index * config.iterations + i) as u64)
+                    .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                let mut rng = StdRng::seed_from_u64(seed);
+                let pnls = resample(*method, &net_pnls, &result.trades, config, &mut rng);
+                simulate(&pnls, initial_balance, config.ruin_threshold)
+            })
+            .collect();
+
+        methods.push(MonteCarloMethodReport {
+            method: *method,
+            summary: summarize(&outcomes, config.confidence),
+        });
+        all_outcomes.extend(outcomes);
+    }
+
+    Ok(MonteCarloReport {
+        strategy_name: result.strategy_name.clone(),
+        config: config.clone(),
+        initial_balance,
+        original_final_balance: result.metadata.final_balance,
+        methods,
+        combined: summarize(&all_outcomes, config.confidence),
+    })
+}
+
+/// Genera la secuencia de P&L de una simulación
+fn resample(
+    method: MonteCarloMethod,
+    net_pnls: &[f64],
+    trades: &[Trade],
+    config: &MonteCarloConfig,
+    rng: &mut StdRng,
+) -> Vec<f64> {
+    match method {
+        MonteCarloMethod::Shuffle => {
+            let mut pnls = net_pnls.to_vec();
+            pnls.shuffle(rng);
+            pnls
+        }
+        MonteCarloMethod::Bootstrap => (0..net_pnls.len())
+            .map(|_| net_pnls[rng.random_range(0..net_pnls.len())])
+            .collect(),
+        MonteCarloMethod::SkipTrades => net_pnls
+            .iter()
+            .copied()
+            .filter(|_| !rng.random_bool(config.skip_probability))
+            .collect(),
+        MonteCarloMethod::CostPerturbation => net_pnls
+            .iter()
+            .zip(trades)
+            .map(|(pnl, trade)| {
+                let notional = (trade.entry_price + trade.exit_price) * trade.size;
+                pnl - notional * config.cost_perturbation * rng.random::<f64>()
+            })
+            .collect(),
+    }
+}
+
+/// Recorre la secuencia de P&L calculando las métricas de la simulación
+fn simulate(pnls: &[f64], initial_balance: f64, ruin_threshold: f64) -> SimulationOutcome {
+    let mut balance = initial_balance;
+    let mut peak = initial_balance;
+    let mut max_drawdown: f64 = 0.0;
+    let mut returns = Vec::with_capacity(pnls.len());
+
+    for pnl in pnls {
+        if balance > 0.0 {
+            returns.push(pnl / balance);
+        }
+        balance += pnl;
+        peak = peak.max(balance);
+        if peak > 0.0 {
+            max_drawdown = max_drawdown.max((peak - balance) / peak);
+        }
+    }
+
+    SimulationOutcome {
+        final_balance: balance,
+        max_drawdown,
+        sharpe_ratio: calculate_sharpe_ratio(&returns, 0.0),
+        ruined: max_drawdown >= ruin_threshold,
+    }
+}
+
+fn summarize(outcomes: &[SimulationOutcome], confidence: f64) -> MonteCarloSummary {
+    let mut final_balances: Vec<f64> = outcomes.iter().map(|o| o.final_balance).collect();
+    let mut drawdowns: Vec<f64> = outcomes.iter().map(|o| o.max_drawdown).collect();
+    let mut sharpes: Vec<f64> = outcomes.iter().map(|o| o.sharpe_ratio).collect();
+    let ruined = outcomes.iter().filter(|o| o.ruined).count();
+
+    MonteCarloSummary {
+        iterations: outcomes.len(),
+        final_balance: ConfidenceInterval::from_samples(&mut final_balances, confidence),
+        max_drawdown: ConfidenceInterval::from_samples(&mut drawdowns, confidence),
+        sharpe_ratio: ConfidenceInterval::from_samples(&mut sharpes, confidence),
+        probability_of_ruin: ruined as f64 / outcomes.len() as f64,
+    }
+}
+
+/// Percentil con interpolación lineal sobre una muestra ordenada
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let position = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    let weight = position - lower as f64;
+    sorted[lower] * (1.0 - weight) + sorted[upper] * weight
+}
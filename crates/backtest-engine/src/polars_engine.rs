@@ -5,7 +5,12 @@
 
 pub mod vectorized;
 pub mod massive;
+pub mod indicator_cache;
+pub mod parallel;
+pub(crate) mod multi_timeframe;
 
 pub use vectorized::PolarsBacktestEngine;
 pub use massive::PolarsVectorizedBacktestEngine;
+pub use indicator_cache::{IndicatorCache, IndicatorCacheStats};
+pub use parallel::{ParallelConfig, BacktestProgress, MassiveBacktestStream};
 
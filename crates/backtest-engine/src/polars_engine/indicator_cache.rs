@@ -0,0 +1,197 @@
+//! Caché de columnas de indicadores compartida entre estrategias
+//!
+//! En un batch masivo muchas estrategias usan los mismos indicadores
+//! (ej: `rsi(14)`). La caché guarda cada serie calculada, indexada por el
+//! dataset y el nombre de columna del indicador, para calcularla una sola vez por
+//! dataset. Series de datasets distintos conviven sin mezclarse, de modo que
+//! varios batches o streams pueden intercalarse sobre el mismo motor.
+//! Tiene un presupuesto de memoria y desaloja las series usadas hace más tiempo (LRU).
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use polars::prelude::*;
+
+/// Presupuesto de memoria por defecto de la caché (512 MB)
+pub const DEFAULT_INDICATOR_CACHE_BUDGET: usize = 512 * 1024 * 1024;
+
+/// Estadísticas de uso de la caché de indicadores
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IndicatorCacheStats {
+    /// Series servidas desde la caché
+    pub hits: usize,
+    /// Series que hubo que calcular
+    pub misses: usize,
+    /// Series desalojadas por el presupuesto de memoria
+    pub evictions: usize,
+    /// Series almacenadas actualmente
+    pub entries: usize,
+    /// Memoria estimada ocupada (bytes)
+    pub memory_bytes: usize,
+}
+
+impl IndicatorCacheStats {
+    /// Proporción de consultas servidas desde la caché (0.0 - 1.0)
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// Memoria fija estimada por entrada (estructuras del mapa, del índice LRU y de la serie)
+const ENTRY_OVERHEAD_BYTES: usize = 128;
+
+/// Serie almacenada con su último uso (para LRU)
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    series: Series,
+    size_bytes: usize,
+    last_used: u64,
+}
+
+/// Identifica el dataset sobre el que se calcularon las series
+///
+/// (número de velas, hash del contenido OHLCV y timestamps). Dos símbolos
+/// sobre la misma ventana temporal tienen claves distintas.
+pub type DatasetKey = (usize, u64);
+
+/// Caché LRU de series de indicadores con presupuesto de memoria
+#[derive(Debug, Clone)]
+pub struct IndicatorCache {
+    budget_bytes: usize,
+    /// Series por dataset y nombre de columna
+    entries: HashMap<DatasetKey, HashMap<String, CacheEntry>>,
+    /// Índice LRU: último uso -> (dataset, nombre de columna) (el primero es el menos usado)
+    lru: BTreeMap<u64, (DatasetKey, String)>,
+    tick: u64,
+    stats: IndicatorCacheStats,
+}
+
+impl IndicatorCache {
+    /// Crea una caché con el presupuesto de memoria dado (0 = deshabilitada)
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            budget_bytes,
+            entries: HashMap::new(),
+            lru: BTreeMap::new(),
+            tick: 0,
+            stats: IndicatorCacheStats::default(),
+        }
+    }
+
+    /// Presupuesto de memoria en bytes
+    pub fn budget_bytes(&self) -> usize {
+        self.budget_bytes
+    }
+
+    /// Estadísticas acumuladas
+    pub fn stats(&self) -> IndicatorCacheStats {
+        self.stats
+    }
+
+    /// Vacía la caché y reinicia las estadísticas
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.lru.clear();
+        self.stats = IndicatorCacheStats::default();
+    }
+
+    /// Busca la serie de una columna calculada sobre `dataset` (cuenta hit o miss)
+    pub fn get(&mut self, dataset: DatasetKey, column_name: &str) -> Option<Series> {
+        self.tick += 1;
+        match self.entries.get_mut(&dataset).and_then(|columns| columns.get_mut(column_name)) {
+            Some(entry) => {
+                if let Some(key) = self.lru.remove(&entry.last_used) {
+                    self.lru.insert(self.tick, key);
+                }
+                entry.last_used = self.tick;
+                self.stats.hits += 1;
+                Some(entry.series.clone())
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Guarda una serie calculada sobre `dataset`, desalojando las menos usadas si no cabe
+    ///
+    /// Una serie mayor que el presupuesto completo no se guarda.
+    pub fn insert(&mut self, dataset: DatasetKey, column_name: String, series: Series) {
+        let size_bytes = Self::estimated_size(&column_name, &series);
+        if size_bytes > self.budget_bytes {
+            return;
+        }
+
+        if let Some(old) = self.remove(dataset, &column_name) {
+            self.lru.remove(&old.last_used);
+        }
+        while self.stats.memory_bytes + size_bytes > self.budget_bytes {
+            if !self.evict_least_recently_used() {
+                break;
+            }
+        }
+
+        self.tick += 1;
+        self.lru.insert(self.tick, (dataset, column_name.clone()));
+        self.entries
+            .entry(dataset)
+            .or_default()
+            .insert(column_name, CacheEntry { series, size_bytes, last_used: self.tick });
+        self.stats.memory_bytes += size_bytes;
+        self.stats.entries += 1;
+    }
+
+    /// Desaloja la serie usada hace más tiempo (O(log n)); retorna false si la caché está vacía
+    fn evict_least_recently_used(&mut self) -> bool {
+        let Some((_, (dataset, column_name))) = self.lru.pop_first() else {
+            return false;
+        };
+
+        if self.remove(dataset, &column_name).is_some() {
+            self.stats.evictions += 1;
+        }
+        true
+    }
+
+    /// Quita una entrada y descuenta su memoria (no toca el índice LRU)
+    fn remove(&mut self, dataset: DatasetKey, column_name: &str) -> Option<CacheEntry> {
+        let columns = self.entries.get_mut(&dataset)?;
+        let entry = columns.remove(column_name)?;
+        if columns.is_empty() {
+            self.entries.remove(&dataset);
+        }
+        self.stats.memory_bytes -= entry.size_bytes;
+        self.stats.entries -= 1;
+        Some(entry)
+    }
+
+    /// Memoria estimada de una entrada: valores, nombre de columna y estructuras auxiliares
+    fn estimated_size(column_name: &str, series: &Series) -> usize {
+        series.len() * std::mem::size_of::<f64>() + 2 * column_name.len() + ENTRY_OVERHEAD_BYTES
+    }
+
+    /// Clave del dataset de un DataFrame de velas (altura y hash de sus columnas)
+    pub fn dataset_key(df: &DataFrame) -> DatasetKey {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for column in df.get_columns() {
+            column.name().as_str().hash(&mut hasher);
+            if let Ok(values) = column.i64() {
+                values.into_iter().for_each(|v| v.hash(&mut hasher));
+            } else if let Ok(values) = column.f64() {
+                values.into_iter().for_each(|v| v.map(f64::to_bits).hash(&mut hasher));
+            }
+        }
+        (df.height(), hasher.finish())
+    }
+}
+
+impl Default for IndicatorCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_INDICATOR_CACHE_BUDGET)
+    }
+}
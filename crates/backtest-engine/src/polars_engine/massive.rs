@@ -9,31 +9,61 @@
 //! - Usa expresiones de Polars para señales vectorizadas
 //! - Cálculo paralelo de métricas
 //! - Throughput masivo optimizado
+//! - Caché de indicadores compartida entre las estrategias del batch
+//! - Condiciones en timeframes superiores, sin look-ahead
 
+use std::sync::{Mutex, MutexGuard};
 use polars::prelude::*;
-use darwinx_core::Candle;
+use darwinx_core::{Candle, PositionSide, TimeFrame};
 use darwinx_generator::StrategyAST;
-use darwinx_generator::ast::nodes::{LogicalOperator, Comparison, ConditionValue};
+use darwinx_generator::ast::nodes::{LogicalOperator, Comparison, ConditionValue, IndicatorType};
 use darwinx_indicators::registry;
-use darwinx_indicators::trend::{sma, ema};
-use darwinx_indicators::trend::wma::wma;
-use darwinx_indicators::trend::vwma::vwma;
-use darwinx_indicators::momentum::{rsi, macd};
-use darwinx_indicators::momentum::stochastic::stochastic;
-use darwinx_indicators::momentum::roc::roc;
-use darwinx_indicators::volatility::{atr, bollinger_bands, keltner_channels};
-use darwinx_indicators::volume::{obv, mfi, vwap};
 use crate::error::BacktestError;
-use crate::types::{BacktestResult, BacktestMetrics, Trade};
+use crate::types::{BacktestResult, BacktestMetrics, EquityPoint, ExitReason, Trade};
+use crate::metrics::{apply_buy_and_hold_benchmark, calculate_mark_to_market_equity};
 use crate::config::BacktestConfig;
+use crate::sizing::{atr_series, PositionSizer, SizingContext};
+use crate::exits::{session_closes_after, ExitIndicators};
+use crate::positions::{Lot, OpenPosition};
+use crate::costs::{FeeLedger, Liquidity, SlippageModel, SlippageSeries};
+use crate::margin::{MarginConfig, MarginMode, MarginStats};
+use super::indicator_cache::{IndicatorCache, IndicatorCacheStats, DEFAULT_INDICATOR_CACHE_BUDGET};
+use super::multi_timeframe::{base_interval, condition_timeframe, higher_timeframe_column, HigherTimeframeBars};
 
 /// Motor de backtest masivo vectorizado con Polars
-pub struct PolarsVectorizedBacktestEngine;
+pub struct PolarsVectorizedBacktestEngine {
+    /// Series de indicadores ya calculadas, compartidas entre estrategias
+    indicator_cache: Mutex<IndicatorCache>,
+}
 
 impl PolarsVectorizedBacktestEngine {
     /// Crea un nuevo motor vectorizado
     pub fn new() -> Self {
-        Self
+        Self::with_indicator_cache_budget(DEFAULT_INDICATOR_CACHE_BUDGET)
+    }
+
+    /// Crea un motor con un presupuesto de memoria (bytes) para la caché de indicadores
+    ///
+    /// Un presupuesto de 0 deshabilita la caché.
+    pub fn with_indicator_cache_budget(budget_bytes: usize) -> Self {
+        Self {
+            indicator_cache: Mutex::new(IndicatorCache::new(budget_bytes)),
+        }
+    }
+
+    /// Estadísticas de la caché de indicadores (acumuladas desde la creación)
+    pub fn indicator_cache_stats(&self) -> IndicatorCacheStats {
+        self.cache().stats()
+    }
+
+    /// Vacía la caché de indicadores y reinicia sus estadísticas
+    pub fn clear_indicator_cache(&self) {
+        self.cache().clear();
+    }
+
+    pub(crate) fn cache(&self) -> MutexGuard<'_, IndicatorCache> {
+        // Un panic con el lock tomado no deja la caché inconsistente: se reutiliza
+        self.indicator_cache.lock().unwrap_or_else(|e| e.into_inner())
     }
 
     /// Ejecuta backtest masivo para múltiples estrategias usando Polars
@@ -64,35 +94,49 @@ impl PolarsVectorizedBacktestEngine {
         let mut results = Vec::with_capacity(strategies.len());
         
         for strategy in strategies {
-            match self.backtest_single_strategy(&df, &strategy, config).await {
-                Ok(result) => results.push(result),
-                Err(e) => {
-                    // Log error pero continúa con otras estrategias
-                    eprintln!("Error backtesting strategy {}: {}", strategy.name, e);
-                    // Crear resultado con error
-                    results.push(BacktestResult {
-                        strategy_name: strategy.name,
-                        metrics: BacktestMetrics::default(),
-                        trades: Vec::new(),
-                        equity_curve: Vec::new(),
-                        metadata: crate::types::BacktestMetadata {
-                            start_date: candles[0].timestamp,
-                            end_date: candles.last().unwrap().timestamp,
-                            total_candles: candles.len(),
-                            initial_balance: config.initial_balance,
-                            final_balance: config.initial_balance,
-                            config: config.clone(),
-                        },
-                    });
-                }
-            }
+            results.push(self.backtest_or_empty(&df, &strategy, config));
         }
 
         Ok(results)
     }
 
+    /// Ejecuta una estrategia; si falla, registra el error y retorna un resultado vacío
+    ///
+    /// Un error en una estrategia no debe abortar el batch completo.
+    pub(crate) fn backtest_or_empty(
+        &self,
+        df: &DataFrame,
+        strategy: &StrategyAST,
+        config: &BacktestConfig,
+    ) -> BacktestResult {
+        match self.backtest_single_strategy(df, strategy, config) {
+            Ok(result) => result,
+            Err(e) => {
+                // Log error pero continúa con otras estrategias
+                eprintln!("Error backtesting strategy {}: {}", strategy.name, e);
+                let timestamps = df.column("timestamp").ok().and_then(|c| c.i64().ok().cloned());
+                let timestamp_at = |i: usize| timestamps.as_ref().and_then(|t| t.get(i)).unwrap_or(0);
+                // Crear resultado con error
+                BacktestResult {
+                    strategy_name: strategy.name.clone(),
+                    metrics: BacktestMetrics::default(),
+                    trades: Vec::new(),
+                    equity_curve: Vec::new(),
+                    metadata: crate::types::BacktestMetadata {
+                        start_date: timestamp_at(0),
+                        end_date: timestamp_at(df.height().saturating_sub(1)),
+                        total_candles: df.height(),
+                        initial_balance: config.initial_balance,
+                        final_balance: config.initial_balance,
+                        config: config.clone(),
+                    },
+                }
+            }
+        }
+    }
+
     /// Convierte candles a DataFrame de Polars
-    fn candles_to_dataframe(&self, candles: &[Candle]) -> Result<DataFrame, BacktestError> {
+    pub(crate) fn candles_to_dataframe(&self, candles: &[Candle]) -> Result<DataFrame, BacktestError> {
         let timestamps: Vec<i64> = candles.iter().map(|c| c.timestamp).collect();
         let opens: Vec<f64> = candles.iter().map(|c| c.open).collect();
         let highs: Vec<f64> = candles.iter().map(|c| c.high).collect();
@@ -113,49 +157,85 @@ impl PolarsVectorizedBacktestEngine {
         Ok(df)
     }
 
-    /// Backtest de una sola estrategia usando Polars
-    async fn backtest_single_strategy(
+    /// Añade al DataFrame los indicadores de la estrategia y las columnas de señales
+    ///
+    /// Columnas booleanas: `entry_signal`, `exit_signal`, `short_entry_signal` y
+    /// `short_exit_signal`. Usa la caché de indicadores ligada al dataset actual.
+    /// Las condiciones con timeframe superior usan columnas proyectadas desde ese
+    /// timeframe (ver `multi_timeframe`).
+    pub(crate) fn compute_signals(
         &self,
         df: &DataFrame,
         strategy: &StrategyAST,
-        config: &BacktestConfig,
-    ) -> Result<BacktestResult, BacktestError> {
+    ) -> Result<DataFrame, BacktestError> {
+        // Intervalo de las velas, solo necesario para resolver timeframes superiores
+        let base_millis = if strategy.higher_timeframes().is_empty() {
+            None
+        } else {
+            base_interval(&self.extract_timestamps(df)?)
+        };
+
         // 1. Identificar todos los indicadores necesarios
-        let required_indicators = self.collect_required_indicators(strategy);
+        let required_indicators = self.collect_required_indicators(strategy, base_millis);
         
         // 2. Pre-calcular todos los indicadores en el DataFrame
-        let df_with_indicators = self.precompute_indicators(df, &required_indicators)?;
+        let df_with_indicators = self.precompute_indicators(df, &required_indicators, base_millis)?;
         
         // 3. Convertir condiciones de entrada a expresiones de Polars (ahora pueden referenciar columnas calculadas)
         let entry_signal = self.conditions_to_polars_expr(
             &strategy.entry_rules.conditions,
             strategy.entry_rules.operator,
-            &df_with_indicators,
+            base_millis,
         )?;
         
         // 4. Convertir condiciones de salida a expresiones de Polars
         let exit_signal = self.conditions_to_polars_expr(
             &strategy.exit_rules.conditions,
             strategy.exit_rules.operator,
-            &df_with_indicators,
+            base_millis,
         )?;
 
+        // 4b. Convertir reglas en corto (si existen) a expresiones de Polars
+        let short_entry_signal = match &strategy.short_entry_rules {
+            Some(rules) => self.conditions_to_polars_expr(&rules.conditions, rules.operator, base_millis)?,
+            None => lit(false),
+        };
+        let short_exit_signal = match &strategy.short_exit_rules {
+            Some(rules) => self.conditions_to_polars_expr(&rules.conditions, rules.operator, base_millis)?,
+            None => lit(false),
+        };
+
         // 5. Calcular señales de entrada y salida vectorizadas
-        let df_with_signals = df_with_indicators
+        df_with_indicators
             .lazy()
             .with_columns([
                 entry_signal.alias("entry_signal"),
                 exit_signal.alias("exit_signal"),
+                short_entry_signal.alias("short_entry_signal"),
+                short_exit_signal.alias("short_exit_signal"),
             ])
             .collect()
-            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Polars error: {}", e)))?;
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Polars error: {}", e)))
+    }
 
-        // DIAGNÓSTICO: Contar cuántas señales de entrada hay
-        let entry_signal_col = df_with_signals.column("entry_signal")
-            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get entry_signal: {}", e)))?;
-        let entry_signals = entry_signal_col.bool()
-            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast entry_signal: {}", e)))?;
-        let true_signals = entry_signals.iter().filter(|opt| opt.unwrap_or(false)).count();
+    /// Backtest de una sola estrategia usando Polars
+    fn backtest_single_strategy(
+        &self,
+        df: &DataFrame,
+        strategy: &StrategyAST,
+        config: &BacktestConfig,
+    ) -> Result<BacktestResult, BacktestError> {
+        let df_with_signals = self.compute_signals(df, strategy)?;
+
+        // DIAGNÓSTICO: Contar cuántas señales de entrada hay (largo + corto)
+        let mut true_signals = 0;
+        for signal_name in ["entry_signal", "short_entry_signal"] {
+            let signal_col = df_with_signals.column(signal_name)
+                .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get {}: {}", signal_name, e)))?;
+            let signals = signal_col.bool()
+                .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast {}: {}", signal_name, e)))?;
+            true_signals += signals.iter().filter(|opt| opt.unwrap_or(false)).count();
+        }
         let total_candles_signals = df_with_signals.height();
         
         // Logging de diagnóstico: si no hay señales, puede indicar un problema
@@ -169,33 +249,55 @@ impl PolarsVectorizedBacktestEngine {
             }
         }
 
+        // Las velas anteriores a `trading_start` solo calientan indicadores y salidas
+        let timestamp_col = df.column("timestamp")
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get timestamp column: {}", e)))?;
+        let mut timestamps: Vec<i64> = timestamp_col.i64()
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast timestamp: {}", e)))?
+            .iter()
+            .map(|opt| opt.unwrap_or(0))
+            .collect();
+        let start = config.trading_start.map_or(0, |start| timestamps.partition_point(|&t| t < start));
+        if start == timestamps.len() {
+            return Err(BacktestError::DataError(anyhow::anyhow!("No candles after trading start")));
+        }
+
         // Simular trades basado en señales
-        let trades = self.calculate_trades_from_signals(&df_with_signals, config)?;
+        let (trades, margin_stats) = self.calculate_trades_from_signals(&df_with_signals, start, config)?;
+
+        // Curva de equity mark-to-market vela a vela (base de las métricas de riesgo y retorno)
+        let [mut closes, ..] = self.extract_ohlcv(df)?;
+        timestamps.drain(..start);
+        closes.drain(..start);
+        let equity_curve = calculate_mark_to_market_equity(&trades, &timestamps, &closes, config);
 
         // Calcular métricas
-        let mut metrics = self.calculate_metrics_from_trades(&trades, config)?;
+        let mut metrics = self.calculate_metrics_from_trades(&trades, &equity_curve, config)?;
         // Guardar entry_signals_count para diagnóstico
         metrics.entry_signals_count = true_signals;
+        apply_buy_and_hold_benchmark(&mut metrics, &equity_curve, &closes);
+        if config.margin.is_some() {
+            metrics.max_margin_usage = margin_stats.max_usage();
+            metrics.average_margin_usage = margin_stats.average_usage();
+            metrics.min_liquidation_distance = margin_stats.min_liquidation_distance();
+        }
 
         // Obtener metadata desde DataFrame
-        let timestamp_col = df.column("timestamp")
-            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get timestamp column: {}", e)))?;
-        let timestamp_series = timestamp_col.i64()
-            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast timestamp: {}", e)))?;
-        
-        let first_timestamp = timestamp_series.get(0)
+        let first_timestamp = *timestamps.first()
             .ok_or_else(|| BacktestError::DataError(anyhow::anyhow!("No data")))?;
-        let last_timestamp = timestamp_series.get(df.height() - 1)
+        let last_timestamp = *timestamps.last()
             .ok_or_else(|| BacktestError::DataError(anyhow::anyhow!("No data")))?;
 
-        let total_candles = df.height();
-        let final_balance = config.initial_balance + trades.iter().map(|t| t.pnl).sum::<f64>();
+        let total_candles = timestamps.len();
+        // El P&L de cada trade solo descuenta la comisión de salida; la de entrada
+        // se paga al abrir y también reduce el balance de la cuenta
+        let final_balance = config.initial_balance + trades.iter().map(Trade::net_pnl).sum::<f64>();
 
         Ok(BacktestResult {
             strategy_name: strategy.name.clone(),
             metrics,
             trades,
-            equity_curve: Vec::new(), // Se puede calcular después si es necesario
+            equity_curve,
             metadata: crate::types::BacktestMetadata {
                 start_date: first_timestamp,
                 end_date: last_timestamp,
@@ -212,7 +314,7 @@ impl PolarsVectorizedBacktestEngine {
         &self,
         conditions: &[darwinx_generator::ast::nodes::Condition],
         operator: LogicalOperator,
-        df: &DataFrame,
+        base_millis: Option<i64>,
     ) -> Result<Expr, BacktestError> {
         if conditions.is_empty() {
             return Ok(lit(false));
@@ -221,7 +323,7 @@ impl PolarsVectorizedBacktestEngine {
         // Convertir cada condición a expresión Polars
         let mut condition_exprs = Vec::new();
         for condition in conditions {
-            let expr = self.condition_to_polars_expr(condition, df)?;
+            let expr = self.condition_to_polars_expr(condition, base_millis)?;
             condition_exprs.push(expr);
         }
 
@@ -246,53 +348,50 @@ impl PolarsVectorizedBacktestEngine {
     fn condition_to_polars_expr(
         &self,
         condition: &darwinx_generator::ast::nodes::Condition,
-        df: &DataFrame,
+        base_millis: Option<i64>,
     ) -> Result<Expr, BacktestError> {
-        // Calcular el indicador usando Polars
-        let indicator_expr = self.indicator_to_polars_expr(&condition.indicator, df)?;
+        // Indicador y valor se leen de las columnas del timeframe de la condición
+        let timeframe = condition_timeframe(condition.timeframe, base_millis);
+        let indicator_expr = self.indicator_to_polars_expr(&condition.indicator, timeframe)?;
         
         // Obtener el valor de comparación
         let compare_value = match &condition.value {
             ConditionValue::Number(n) => lit(*n),
-            ConditionValue::Price => col("close"),
-            ConditionValue::Indicator(ind) => self.indicator_to_polars_expr(ind, df)?,
+            ConditionValue::Price => col(Self::price_column_name(timeframe)),
+            ConditionValue::Indicator(ind) => self.indicator_to_polars_expr(ind, timeframe)?,
         };
 
         // Aplicar comparación con manejo de NaN
-        // Polars: NaN comparado con cualquier valor resulta en null, que se convierte en false
-        // Necesitamos filtrar NaN antes de comparar para que las condiciones se evalúen correctamente
-        // IMPORTANTE: También necesitamos verificar que compare_value no sea null/NaN
+        // Polars ordena NaN por encima de cualquier valor (NaN > x es true), y los indicadores
+        // usan NaN durante el warmup y antes de la primera barra cerrada de un timeframe
+        // superior: ambos operandos deben ser válidos (ni null ni NaN) para que haya señal
         let comparison_expr = match condition.comparison {
             Comparison::GreaterThan => {
-                // Solo evaluar si ambos valores son válidos (no null/NaN)
-                indicator_expr.clone()
-                    .is_not_null()
-                    .and(compare_value.clone().is_not_null())
+                Self::operands_valid(&[&indicator_expr, &compare_value])
                     .and(indicator_expr.gt(compare_value))
             }
             Comparison::LessThan => {
-                indicator_expr.clone()
-                    .is_not_null()
-                    .and(compare_value.clone().is_not_null())
+                Self::operands_valid(&[&indicator_expr, &compare_value])
                     .and(indicator_expr.lt(compare_value))
             }
             Comparison::Equals => {
-                indicator_expr.clone()
-                    .is_not_null()
-                    .and(compare_value.clone().is_not_null())
+                Self::operands_valid(&[&indicator_expr, &compare_value])
                     .and(indicator_expr.eq(compare_value))
             }
             Comparison::CrossesAbove => {
-                // Simplificado: solo comparación directa con filtro de NaN
-                indicator_expr.clone()
-                    .is_not_null()
-                    .and(compare_value.clone().is_not_null())
+                // Cruce real: en la vela anterior estaba por debajo (o igual) y en la actual por encima
+                let (prev_indicator, prev_compare) =
+                    self.previous_bar_exprs(&indicator_expr, &compare_value, &condition.value);
+                Self::crossing_operands_valid(&indicator_expr, &compare_value, &prev_indicator, &prev_compare)
+                    .and(prev_indicator.lt_eq(prev_compare))
                     .and(indicator_expr.gt(compare_value))
             }
             Comparison::CrossesBelow => {
-                indicator_expr.clone()
-                    .is_not_null()
-                    .and(compare_value.clone().is_not_null())
+                // Cruce real: en la vela anterior estaba por encima (o igual) y en la actual por debajo
+                let (prev_indicator, prev_compare) =
+                    self.previous_bar_exprs(&indicator_expr, &compare_value, &condition.value);
+                Self::crossing_operands_valid(&indicator_expr, &compare_value, &prev_indicator, &prev_compare)
+                    .and(prev_indicator.gt_eq(prev_compare))
                     .and(indicator_expr.lt(compare_value))
             }
         };
@@ -300,33 +399,62 @@ impl PolarsVectorizedBacktestEngine {
         Ok(comparison_expr)
     }
 
+    /// Obtiene las expresiones de la vela anterior (shift de 1) para evaluar cruces
+    ///
+    /// Un valor numérico constante no se desplaza: su valor anterior es el mismo.
+    fn previous_bar_exprs(
+        &self,
+        indicator_expr: &Expr,
+        compare_value: &Expr,
+        value: &ConditionValue,
+    ) -> (Expr, Expr) {
+        let prev_indicator = indicator_expr.clone().shift(lit(1));
+        let prev_compare = match value {
+            ConditionValue::Number(n) => lit(*n),
+            ConditionValue::Price | ConditionValue::Indicator(_) => compare_value.clone().shift(lit(1)),
+        };
+        (prev_indicator, prev_compare)
+    }
+
+    /// Verifica que los cuatro operandos de un cruce sean válidos (ni null ni NaN)
+    ///
+    /// Los indicadores usan NaN durante el warmup y el shift introduce null en la primera vela;
+    /// sin este filtro el primer valor válido tras el warmup podría interpretarse como un cruce.
+    fn crossing_operands_valid(current: &Expr, compare: &Expr, prev: &Expr, prev_compare: &Expr) -> Expr {
+        Self::operands_valid(&[current, compare, prev, prev_compare])
+    }
+
+    /// Verifica que todos los operandos sean válidos (ni null ni NaN)
+    fn operands_valid(operands: &[&Expr]) -> Expr {
+        operands
+            .iter()
+            .map(|&e| e.clone().is_not_null().and(e.clone().is_not_nan()))
+            .reduce(|acc, e| acc.and(e))
+            .unwrap_or_else(|| lit(true))
+    }
+
     /// Recolecta todos los indicadores únicos necesarios para la estrategia
-    fn collect_required_indicators(&self, strategy: &StrategyAST) -> Vec<darwinx_generator::ast::nodes::IndicatorType> {
+    ///
+    /// Cada indicador va con su timeframe superior (None = serie principal).
+    fn collect_required_indicators(&self, strategy: &StrategyAST, base_millis: Option<i64>) -> Vec<(IndicatorType, Option<TimeFrame>)> {
         let mut indicators = Vec::new();
         
         // Helper para agregar si no existe
-        let mut add_if_not_exists = |ind: darwinx_generator::ast::nodes::IndicatorType| {
+        let mut add_if_not_exists = |ind: IndicatorType, timeframe: Option<TimeFrame>| {
             let key = format!("{}_{:?}", ind.name, ind.params);
-            if !indicators.iter().any(|i: &darwinx_generator::ast::nodes::IndicatorType| {
-                format!("{}_{:?}", i.name, i.params) == key
+            if !indicators.iter().any(|(i, tf): &(IndicatorType, Option<TimeFrame>)| {
+                *tf == timeframe && format!("{}_{:?}", i.name, i.params) == key
             }) {
-                indicators.push(ind);
+                indicators.push((ind, timeframe));
             }
         };
         
-        // Recolectar de condiciones de entrada
-        for condition in &strategy.entry_rules.conditions {
-            add_if_not_exists(condition.indicator.clone());
+        // Recolectar de todas las condiciones (largo y corto, entrada y salida)
+        for condition in strategy.all_conditions() {
+            let timeframe = condition_timeframe(condition.timeframe, base_millis);
+            add_if_not_exists(condition.indicator.clone(), timeframe);
             if let ConditionValue::Indicator(ind) = &condition.value {
-                add_if_not_exists(ind.clone());
-            }
-        }
-        
-        // Recolectar de condiciones de salida
-        for condition in &strategy.exit_rules.conditions {
-            add_if_not_exists(condition.indicator.clone());
-            if let ConditionValue::Indicator(ind) = &condition.value {
-                add_if_not_exists(ind.clone());
+                add_if_not_exists(ind.clone(), timeframe);
             }
         }
         
@@ -334,60 +462,88 @@ impl PolarsVectorizedBacktestEngine {
     }
 
     /// Pre-calcula todos los indicadores necesarios en el DataFrame
+    ///
+    /// Las series se toman de la caché compartida si ya se calcularon para
+    /// otra estrategia del mismo dataset (identificado por el contenido de `df`,
+    /// de modo que batches intercalados sobre datasets distintos no se mezclan).
+    /// Los indicadores de timeframes superiores
+    /// se calculan sobre las barras agrupadas y se proyectan sobre las velas; cada
+    /// timeframe superior añade también su precio de cierre.
     fn precompute_indicators(
         &self,
         df: &DataFrame,
-        indicators: &[darwinx_generator::ast::nodes::IndicatorType],
+        indicators: &[(IndicatorType, Option<TimeFrame>)],
+        base_millis: Option<i64>,
     ) -> Result<DataFrame, BacktestError> {
-        // Obtener todas las columnas necesarias
-        let close_series = df.column("close")
-            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get close column: {}", e)))?;
-        let close_values: Vec<f64> = close_series.f64()
-            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast close: {}", e)))?
-            .into_iter()
-            .map(|opt| opt.unwrap_or(0.0))
-            .collect();
-        
-        // Obtener high, low, volume si están disponibles (para indicadores que los necesitan)
-        let high_values: Vec<f64> = df.column("high")
-            .ok()
-            .and_then(|s| s.f64().ok())
-            .map(|s| s.into_iter().map(|opt| opt.unwrap_or(0.0)).collect())
-            .unwrap_or_else(|| close_values.clone());
-        
-        let low_values: Vec<f64> = df.column("low")
-            .ok()
-            .and_then(|s| s.f64().ok())
-            .map(|s| s.into_iter().map(|opt| opt.unwrap_or(0.0)).collect())
-            .unwrap_or_else(|| close_values.clone());
-        
-        let volume_values: Vec<f64> = df.column("volume")
-            .ok()
-            .and_then(|s| s.f64().ok())
-            .map(|s| s.into_iter().map(|opt| opt.unwrap_or(0.0)).collect())
-            .unwrap_or_else(|| vec![1000.0; close_values.len()]);
-        
         // Crear columnas para cada indicador
-        let mut new_columns = Vec::new();
-        let mut computed = Vec::new();
-        
-        for indicator in indicators {
-            let col_name = self.indicator_column_name(indicator);
-            if computed.contains(&col_name) {
+        let mut new_columns: Vec<Series> = Vec::new();
+        // Series OHLCV, extraídas solo si algún indicador no está en caché
+        let mut ohlcv: Option<[Vec<f64>; 4]> = None;
+        // Barras de cada timeframe superior, agrupadas solo si hacen falta
+        let mut higher_bars: Vec<(TimeFrame, HigherTimeframeBars)> = Vec::new();
+        // Dataset de las series cacheadas, calculado solo si hay indicadores
+        let mut dataset = None;
+
+        let mut timeframes: Vec<TimeFrame> = Vec::new();
+        for timeframe in indicators.iter().filter_map(|(_, tf)| *tf) {
+            if !timeframes.contains(&timeframe) {
+                timeframes.push(timeframe);
+            }
+        }
+        let prices = timeframes.iter().map(|&timeframe| (None, Some(timeframe)));
+        let required = indicators.iter().map(|(ind, tf)| (Some(ind), *tf)).chain(prices);
+
+        for (indicator, timeframe) in required {
+            let col_name = match indicator {
+                Some(ind) => self.condition_column_name(ind, timeframe),
+                None => Self::price_column_name(timeframe),
+            };
+            if new_columns.iter().any(|s| s.name().as_str() == col_name) {
                 continue; // Ya calculado
             }
-            
-            // Calcular indicador usando las funciones existentes
-            let values = self.calculate_indicator_values(
-                indicator,
-                &close_values,
-                &high_values,
-                &low_values,
-                &volume_values,
-            )?;
+
+            let dataset = *dataset.get_or_insert_with(|| IndicatorCache::dataset_key(df));
+            if let Some(series) = self.cache().get(dataset, &col_name) {
+                new_columns.push(series);
+                continue;
+            }
+
+            if ohlcv.is_none() {
+                ohlcv = Some(self.extract_ohlcv(df)?);
+            }
+            let values = match (timeframe, base_millis) {
+                (Some(timeframe), Some(base_millis)) => {
+                    if !higher_bars.iter().any(|(tf, _)| *tf == timeframe) {
+                        let timestamps = self.extract_timestamps(df)?;
+                        let bars = HigherTimeframeBars::resample(&timestamps, ohlcv.as_ref().unwrap(), base_millis, timeframe);
+                        higher_bars.push((timeframe, bars));
+                    }
+                    let bars = &higher_bars.iter().find(|(tf, _)| *tf == timeframe).unwrap().1;
+                    let [close_values, high_values, low_values, volume_values] = &bars.ohlcv;
+                    let bar_values = match indicator {
+                        Some(ind) => self.calculate_indicator_values(ind, close_values, high_values, low_values, volume_values)?,
+                        None => close_values.clone(),
+                    };
+                    bars.align(&bar_values)
+                }
+                _ => {
+                    let [close_values, high_values, low_values, volume_values] = ohlcv.as_ref().unwrap();
+                    // Calcular indicador usando las funciones existentes
+                    match indicator {
+                        Some(ind) => self.calculate_indicator_values(
+                            ind,
+                            close_values,
+                            high_values,
+                            low_values,
+                            volume_values,
+                        )?,
+                        None => close_values.clone(),
+                    }
+                }
+            };
             let series = Series::new(col_name.as_str().into(), values);
+            self.cache().insert(dataset, col_name, series.clone());
             new_columns.push(series);
-            computed.push(col_name);
         }
         
         if new_columns.is_empty() {
@@ -414,8 +570,52 @@ impl PolarsVectorizedBacktestEngine {
         Ok(df_with_indicators)
     }
 
+    /// Extrae los timestamps del DataFrame
+    pub(crate) fn extract_timestamps(&self, df: &DataFrame) -> Result<Vec<i64>, BacktestError> {
+        Ok(df.column("timestamp")
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get timestamp column: {}", e)))?
+            .i64()
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast timestamp: {}", e)))?
+            .iter()
+            .map(|opt| opt.unwrap_or(0))
+            .collect())
+    }
+
+    /// Extrae las series close, high, low y volume del DataFrame
+    pub(crate) fn extract_ohlcv(&self, df: &DataFrame) -> Result<[Vec<f64>; 4], BacktestError> {
+        // Obtener todas las columnas necesarias
+        let close_series = df.column("close")
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get close column: {}", e)))?;
+        let close_values: Vec<f64> = close_series.f64()
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast close: {}", e)))?
+            .into_iter()
+            .map(|opt| opt.unwrap_or(0.0))
+            .collect();
+        
+        // Obtener high, low, volume si están disponibles (para indicadores que los necesitan)
+        let high_values: Vec<f64> = df.column("high")
+            .ok()
+            .and_then(|s| s.f64().ok())
+            .map(|s| s.into_iter().map(|opt| opt.unwrap_or(0.0)).collect())
+            .unwrap_or_else(|| close_values.clone());
+        
+        let low_values: Vec<f64> = df.column("low")
+            .ok()
+            .and_then(|s| s.f64().ok())
+            .map(|s| s.into_iter().map(|opt| opt.unwrap_or(0.0)).collect())
+            .unwrap_or_else(|| close_values.clone());
+        
+        let volume_values: Vec<f64> = df.column("volume")
+            .ok()
+            .and_then(|s| s.f64().ok())
+            .map(|s| s.into_iter().map(|opt| opt.unwrap_or(0.0)).collect())
+            .unwrap_or_else(|| vec![1000.0; close_values.len()]);
+
+        Ok([close_values, high_values, low_values, volume_values])
+    }
+
     /// Calcula los valores de un indicador para una serie de precios
-    fn calculate_indicator_values(
+    pub(crate) fn calculate_indicator_values(
         &self,
         indicator: &darwinx_generator::ast::nodes::IndicatorType,
         prices: &[f64],
@@ -427,277 +627,27 @@ impl PolarsVectorizedBacktestEngine {
         let params = &indicator.params;
 
         // Verificar que el indicador existe en el registry
-        let _metadata = registry::get(name)
+        let metadata = registry::get(name)
             .ok_or_else(|| BacktestError::StrategyError(format!("Indicator '{}' not found in registry", name)))?;
-
-        match name {
-            "sma" => {
-                let period = params.get(0)
-                    .ok_or_else(|| BacktestError::StrategyError("SMA requires period parameter".to_string()))?;
-                let period_usize = *period as usize;
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let start = if end > period_usize { end - period_usize } else { 0 };
-                    let slice = &prices[start..end];
-                    if let Some(sma_val) = sma(slice, period_usize) {
-                        values.push(sma_val);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "ema" => {
-                let period = params.get(0)
-                    .ok_or_else(|| BacktestError::StrategyError("EMA requires period parameter".to_string()))?;
-                let period_usize = *period as usize;
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let start = if end > period_usize { end - period_usize } else { 0 };
-                    let slice = &prices[start..end];
-                    if let Some(ema_val) = ema(slice, period_usize) {
-                        values.push(ema_val);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "wma" => {
-                let period = params.get(0)
-                    .ok_or_else(|| BacktestError::StrategyError("WMA requires period parameter".to_string()))?;
-                let period_usize = *period as usize;
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let start = if end > period_usize { end - period_usize } else { 0 };
-                    let slice = &prices[start..end];
-                    if let Some(wma_val) = wma(slice, period_usize) {
-                        values.push(wma_val);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "vwma" => {
-                let period = params.get(0)
-                    .ok_or_else(|| BacktestError::StrategyError("VWMA requires period parameter".to_string()))?;
-                let period_usize = *period as usize;
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let start = if end > period_usize { end - period_usize } else { 0 };
-                    let price_slice = &prices[start..end];
-                    let volume_slice = &volumes[start..end];
-                    if let Some(vwma_val) = vwma(price_slice, volume_slice, period_usize) {
-                        values.push(vwma_val);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "rsi" => {
-                let period = params.get(0)
-                    .ok_or_else(|| BacktestError::StrategyError("RSI requires period parameter".to_string()))?;
-                let period_usize = *period as usize;
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let start = if end > period_usize + 1 { end - period_usize - 1 } else { 0 };
-                    let slice = &prices[start..end];
-                    if let Some(rsi_val) = rsi(slice, period_usize) {
-                        values.push(rsi_val);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "macd" => {
-                let fast = params.get(0)
-                    .ok_or_else(|| BacktestError::StrategyError("MACD requires fast_period parameter".to_string()))?;
-                let slow = params.get(1)
-                    .ok_or_else(|| BacktestError::StrategyError("MACD requires slow_period parameter".to_string()))?;
-                let signal = params.get(2)
-                    .ok_or_else(|| BacktestError::StrategyError("MACD requires signal_period parameter".to_string()))?;
-                let fast_usize = *fast as usize;
-                let slow_usize = *slow as usize;
-                let signal_usize = *signal as usize;
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let start = if end > slow_usize { end - slow_usize } else { 0 };
-                    let slice = &prices[start..end];
-                    if let Some((macd_line, _, _)) = macd(slice, fast_usize, slow_usize, signal_usize) {
-                        values.push(macd_line);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "stochastic" => {
-                let period = params.get(0)
-                    .ok_or_else(|| BacktestError::StrategyError("Stochastic requires period parameter".to_string()))?;
-                let period_usize = *period as usize;
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let start = if end > period_usize { end - period_usize } else { 0 };
-                    let high_slice = &highs[start..end];
-                    let low_slice = &lows[start..end];
-                    let close_slice = &prices[start..end];
-                    if let Some(stoch_val) = stochastic(high_slice, low_slice, close_slice, period_usize) {
-                        values.push(stoch_val);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "roc" => {
-                let period = params.get(0)
-                    .ok_or_else(|| BacktestError::StrategyError("ROC requires period parameter".to_string()))?;
-                let period_usize = *period as usize;
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let start = if end > period_usize + 1 { end - period_usize - 1 } else { 0 };
-                    let slice = &prices[start..end];
-                    if let Some(roc_val) = roc(slice, period_usize) {
-                        values.push(roc_val);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "atr" => {
-                let period = params.get(0)
-                    .ok_or_else(|| BacktestError::StrategyError("ATR requires period parameter".to_string()))?;
-                let period_usize = *period as usize;
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let start = if end > period_usize + 1 { end - period_usize - 1 } else { 0 };
-                    let high_slice = &highs[start..end];
-                    let low_slice = &lows[start..end];
-                    let close_slice = &prices[start..end];
-                    if let Some(atr_val) = atr(high_slice, low_slice, close_slice, period_usize) {
-                        values.push(atr_val);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "bollinger_bands" => {
-                let period = params.get(0)
-                    .ok_or_else(|| BacktestError::StrategyError("Bollinger requires period parameter".to_string()))?;
-                let std_dev = params.get(1)
-                    .ok_or_else(|| BacktestError::StrategyError("Bollinger requires std_dev parameter".to_string()))?;
-                let period_usize = *period as usize;
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let start = if end > period_usize { end - period_usize } else { 0 };
-                    let slice = &prices[start..end];
-                    // Bollinger retorna (lower, middle, upper), usamos middle
-                    if let Some((_, middle, _)) = bollinger_bands(slice, period_usize, *std_dev) {
-                        values.push(middle);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "keltner_channels" => {
-                let period = params.get(0)
-                    .ok_or_else(|| BacktestError::StrategyError("Keltner requires period parameter".to_string()))?;
-                let multiplier = params.get(1)
-                    .ok_or_else(|| BacktestError::StrategyError("Keltner requires multiplier parameter".to_string()))?;
-                let period_usize = *period as usize;
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let start = if end > period_usize + 1 { end - period_usize - 1 } else { 0 };
-                    let high_slice = &highs[start..end];
-                    let low_slice = &lows[start..end];
-                    let close_slice = &prices[start..end];
-                    // Keltner retorna (lower, middle, upper), usamos middle
-                    if let Some((_, middle, _)) = keltner_channels(high_slice, low_slice, close_slice, period_usize, *multiplier) {
-                        values.push(middle);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "obv" => {
-                // OBV retorna Vec<f64>, usamos el último valor
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let price_slice = &prices[0..end];
-                    let volume_slice = &volumes[0..end];
-                    if let Some(obv_values) = obv(price_slice, volume_slice) {
-                        values.push(*obv_values.last().unwrap_or(&f64::NAN));
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "mfi" => {
-                let period = params.get(0)
-                    .ok_or_else(|| BacktestError::StrategyError("MFI requires period parameter".to_string()))?;
-                let period_usize = *period as usize;
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let start = if end > period_usize + 1 { end - period_usize - 1 } else { 0 };
-                    let high_slice = &highs[start..end];
-                    let low_slice = &lows[start..end];
-                    let close_slice = &prices[start..end];
-                    let volume_slice = &volumes[start..end];
-                    if let Some(mfi_val) = mfi(high_slice, low_slice, close_slice, volume_slice, period_usize) {
-                        values.push(mfi_val);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            "vwap" => {
-                // VWAP se calcula desde el inicio hasta cada punto
-                let mut values = Vec::with_capacity(prices.len());
-                for i in 0..prices.len() {
-                    let end = i + 1;
-                    let high_slice = &highs[0..end];
-                    let low_slice = &lows[0..end];
-                    let close_slice = &prices[0..end];
-                    let volume_slice = &volumes[0..end];
-                    if let Some(vwap_val) = vwap(high_slice, low_slice, close_slice, volume_slice) {
-                        values.push(vwap_val);
-                    } else {
-                        values.push(f64::NAN);
-                    }
-                }
-                Ok(values)
-            }
-            _ => {
-                // Para otros indicadores, usar close como fallback temporal
-                Ok(prices.to_vec())
-            }
+        if params.len() < metadata.parameters.len() {
+            return Err(BacktestError::StrategyError(format!(
+                "Indicator '{}' requires {} parameters, got {}",
+                name,
+                metadata.parameters.len(),
+                params.len()
+            )));
         }
+
+        // Cálculo incremental O(n): el valor en cada vela arrastra el estado de las
+        // anteriores (EMA, RSI y ATR de Wilder) y es NaN durante el warmup
+        let mut streaming = darwinx_indicators::streaming::create(name, params).ok_or_else(|| {
+            BacktestError::StrategyError(format!("Indicator '{}' has no streaming implementation", name))
+        })?;
+        Ok(streaming.compute_series_ohlcv(highs, lows, prices, volumes))
     }
 
     /// Genera el nombre de columna para un indicador
-    fn indicator_column_name(&self, indicator: &darwinx_generator::ast::nodes::IndicatorType) -> String {
+    pub(crate) fn indicator_column_name(&self, indicator: &darwinx_generator::ast::nodes::IndicatorType) -> String {
         if indicator.params.is_empty() {
             indicator.name.clone()
         } else {
@@ -711,28 +661,50 @@ impl PolarsVectorizedBacktestEngine {
     }
 
 
+    /// Nombre de columna de un indicador en el timeframe de una condición (None = principal)
+    pub(crate) fn condition_column_name(&self, indicator: &IndicatorType, timeframe: Option<TimeFrame>) -> String {
+        let name = self.indicator_column_name(indicator);
+        match timeframe {
+            Some(timeframe) => higher_timeframe_column(&name, timeframe),
+            None => name,
+        }
+    }
+
+    /// Nombre de columna del precio de cierre en el timeframe de una condición
+    pub(crate) fn price_column_name(timeframe: Option<TimeFrame>) -> String {
+        match timeframe {
+            Some(timeframe) => higher_timeframe_column("close", timeframe),
+            None => "close".to_string(),
+        }
+    }
+
     /// Convierte un indicador a expresión de Polars (ahora referencia columnas pre-calculadas)
     fn indicator_to_polars_expr(
         &self,
-        indicator: &darwinx_generator::ast::nodes::IndicatorType,
-        _df: &DataFrame,
+        indicator: &IndicatorType,
+        timeframe: Option<TimeFrame>,
     ) -> Result<Expr, BacktestError> {
         // Ahora simplemente referenciamos la columna pre-calculada
-        let col_name = self.indicator_column_name(indicator);
+        let col_name = self.condition_column_name(indicator, timeframe);
         Ok(col(&col_name))
     }
 
-    /// Calcula trades desde señales vectorizadas
+    /// Calcula trades desde señales vectorizadas, operando desde la vela `start`
     fn calculate_trades_from_signals(
         &self,
         df: &DataFrame,
+        start: usize,
         config: &BacktestConfig,
-    ) -> Result<Vec<Trade>, BacktestError> {
+    ) -> Result<(Vec<Trade>, MarginStats), BacktestError> {
         // Obtener columnas de señales y precios
         let entry_signal_col = df.column("entry_signal")
             .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get entry_signal: {}", e)))?;
         let exit_signal_col = df.column("exit_signal")
             .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get exit_signal: {}", e)))?;
+        let short_entry_signal_col = df.column("short_entry_signal")
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get short_entry_signal: {}", e)))?;
+        let short_exit_signal_col = df.column("short_exit_signal")
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get short_exit_signal: {}", e)))?;
         let close_col = df.column("close")
             .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get close: {}", e)))?;
         let high_col = df.column("high")
@@ -746,6 +718,10 @@ impl PolarsVectorizedBacktestEngine {
             .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast entry_signal: {}", e)))?;
         let exit_signals = exit_signal_col.bool()
             .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast exit_signal: {}", e)))?;
+        let short_entry_signals = short_entry_signal_col.bool()
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast short_entry_signal: {}", e)))?;
+        let short_exit_signals = short_exit_signal_col.bool()
+            .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast short_exit_signal: {}", e)))?;
         let closes = close_col.f64()
             .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast close: {}", e)))?;
         let highs = high_col.f64()
@@ -754,160 +730,334 @@ impl PolarsVectorizedBacktestEngine {
             .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast low: {}", e)))?;
         let timestamps = timestamp_col.i64()
             .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast timestamp: {}", e)))?;
+        // Volumen solo si el modelo de slippage lo necesita
+        let volumes_vec = match config.cost_model.slippage {
+            SlippageModel::VolumeImpact { .. } => {
+                let [.., volumes] = self.extract_ohlcv(df)?;
+                volumes
+            }
+            _ => Vec::new(),
+        };
 
         // Simular trading
         let mut trades = Vec::new();
-        let mut in_position = false;
-        let mut entry_price = 0.0;
-        let mut entry_timestamp = 0i64;
-        let mut entry_size = 0.0; // Guardar el tamaño de la posición al abrir
+        // Posiciones abiertas, todas del mismo lado (una señal contraria las cierra)
+        let mut positions: Vec<OpenPosition> = Vec::new();
+        let mut next_position_id = 0;
         let mut balance = config.initial_balance;
-        
+        let max_positions = config.max_positions.max(1);
 
         // Convertir las señales booleanas a vectores para acceso eficiente
         // Esto evita problemas con ChunkedArray::get() vs iter()
         let entry_signals_vec: Vec<bool> = entry_signals.iter().map(|opt| opt.unwrap_or(false)).collect();
         let exit_signals_vec: Vec<bool> = exit_signals.iter().map(|opt| opt.unwrap_or(false)).collect();
+        let short_entry_signals_vec: Vec<bool> = short_entry_signals.iter().map(|opt| opt.unwrap_or(false)).collect();
+        let short_exit_signals_vec: Vec<bool> = short_exit_signals.iter().map(|opt| opt.unwrap_or(false)).collect();
         let closes_vec: Vec<f64> = closes.iter().map(|opt| opt.unwrap_or(0.0)).collect();
         let highs_vec: Vec<f64> = highs.iter().map(|opt| opt.unwrap_or(0.0)).collect();
         let lows_vec: Vec<f64> = lows.iter().map(|opt| opt.unwrap_or(0.0)).collect();
         let timestamps_vec: Vec<i64> = timestamps.iter().map(|opt| opt.unwrap_or(0)).collect();
+        // ATR solo si el modelo de tamaño lo necesita
+        let atr_vec = config.position_sizing.atr_period()
+            .map(|period| atr_series(&highs_vec, &lows_vec, &closes_vec, period));
+        let exit_indicators = ExitIndicators::new(&highs_vec, &lows_vec, &closes_vec, config);
+        let slippage_series = SlippageSeries::new(&highs_vec, &lows_vec, &closes_vec, &volumes_vec, config);
+        let mut fees = FeeLedger::new();
+        let mut margin_stats = MarginStats::default();
         
-        for i in 0..df.height() {
+        for i in start..df.height() {
             let entry_signal = entry_signals_vec[i];
             let exit_signal = exit_signals_vec[i];
+            let short_entry_signal = short_entry_signals_vec[i];
+            let short_exit_signal = short_exit_signals_vec[i];
             let close = closes_vec[i];
             let high = highs_vec[i];
             let low = lows_vec[i];
             let timestamp = timestamps_vec[i];
 
+            let next_timestamp = timestamps_vec.get(i + 1).copied();
+
             // IMPORTANTE: Primero verificar si debemos salir (si estamos en posición)
             // Esto permite salir y entrar en la misma vela si es necesario
-            if in_position {
-                // Verificar stop loss y take profit primero (tienen prioridad)
-                let mut should_exit = false;
-                let mut exit_reason = String::new();
-                let mut exit_price = close;
-
-                // Verificar Take Profit
-                if let Some(tp_percent) = config.take_profit_percent {
-                    let tp_price = entry_price * (1.0 + tp_percent);
-                    if high >= tp_price {
-                        exit_price = tp_price;
-                        should_exit = true;
-                        exit_reason = "TakeProfit".to_string();
+            // En margen cruzado todas las posiciones comparten el precio de liquidación
+            let cross_liquidation = config.margin.filter(|m| m.mode == MarginMode::Cross).and_then(|margin| {
+                let side = positions.first()?.side;
+                let size = positions.iter().map(OpenPosition::size).sum();
+                let notional = positions.iter().map(OpenPosition::notional).sum();
+                margin.cross_liquidation_price(side, balance, size, notional)
+            });
+            let mut still_open = Vec::with_capacity(positions.len());
+            for mut position in positions.drain(..) {
+                let side = position.side;
+                let entry_price = position.average_entry_price();
+
+                // Los stops y el take profit tienen prioridad; se ejecutan al precio exacto
+                // (sin slippage adicional)
+                let mut exit = position.exits.check_levels(entry_price, high, low, config)
+                    .map(|(price, reason)| (price, 0.0, reason));
+
+                // La liquidación se ejecuta antes que cualquier nivel salvo un stop por delante de ella
+                let liquidation_price = match config.margin {
+                    Some(margin) if margin.mode == MarginMode::Isolated => Some(margin.isolated_liquidation_price(side, entry_price)),
+                    Some(_) => cross_liquidation,
+                    None => None,
+                };
+                if let Some(liquidation_price) = liquidation_price {
+                    let adverse = match side {
+                        PositionSide::Long => low,
+                        PositionSide::Short => high,
+                    };
+                    margin_stats.record_distance(MarginConfig::liquidation_distance(side, adverse, liquidation_price));
+                    let stop_first = exit.is_some_and(|(price, _, reason)| {
+                        reason != ExitReason::TakeProfit && MarginConfig::liquidation_distance(side, price, liquidation_price) > 0.0
+                    });
+                    if MarginConfig::touched(side, liquidation_price, high, low) && !stop_first {
+                        exit = Some((liquidation_price, 0.0, ExitReason::Liquidation));
                     }
                 }
 
-                // Verificar Stop Loss (solo si no se alcanzó TP)
-                if !should_exit {
-                    if let Some(sl_percent) = config.stop_loss_percent {
-                        let sl_price = entry_price * (1.0 - sl_percent);
-                        if low <= sl_price {
-                            exit_price = sl_price;
-                            should_exit = true;
-                            exit_reason = "StopLoss".to_string();
-                        }
+                // Después, salidas por tiempo y por señal al cierre de la vela
+                // (una señal de entrada en el lado contrario también cierra la posición)
+                let signal = match side {
+                    PositionSide::Long => exit_signal || short_entry_signal,
+                    PositionSide::Short => short_exit_signal || entry_signal,
+                };
+                if exit.is_none() {
+                    let reason = position.exits.check_timed(i, timestamp, next_timestamp, config)
+                        .or(signal.then_some(ExitReason::Signal));
+                    if let Some(reason) = reason {
+                        let slippage = slippage_series.slippage(i, true, close, position.size(), config);
+                        let exit_price = match side {
+                            PositionSide::Long => close - slippage,
+                            PositionSide::Short => close + slippage,
+                        };
+                        exit = Some((exit_price, slippage, reason));
                     }
                 }
 
-                // Si no se alcanzó SL/TP, verificar señal de salida
-                if !should_exit && exit_signal {
-                    should_exit = true;
-                    exit_reason = "Signal".to_string();
-                    let slippage = config.calculate_slippage(close);
-                    exit_price = close - slippage;
+                // Cerrar la posición completa: un trade por lote
+                if let Some((exit_price, slippage, reason)) = exit {
+                    let lots = std::mem::take(&mut position.lots);
+                    balance += self.close_lots(&position, lots, timestamp, exit_price, slippage, reason, &mut fees, config, &mut trades);
+                    continue;
                 }
 
-                // Cerrar posición si es necesario
-                if should_exit {
-                    // Usar el tamaño guardado al abrir la posición, no recalcularlo
-                    let trade_value = exit_price * entry_size;
-                    let commission = config.calculate_commission(trade_value);
-                    let pnl = (exit_price - entry_price) * entry_size - commission;
-                    let slippage = if exit_reason == "Signal" {
-                        config.calculate_slippage(close) * entry_size
-                    } else {
-                        0.0 // SL/TP se ejecutan al precio exacto (sin slippage adicional)
+                // Tomas de beneficio parciales al precio exacto de cada nivel alcanzado
+                while let Some(level) = config.scale_out.get(position.scale_outs) {
+                    let (price, touched) = match side {
+                        PositionSide::Long => {
+                            let price = entry_price * (1.0 + level.profit_percent);
+                            (price, high >= price)
+                        }
+                        PositionSide::Short => {
+                            let price = entry_price * (1.0 - level.profit_percent);
+                            (price, low <= price)
+                        }
                     };
+                    if !touched {
+                        break;
+                    }
+                    position.scale_outs += 1;
+                    let lots = position.take(position.size() * level.fraction.clamp(0.0, 1.0), config.close_order);
+                    balance += self.close_lots(&position, lots, timestamp, price, 0.0, ExitReason::PartialTakeProfit, &mut fees, config, &mut trades);
+                }
 
-                    trades.push(Trade {
-                        entry_timestamp,
-                        exit_timestamp: timestamp,
-                        entry_price,
-                        exit_price,
-                        size: entry_size,
-                        is_long: true,
-                        pnl,
-                        commission,
-                        slippage,
-                        exit_reason,
-                    });
-
-                    balance += pnl;
-                    in_position = false;
+                if !position.lots.is_empty() {
+                    let entry_price = position.average_entry_price();
+                    position.exits.update(i, entry_price, high, low, &exit_indicators, config);
+                    still_open.push(position);
                 }
             }
+            positions = still_open;
             
-            // Después de verificar salida, verificar entrada (si no estamos en posición)
-            if !in_position && entry_signal {
-                // Entrar en posición long
-                let slippage = config.calculate_slippage(close);
-                entry_price = close + slippage;
-                entry_timestamp = timestamp;
-                
-                // Position sizing FIJO para comparar estrategias de forma justa
-                // El tamaño es siempre el mismo: (balance * position_size_percent) / max_positions
-                let position_value = (config.initial_balance * config.position_size_percent) 
-                    / config.max_positions as f64;
-                entry_size = position_value / entry_price;
+            // Después de verificar salida, verificar entrada
+            // Señales simultáneas en ambos lados son ambiguas y se ignoran
+            let entry_side = match (entry_signal, short_entry_signal) {
+                (true, false) => Some(PositionSide::Long),
+                (false, true) => Some(PositionSide::Short),
+                _ => None,
+            };
+            // En la última vela de la sesión y en las velas sintéticas no se abren posiciones
+            let entry_side = entry_side.filter(|_| {
+                !session_closes_after(timestamp, next_timestamp, config) && !config.is_synthetic_bar(timestamp)
+            });
+            if let Some(side) = entry_side {
+                // En largo compramos más caro, en corto vendemos más barato. El tamaño se
+                // calcula con el slippage de una orden mínima y el fill con el de su tamaño
+                let with_slippage = |slippage: f64| match side {
+                    PositionSide::Long => close + slippage,
+                    PositionSide::Short => close - slippage,
+                };
+                let entry_price = with_slippage(slippage_series.slippage(i, true, close, 0.0, config));
+
+                // Una señal repetida añade un lote a la última posición (pyramiding) o,
+                // si ya no admite más, abre otra posición mientras quede hueco en `max_positions`
+                let pyramid = positions
+                    .last()
+                    .filter(|p| p.side == side && p.adds < config.pyramiding.max_adds);
+                let entry_size = match pyramid {
+                    Some(position) => {
+                        position.initial_size() * config.pyramiding.size_decay.powi(position.adds as i32 + 1)
+                    }
+                    // Por defecto el tamaño es fijo para comparar estrategias de forma justa:
+                    // (initial_balance * position_size_percent) / max_positions
+                    None if positions.len() < max_positions => config.position_sizing.position_size(&SizingContext {
+                        side,
+                        entry_price,
+                        balance,
+                        atr: atr_vec.as_ref().and_then(|atr| atr[i]),
+                        closed_trades: &trades,
+                        config,
+                    }),
+                    None => 0.0,
+                };
+                let pyramid = pyramid.is_some();
+                let entry_price = with_slippage(slippage_series.slippage(i, true, close, entry_size, config));
                 
-                let commission = config.calculate_commission(entry_price * entry_size);
-                let required_balance = entry_price * entry_size + commission;
+                let commission = fees.quote(config, timestamp, entry_price * entry_size, Liquidity::Taker);
+                let required_balance = config.initial_margin(entry_price * entry_size) + commission;
+                // El margen de las posiciones abiertas no está disponible para nuevas entradas
+                let available_balance = balance - positions.iter().map(|p| config.initial_margin(p.notional())).sum::<f64>();
                 
-                if balance >= required_balance {
+                if entry_size > 0.0 && available_balance >= required_balance {
+                    let commission = fees.charge(config, timestamp, entry_price * entry_size, Liquidity::Taker);
                     balance -= commission;
-                    in_position = true;
+                    let lot = Lot { entry_timestamp: timestamp, entry_price, size: entry_size, commission };
+                    match positions.last_mut().filter(|_| pyramid) {
+                        Some(position) => {
+                            position.lots.push(lot);
+                            position.adds += 1;
+                        }
+                        None => {
+                            let mut position = OpenPosition::new(next_position_id, side, lot, i);
+                            position.exits.refresh_indicator_stops(i, &exit_indicators, config);
+                            positions.push(position);
+                            next_position_id += 1;
+                        }
+                    }
                 }
             }
+
+            if config.margin.is_some() && !positions.is_empty() {
+                let used_margin = positions.iter().map(|p| config.initial_margin(p.notional())).sum();
+                let unrealized: f64 = positions.iter().map(|p| p.unrealized_pnl(close)).sum();
+                margin_stats.record_usage(used_margin, balance + unrealized);
+            }
         }
 
-        // Cerrar posición abierta al final si existe
-        if in_position {
+        // Cerrar posiciones abiertas al final si existen
+        if !positions.is_empty() {
             let last_close = closes.get(df.height() - 1)
                 .ok_or_else(|| BacktestError::DataError(anyhow::anyhow!("Missing last close")))?;
             let last_timestamp = timestamps.get(df.height() - 1)
                 .ok_or_else(|| BacktestError::DataError(anyhow::anyhow!("Missing last timestamp")))?;
             
-            let slippage = config.calculate_slippage(last_close);
-            let exit_price = last_close - slippage;
-            // Usar el tamaño guardado al abrir la posición, no recalcularlo
-            let trade_value = exit_price * entry_size;
-            let commission = config.calculate_commission(trade_value);
-            let pnl = (exit_price - entry_price) * entry_size - commission;
-
-            trades.push(Trade {
-                entry_timestamp,
-                exit_timestamp: last_timestamp,
-                entry_price,
-                exit_price,
-                size: entry_size,
-                is_long: true,
-                pnl,
-                commission,
-                slippage: slippage * entry_size,
-                exit_reason: "End of data".to_string(),
-            });
+            let last_index = df.height() - 1;
+            for mut position in positions {
+                let slippage = slippage_series.slippage(last_index, true, last_close, position.size(), config);
+                let exit_price = match position.side {
+                    PositionSide::Long => last_close - slippage,
+                    PositionSide::Short => last_close + slippage,
+                };
+                let lots = std::mem::take(&mut position.lots);
+                self.close_lots(&position, lots, last_timestamp, exit_price, slippage, ExitReason::EndOfData, &mut fees, config, &mut trades);
+            }
         }
         
 
-        Ok(trades)
+        Ok((trades, margin_stats))
+    }
+
+    /// Cierra lotes de una posición al mismo precio: registra un trade por lote y devuelve el P&L total
+    ///
+    /// `slippage` es por unidad.
+    #[allow(clippy::too_many_arguments)]
+    fn close_lots(
+        &self,
+        position: &OpenPosition,
+        lots: Vec<Lot>,
+        exit_timestamp: i64,
+        exit_price: f64,
+        slippage: f64,
+        exit_reason: ExitReason,
+        fees: &mut FeeLedger,
+        config: &BacktestConfig,
+        trades: &mut Vec<Trade>,
+    ) -> f64 {
+        let mut pnl = 0.0;
+        for lot in lots {
+            let trade = self.close_trade(
+                position.side,
+                lot,
+                exit_timestamp,
+                exit_price,
+                slippage * lot.size,
+                exit_reason,
+                Liquidity::for_exit(exit_reason),
+                fees,
+                config,
+            );
+            pnl += trade.pnl;
+            trades.push(Trade { position_id: position.id, ..trade });
+        }
+        pnl
+    }
+
+    /// Construye el trade resultante de cerrar el lote `lot`
+    ///
+    /// El P&L descuenta la comisión de salida (más la de liquidación si la hay) y el
+    /// funding; en corto es inverso y descuenta además el costo de préstamo acumulado. `position_id` queda a 0; lo
+    /// asigna quien agrupa los trades en posiciones.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn close_trade(
+        &self,
+        side: PositionSide,
+        lot: Lot,
+        exit_timestamp: i64,
+        exit_price: f64,
+        slippage: f64,
+        exit_reason: ExitReason,
+        liquidity: Liquidity,
+        fees: &mut FeeLedger,
+        config: &BacktestConfig,
+    ) -> Trade {
+        let Lot { entry_timestamp, entry_price, size, commission: entry_commission } = lot;
+        let mut commission = fees.charge(config, exit_timestamp, exit_price * size, liquidity);
+        if exit_reason == ExitReason::Liquidation && let Some(margin) = &config.margin {
+            commission += margin.liquidation_fee(exit_price * size);
+        }
+        let funding = config.cost_model.funding_payment(side, entry_price * size, entry_timestamp, exit_timestamp);
+        let (pnl, borrow_cost) = match side {
+            PositionSide::Long => ((exit_price - entry_price) * size - commission - funding, 0.0),
+            PositionSide::Short => {
+                let borrow_cost = config.calculate_borrow_cost(entry_price * size, exit_timestamp - entry_timestamp);
+                ((entry_price - exit_price) * size - commission - funding - borrow_cost, borrow_cost)
+            }
+        };
+
+        Trade {
+            entry_timestamp,
+            exit_timestamp,
+            entry_price,
+            exit_price,
+            size,
+            is_long: side == PositionSide::Long,
+            pnl,
+            commission,
+            slippage,
+            borrow_cost,
+            exit_reason,
+            position_id: 0,
+            entry_commission,
+            funding,
+        }
     }
 
     /// Calcula métricas desde trades
-    fn calculate_metrics_from_trades(
+    pub(crate) fn calculate_metrics_from_trades(
         &self,
         trades: &[Trade],
+        equity_curve: &[EquityPoint],
         config: &BacktestConfig,
     ) -> Result<BacktestMetrics, BacktestError> {
         use crate::metrics::*;
@@ -928,8 +1078,8 @@ impl PolarsVectorizedBacktestEngine {
         let largest_loss = calculate_largest_loss(trades);
         let expectancy = calculate_expectancy(trades);
 
-        // Calcular returns
-        let total_pnl: f64 = trades.iter().map(|t| t.pnl).sum();
+        // Calcular returns sobre el P&L neto, igual que el balance final y la curva de equity
+        let total_pnl: f64 = trades.iter().map(Trade::net_pnl).sum();
         let total_return = total_pnl / config.initial_balance;
 
         // Calcular ROI sobre capital arriesgado
@@ -942,22 +1092,11 @@ impl PolarsVectorizedBacktestEngine {
             0.0
         };
 
-        // Calcular equity curve para métricas de riesgo
-        let mut balance = config.initial_balance;
-        let mut equity_curve = Vec::new();
-        for trade in trades {
-            balance += trade.pnl;
-            equity_curve.push(crate::types::EquityPoint {
-                timestamp: trade.exit_timestamp,
-                balance,
-                drawdown: 0.0,
-            });
-        }
-
-        let max_drawdown = calculate_max_drawdown(&equity_curve);
-        let max_drawdown_duration = calculate_max_drawdown_duration(&equity_curve);
+        // Riesgo sobre la curva mark-to-market: incluye los drawdowns dentro de cada trade
+        let max_drawdown = calculate_max_drawdown(equity_curve);
+        let max_drawdown_duration = calculate_max_drawdown_duration(equity_curve);
 
-        // Calcular returns periódicos para Sharpe/Sortino
+        // Calcular returns por vela para Sharpe/Sortino
         let returns: Vec<f64> = equity_curve
             .windows(2)
             .map(|w| (w[1].balance - w[0].balance) / w[0].balance)
@@ -978,6 +1117,8 @@ impl PolarsVectorizedBacktestEngine {
         let calmar_ratio = calculate_calmar_ratio(annualized_return, max_drawdown);
 
         let var_95 = calculate_var_95(&returns);
+        let returns_skewness = calculate_skewness(&returns);
+        let returns_kurtosis = calculate_kurtosis(&returns);
         let total_profit = calculate_total_profit(trades);
         let total_loss = calculate_total_loss(trades);
         let recovery_factor = calculate_recovery_factor(total_profit, max_drawdown * config.initial_balance);
@@ -1004,10 +1145,11 @@ impl PolarsVectorizedBacktestEngine {
         };
 
         // Contar trades por razón de salida
-        let stop_loss_exits = trades.iter().filter(|t| t.exit_reason == "StopLoss").count();
-        let take_profit_exits = trades.iter().filter(|t| t.exit_reason == "TakeProfit").count();
-        let signal_exits = trades.iter().filter(|t| t.exit_reason == "Signal").count();
-        let end_of_data_exits = trades.iter().filter(|t| t.exit_reason == "End of data").count();
+        let count_exits = |reason| count_trades_by_exit_reason(trades, reason);
+        let stop_loss_exits = count_exits(ExitReason::StopLoss);
+        let take_profit_exits = count_exits(ExitReason::TakeProfit);
+        let signal_exits = count_exits(ExitReason::Signal);
+        let end_of_data_exits = count_exits(ExitReason::EndOfData);
 
         Ok(BacktestMetrics {
             total_return,
@@ -1044,7 +1186,42 @@ impl PolarsVectorizedBacktestEngine {
             take_profit_exits,
             signal_exits,
             end_of_data_exits,
+            trailing_stop_exits: count_exits(ExitReason::TrailingStop),
+            break_even_exits: count_exits(ExitReason::BreakEven),
+            chandelier_exits: count_exits(ExitReason::ChandelierExit),
+            max_bars_exits: count_exits(ExitReason::MaxBars),
+            session_close_exits: count_exits(ExitReason::SessionClose),
+            liquidation_exits: count_exits(ExitReason::Liquidation),
+            exit_reasons: calculate_exit_reason_breakdown(trades),
             entry_signals_count: 0, // Se establece en backtest_single_strategy
+            long_trades: count_trades_by_side(trades, true),
+            short_trades: count_trades_by_side(trades, false),
+            long_win_rate: calculate_win_rate_by_side(trades, true),
+            short_win_rate: calculate_win_rate_by_side(trades, false),
+            long_pnl: calculate_pnl_by_side(trades, true),
+            short_pnl: calculate_pnl_by_side(trades, false),
+            sharpe_observations: returns.len(),
+            returns_skewness,
+            returns_kurtosis,
+            probabilistic_sharpe_ratio: calculate_probabilistic_sharpe_ratio(
+                sharpe_ratio,
+                0.0,
+                returns.len(),
+                returns_skewness,
+                returns_kurtosis,
+            ),
+            // Margen y benchmark se establecen en backtest_single_strategy
+            max_margin_usage: 0.0,
+            average_margin_usage: 0.0,
+            min_liquidation_distance: None,
+            benchmark_return: 0.0,
+            excess_return: 0.0,
+            alpha: 0.0,
+            beta: 0.0,
+            information_ratio: 0.0,
+            tracking_error: 0.0,
+            up_capture: 0.0,
+            down_capture: 0.0,
         })
     }
 }
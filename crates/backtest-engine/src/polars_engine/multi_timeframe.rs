@@ -0,0 +1,92 @@
+//! Condiciones evaluadas en timeframes superiores
+//!
+//! Las velas de la serie principal se agrupan en barras del timeframe superior
+//! (alineadas al calendario con `TimeFrame::bar_start`: semanas desde el lunes y
+//! meses naturales) y los valores calculados sobre esas barras se proyectan sobre
+//! la serie principal sin look-ahead: cada vela solo ve la última barra superior
+//! que ya había cerrado al cierre de la vela.
+
+use darwinx_core::TimeFrame;
+use darwinx_generator::ast::nodes::ConditionTimeframe;
+
+/// Intervalo de la serie principal: la menor diferencia positiva entre velas consecutivas
+pub(crate) fn base_interval(timestamps: &[i64]) -> Option<i64> {
+    timestamps
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|&diff| diff > 0)
+        .min()
+}
+
+/// Timeframe de una condición si es superior al principal
+///
+/// None si la condición se evalúa en la serie principal (sin timeframe o con uno
+/// que no supera el intervalo de las velas). Los timeframes relativos se resuelven
+/// sobre el intervalo de las velas, que debe ser un número entero de minutos.
+pub(crate) fn condition_timeframe(timeframe: Option<ConditionTimeframe>, base_millis: Option<i64>) -> Option<TimeFrame> {
+    let base_millis = base_millis?;
+    let timeframe = match timeframe? {
+        ConditionTimeframe::Relative(multiple) => TimeFrame::from_millis(base_millis * i64::from(multiple))?,
+        ConditionTimeframe::Absolute(timeframe) => timeframe,
+    };
+    (timeframe.to_millis() > base_millis).then_some(timeframe)
+}
+
+/// Nombre de la columna de una serie calculada en un timeframe superior
+pub(crate) fn higher_timeframe_column(column: &str, timeframe: TimeFrame) -> String {
+    format!("{}@{}", column, timeframe)
+}
+
+/// Serie principal agrupada en barras de un timeframe superior
+pub(crate) struct HigherTimeframeBars {
+    /// close, high, low y volume de cada barra (mismo orden que `extract_ohlcv`)
+    pub ohlcv: [Vec<f64>; 4],
+    /// Índice de la última barra cerrada al cierre de cada vela principal
+    visible: Vec<Option<usize>>,
+}
+
+impl HigherTimeframeBars {
+    /// Agrupa las velas (`ohlcv` en el orden close, high, low, volume) en barras de `timeframe`
+    pub(crate) fn resample(timestamps: &[i64], ohlcv: &[Vec<f64>; 4], base_millis: i64, timeframe: TimeFrame) -> Self {
+        let [closes, highs, lows, volumes] = ohlcv;
+        let mut starts: Vec<i64> = Vec::new();
+        let mut bars: [Vec<f64>; 4] = Default::default();
+
+        for (i, &timestamp) in timestamps.iter().enumerate() {
+            let start = timeframe.bar_start(timestamp);
+            if starts.last() == Some(&start) {
+                let last = bars[0].len() - 1;
+                bars[0][last] = closes[i];
+                bars[1][last] = bars[1][last].max(highs[i]);
+                bars[2][last] = bars[2][last].min(lows[i]);
+                bars[3][last] += volumes[i];
+            } else {
+                starts.push(start);
+                bars[0].push(closes[i]);
+                bars[1].push(highs[i]);
+                bars[2].push(lows[i]);
+                bars[3].push(volumes[i]);
+            }
+        }
+
+        // Una barra es visible desde la vela cuyo cierre alcanza el final de la barra
+        let mut visible = Vec::with_capacity(timestamps.len());
+        let mut closed = 0;
+        for &timestamp in timestamps {
+            while closed < starts.len() && timeframe.next_bar_start(starts[closed]) <= timestamp + base_millis {
+                closed += 1;
+            }
+            visible.push(closed.checked_sub(1));
+        }
+
+        Self { ohlcv: bars, visible }
+    }
+
+    /// Proyecta valores por barra superior sobre las velas principales (NaN sin barra cerrada)
+    pub(crate) fn align(&self, values: &[f64]) -> Vec<f64> {
+        self.visible
+            .iter()
+            .map(|bar| bar.and_then(|k| values.get(k).copied()).unwrap_or(f64::NAN))
+            .collect()
+    }
+}
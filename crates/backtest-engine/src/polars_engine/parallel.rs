@@ -0,0 +1,281 @@
+//! Ejecución paralela del backtest masivo con rayon
+//!
+//! Las estrategias se procesan por bloques (`chunk_size`): cada bloque se
+//! reparte entre los hilos y sus resultados se entregan en el mismo orden en
+//! que llegaron las estrategias. Como solo se retiene un bloque de resultados
+//! a la vez, consumir el stream mantiene la memoria acotada aunque el batch
+//! tenga 100k estrategias.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use polars::prelude::DataFrame;
+use rayon::prelude::*;
+use darwinx_core::Candle;
+use darwinx_generator::StrategyAST;
+use crate::config::BacktestConfig;
+use crate::error::BacktestError;
+use crate::metrics::downsample_equity_curve;
+use crate::types::{BacktestResult, EquityPoint};
+use super::massive::PolarsVectorizedBacktestEngine;
+
+/// Tamaño de bloque por defecto del stream de resultados
+pub const DEFAULT_CHUNK_SIZE: usize = 1024;
+
+/// Progreso del backtest masivo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BacktestProgress {
+    /// Estrategias terminadas
+    pub completed: usize,
+    /// Total de estrategias del batch
+    pub total: usize,
+}
+
+/// Callback de progreso; se invoca desde los hilos de trabajo tras cada estrategia
+pub type ProgressCallback = Arc<dyn Fn(BacktestProgress) + Send + Sync>;
+
+/// Curva de equity conservada en cada resultado
+///
+/// La curva mark-to-market siempre se calcula porque alimenta las métricas; esta
+/// opción solo decide cuánto de ella se retiene en memoria.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EquityCurveOutput {
+    /// Un punto por vela
+    Full,
+    /// Como máximo N puntos, conservando picos y valles
+    Downsampled(usize),
+    /// Sin curva (solo métricas resumen)
+    Skip,
+}
+
+impl EquityCurveOutput {
+    fn apply(&self, curve: &mut Vec<EquityPoint>) {
+        match self {
+            EquityCurveOutput::Full => {}
+            EquityCurveOutput::Downsampled(max_points) => {
+                *curve = downsample_equity_curve(curve, *max_points);
+            }
+            EquityCurveOutput::Skip => *curve = Vec::new(),
+        }
+    }
+}
+
+/// Configuración de la ejecución paralela
+#[derive(Clone)]
+pub struct ParallelConfig {
+    /// Número de hilos (None = pool global de rayon)
+    pub num_threads: Option<usize>,
+    /// Estrategias procesadas por bloque (resultados retenidos como máximo)
+    pub chunk_size: usize,
+    /// Conservar la lista de trades en cada resultado (las métricas siempre se calculan)
+    pub keep_trades: bool,
+    /// Qué parte de la curva de equity conservar en cada resultado
+    pub equity_curve: EquityCurveOutput,
+    /// Callback de progreso opcional
+    pub progress: Option<ProgressCallback>,
+}
+
+impl ParallelConfig {
+    /// Fija el número de hilos
+    pub fn with_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = Some(num_threads);
+        self
+    }
+
+    /// Fija el tamaño de bloque (mínimo 1)
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Descarta los trades de cada resultado para ahorrar memoria
+    pub fn without_trades(mut self) -> Self {
+        self.keep_trades = false;
+        self
+    }
+
+    /// Descarta la curva de equity de cada resultado (las métricas se calculan igual)
+    pub fn without_equity_curve(mut self) -> Self {
+        self.equity_curve = EquityCurveOutput::Skip;
+        self
+    }
+
+    /// Reduce la curva de equity de cada resultado a como máximo `max_points` puntos
+    pub fn with_equity_curve_points(mut self, max_points: usize) -> Self {
+        self.equity_curve = EquityCurveOutput::Downsampled(max_points);
+        self
+    }
+
+    /// Registra un callback de progreso
+    pub fn with_progress(mut self, progress: impl Fn(BacktestProgress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+}
+
+impl Default for ParallelConfig {
+    fn default() -> Self {
+        Self {
+            num_threads: None,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            keep_trades: true,
+            equity_curve: EquityCurveOutput::Full,
+            progress: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for ParallelConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParallelConfig")
+            .field("num_threads", &self.num_threads)
+            .field("chunk_size", &self.chunk_size)
+            .field("keep_trades", &self.keep_trades)
+            .field("equity_curve", &self.equity_curve)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// Iterador de resultados del backtest masivo paralelo
+///
+/// Los resultados salen en el orden de las estrategias de entrada,
+/// independientemente del número de hilos.
+pub struct MassiveBacktestStream<'a> {
+    engine: &'a PolarsVectorizedBacktestEngine,
+    config: &'a BacktestConfig,
+    df: DataFrame,
+    strategies: std::vec::IntoIter<StrategyAST>,
+    buffer: VecDeque<BacktestResult>,
+    pool: Option<rayon::ThreadPool>,
+    options: ParallelConfig,
+    completed: AtomicUsize,
+    total: usize,
+}
+
+impl<'a> MassiveBacktestStream<'a> {
+    pub(crate) fn new(
+        engine: &'a PolarsVectorizedBacktestEngine,
+        strategies: Vec<StrategyAST>,
+        df: DataFrame,
+        config: &'a BacktestConfig,
+        options: ParallelConfig,
+    ) -> Result<Self, BacktestError> {
+        let pool = match options.num_threads {
+            Some(num_threads) => Some(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .map_err(|e| BacktestError::ConfigError(format!("Failed to build thread pool: {}", e)))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            engine,
+            config,
+            df,
+            total: strategies.len(),
+            strategies: strategies.into_iter(),
+            buffer: VecDeque::new(),
+            pool,
+            options,
+            completed: AtomicUsize::new(0),
+        })
+    }
+
+    /// Total de estrategias del batch
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Procesa en paralelo el siguiente bloque de estrategias
+    fn fill_next_chunk(&mut self) {
+        let chunk: Vec<StrategyAST> = self.strategies.by_ref().take(self.options.chunk_size.max(1)).collect();
+        if chunk.is_empty() {
+            return;
+        }
+
+        let Self { engine, config, df, options, completed, total, .. } = &*self;
+        let run = || {
+            // `collect` sobre un iterador indexado conserva el orden de entrada
+            chunk
+                .par_iter()
+                .map(|strategy| {
+                    let mut result = engine.backtest_or_empty(df, strategy, config);
+                    if !options.keep_trades {
+                        result.trades = Vec::new();
+                    }
+                    options.equity_curve.apply(&mut result.equity_curve);
+                    let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
+                    if let Some(progress) = &options.progress {
+                        progress(BacktestProgress { completed: done, total: *total });
+                    }
+                    result
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let results = match &self.pool {
+            Some(pool) => pool.install(run),
+            None => run(),
+        };
+        self.buffer.extend(results);
+    }
+}
+
+impl Iterator for MassiveBacktestStream<'_> {
+    type Item = BacktestResult;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.is_empty() {
+            self.fill_next_chunk();
+        }
+        self.buffer.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.buffer.len() + self.strategies.len();
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for MassiveBacktestStream<'_> {}
+
+impl PolarsVectorizedBacktestEngine {
+    /// Ejecuta el backtest masivo en paralelo retornando un stream de resultados
+    ///
+    /// Los indicadores se comparten entre hilos mediante la caché del motor.
+    /// Los resultados se calculan por bloques a medida que se consume el stream;
+    /// cada bloque busca sus series con la clave de su propio dataset, así que
+    /// otras llamadas al mismo motor mientras el stream sigue abierto (incluso
+    /// `clear_indicator_cache`) no le devuelven columnas de otros datos.
+    pub fn stream_massive_backtest<'a>(
+        &'a self,
+        strategies: Vec<StrategyAST>,
+        candles: &[Candle],
+        config: &'a BacktestConfig,
+        options: ParallelConfig,
+    ) -> Result<MassiveBacktestStream<'a>, BacktestError> {
+        if candles.is_empty() {
+            return Err(BacktestError::DataError(anyhow::anyhow!(
+                "No candles provided for backtest"
+            )));
+        }
+
+        let df = self.candles_to_dataframe(candles)?;
+
+        MassiveBacktestStream::new(self, strategies, df, config, options)
+    }
+
+    /// Ejecuta el backtest masivo en paralelo y retorna todos los resultados en orden
+    pub fn run_massive_backtest_parallel(
+        &self,
+        strategies: Vec<StrategyAST>,
+        candles: &[Candle],
+        config: &BacktestConfig,
+        options: ParallelConfig,
+    ) -> Result<Vec<BacktestResult>, BacktestError> {
+        Ok(self.stream_massive_backtest(strategies, candles, config, options)?.collect())
+    }
+}
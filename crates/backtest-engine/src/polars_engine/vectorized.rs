@@ -2,11 +2,16 @@
 //!
 //! Este motor procesa múltiples velas simultáneamente usando operaciones
 //! vectorizadas de Polars para máximo throughput.
+//!
+//! El motor no calcula indicadores: delega las decisiones en implementaciones
+//! de [`Strategy`], que deben usar la API incremental de
+//! `darwinx_indicators::streaming` (la misma que el motor masivo) para no
+//! recalcular ventanas completas en cada vela.
 
 use async_trait::async_trait;
 use crate::data_provider::DataProvider;
 use crate::error::BacktestError;
-use crate::types::{BacktestResult, BacktestMetrics, Trade, EquityPoint, BacktestMetadata};
+use crate::types::{BacktestResult, BacktestMetrics, Trade, EquityPoint, BacktestMetadata, ExitReason};
 use crate::config::BacktestConfig;
 
 /// Motor de backtest vectorizado usando Polars
@@ -147,7 +152,7 @@ impl BacktestEngine for PolarsBacktestEngine {
                         config,
                     )?;
                     balance = trade.pnl + balance - trade.commission;
-                    trades.push(trade);
+                    trades.push(Trade { position_id: trades.len(), ..trade });
                     current_position = None;
                 }
             } else {
@@ -223,7 +228,7 @@ impl BacktestEngine for PolarsBacktestEngine {
                 config,
             )?;
             balance = trade.pnl + balance - trade.commission;
-            trades.push(trade);
+            trades.push(Trade { position_id: trades.len(), ..trade });
         }
 
         // Calcular métricas
@@ -298,10 +303,19 @@ impl PolarsBacktestEngine {
         let commission = config.calculate_commission(trade_value);
         let slippage = config.calculate_slippage(exit_price) * position.size;
 
+        let borrow_cost = if position.is_long {
+            0.0
+        } else {
+            config.calculate_borrow_cost(
+                position.entry_price * position.size,
+                exit_timestamp - position.entry_timestamp,
+            )
+        };
+
         let pnl = if position.is_long {
             (exit_price - position.entry_price) * position.size - commission
         } else {
-            (position.entry_price - exit_price) * position.size - commission
+            (position.entry_price - exit_price) * position.size - commission - borrow_cost
         };
 
         Ok(Trade {
@@ -314,7 +328,11 @@ impl PolarsBacktestEngine {
             pnl,
             commission,
             slippage,
-            exit_reason: "Strategy signal".to_string(),
+            borrow_cost,
+            exit_reason: ExitReason::Signal,
+            position_id: 0,
+            entry_commission: config.calculate_commission(position.entry_price * position.size),
+            funding: 0.0,
         })
     }
 
@@ -365,6 +383,8 @@ impl PolarsBacktestEngine {
         let max_drawdown_duration = calculate_max_drawdown_duration(equity_curve);
         let calmar_ratio = calculate_calmar_ratio(annualized_return, max_drawdown);
         let var_95 = calculate_var_95(&returns);
+        let returns_skewness = calculate_skewness(&returns);
+        let returns_kurtosis = calculate_kurtosis(&returns);
 
         // Statistics
         let total_trades = trades.len();
@@ -404,10 +424,11 @@ impl PolarsBacktestEngine {
         };
 
         // Contar trades por razón de salida
-        let stop_loss_exits = trades.iter().filter(|t| t.exit_reason == "StopLoss").count();
-        let take_profit_exits = trades.iter().filter(|t| t.exit_reason == "TakeProfit").count();
-        let signal_exits = trades.iter().filter(|t| t.exit_reason == "Signal").count();
-        let end_of_data_exits = trades.iter().filter(|t| t.exit_reason == "End of data" || t.exit_reason == "EndOfData").count();
+        let count_exits = |reason| count_trades_by_exit_reason(trades, reason);
+        let stop_loss_exits = count_exits(ExitReason::StopLoss);
+        let take_profit_exits = count_exits(ExitReason::TakeProfit);
+        let signal_exits = count_exits(ExitReason::Signal);
+        let end_of_data_exits = count_exits(ExitReason::EndOfData);
 
         Ok(BacktestMetrics {
             total_return,
@@ -444,7 +465,41 @@ impl PolarsBacktestEngine {
             take_profit_exits,
             signal_exits,
             end_of_data_exits,
+            trailing_stop_exits: count_exits(ExitReason::TrailingStop),
+            break_even_exits: count_exits(ExitReason::BreakEven),
+            chandelier_exits: count_exits(ExitReason::ChandelierExit),
+            max_bars_exits: count_exits(ExitReason::MaxBars),
+            session_close_exits: count_exits(ExitReason::SessionClose),
+            liquidation_exits: count_exits(ExitReason::Liquidation),
+            exit_reasons: calculate_exit_reason_breakdown(trades),
             entry_signals_count: 0, // No disponible en este engine
+            long_trades: count_trades_by_side(trades, true),
+            short_trades: count_trades_by_side(trades, false),
+            long_win_rate: calculate_win_rate_by_side(trades, true),
+            short_win_rate: calculate_win_rate_by_side(trades, false),
+            long_pnl: calculate_pnl_by_side(trades, true),
+            short_pnl: calculate_pnl_by_side(trades, false),
+            sharpe_observations: returns.len(),
+            returns_skewness,
+            returns_kurtosis,
+            probabilistic_sharpe_ratio: calculate_probabilistic_sharpe_ratio(
+                sharpe_ratio,
+                0.0,
+                returns.len(),
+                returns_skewness,
+                returns_kurtosis,
+            ),
+            max_margin_usage: 0.0,
+            average_margin_usage: 0.0,
+            min_liquidation_distance: None,
+            benchmark_return: 0.0,
+            excess_return: 0.0,
+            alpha: 0.0,
+            beta: 0.0,
+            information_ratio: 0.0,
+            tracking_error: 0.0,
+            up_capture: 0.0,
+            down_capture: 0.0,
         })
     }
 }
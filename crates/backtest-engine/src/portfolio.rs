@@ -0,0 +1,667 @@
+//! Backtest de cartera: varios símbolos y estrategias con capital compartido
+//!
+//! Cada `PortfolioSleeve` opera una estrategia sobre un símbolo. Las señales se
+//! calculan con el motor masivo sobre las velas de su símbolo y la simulación
+//! recorre la línea temporal común (la unión de los timestamps de todos los
+//! símbolos) con una sola caja compartida.
+//!
+//! Cada estrategia tiene como objetivo la misma fracción del equity:
+//! `position_size_percent / número de estrategias`. Las entradas se recortan para
+//! respetar la exposición máxima por símbolo y total, y la caja disponible. Las
+//! reglas de salida (stops, take profit, salidas por tiempo) son las de
+//! `BacktestConfig`, con una posición por estrategia.
+//!
+//! El informe incluye la equity de la cartera, la correlación entre los retornos
+//! de las estrategias y la atribución del P&L y la exposición por símbolo.
+
+use std::collections::{BTreeSet, HashMap};
+use serde::{Deserialize, Serialize};
+use darwinx_core::{Candle, PositionSide};
+use darwinx_generator::StrategyAST;
+use crate::config::BacktestConfig;
+use crate::costs::{FeeLedger, Liquidity, SlippageSeries};
+use crate::error::BacktestError;
+use crate::exits::{session_closes_after, ExitIndicators, ExitTracker};
+use crate::metrics::calculate_correlation;
+use crate::polars_engine::massive::PolarsVectorizedBacktestEngine;
+use crate::positions::Lot;
+use crate::types::{BacktestMetrics, EquityPoint, ExitReason, Trade};
+
+/// Regla de rebalanceo de las posiciones abiertas hacia su asignación objetivo
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum RebalanceRule {
+    /// Sin rebalanceo: cada posición conserva su tamaño de entrada
+    #[default]
+    Never,
+    /// Cada `bars` pasos de la línea temporal
+    Periodic { bars: usize },
+    /// Cuando el valor de una posición se desvía de su objetivo más de `threshold` (ej: 0.2 = 20%)
+    Drift { threshold: f64 },
+}
+
+/// Configuración del backtest de cartera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioConfig {
+    /// Costes y reglas de salida; `initial_balance` es el capital compartido y
+    /// `position_size_percent` la fracción del equity repartida entre las estrategias
+    pub backtest: BacktestConfig,
+    /// Exposición máxima por símbolo como fracción del equity
+    pub max_symbol_exposure: f64,
+    /// Exposición máxima total como fracción del equity
+    pub max_total_exposure: f64,
+    /// Regla de rebalanceo
+    #[serde(default)]
+    pub rebalance: RebalanceRule,
+}
+
+impl Default for PortfolioConfig {
+    fn default() -> Self {
+        Self::new(BacktestConfig::default())
+    }
+}
+
+impl PortfolioConfig {
+    /// Configuración sin límites de exposición adicionales ni rebalanceo
+    pub fn new(backtest: BacktestConfig) -> Self {
+        Self {
+            backtest,
+            max_symbol_exposure: 1.0,
+            max_total_exposure: 1.0,
+            rebalance: RebalanceRule::Never,
+        }
+    }
+
+    /// Limita la exposición de cada símbolo a `fraction` del equity
+    pub fn with_symbol_exposure(mut self, fraction: f64) -> Self {
+        self.max_symbol_exposure = fraction;
+        self
+    }
+
+    /// Limita la exposición total a `fraction` del equity
+    pub fn with_total_exposure(mut self, fraction: f64) -> Self {
+        self.max_total_exposure = fraction;
+        self
+    }
+
+    /// Define la regla de rebalanceo
+    pub fn with_rebalance(mut self, rebalance: RebalanceRule) -> Self {
+        self.rebalance = rebalance;
+        self
+    }
+}
+
+/// Estrategia operada sobre un símbolo de la cartera
+#[derive(Debug, Clone)]
+pub struct PortfolioSleeve {
+    pub symbol: String,
+    pub strategy: StrategyAST,
+}
+
+impl PortfolioSleeve {
+    pub fn new(symbol: impl Into<String>, strategy: StrategyAST) -> Self {
+        Self { symbol: symbol.into(), strategy }
+    }
+
+    /// Etiqueta "SÍMBOLO:estrategia" usada en los informes
+    pub fn label(&self) -> String {
+        format!("{}:{}", self.symbol, self.strategy.name)
+    }
+}
+
+/// Resultado de una estrategia dentro de la cartera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioSleeveReport {
+    pub symbol: String,
+    pub strategy_name: String,
+    pub trades: Vec<Trade>,
+    /// P&L neto, incluidas las comisiones de entrada
+    pub pnl: f64,
+    /// P&L neto sobre el capital inicial de la cartera
+    pub contribution: f64,
+}
+
+/// Atribución de resultados y exposición por símbolo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolAttribution {
+    pub symbol: String,
+    pub trades: usize,
+    /// P&L neto de las estrategias del símbolo
+    pub pnl: f64,
+    /// P&L neto sobre el capital inicial de la cartera
+    pub contribution: f64,
+    /// Exposición media como fracción del equity
+    pub average_exposure: f64,
+    /// Exposición máxima como fracción del equity
+    pub max_exposure: f64,
+}
+
+/// Matriz de correlación entre los retornos por paso de las estrategias
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CorrelationMatrix {
+    /// Etiquetas "SÍMBOLO:estrategia", en el orden de las estrategias
+    pub labels: Vec<String>,
+    pub values: Vec<Vec<f64>>,
+}
+
+impl CorrelationMatrix {
+    /// Correlación entre las estrategias `a` y `b`
+    pub fn get(&self, a: usize, b: usize) -> f64 {
+        self.values[a][b]
+    }
+
+    /// Correlación media entre pares de estrategias distintas
+    pub fn average(&self) -> f64 {
+        let n = self.values.len();
+        if n < 2 {
+            return 0.0;
+        }
+        let sum: f64 = (0..n).flat_map(|a| (a + 1..n).map(move |b| (a, b))).map(|(a, b)| self.values[a][b]).sum();
+        sum / (n * (n - 1) / 2) as f64
+    }
+}
+
+/// Informe del backtest de cartera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioReport {
+    pub config: PortfolioConfig,
+    /// Equity mark-to-market de la cartera en cada paso de la línea temporal
+    pub equity_curve: Vec<EquityPoint>,
+    /// Métricas sobre todos los trades y la equity de la cartera
+    pub metrics: BacktestMetrics,
+    pub initial_balance: f64,
+    pub final_balance: f64,
+    pub sleeves: Vec<PortfolioSleeveReport>,
+    pub symbols: Vec<SymbolAttribution>,
+    pub correlation: CorrelationMatrix,
+    /// Ajustes de tamaño hechos por rebalanceo
+    pub rebalances: usize,
+}
+
+/// Posición abierta de una estrategia de la cartera
+struct SleevePosition {
+    id: usize,
+    side: PositionSide,
+    size: f64,
+    entry_price: f64,
+    entry_timestamp: i64,
+    /// Comisiones de entrada aún no asignadas a un trade
+    entry_commission: f64,
+    exits: ExitTracker,
+}
+
+impl SleevePosition {
+    /// Valor de la posición a `price` (en corto, colateral más P&L)
+    fn marked_value(&self, price: f64) -> f64 {
+        match self.side {
+            PositionSide::Long => self.size * price,
+            PositionSide::Short => self.size * (2.0 * self.entry_price - price),
+        }
+    }
+}
+
+/// Estado de simulación de una estrategia
+struct SleeveState {
+    symbol: usize,
+    entry: Vec<bool>,
+    exit: Vec<bool>,
+    short_entry: Vec<bool>,
+    short_exit: Vec<bool>,
+    position: Option<SleevePosition>,
+    trades: Vec<Trade>,
+    /// P&L realizado neto de comisiones de entrada
+    realized: f64,
+    /// P&L realizado + no realizado en cada paso
+    marked_pnl: Vec<f64>,
+}
+
+/// Velas de un símbolo y su posición en la línea temporal
+struct SymbolState<'a> {
+    name: String,
+    candles: &'a [Candle],
+    index_by_timestamp: HashMap<i64, usize>,
+    exit_indicators: ExitIndicators,
+    slippage: SlippageSeries,
+    /// Vela del paso actual (None si el símbolo no tiene vela en este timestamp)
+    current: Option<usize>,
+    last_close: f64,
+    exposure: Vec<f64>,
+}
+
+/// Caja compartida y estado común de la simulación
+struct PortfolioState {
+    cash: f64,
+    /// Volumen negociado por toda la cartera, para las comisiones por tiers
+    fees: FeeLedger,
+    next_position_id: usize,
+    rebalances: usize,
+}
+
+impl PolarsVectorizedBacktestEngine {
+    /// Ejecuta un backtest de cartera con capital compartido
+    ///
+    /// `data` asocia cada símbolo a sus velas ordenadas por timestamp. Cada
+    /// estrategia de `sleeves` debe referirse a un símbolo presente en `data`.
+    pub fn run_portfolio_backtest(
+        &self,
+        data: &HashMap<String, Vec<Candle>>,
+        sleeves: &[PortfolioSleeve],
+        config: &PortfolioConfig,
+    ) -> Result<PortfolioReport, BacktestError> {
+        if sleeves.is_empty() {
+            return Err(BacktestError::ConfigError("Portfolio needs at least one strategy".to_string()));
+        }
+        let backtest = &config.backtest;
+
+        // Símbolos en el orden en que aparecen en las estrategias
+        let mut symbols: Vec<SymbolState> = Vec::new();
+        let mut states: Vec<SleeveState> = Vec::with_capacity(sleeves.len());
+        for sleeve in sleeves {
+            let symbol = match symbols.iter().position(|s| s.name == sleeve.symbol) {
+                Some(index) => index,
+                None => {
+                    let candles = data
+                        .get(&sleeve.symbol)
+                        .filter(|candles| !candles.is_empty())
+                        .ok_or_else(|| BacktestError::ConfigError(format!("No candles for symbol {}", sleeve.symbol)))?;
+                    let highs: Vec<f64> = candles.iter().map(|c| c.high).collect();
+                    let lows: Vec<f64> = candles.iter().map(|c| c.low).collect();
+                    let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+                    let volumes: Vec<f64> = candles.iter().map(|c| c.volume).collect();
+                    symbols.push(SymbolState {
+                        name: sleeve.symbol.clone(),
+                        candles,
+                        index_by_timestamp: candles.iter().enumerate().map(|(i, c)| (c.timestamp, i)).collect(),
+                        exit_indicators: ExitIndicators::new(&highs, &lows, &closes, backtest),
+                        slippage: SlippageSeries::new(&highs, &lows, &closes, &volumes, backtest),
+                        current: None,
+                        last_close: candles[0].close,
+                        exposure: Vec::new(),
+                    });
+                    symbols.len() - 1
+                }
+            };
+
+            let df = self.candles_to_dataframe(symbols[symbol].candles)?;
+            let signals = self.compute_signals(&df, &sleeve.strategy)?;
+            states.push(SleeveState {
+                symbol,
+                entry: bool_column(&signals, "entry_signal")?,
+                exit: bool_column(&signals, "exit_signal")?,
+                short_entry: bool_column(&signals, "short_entry_signal")?,
+                short_exit: bool_column(&signals, "short_exit_signal")?,
+                position: None,
+                trades: Vec::new(),
+                realized: 0.0,
+                marked_pnl: Vec::new(),
+            });
+        }
+
+        let timeline: Vec<i64> = symbols
+            .iter()
+            .flat_map(|s| s.candles.iter().map(|c| c.timestamp))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let target_fraction = backtest.position_size_percent / sleeves.len() as f64;
+
+        let mut portfolio = PortfolioState {
+            cash: backtest.initial_balance,
+            fees: FeeLedger::new(),
+            next_position_id: 0,
+            rebalances: 0,
+        };
+        let mut equity_curve = Vec::with_capacity(timeline.len());
+        let mut peak = backtest.initial_balance;
+
+        for (step, &timestamp) in timeline.iter().enumerate() {
+            for symbol in symbols.iter_mut() {
+                symbol.current = symbol.index_by_timestamp.get(&timestamp).copied();
+                if let Some(i) = symbol.current {
+                    symbol.last_close = symbol.candles[i].close;
+                }
+            }
+
+            // 1. Salidas: niveles al precio exacto, después tiempo y señal al cierre
+            for state in states.iter_mut() {
+                let symbol = &symbols[state.symbol];
+                let (Some(i), Some(position)) = (symbol.current, state.position.as_mut()) else {
+                    continue;
+                };
+                let candle = &symbol.candles[i];
+                let next_timestamp = symbol.candles.get(i + 1).map(|c| c.timestamp);
+
+                let mut exit = position.exits.check_levels(position.entry_price, candle.high, candle.low, backtest)
+                    .map(|(price, reason)| (price, 0.0, reason));
+                if exit.is_none() {
+                    let signal = match position.side {
+                        PositionSide::Long => state.exit[i] || state.short_entry[i],
+                        PositionSide::Short => state.short_exit[i] || state.entry[i],
+                    };
+                    let reason = position.exits.check_timed(i, candle.timestamp, next_timestamp, backtest)
+                        .or(signal.then_some(ExitReason::Signal));
+                    if let Some(reason) = reason {
+                        let slippage = symbol.slippage.slippage(i, true, candle.close, position.size, backtest);
+                        exit = Some((exit_price(position.side, candle.close, slippage), slippage, reason));
+                    }
+                }
+
+                match exit {
+                    Some((price, slippage, reason)) => {
+                        let size = position.size;
+                        self.reduce_sleeve(state, &mut portfolio, size, candle.timestamp, price, slippage, reason, backtest);
+                    }
+                    None => {
+                        let entry_price = position.entry_price;
+                        position.exits.update(i, entry_price, candle.high, candle.low, &symbol.exit_indicators, backtest);
+                    }
+                }
+            }
+
+            // 2. Rebalanceo de las posiciones abiertas hacia su objetivo
+            let equity = portfolio_equity(&portfolio, &states, &symbols);
+            let target_value = equity * target_fraction;
+            let periodic = matches!(config.rebalance, RebalanceRule::Periodic { bars } if bars > 0 && step > 0 && step % bars == 0);
+            for index in 0..states.len() {
+                let symbol = &symbols[states[index].symbol];
+                let (Some(i), Some(position)) = (symbol.current, states[index].position.as_ref()) else {
+                    continue;
+                };
+                let close = symbol.candles[i].close;
+                let value = position.size * close;
+                let drift = if target_value > 0.0 { (value - target_value).abs() / target_value } else { 0.0 };
+                let rebalance = periodic || matches!(config.rebalance, RebalanceRule::Drift { threshold } if drift > threshold);
+                if !rebalance || drift <= f64::EPSILON {
+                    continue;
+                }
+
+                let side = position.side;
+                if value > target_value {
+                    let size = (value - target_value) / close;
+                    let slippage = symbol.slippage.slippage(i, true, close, size, backtest);
+                    let price = exit_price(side, close, slippage);
+                    let state = &mut states[index];
+                    self.reduce_sleeve(state, &mut portfolio, size, timestamp, price, slippage, ExitReason::Rebalance, backtest);
+                } else {
+                    let room = entry_room(&states, &symbols, states[index].symbol, equity, config);
+                    let rate = portfolio.fees.rate(backtest, timestamp, Liquidity::Taker);
+                    let price = entry_price(side, close, symbol.slippage.slippage(i, true, close, 0.0, backtest));
+                    let size = affordable_size((target_value - value).min(room), price, portfolio.cash, rate);
+                    if size <= 0.0 {
+                        continue;
+                    }
+                    let price = entry_price(side, close, symbol.slippage.slippage(i, true, close, size, backtest));
+                    let commission = portfolio.fees.charge(backtest, timestamp, price * size, Liquidity::Taker);
+                    portfolio.cash -= price * size + commission;
+                    let state = &mut states[index];
+                    state.realized -= commission;
+                    if let Some(position) = state.position.as_mut() {
+                        position.entry_price = (position.entry_price * position.size + price * size) / (position.size + size);
+                        position.size += size;
+                        position.entry_commission += commission;
+                    }
+                }
+                portfolio.rebalances += 1;
+            }
+
+            // 3. Entradas con la asignación objetivo, recortada por los límites y la caja
+            for index in 0..states.len() {
+                let state = &states[index];
+                let symbol = &symbols[state.symbol];
+                let Some(i) = symbol.current.filter(|_| state.position.is_none()) else {
+                    continue;
+                };
+                let side = match (state.entry[i], state.short_entry[i]) {
+                    (true, false) => PositionSide::Long,
+                    (false, true) => PositionSide::Short,
+                    _ => continue,
+                };
+                let candle = &symbol.candles[i];
+                let next_timestamp = symbol.candles.get(i + 1).map(|c| c.timestamp);
+                if session_closes_after(candle.timestamp, next_timestamp, backtest) {
+                    continue;
+                }
+
+                let equity = portfolio_equity(&portfolio, &states, &symbols);
+                let room = entry_room(&states, &symbols, state.symbol, equity, config);
+                let rate = portfolio.fees.rate(backtest, timestamp, Liquidity::Taker);
+                let price = entry_price(side, candle.close, symbol.slippage.slippage(i, true, candle.close, 0.0, backtest));
+                let size = affordable_size((equity * target_fraction).min(room), price, portfolio.cash, rate);
+                if size <= 0.0 {
+                    continue;
+                }
+
+                let price = entry_price(side, candle.close, symbol.slippage.slippage(i, true, candle.close, size, backtest));
+                let commission = portfolio.fees.charge(backtest, timestamp, price * size, Liquidity::Taker);
+                portfolio.cash -= price * size + commission;
+                let mut exits = ExitTracker::new(side, price, i);
+                exits.refresh_indicator_stops(i, &symbol.exit_indicators, backtest);
+                let state = &mut states[index];
+                state.realized -= commission;
+                state.position = Some(SleevePosition {
+                    id: portfolio.next_position_id,
+                    side,
+                    size,
+                    entry_price: price,
+                    entry_timestamp: candle.timestamp,
+                    entry_commission: commission,
+                    exits,
+                });
+                portfolio.next_position_id += 1;
+            }
+
+            // 4. Equity mark-to-market, P&L por estrategia y exposición por símbolo
+            let equity = portfolio_equity(&portfolio, &states, &symbols);
+            for state in states.iter_mut() {
+                let unrealized = state.position.as_ref().map_or(0.0, |p| {
+                    p.marked_value(symbols[state.symbol].last_close) - p.size * p.entry_price
+                });
+                state.marked_pnl.push(state.realized + unrealized);
+            }
+            for (index, symbol) in symbols.iter_mut().enumerate() {
+                let exposure = symbol_exposure(&states, index, symbol.last_close);
+                symbol.exposure.push(if equity > 0.0 { exposure / equity } else { 0.0 });
+            }
+            peak = peak.max(equity);
+            equity_curve.push(EquityPoint {
+                timestamp,
+                balance: equity,
+                drawdown: if peak > 0.0 { (peak - equity) / peak } else { 0.0 },
+            });
+        }
+
+        // Cerrar las posiciones abiertas con la última vela de su símbolo
+        for state in states.iter_mut() {
+            let Some(position) = state.position.as_ref() else {
+                continue;
+            };
+            let symbol = &symbols[state.symbol];
+            let last_index = symbol.candles.len() - 1;
+            let last = &symbol.candles[last_index];
+            let (side, size) = (position.side, position.size);
+            let slippage = symbol.slippage.slippage(last_index, true, last.close, size, backtest);
+            self.reduce_sleeve(state, &mut portfolio, size, last.timestamp, exit_price(side, last.close, slippage), slippage, ExitReason::EndOfData, backtest);
+        }
+
+        let mut trades: Vec<Trade> = states.iter().flat_map(|s| s.trades.iter().cloned()).collect();
+        trades.sort_by_key(|t| (t.exit_timestamp, t.entry_timestamp));
+        let metrics = self.calculate_metrics_from_trades(&trades, &equity_curve, backtest)?;
+        let initial_balance = backtest.initial_balance;
+
+        // Correlación entre los retornos por paso (sobre el equity del paso anterior)
+        let returns: Vec<Vec<f64>> = states
+            .iter()
+            .map(|state| {
+                state.marked_pnl
+                    .windows(2)
+                    .zip(&equity_curve)
+                    .map(|(pnl, previous)| if previous.balance > 0.0 { (pnl[1] - pnl[0]) / previous.balance } else { 0.0 })
+                    .collect()
+            })
+            .collect();
+        let correlation = CorrelationMatrix {
+            labels: sleeves.iter().map(PortfolioSleeve::label).collect(),
+            values: returns
+                .iter()
+                .enumerate()
+                .map(|(a, ra)| {
+                    returns.iter().enumerate().map(|(b, rb)| if a == b { 1.0 } else { calculate_correlation(ra, rb) }).collect()
+                })
+                .collect(),
+        };
+
+        let symbol_reports = symbols
+            .iter()
+            .enumerate()
+            .map(|(index, symbol)| {
+                let symbol_states = states.iter().filter(|s| s.symbol == index);
+                let pnl: f64 = symbol_states.clone().map(|s| s.realized).sum();
+                SymbolAttribution {
+                    symbol: symbol.name.clone(),
+                    trades: symbol_states.map(|s| s.trades.len()).sum(),
+                    pnl,
+                    contribution: pnl / initial_balance,
+                    average_exposure: symbol.exposure.iter().sum::<f64>() / symbol.exposure.len().max(1) as f64,
+                    max_exposure: symbol.exposure.iter().copied().fold(0.0, f64::max),
+                }
+            })
+            .collect();
+
+        let sleeve_reports = sleeves
+            .iter()
+            .zip(states)
+            .map(|(sleeve, state)| PortfolioSleeveReport {
+                symbol: sleeve.symbol.clone(),
+                strategy_name: sleeve.strategy.name.clone(),
+                pnl: state.realized,
+                contribution: state.realized / initial_balance,
+                trades: state.trades,
+            })
+            .collect();
+
+        Ok(PortfolioReport {
+            config: config.clone(),
+            equity_curve,
+            metrics,
+            initial_balance,
+            final_balance: portfolio.cash,
+            sleeves: sleeve_reports,
+            symbols: symbol_reports,
+            correlation,
+            rebalances: portfolio.rebalances,
+        })
+    }
+
+    /// Cierra `size` unidades de la posición de una estrategia y devuelve su valor a la caja
+    #[allow(clippy::too_many_arguments)]
+    fn reduce_sleeve(
+        &self,
+        state: &mut SleeveState,
+        portfolio: &mut PortfolioState,
+        size: f64,
+        exit_timestamp: i64,
+        exit_price: f64,
+        slippage: f64,
+        exit_reason: ExitReason,
+        config: &BacktestConfig,
+    ) {
+        let Some(position) = state.position.as_mut() else {
+            return;
+        };
+        let size = size.min(position.size);
+        // Parte de las comisiones de entrada proporcional al tamaño cerrado
+        let commission = position.entry_commission * size / position.size;
+        position.entry_commission -= commission;
+        let lot = Lot {
+            entry_timestamp: position.entry_timestamp,
+            entry_price: position.entry_price,
+            size,
+            commission,
+        };
+        let trade = self.close_trade(
+            position.side,
+            lot,
+            exit_timestamp,
+            exit_price,
+            slippage * size,
+            exit_reason,
+            Liquidity::for_exit(exit_reason),
+            &mut portfolio.fees,
+            config,
+        );
+
+        portfolio.cash += position.entry_price * size + trade.pnl;
+        state.realized += trade.pnl;
+        state.trades.push(Trade { position_id: position.id, ..trade });
+        position.size -= size;
+        if position.size <= f64::EPSILON * size.max(1.0) {
+            state.position = None;
+        }
+    }
+}
+
+/// Columna booleana de señales (los nulos cuentan como false)
+fn bool_column(df: &polars::prelude::DataFrame, name: &str) -> Result<Vec<bool>, BacktestError> {
+    let column = df.column(name)
+        .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to get {}: {}", name, e)))?;
+    let values = column.bool()
+        .map_err(|e| BacktestError::DataError(anyhow::anyhow!("Failed to cast {}: {}", name, e)))?;
+    Ok(values.iter().map(|opt| opt.unwrap_or(false)).collect())
+}
+
+/// En largo compramos más caro, en corto vendemos más barato
+fn entry_price(side: PositionSide, close: f64, slippage: f64) -> f64 {
+    match side {
+        PositionSide::Long => close + slippage,
+        PositionSide::Short => close - slippage,
+    }
+}
+
+fn exit_price(side: PositionSide, close: f64, slippage: f64) -> f64 {
+    match side {
+        PositionSide::Long => close - slippage,
+        PositionSide::Short => close + slippage,
+    }
+}
+
+/// Caja más el valor de mercado de las posiciones abiertas
+fn portfolio_equity(portfolio: &PortfolioState, states: &[SleeveState], symbols: &[SymbolState]) -> f64 {
+    portfolio.cash
+        + states
+            .iter()
+            .filter_map(|s| s.position.as_ref().map(|p| p.marked_value(symbols[s.symbol].last_close)))
+            .sum::<f64>()
+}
+
+/// Exposición bruta (largos y cortos) de un símbolo a `price`
+fn symbol_exposure(states: &[SleeveState], symbol: usize, price: f64) -> f64 {
+    states
+        .iter()
+        .filter(|s| s.symbol == symbol)
+        .filter_map(|s| s.position.as_ref())
+        .map(|p| p.size * price)
+        .sum()
+}
+
+/// Valor que aún cabe en los límites de exposición del símbolo y de la cartera
+fn entry_room(
+    states: &[SleeveState],
+    symbols: &[SymbolState],
+    symbol: usize,
+    equity: f64,
+    config: &PortfolioConfig,
+) -> f64 {
+    let total: f64 = (0..symbols.len()).map(|s| symbol_exposure(states, s, symbols[s].last_close)).sum();
+    let symbol_room = equity * config.max_symbol_exposure - symbol_exposure(states, symbol, symbols[symbol].last_close);
+    let total_room = equity * config.max_total_exposure - total;
+    symbol_room.min(total_room).max(0.0)
+}
+
+/// Unidades por valor `value` a `price` que la caja puede pagar con la comisión a `commission_rate`
+fn affordable_size(value: f64, price: f64, cash: f64, commission_rate: f64) -> f64 {
+    if value <= 0.0 || price <= 0.0 {
+        return 0.0;
+    }
+    let value = value.min(cash / (1.0 + commission_rate));
+    if value <= 0.0 { 0.0 } else { value / price }
+}
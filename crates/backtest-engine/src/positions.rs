@@ -0,0 +1,156 @@
+//! Posiciones múltiples, pyramiding y cierres parciales del motor masivo
+//!
+//! Cada posición agrupa uno o varios lotes del mismo lado: el lote inicial y los
+//! añadidos por pyramiding cuando se repite la señal de entrada. Las reglas de
+//! salida se evalúan sobre el precio medio de la posición y, al cerrarla, cada
+//! lote genera su propio `Trade` con el `position_id` de la posición.
+//!
+//! Los cierres parciales (`scale_out`) reducen la posición tomando lotes en orden
+//! FIFO o LIFO según `close_order`.
+
+use serde::{Deserialize, Serialize};
+use darwinx_core::PositionSide;
+use crate::exits::ExitTracker;
+
+/// Reglas de pyramiding: añadir a una posición abierta cuando se repite la señal
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PyramidingConfig {
+    /// Máximo de lotes añadidos a una posición (0 = deshabilitado)
+    pub max_adds: usize,
+    /// Factor sobre el tamaño del lote inicial para cada añadido: el añadido k
+    /// tiene `size_decay^k` veces el tamaño inicial (1.0 = mismo tamaño)
+    pub size_decay: f64,
+}
+
+impl Default for PyramidingConfig {
+    fn default() -> Self {
+        Self {
+            max_adds: 0,
+            size_decay: 1.0,
+        }
+    }
+}
+
+/// Nivel de toma de beneficios parcial
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScaleOutLevel {
+    /// Beneficio sobre el precio medio de entrada que activa el nivel (ej: 0.02 = 2%)
+    pub profit_percent: f64,
+    /// Fracción del tamaño abierto a cerrar (ej: 0.5 = la mitad)
+    pub fraction: f64,
+}
+
+/// Orden en que se cierran los lotes de una posición en los cierres parciales
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseOrder {
+    /// Primero los lotes más antiguos
+    #[default]
+    Fifo,
+    /// Primero los lotes más recientes
+    Lifo,
+}
+
+impl CloseOrder {
+    /// Nombre usado en logs y persistencia
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CloseOrder::Fifo => "fifo",
+            CloseOrder::Lifo => "lifo",
+        }
+    }
+}
+
+/// Lote de una posición
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Lot {
+    pub(crate) entry_timestamp: i64,
+    pub(crate) entry_price: f64,
+    pub(crate) size: f64,
+    /// Comisión de entrada del lote
+    pub(crate) commission: f64,
+}
+
+/// Posición abierta en el motor masivo
+#[derive(Debug, Clone)]
+pub(crate) struct OpenPosition {
+    pub(crate) id: usize,
+    pub(crate) side: PositionSide,
+    /// Lotes abiertos, en orden de entrada
+    pub(crate) lots: Vec<Lot>,
+    /// Lotes añadidos por pyramiding
+    pub(crate) adds: usize,
+    /// Niveles de `scale_out` ya ejecutados (en orden de beneficio)
+    pub(crate) scale_outs: usize,
+    /// Trailing stops, break-even y salidas por tiempo
+    pub(crate) exits: ExitTracker,
+}
+
+impl OpenPosition {
+    pub(crate) fn new(id: usize, side: PositionSide, lot: Lot, entry_index: usize) -> Self {
+        Self {
+            id,
+            side,
+            lots: vec![lot],
+            adds: 0,
+            scale_outs: 0,
+            exits: ExitTracker::new(side, lot.entry_price, entry_index),
+        }
+    }
+
+    /// Tamaño total abierto
+    pub(crate) fn size(&self) -> f64 {
+        self.lots.iter().map(|lot| lot.size).sum()
+    }
+
+    /// Capital comprometido a precio de entrada
+    pub(crate) fn notional(&self) -> f64 {
+        self.lots.iter().map(|lot| lot.entry_price * lot.size).sum()
+    }
+
+    /// Precio medio de entrada ponderado por tamaño
+    pub(crate) fn average_entry_price(&self) -> f64 {
+        let size = self.size();
+        if size > 0.0 { self.notional() / size } else { 0.0 }
+    }
+
+    /// P&L no realizado a precio `price`, sin costes
+    pub(crate) fn unrealized_pnl(&self, price: f64) -> f64 {
+        let pnl = (price - self.average_entry_price()) * self.size();
+        match self.side {
+            PositionSide::Long => pnl,
+            PositionSide::Short => -pnl,
+        }
+    }
+
+    /// Tamaño del lote inicial (base de los añadidos por pyramiding)
+    pub(crate) fn initial_size(&self) -> f64 {
+        self.lots.first().map(|lot| lot.size).unwrap_or(0.0)
+    }
+
+    /// Saca `size` unidades de la posición en el orden dado, partiendo el último lote si hace falta
+    pub(crate) fn take(&mut self, size: f64, order: CloseOrder) -> Vec<Lot> {
+        let mut remaining = size;
+        let mut taken = Vec::new();
+        while remaining > f64::EPSILON * size.max(1.0) && !self.lots.is_empty() {
+            let index = match order {
+                CloseOrder::Fifo => 0,
+                CloseOrder::Lifo => self.lots.len() - 1,
+            };
+            let lot = &mut self.lots[index];
+            if lot.size <= remaining {
+                remaining -= lot.size;
+                taken.push(self.lots.remove(index));
+            } else {
+                // La comisión de entrada se reparte en proporción al tamaño
+                let commission = lot.commission * remaining / lot.size;
+                lot.size -= remaining;
+                lot.commission -= commission;
+                taken.push(Lot { size: remaining, commission, ..*lot });
+                remaining = 0.0;
+            }
+        }
+        taken
+    }
+}
@@ -0,0 +1,418 @@
+//! Scoring y ranking de resultados de backtest
+//!
+//! `ScoreSpec` describe de forma declarativa cómo se puntúa un batch de
+//! resultados: qué métricas entran en el score, cómo se normaliza cada una, con
+//! qué peso y qué restricciones duras deben cumplir. Se puede cargar desde TOML:
+//!
+//! ```toml
+//! ranking = "pareto"
+//!
+//! [[terms]]
+//! metric = "sharpe_ratio"
+//! weight = 0.6
+//! normalization = { method = "percentile" }
+//!
+//! [[terms]]
+//! metric = "max_drawdown"
+//! weight = 0.4
+//! normalization = { method = "min_max", min = 0.0, max = 0.5 }
+//!
+//! [[constraints]]
+//! metric = "total_trades"
+//! min = 30
+//! ```
+//!
+//! Las normalizaciones por percentil y por rango dependen del batch, así que el
+//! score de un resultado solo es comparable con los del mismo batch. Con ranking
+//! Pareto se ordena por frente no dominado y, dentro de cada frente, por score.
+
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::error::BacktestError;
+use crate::types::BacktestMetrics;
+
+/// Métrica de `BacktestMetrics` utilizable en un score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreMetric {
+    TotalReturn,
+    AnnualizedReturn,
+    SharpeRatio,
+    SortinoRatio,
+    CalmarRatio,
+    ProfitFactor,
+    WinRate,
+    Expectancy,
+    RecoveryFactor,
+    MaxDrawdown,
+    TotalTrades,
+    ProbabilisticSharpeRatio,
+    ExcessReturn,
+    Alpha,
+    Beta,
+    InformationRatio,
+}
+
+impl ScoreMetric {
+    /// Valor de la métrica en un resultado
+    pub fn value(&self, m: &BacktestMetrics) -> f64 {
+        match self {
+            ScoreMetric::TotalReturn => m.total_return,
+            ScoreMetric::AnnualizedReturn => m.annualized_return,
+            ScoreMetric::SharpeRatio => m.sharpe_ratio,
+            ScoreMetric::SortinoRatio => m.sortino_ratio,
+            ScoreMetric::CalmarRatio => m.calmar_ratio,
+            ScoreMetric::ProfitFactor => m.profit_factor,
+            ScoreMetric::WinRate => m.win_rate,
+            ScoreMetric::Expectancy => m.expectancy,
+            ScoreMetric::RecoveryFactor => m.recovery_factor,
+            ScoreMetric::MaxDrawdown => m.max_drawdown,
+            ScoreMetric::TotalTrades => m.total_trades as f64,
+            ScoreMetric::ProbabilisticSharpeRatio => m.probabilistic_sharpe_ratio,
+            ScoreMetric::ExcessReturn => m.excess_return,
+            ScoreMetric::Alpha => m.alpha,
+            ScoreMetric::Beta => m.beta,
+            ScoreMetric::InformationRatio => m.information_ratio,
+        }
+    }
+
+    /// Indica si un valor mayor es mejor (el drawdown y la beta puntúan al revés)
+    pub fn higher_is_better(&self) -> bool {
+        !matches!(self, ScoreMetric::MaxDrawdown | ScoreMetric::Beta)
+    }
+}
+
+/// Normalización de una métrica a [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Normalization {
+    /// Lineal entre `min` y `max`, recortada a [0, 1]
+    MinMax { min: f64, max: f64 },
+    /// Percentil dentro del batch (los empates comparten el percentil medio)
+    Percentile,
+    /// Posición en el batch ordenado: 0 la peor y 1 la mejor (los empates comparten la menor)
+    Rank,
+}
+
+/// Término ponderado del score
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreTerm {
+    pub metric: ScoreMetric,
+    pub weight: f64,
+    #[serde(default = "default_normalization")]
+    pub normalization: Normalization,
+}
+
+fn default_normalization() -> Normalization {
+    Normalization::Percentile
+}
+
+impl ScoreTerm {
+    pub fn new(metric: ScoreMetric, weight: f64, normalization: Normalization) -> Self {
+        Self { metric, weight, normalization }
+    }
+}
+
+/// Restricción dura: los resultados que no la cumplen no se rankean
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreConstraint {
+    pub metric: ScoreMetric,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+}
+
+impl ScoreConstraint {
+    /// Indica si el resultado cumple la restricción
+    pub fn passes(&self, m: &BacktestMetrics) -> bool {
+        let value = self.metric.value(m);
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+/// Método de ranking
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingMethod {
+    /// Score ponderado descendente
+    #[default]
+    Weighted,
+    /// Frente de Pareto sobre las métricas de los términos y score dentro de cada frente
+    Pareto,
+}
+
+/// Resultado rankeado
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RankedResult {
+    /// Posición del resultado en el batch de entrada
+    pub index: usize,
+    pub score: f64,
+    /// Frente de Pareto (0 = no dominado; solo con ranking Pareto)
+    pub front: Option<usize>,
+}
+
+/// Especificación declarativa del score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreSpec {
+    pub terms: Vec<ScoreTerm>,
+    #[serde(default)]
+    pub constraints: Vec<ScoreConstraint>,
+    #[serde(default)]
+    pub ranking: RankingMethod,
+}
+
+impl Default for ScoreSpec {
+    /// Score compuesto clásico: Sharpe, Sortino, Profit Factor, Return y Drawdown
+    fn default() -> Self {
+        Self::from_weights(&[0.3, 0.2, 0.2, 0.15, 0.15])
+    }
+}
+
+impl ScoreSpec {
+    /// Score compuesto clásico con pesos para Sharpe, Sortino, Profit Factor, Return y Drawdown
+    ///
+    /// Sharpe y Sortino se normalizan entre -2 y 2, el Profit Factor entre 0 y 5, el
+    /// retorno entre -100% y 100% y el drawdown entre 0 y 100%.
+    pub fn from_weights(weights: &[f64; 5]) -> Self {
+        let min_max = |min, max| Normalization::MinMax { min, max };
+        let metrics = [
+            (ScoreMetric::SharpeRatio, min_max(-2.0, 2.0)),
+            (ScoreMetric::SortinoRatio, min_max(-2.0, 2.0)),
+            (ScoreMetric::ProfitFactor, min_max(0.0, 5.0)),
+            (ScoreMetric::TotalReturn, min_max(-1.0, 1.0)),
+            (ScoreMetric::MaxDrawdown, min_max(0.0, 1.0)),
+        ];
+        Self {
+            terms: metrics
+                .into_iter()
+                .zip(weights)
+                .map(|((metric, normalization), &weight)| ScoreTerm::new(metric, weight, normalization))
+                .collect(),
+            constraints: Vec::new(),
+            ranking: RankingMethod::Weighted,
+        }
+    }
+
+    /// Parsea y valida una especificación en TOML
+    pub fn from_toml_str(toml: &str) -> Result<Self, BacktestError> {
+        let spec: Self = toml::from_str(toml)
+            .map_err(|e| BacktestError::ConfigError(format!("Score spec inválida: {}", e)))?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Carga y valida una especificación desde un fichero TOML
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BacktestError> {
+        let path = path.as_ref();
+        let toml = std::fs::read_to_string(path)
+            .map_err(|e| BacktestError::ConfigError(format!("No se pudo leer {}: {}", path.display(), e)))?;
+        Self::from_toml_str(&toml)
+    }
+
+    /// Comprueba que haya términos y que los pesos y rangos sean válidos
+    pub fn validate(&self) -> Result<(), BacktestError> {
+        if self.terms.is_empty() {
+            return Err(BacktestError::ConfigError("El score necesita al menos un término".to_string()));
+        }
+        if self.terms.iter().any(|t| t.weight.is_nan() || t.weight < 0.0) || self.total_weight() <= 0.0 {
+            return Err(BacktestError::ConfigError("Los pesos del score deben ser >= 0 y sumar más de 0".to_string()));
+        }
+        for term in &self.terms {
+            if let Normalization::MinMax { min, max } = term.normalization
+                && max.partial_cmp(&min) != Some(std::cmp::Ordering::Greater)
+            {
+                return Err(BacktestError::ConfigError(format!("Rango min_max inválido para {:?}: [{}, {}]", term.metric, min, max)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sustituye la métrica `from` por `to` en los términos del score
+    pub fn with_metric_replaced(mut self, from: ScoreMetric, to: ScoreMetric) -> Self {
+        for term in self.terms.iter_mut().filter(|t| t.metric == from) {
+            term.metric = to;
+        }
+        self
+    }
+
+    /// Añade una restricción dura
+    pub fn with_constraint(mut self, constraint: ScoreConstraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Define el método de ranking
+    pub fn with_ranking(mut self, ranking: RankingMethod) -> Self {
+        self.ranking = ranking;
+        self
+    }
+
+    /// Indica si el resultado cumple todas las restricciones
+    pub fn passes(&self, m: &BacktestMetrics) -> bool {
+        self.constraints.iter().all(|c| c.passes(m))
+    }
+
+    fn total_weight(&self) -> f64 {
+        self.terms.iter().map(|t| t.weight).sum()
+    }
+
+    /// Score en [0, 1] de cada resultado del batch (None si no cumple las restricciones)
+    ///
+    /// Los pesos se normalizan para sumar 1. Los percentiles y rangos se calculan
+    /// solo entre los resultados que cumplen las restricciones.
+    pub fn score_batch(&self, batch: &[&BacktestMetrics]) -> Vec<Option<f64>> {
+        self.score_against(batch, batch)
+    }
+
+    /// Score de cada resultado del batch normalizado respecto a una población fija
+    ///
+    /// Con una referencia fija (p. ej. la población inicial de una evolución) el
+    /// score de una estrategia no depende del resto del batch, así que es comparable
+    /// entre batches. Los percentiles y rangos se calculan frente a los resultados
+    /// de `reference` que cumplen las restricciones; si no hay ninguno se usa el batch.
+    pub fn score_against(&self, batch: &[&BacktestMetrics], reference: &[&BacktestMetrics]) -> Vec<Option<f64>> {
+        let eligible: Vec<usize> = (0..batch.len()).filter(|&i| self.passes(batch[i])).collect();
+        let mut reference: Vec<&BacktestMetrics> = reference.iter().copied().filter(|m| self.passes(m)).collect();
+        if reference.is_empty() {
+            reference = eligible.iter().map(|&i| batch[i]).collect();
+        }
+        let total_weight = self.total_weight();
+        let mut scores = vec![0.0; eligible.len()];
+
+        for term in &self.terms {
+            let values: Vec<f64> = eligible.iter().map(|&i| term.metric.value(batch[i])).collect();
+            let reference_values: Vec<f64> = reference.iter().map(|m| term.metric.value(m)).collect();
+            let normalized = normalize(&values, &reference_values, term.normalization, term.metric.higher_is_better());
+            for (score, value) in scores.iter_mut().zip(normalized) {
+                *score += term.weight / total_weight * value;
+            }
+        }
+
+        let mut result = vec![None; batch.len()];
+        for (&i, score) in eligible.iter().zip(scores) {
+            result[i] = Some(score);
+        }
+        result
+    }
+
+    /// Rankea el batch: solo los resultados que cumplen las restricciones, el mejor primero
+    pub fn rank(&self, batch: &[&BacktestMetrics]) -> Vec<RankedResult> {
+        let mut ranked: Vec<RankedResult> = self
+            .score_batch(batch)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, score)| score.map(|score| RankedResult { index, score, front: None }))
+            .collect();
+
+        if self.ranking == RankingMethod::Pareto {
+            let objectives: Vec<Vec<f64>> = ranked
+                .iter()
+                .map(|r| {
+                    self.terms
+                        .iter()
+                        .filter(|t| t.weight > 0.0)
+                        .map(|t| {
+                            let value = t.metric.value(batch[r.index]);
+                            if t.metric.higher_is_better() { value } else { -value }
+                        })
+                        .collect()
+                })
+                .collect();
+            for (entry, front) in ranked.iter_mut().zip(pareto_fronts(&objectives)) {
+                entry.front = Some(front);
+            }
+        }
+
+        // Un score no finito queda al final de su frente
+        let sort_key = |score: f64| if score.is_finite() { score } else { f64::NEG_INFINITY };
+        ranked.sort_by(|a, b| {
+            a.front
+                .cmp(&b.front)
+                .then(sort_key(b.score).total_cmp(&sort_key(a.score)))
+                .then(a.index.cmp(&b.index))
+        });
+        ranked
+    }
+}
+
+/// Normaliza los valores a [0, 1] respecto a la población `reference`, donde 1 es el mejor
+///
+/// Percentil y rango ordenan la referencia una vez y ubican cada valor con una
+/// búsqueda binaria. Los NaN puntúan 0 y cuentan como los peores de la referencia.
+fn normalize(values: &[f64], reference: &[f64], normalization: Normalization, higher_is_better: bool) -> Vec<f64> {
+    let oriented = |x: f64| if higher_is_better { x } else { 1.0 - x };
+    let n = reference.len();
+    match normalization {
+        Normalization::MinMax { min, max } => values
+            .iter()
+            .map(|v| if v.is_nan() { 0.0 } else { oriented(((v - min) / (max - min)).clamp(0.0, 1.0)) })
+            .collect(),
+        Normalization::Rank if n < 2 => values.iter().map(|v| if v.is_nan() { 0.0 } else { 1.0 }).collect(),
+        Normalization::Percentile | Normalization::Rank => {
+            // Con orientación descendente el mejor es el menor valor: se invierte el signo
+            // (sumar 0.0 convierte -0.0 en 0.0 para que ambos empaten)
+            let goodness = |x: f64| if higher_is_better { x + 0.0 } else { -x + 0.0 };
+            let mut sorted: Vec<f64> = reference.iter().filter(|x| !x.is_nan()).map(|&x| goodness(x)).collect();
+            sorted.sort_by(f64::total_cmp);
+            let nan_count = n - sorted.len();
+
+            values
+                .iter()
+                .map(|&v| {
+                    if v.is_nan() {
+                        return 0.0;
+                    }
+                    let g = goodness(v);
+                    let worse = sorted.partition_point(|&x| x < g);
+                    let equal = sorted.partition_point(|&x| x <= g) - worse;
+                    let worse = (nan_count + worse) as f64;
+                    match normalization {
+                        Normalization::Percentile => (worse + 0.5 * equal as f64) / n as f64,
+                        _ => (worse / (n - 1) as f64).min(1.0),
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+/// Frente de Pareto de cada punto (0 = no dominado) maximizando todos los objetivos
+///
+/// Ordenación no dominada eficiente (ENS-BS): tras ordenar los puntos de forma
+/// lexicográfica descendente ninguno puede dominar a uno anterior, así que cada
+/// punto se asigna al primer frente sin dominadores, buscado de forma binaria.
+/// Los NaN se tratan como el peor valor posible.
+fn pareto_fronts(objectives: &[Vec<f64>]) -> Vec<usize> {
+    let objectives: Vec<Vec<f64>> = objectives
+        .iter()
+        .map(|point| point.iter().map(|&x| if x.is_nan() { f64::NEG_INFINITY } else { x + 0.0 }).collect())
+        .collect();
+    let dominates = |a: &[f64], b: &[f64]| {
+        a.iter().zip(b).all(|(x, y)| x >= y) && a.iter().zip(b).any(|(x, y)| x > y)
+    };
+
+    let mut order: Vec<usize> = (0..objectives.len()).collect();
+    order.sort_by(|&a, &b| {
+        objectives[b]
+            .iter()
+            .zip(&objectives[a])
+            .map(|(x, y)| x.total_cmp(y))
+            .find(|o| o.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut fronts = vec![0; objectives.len()];
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    for i in order {
+        // Si algún punto del frente k domina a i, también lo domina alguno del frente k - 1
+        let dominated_in = |front: &Vec<usize>| front.iter().rev().any(|&j| dominates(&objectives[j], &objectives[i]));
+        let front = members.partition_point(dominated_in);
+        if front == members.len() {
+            members.push(Vec::new());
+        }
+        members[front].push(i);
+        fronts[i] = front;
+    }
+    fronts
+}
@@ -0,0 +1,239 @@
+//! Modelos de tamaño de posición
+//!
+//! Cada modelo implementa `PositionSizer` y calcula las unidades a abrir a
+//! partir del precio de entrada, el balance y el historial de trades cerrados.
+//! `BacktestConfig.position_sizing` selecciona el modelo y se serializa con la
+//! configuración del backtest.
+//!
+//! Los parámetros compartidos salen de la propia configuración:
+//! `position_size_percent` (fracción del balance por posición) y
+//! `risk_per_trade` (fracción del balance arriesgada por trade).
+
+use serde::{Deserialize, Serialize};
+use darwinx_core::{Candle, PositionSide};
+use darwinx_indicators::volatility::atr::Atr;
+use darwinx_indicators::StreamingIndicator;
+use crate::config::BacktestConfig;
+use crate::types::Trade;
+
+/// Datos disponibles al dimensionar una entrada
+#[derive(Debug, Clone, Copy)]
+pub struct SizingContext<'a> {
+    /// Lado de la posición
+    pub side: PositionSide,
+    /// Precio de entrada (con slippage)
+    pub entry_price: f64,
+    /// Balance actual de la cuenta
+    pub balance: f64,
+    /// ATR en la vela de entrada (solo si el modelo lo pide)
+    pub atr: Option<f64>,
+    /// Trades cerrados hasta el momento, en orden
+    pub closed_trades: &'a [Trade],
+    /// Configuración del backtest
+    pub config: &'a BacktestConfig,
+}
+
+/// Modelo de tamaño de posición
+pub trait PositionSizer {
+    /// Unidades a abrir; 0 significa no abrir la posición
+    fn position_size(&self, ctx: &SizingContext<'_>) -> f64;
+
+    /// Periodo de ATR que necesita el modelo (None = no usa ATR)
+    fn atr_period(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Fracción fija del balance: `position_size_percent / max_positions`
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct FixedFractional {
+    /// Usar el balance actual en lugar del inicial (interés compuesto)
+    #[serde(default)]
+    pub compound: bool,
+}
+
+impl PositionSizer for FixedFractional {
+    fn position_size(&self, ctx: &SizingContext<'_>) -> f64 {
+        // Sin interés compuesto el tamaño es igual para todas las estrategias
+        let base = if self.compound { ctx.balance } else { ctx.config.initial_balance };
+        let position_value = base * ctx.config.position_size_percent / ctx.config.max_positions as f64;
+        position_value / ctx.entry_price
+    }
+}
+
+/// Cantidad fija de unidades por posición
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FixedQuantity {
+    /// Unidades por posición
+    pub quantity: f64,
+}
+
+impl PositionSizer for FixedQuantity {
+    fn position_size(&self, _ctx: &SizingContext<'_>) -> f64 {
+        self.quantity
+    }
+}
+
+/// Volatilidad objetivo: arriesga `risk_per_trade` del balance en `atr_multiple` ATRs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VolatilityTarget {
+    /// Periodo del ATR
+    pub atr_period: usize,
+    /// Distancia de riesgo en múltiplos de ATR
+    pub atr_multiple: f64,
+}
+
+impl PositionSizer for VolatilityTarget {
+    fn position_size(&self, ctx: &SizingContext<'_>) -> f64 {
+        match ctx.atr {
+            Some(atr) if atr > 0.0 && self.atr_multiple > 0.0 => {
+                capped_size(ctx, ctx.balance * ctx.config.risk_per_trade / (atr * self.atr_multiple))
+            }
+            // Sin ATR todavía (inicio de los datos) no se opera
+            _ => 0.0,
+        }
+    }
+
+    fn atr_period(&self) -> Option<usize> {
+        Some(self.atr_period)
+    }
+}
+
+/// Riesgo fijo por trade: pierde `risk_per_trade` del balance si salta el stop loss
+///
+/// Sin `stop_loss_percent` configurado se comporta como `FixedFractional`.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct RiskPerTrade;
+
+impl PositionSizer for RiskPerTrade {
+    fn position_size(&self, ctx: &SizingContext<'_>) -> f64 {
+        match ctx.config.stop_loss_percent {
+            Some(stop) if stop > 0.0 => {
+                capped_size(ctx, ctx.balance * ctx.config.risk_per_trade / (ctx.entry_price * stop))
+            }
+            _ => FixedFractional::default().position_size(ctx),
+        }
+    }
+}
+
+/// Fracción de Kelly estimada sobre los últimos trades cerrados
+///
+/// Kelly = W - (1 - W) / R, con W el win rate y R el cociente entre el retorno
+/// medio ganador y el perdedor. La posición usa `fraction` de Kelly, limitada a
+/// `position_size_percent`. Hasta tener `min_trades` trades se usa
+/// `FixedFractional`; sin ventaja estimada (Kelly <= 0) no se abren posiciones.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KellyFraction {
+    /// Fracción de Kelly a usar (ej: 0.5 = medio Kelly)
+    pub fraction: f64,
+    /// Trades recientes usados para estimar Kelly (0 = todos)
+    pub lookback: usize,
+    /// Trades necesarios antes de aplicar Kelly
+    pub min_trades: usize,
+}
+
+impl KellyFraction {
+    /// Kelly estimado sobre los trades dados (None si no hay ganadores o perdedores)
+    pub fn kelly(trades: &[Trade]) -> Option<f64> {
+        let returns: Vec<f64> = trades
+            .iter()
+            .filter(|t| t.entry_price > 0.0 && t.size > 0.0)
+            .map(|t| t.pnl / (t.entry_price * t.size))
+            .collect();
+        let wins: Vec<f64> = returns.iter().copied().filter(|r| *r > 0.0).collect();
+        let losses: Vec<f64> = returns.iter().copied().filter(|r| *r < 0.0).map(f64::abs).collect();
+        if wins.is_empty() || losses.is_empty() {
+            return None;
+        }
+
+        let win_rate = wins.len() as f64 / returns.len() as f64;
+        let payoff = (wins.iter().sum::<f64>() / wins.len() as f64) / (losses.iter().sum::<f64>() / losses.len() as f64);
+        Some(win_rate - (1.0 - win_rate) / payoff)
+    }
+}
+
+impl PositionSizer for KellyFraction {
+    fn position_size(&self, ctx: &SizingContext<'_>) -> f64 {
+        let start = match self.lookback {
+            0 => 0,
+            lookback => ctx.closed_trades.len().saturating_sub(lookback),
+        };
+        let recent = &ctx.closed_trades[start..];
+        if recent.len() < self.min_trades.max(1) {
+            return FixedFractional::default().position_size(ctx);
+        }
+
+        // Sin perdedores (o sin ganadores) el estimador no es fiable: se usa el máximo (o nada)
+        let kelly = Self::kelly(recent).unwrap_or(if recent.iter().any(|t| t.pnl > 0.0) { 1.0 } else { 0.0 });
+        let fraction = (kelly * self.fraction).clamp(0.0, ctx.config.position_size_percent);
+        ctx.balance * fraction / ctx.config.max_positions as f64 / ctx.entry_price
+    }
+}
+
+/// Modelo de tamaño seleccionable desde la configuración
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum PositionSizing {
+    FixedFractional(FixedFractional),
+    FixedQuantity(FixedQuantity),
+    VolatilityTarget(VolatilityTarget),
+    RiskPerTrade(RiskPerTrade),
+    Kelly(KellyFraction),
+}
+
+impl Default for PositionSizing {
+    fn default() -> Self {
+        PositionSizing::FixedFractional(FixedFractional::default())
+    }
+}
+
+impl PositionSizing {
+    /// Nombre usado en logs y persistencia
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PositionSizing::FixedFractional(_) => "fixed_fractional",
+            PositionSizing::FixedQuantity(_) => "fixed_quantity",
+            PositionSizing::VolatilityTarget(_) => "volatility_target",
+            PositionSizing::RiskPerTrade(_) => "risk_per_trade",
+            PositionSizing::Kelly(_) => "kelly",
+        }
+    }
+
+    fn sizer(&self) -> &dyn PositionSizer {
+        match self {
+            PositionSizing::FixedFractional(sizer) => sizer,
+            PositionSizing::FixedQuantity(sizer) => sizer,
+            PositionSizing::VolatilityTarget(sizer) => sizer,
+            PositionSizing::RiskPerTrade(sizer) => sizer,
+            PositionSizing::Kelly(sizer) => sizer,
+        }
+    }
+}
+
+impl PositionSizer for PositionSizing {
+    fn position_size(&self, ctx: &SizingContext<'_>) -> f64 {
+        let size = self.sizer().position_size(ctx);
+        if size.is_finite() { size.max(0.0) } else { 0.0 }
+    }
+
+    fn atr_period(&self) -> Option<usize> {
+        self.sizer().atr_period()
+    }
+}
+
+/// ATR de Wilder vela a vela (None mientras no hay suficientes velas)
+pub fn atr_series(highs: &[f64], lows: &[f64], closes: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut atr = Atr::new(period);
+    highs
+        .iter()
+        .zip(lows)
+        .zip(closes)
+        .map(|((high, low), close)| atr.update(&Candle::new(0, *close, *high, *low, *close, 0.0)))
+        .collect()
+}
+
+/// Limita el tamaño de los modelos por riesgo al balance disponible por posición
+fn capped_size(ctx: &SizingContext<'_>, size: f64) -> f64 {
+    let max_size = ctx.balance / ctx.config.max_positions as f64 / ctx.entry_price;
+    size.min(max_size)
+}
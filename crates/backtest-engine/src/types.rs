@@ -23,6 +23,8 @@ pub struct BacktestResult {
 pub struct BacktestMetrics {
     // Returns
     pub total_return: f64,
+    /// Retorno anualizado con el tiempo transcurrido (no con el número de velas),
+    /// válido también con barras no temporales
     pub annualized_return: f64,
     pub sharpe_ratio: f64,
     pub sortino_ratio: f64,
@@ -89,8 +91,97 @@ pub struct BacktestMetrics {
     pub signal_exits: usize,
     /// Número de trades cerrados al final de datos
     pub end_of_data_exits: usize,
+    /// Número de trades cerrados por trailing stop (porcentual o ATR)
+    #[serde(default)]
+    pub trailing_stop_exits: usize,
+    /// Número de trades cerrados por el stop en break-even
+    #[serde(default)]
+    pub break_even_exits: usize,
+    /// Número de trades cerrados por chandelier exit
+    #[serde(default)]
+    pub chandelier_exits: usize,
+    /// Número de trades cerrados por exceder el máximo de velas
+    #[serde(default)]
+    pub max_bars_exits: usize,
+    /// Número de trades cerrados por cierre de sesión
+    #[serde(default)]
+    pub session_close_exits: usize,
+    /// Número de trades cerrados por liquidación forzosa
+    #[serde(default)]
+    pub liquidation_exits: usize,
+    /// Número de trades, P&L y win rate por razón de salida (solo razones con trades)
+    #[serde(default)]
+    pub exit_reasons: Vec<ExitReasonStats>,
     /// Número de señales de entrada generadas (para diagnóstico)
     pub entry_signals_count: usize,
+
+    // Long/Short breakdown
+    /// Número de trades en largo
+    #[serde(default)]
+    pub long_trades: usize,
+    /// Número de trades en corto
+    #[serde(default)]
+    pub short_trades: usize,
+    /// Win rate de los trades en largo
+    #[serde(default)]
+    pub long_win_rate: f64,
+    /// Win rate de los trades en corto
+    #[serde(default)]
+    pub short_win_rate: f64,
+    /// P&L neto de los trades en largo
+    #[serde(default)]
+    pub long_pnl: f64,
+    /// P&L neto de los trades en corto
+    #[serde(default)]
+    pub short_pnl: f64,
+    // Significance
+    /// Número de retornos usados para el Sharpe
+    #[serde(default)]
+    pub sharpe_observations: usize,
+    /// Asimetría de los retornos
+    #[serde(default)]
+    pub returns_skewness: f64,
+    /// Curtosis (no excesiva) de los retornos
+    #[serde(default)]
+    pub returns_kurtosis: f64,
+    /// Probabilistic Sharpe Ratio contra un Sharpe de referencia de 0
+    #[serde(default)]
+    pub probabilistic_sharpe_ratio: f64,
+    // Margin
+    /// Máximo del margen usado sobre el equity (solo con `BacktestConfig::margin`)
+    #[serde(default)]
+    pub max_margin_usage: f64,
+    /// Media del margen usado sobre el equity en las velas con posiciones abiertas
+    #[serde(default)]
+    pub average_margin_usage: f64,
+    /// Menor distancia relativa entre el precio más adverso de una vela y el de liquidación
+    #[serde(default)]
+    pub min_liquidation_distance: Option<f64>,
+    // Benchmark
+    /// Retorno de comprar y mantener el activo en las mismas velas
+    #[serde(default)]
+    pub benchmark_return: f64,
+    /// Retorno total menos el del benchmark
+    #[serde(default)]
+    pub excess_return: f64,
+    /// Alpha de Jensen anualizado frente al benchmark
+    #[serde(default)]
+    pub alpha: f64,
+    /// Beta de los retornos por vela frente al benchmark
+    #[serde(default)]
+    pub beta: f64,
+    /// Information ratio por vela frente al benchmark
+    #[serde(default)]
+    pub information_ratio: f64,
+    /// Tracking error por vela frente al benchmark
+    #[serde(default)]
+    pub tracking_error: f64,
+    /// Captura alcista: retorno medio en velas alcistas del benchmark sobre el suyo
+    #[serde(default)]
+    pub up_capture: f64,
+    /// Captura bajista: retorno medio en velas bajistas del benchmark sobre el suyo
+    #[serde(default)]
+    pub down_capture: f64,
 }
 
 impl Default for BacktestMetrics {
@@ -130,7 +221,35 @@ impl Default for BacktestMetrics {
             take_profit_exits: 0,
             signal_exits: 0,
             end_of_data_exits: 0,
+            trailing_stop_exits: 0,
+            break_even_exits: 0,
+            chandelier_exits: 0,
+            max_bars_exits: 0,
+            session_close_exits: 0,
+            liquidation_exits: 0,
+            exit_reasons: Vec::new(),
             entry_signals_count: 0,
+            long_trades: 0,
+            short_trades: 0,
+            long_win_rate: 0.0,
+            short_win_rate: 0.0,
+            long_pnl: 0.0,
+            short_pnl: 0.0,
+            sharpe_observations: 0,
+            returns_skewness: 0.0,
+            returns_kurtosis: 0.0,
+            probabilistic_sharpe_ratio: 0.0,
+            max_margin_usage: 0.0,
+            average_margin_usage: 0.0,
+            min_liquidation_distance: None,
+            benchmark_return: 0.0,
+            excess_return: 0.0,
+            alpha: 0.0,
+            beta: 0.0,
+            information_ratio: 0.0,
+            tracking_error: 0.0,
+            up_capture: 0.0,
+            down_capture: 0.0,
         }
     }
 }
@@ -152,12 +271,136 @@ pub struct Trade {
     pub is_long: bool,
     /// P&L del trade
     pub pnl: f64,
-    /// Comisiones pagadas
+    /// Comisión de salida (incluida en `pnl`)
     pub commission: f64,
     /// Slippage incurrido
     pub slippage: f64,
+    /// Costo de préstamo/funding (solo posiciones cortas)
+    #[serde(default)]
+    pub borrow_cost: f64,
+    /// Razón de salida
+    pub exit_reason: ExitReason,
+    /// Posición a la que pertenece el trade: los lotes de pyramiding y los
+    /// cierres parciales de una misma posición comparten id
+    #[serde(default)]
+    pub position_id: usize,
+    /// Comisión de entrada (pagada al abrir, no incluida en `pnl`)
+    #[serde(default)]
+    pub entry_commission: f64,
+    /// Funding pagado (negativo si se cobró), incluido en `pnl`
+    #[serde(default)]
+    pub funding: f64,
+}
+
+impl Trade {
+    /// P&L neto, descontando también la comisión de entrada
+    pub fn net_pnl(&self) -> f64 {
+        self.pnl - self.entry_commission
+    }
+}
+
+/// Razón por la que se cerró un trade
+///
+/// Se serializa con los mismos textos que usaban los motores antes del enum, así
+/// que los resultados guardados siguen siendo legibles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ExitReason {
+    /// Señal de salida de la estrategia (o de entrada en el lado contrario)
+    #[serde(alias = "Strategy signal")]
+    Signal,
+    /// Stop loss fijo
+    StopLoss,
+    /// Take profit fijo
+    TakeProfit,
+    /// Toma de beneficios parcial (`scale_out`)
+    PartialTakeProfit,
+    /// Trailing stop porcentual o por ATR
+    TrailingStop,
+    /// Stop movido al precio de entrada
+    BreakEven,
+    /// Chandelier exit
+    ChandelierExit,
+    /// Máximo de velas en el trade
+    MaxBars,
+    /// Cierre de sesión
+    SessionClose,
+    /// Reducción por rebalanceo de cartera
+    Rebalance,
+    /// Liquidación forzosa por falta de margen
+    Liquidation,
+    /// Posición abierta al final de los datos
+    #[serde(rename = "End of data", alias = "EndOfData")]
+    EndOfData,
+}
+
+impl ExitReason {
+    /// Todas las razones, en el orden de los informes
+    pub const ALL: [ExitReason; 12] = [
+        ExitReason::Signal,
+        ExitReason::StopLoss,
+        ExitReason::TakeProfit,
+        ExitReason::PartialTakeProfit,
+        ExitReason::TrailingStop,
+        ExitReason::BreakEven,
+        ExitReason::ChandelierExit,
+        ExitReason::MaxBars,
+        ExitReason::SessionClose,
+        ExitReason::Rebalance,
+        ExitReason::Liquidation,
+        ExitReason::EndOfData,
+    ];
+
+    /// Texto usado en la serialización y en la base de datos
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExitReason::Signal => "Signal",
+            ExitReason::StopLoss => "StopLoss",
+            ExitReason::TakeProfit => "TakeProfit",
+            ExitReason::PartialTakeProfit => "PartialTakeProfit",
+            ExitReason::TrailingStop => "TrailingStop",
+            ExitReason::BreakEven => "BreakEven",
+            ExitReason::ChandelierExit => "ChandelierExit",
+            ExitReason::MaxBars => "MaxBars",
+            ExitReason::SessionClose => "SessionClose",
+            ExitReason::Rebalance => "Rebalance",
+            ExitReason::Liquidation => "Liquidation",
+            ExitReason::EndOfData => "End of data",
+        }
+    }
+}
+
+impl std::fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ExitReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Strategy signal" => Ok(ExitReason::Signal),
+            "EndOfData" => Ok(ExitReason::EndOfData),
+            _ => ExitReason::ALL
+                .into_iter()
+                .find(|reason| reason.as_str() == s)
+                .ok_or_else(|| format!("Unknown exit reason: {}", s)),
+        }
+    }
+}
+
+/// Resumen de los trades cerrados por una misma razón
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExitReasonStats {
     /// Razón de salida
-    pub exit_reason: String,
+    pub reason: ExitReason,
+    /// Número de trades
+    pub trades: usize,
+    /// P&L neto de esos trades
+    pub pnl: f64,
+    /// Fracción de esos trades con P&L positivo
+    pub win_rate: f64,
 }
 
 /// Punto en la curva de equity
@@ -0,0 +1,370 @@
+//! Análisis walk-forward
+//!
+//! Divide la serie de velas en ventanas consecutivas de in-sample (IS) y
+//! out-of-sample (OOS). En cada ventana las estrategias se seleccionan (generan,
+//! evolucionan, rankean) solo con las velas IS y después se evalúan sobre las
+//! velas OOS que siguen, que la selección nunca vio.
+//!
+//! - **Rolling**: la ventana IS tiene longitud fija y avanza con cada ventana
+//! - **Anchored**: la ventana IS empieza siempre en la primera vela y crece
+//!
+//! La eficiencia walk-forward compara el retorno por vela OOS con el retorno
+//! por vela IS: 1.0 significa que la estrategia mantuvo fuera de muestra el
+//! rendimiento que mostró dentro de muestra.
+//!
+//! El backtest OOS arranca con las últimas velas IS como warmup para que los
+//! indicadores no empiecen en frío; solo cuentan los trades y la equity del tramo OOS.
+
+use std::ops::Range;
+use serde::{Deserialize, Serialize};
+use darwinx_core::Candle;
+use darwinx_generator::StrategyAST;
+use crate::config::BacktestConfig;
+use crate::error::BacktestError;
+use crate::polars_engine::massive::PolarsVectorizedBacktestEngine;
+use crate::polars_engine::multi_timeframe::{base_interval, condition_timeframe};
+use crate::polars_engine::parallel::{EquityCurveOutput, ParallelConfig};
+use crate::types::{BacktestMetrics, BacktestResult, EquityPoint};
+
+/// Forma en que avanza la ventana in-sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WalkForwardMode {
+    /// Ventana IS de longitud fija que se desplaza
+    Rolling,
+    /// Ventana IS anclada al inicio de los datos
+    Anchored,
+}
+
+impl WalkForwardMode {
+    /// Nombre usado para persistir el modo
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WalkForwardMode::Rolling => "rolling",
+            WalkForwardMode::Anchored => "anchored",
+        }
+    }
+}
+
+/// Configuración del análisis walk-forward
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardConfig {
+    /// Número de ventanas OOS
+    pub windows: usize,
+    /// Proporción IS de cada ventana IS+OOS (ej: 0.7 = 70% IS, 30% OOS)
+    pub in_sample_ratio: f64,
+    /// Rolling o anchored
+    pub mode: WalkForwardMode,
+}
+
+impl Default for WalkForwardConfig {
+    fn default() -> Self {
+        Self {
+            windows: 5,
+            in_sample_ratio: 0.7,
+            mode: WalkForwardMode::Rolling,
+        }
+    }
+}
+
+impl WalkForwardConfig {
+    /// Crea una configuración rolling
+    pub fn new(windows: usize, in_sample_ratio: f64) -> Self {
+        Self {
+            windows,
+            in_sample_ratio,
+            mode: WalkForwardMode::Rolling,
+        }
+    }
+
+    /// Usa ventanas IS ancladas al inicio de los datos
+    pub fn anchored(mut self) -> Self {
+        self.mode = WalkForwardMode::Anchored;
+        self
+    }
+
+    /// Divide `total_candles` velas en ventanas IS/OOS
+    ///
+    /// Los tramos OOS son consecutivos y no se solapan; el último absorbe las
+    /// velas sobrantes de la división. La primera ventana IS tiene la misma
+    /// longitud en ambos modos.
+    pub fn split(&self, total_candles: usize) -> Result<Vec<WalkForwardWindow>, BacktestError> {
+        if self.windows == 0 {
+            return Err(BacktestError::ConfigError("Walk-forward requires at least one window".to_string()));
+        }
+        if !(self.in_sample_ratio > 0.0 && self.in_sample_ratio < 1.0) {
+            return Err(BacktestError::ConfigError(format!(
+                "In-sample ratio must be between 0 and 1, got {}",
+                self.in_sample_ratio
+            )));
+        }
+
+        // total = IS + windows * OOS, con IS / (IS + OOS) = in_sample_ratio
+        let is_per_oos = self.in_sample_ratio / (1.0 - self.in_sample_ratio);
+        let oos_len = (total_candles as f64 / (self.windows as f64 + is_per_oos)).floor() as usize;
+        let is_len = (oos_len as f64 * is_per_oos).floor() as usize;
+        if oos_len == 0 || is_len == 0 {
+            return Err(BacktestError::DataError(anyhow::anyhow!(
+                "Not enough candles ({}) for {} walk-forward windows",
+                total_candles,
+                self.windows
+            )));
+        }
+
+        Ok((0..self.windows)
+            .map(|index| {
+                let is_end = is_len + index * oos_len;
+                let is_start = match self.mode {
+                    WalkForwardMode::Rolling => index * oos_len,
+                    WalkForwardMode::Anchored => 0,
+                };
+                let oos_end = if index + 1 == self.windows {
+                    total_candles
+                } else {
+                    is_end + oos_len
+                };
+                WalkForwardWindow {
+                    index,
+                    in_sample: is_start..is_end,
+                    out_of_sample: is_end..oos_end,
+                }
+            })
+            .collect())
+    }
+}
+
+/// Ventana walk-forward (rangos de índices de vela)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalkForwardWindow {
+    pub index: usize,
+    pub in_sample: Range<usize>,
+    pub out_of_sample: Range<usize>,
+}
+
+impl WalkForwardWindow {
+    /// Velas in-sample de la ventana
+    pub fn in_sample_candles<'a>(&self, candles: &'a [Candle]) -> &'a [Candle] {
+        &candles[self.in_sample.clone()]
+    }
+
+    /// Velas out-of-sample de la ventana
+    pub fn out_of_sample_candles<'a>(&self, candles: &'a [Candle]) -> &'a [Candle] {
+        &candles[self.out_of_sample.clone()]
+    }
+}
+
+/// Resultado IS/OOS de una estrategia seleccionada en una ventana
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardStrategyResult {
+    pub window: usize,
+    pub strategy_name: String,
+    pub in_sample: BacktestMetrics,
+    pub out_of_sample: BacktestMetrics,
+    /// Retorno total IS (según balance final)
+    pub in_sample_return: f64,
+    /// Retorno total OOS (según balance final)
+    pub out_of_sample_return: f64,
+    /// Retorno por vela OOS / retorno por vela IS (None si el retorno IS no es positivo)
+    pub efficiency: Option<f64>,
+}
+
+/// Resultado de una ventana walk-forward
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardWindowReport {
+    pub window: WalkForwardWindow,
+    pub in_sample_start: i64,
+    pub in_sample_end: i64,
+    pub out_of_sample_start: i64,
+    pub out_of_sample_end: i64,
+    /// Estrategias seleccionadas, en el orden de la selección (la primera es la operada)
+    pub strategies: Vec<WalkForwardStrategyResult>,
+}
+
+/// Informe completo del análisis walk-forward
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardReport {
+    pub config: WalkForwardConfig,
+    pub windows: Vec<WalkForwardWindowReport>,
+    /// Equity OOS encadenada operando la primera estrategia seleccionada de cada ventana
+    pub oos_equity: Vec<EquityPoint>,
+    /// Retorno total de la equity OOS encadenada
+    pub oos_total_return: f64,
+    /// Máximo drawdown de la equity OOS encadenada (0.0 - 1.0)
+    pub oos_max_drawdown: f64,
+    /// Eficiencia media de las estrategias operadas (None si ninguna es calculable)
+    pub efficiency: Option<f64>,
+}
+
+impl WalkForwardReport {
+    /// Todas las estrategias evaluadas, ventana por ventana
+    pub fn strategy_results(&self) -> impl Iterator<Item = &WalkForwardStrategyResult> {
+        self.windows.iter().flat_map(|w| w.strategies.iter())
+    }
+}
+
+/// Eficiencia walk-forward: retorno por vela OOS / retorno por vela IS
+pub fn walk_forward_efficiency(
+    in_sample_return: f64,
+    in_sample_candles: usize,
+    out_of_sample_return: f64,
+    out_of_sample_candles: usize,
+) -> Option<f64> {
+    if in_sample_return <= 0.0 || in_sample_candles == 0 || out_of_sample_candles == 0 {
+        return None;
+    }
+    let is_per_candle = in_sample_return / in_sample_candles as f64;
+    let oos_per_candle = out_of_sample_return / out_of_sample_candles as f64;
+    Some(oos_per_candle / is_per_candle)
+}
+
+/// Veces el lookback más largo usadas como warmup: las medias exponenciales y
+/// las de Wilder tardan varios periodos en olvidar su valor inicial
+const WARMUP_LOOKBACK_MULTIPLE: usize = 3;
+
+/// Velas que necesita la condición más lenta de las estrategias para dar su primer valor
+///
+/// Suma los parámetros de cada indicador (ej: MACD 12/26/9) más la vela previa de
+/// los cruces; en timeframes superiores el lookback se cuenta en velas principales.
+fn strategies_lookback(strategies: &[StrategyAST], base_millis: Option<i64>) -> usize {
+    strategies
+        .iter()
+        .flat_map(|strategy| strategy.all_conditions())
+        .map(|condition| {
+            let mut indicators = vec![&condition.indicator];
+            if let darwinx_generator::ast::nodes::ConditionValue::Indicator(indicator) = &condition.value {
+                indicators.push(indicator);
+            }
+            let bars = indicators
+                .iter()
+                .map(|indicator| indicator.params.iter().map(|p| p.max(0.0)).sum::<f64>().ceil() as usize)
+                .max()
+                .unwrap_or(0)
+                + 1;
+            let scale = match (condition_timeframe(condition.timeframe, base_millis), base_millis) {
+                (Some(timeframe), Some(base)) => (timeframe.to_millis() as f64 / base as f64).ceil() as usize,
+                _ => 1,
+            };
+            bars * scale
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn result_return(result: &BacktestResult) -> f64 {
+    if result.metadata.initial_balance > 0.0 {
+        result.metadata.final_balance / result.metadata.initial_balance - 1.0
+    } else {
+        0.0
+    }
+}
+
+impl PolarsVectorizedBacktestEngine {
+    /// Ejecuta un análisis walk-forward
+    ///
+    /// Para cada ventana llama a `select` con las velas IS; la función retorna
+    /// las estrategias elegidas (ej: generación + evolución + ranking sobre IS).
+    /// Cada estrategia elegida se backtestea sobre IS y OOS con `config`; el
+    /// backtest OOS empieza con un warmup de velas IS (al menos el lookback más
+    /// largo de las estrategias) que no opera ni cuenta en las métricas.
+    /// Los backtests se ejecutan con rayon según `options`, así que la llamada
+    /// es bloqueante.
+    pub fn run_walk_forward<S>(
+        &self,
+        candles: &[Candle],
+        walk_forward: &WalkForwardConfig,
+        config: &BacktestConfig,
+        options: ParallelConfig,
+        mut select: S,
+    ) -> Result<WalkForwardReport, BacktestError>
+    where
+        S: FnMut(&WalkForwardWindow, &[Candle]) -> Result<Vec<StrategyAST>, BacktestError>,
+    {
+        let windows = walk_forward.split(candles.len())?;
+        let timestamps: Vec<i64> = candles.iter().map(|c| c.timestamp).collect();
+        let base_millis = base_interval(&timestamps);
+        // La equity OOS necesita la curva completa de la estrategia operada
+        let mut options = options;
+        options.equity_curve = EquityCurveOutput::Full;
+
+        let mut reports = Vec::with_capacity(windows.len());
+        let mut equity = config.initial_balance;
+        let mut peak = equity;
+        let mut oos_max_drawdown: f64 = 0.0;
+        let mut oos_equity = Vec::new();
+
+        for window in windows {
+            let is_candles = window.in_sample_candles(candles);
+            let oos_candles = window.out_of_sample_candles(candles);
+            let selected = select(&window, is_candles)?;
+
+            let is_results = self.run_massive_backtest_parallel(selected.clone(), is_candles, config, options.clone())?;
+            let warmup = strategies_lookback(&selected, base_millis) * WARMUP_LOOKBACK_MULTIPLE;
+            let oos_with_warmup = &candles[window.out_of_sample.start.saturating_sub(warmup)..window.out_of_sample.end];
+            let oos_config = config.clone().with_trading_start(oos_candles[0].timestamp);
+            let oos_results = self.run_massive_backtest_parallel(selected, oos_with_warmup, &oos_config, options.clone())?;
+
+            // Encadenar la equity OOS mark-to-market de la estrategia operada (la primera seleccionada)
+            if let Some(traded) = oos_results.first() {
+                let scale = equity / config.initial_balance;
+                for point in &traded.equity_curve {
+                    let balance = point.balance * scale;
+                    peak = peak.max(balance);
+                    let drawdown = if peak > 0.0 { (peak - balance) / peak } else { 0.0 };
+                    oos_max_drawdown = oos_max_drawdown.max(drawdown);
+                    oos_equity.push(EquityPoint { timestamp: point.timestamp, balance, drawdown });
+                }
+                equity *= 1.0 + result_return(traded);
+            }
+
+            let strategies = is_results
+                .into_iter()
+                .zip(oos_results)
+                .map(|(is_result, oos_result)| {
+                    let in_sample_return = result_return(&is_result);
+                    let out_of_sample_return = result_return(&oos_result);
+                    WalkForwardStrategyResult {
+                        window: window.index,
+                        efficiency: walk_forward_efficiency(
+                            in_sample_return,
+                            is_candles.len(),
+                            out_of_sample_return,
+                            oos_candles.len(),
+                        ),
+                        strategy_name: is_result.strategy_name,
+                        in_sample: is_result.metrics,
+                        out_of_sample: oos_result.metrics,
+                        in_sample_return,
+                        out_of_sample_return,
+                    }
+                })
+                .collect();
+
+            reports.push(WalkForwardWindowReport {
+                in_sample_start: is_candles[0].timestamp,
+                in_sample_end: is_candles[is_candles.len() - 1].timestamp,
+                out_of_sample_start: oos_candles[0].timestamp,
+                out_of_sample_end: oos_candles[oos_candles.len() - 1].timestamp,
+                window,
+                strategies,
+            });
+        }
+
+        let traded_efficiencies: Vec<f64> = reports
+            .iter()
+            .filter_map(|r| r.strategies.first().and_then(|s| s.efficiency))
+            .collect();
+        let efficiency = if traded_efficiencies.is_empty() {
+            None
+        } else {
+            Some(traded_efficiencies.iter().sum::<f64>() / traded_efficiencies.len() as f64)
+        };
+
+        Ok(WalkForwardReport {
+            config: walk_forward.clone(),
+            windows: reports,
+            oos_equity,
+            oos_total_return: equity / config.initial_balance - 1.0,
+            oos_max_drawdown,
+            efficiency,
+        })
+    }
+}
@@ -0,0 +1,67 @@
+//! Tests de integración con barras no temporales
+//!
+//! Las barras de volumen, Renko, etc. tienen timestamps irregulares; los motores
+//! deben tratarlas igual que las velas temporales y registrar el tipo de barra.
+
+use darwinx_backtest_engine::*;
+use darwinx_core::{BarType, Candle, TimeFrame};
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+
+/// Velas en zigzag con separaciones irregulares, como las de barras de volumen
+fn create_irregular_candles() -> Vec<Candle> {
+    let mut timestamp = BASE_TIMESTAMP;
+    let mut close = 100.0;
+
+    (0..120)
+        .map(|i| {
+            timestamp += 1_000 * (1 + (i * 7) % 13) as i64;
+            close += if (i / 20) % 2 == 0 { 1.5 } else { -1.5 };
+            Candle::new(timestamp, close, close + 1.0, close - 1.0, close, 500.0)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_engines_record_bar_type_on_irregular_bars() {
+    let candles = create_irregular_candles();
+    let strategy = StrategyBuilder::new("VolumeBars".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above("sma", vec![3.0], "sma", vec![8.0]))
+        .add_exit_condition(ConditionBuilder::crosses_below("sma", vec![3.0], "sma", vec![8.0]))
+        .build();
+    let bar_type = BarType::Volume { threshold: 500.0 };
+    let config = BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.1)
+        .with_bar_type(bar_type);
+
+    let polars = PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(vec![strategy.clone()], candles.clone(), &config)
+        .await
+        .unwrap()
+        .remove(0);
+    let event = EventDrivenBacktestEngine::new()
+        .run_ast(strategy, &SingleTimeFrameProvider::new(candles, TimeFrame::H1), &config)
+        .await
+        .unwrap();
+
+    assert_eq!(polars.metadata.config.bar_type, Some(bar_type));
+    assert_eq!(event.metadata.config.bar_type, Some(bar_type));
+
+    assert!(!polars.trades.is_empty());
+    assert_eq!(polars.trades.len(), event.trades.len());
+    for (p, e) in polars.trades.iter().zip(&event.trades) {
+        assert_eq!(p.entry_timestamp, e.entry_timestamp);
+        assert_eq!(p.exit_timestamp, e.exit_timestamp);
+    }
+
+    // La anualización usa el tiempo transcurrido, no el número de barras
+    for result in [&polars, &event] {
+        let span_ms = result.equity_curve.last().unwrap().timestamp - result.equity_curve[0].timestamp;
+        let days = span_ms as f64 / 86_400_000.0;
+        assert_eq!(result.metrics.annualized_return, metrics::calculate_annualized_return(result.metrics.total_return, days));
+    }
+
+    // El tipo de barra viaja con los metadatos serializados
+    let json = serde_json::to_string(&polars.metadata).unwrap();
+    assert!(json.contains(r#""bar_type":{"type":"volume","threshold":500.0}"#));
+}
@@ -0,0 +1,104 @@
+//! Tests de integración de la comparación contra comprar y mantener
+
+use darwinx_backtest_engine::*;
+use darwinx_backtest_engine::metrics::{calculate_benchmark_comparison, calculate_buy_and_hold_returns};
+use darwinx_core::{Candle, TimeFrame};
+use darwinx_generator::StrategyAST;
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+const HOUR_MS: i64 = 3_600_000;
+
+fn candles_from_closes(closes: &[f64]) -> Vec<Candle> {
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| Candle::new(BASE_TIMESTAMP + i as i64 * HOUR_MS, close, close + 0.5, close - 0.5, close, 1000.0))
+        .collect()
+}
+
+fn strategy(threshold: f64) -> StrategyAST {
+    StrategyBuilder::new("Above".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above_value("sma", vec![1.0], threshold))
+        .build()
+}
+
+async fn run(closes: &[f64], strategy: StrategyAST) -> BacktestResult {
+    let config = BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 1.0);
+    PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(vec![strategy], candles_from_closes(closes), &config)
+        .await
+        .unwrap()
+        .remove(0)
+}
+
+#[test]
+fn test_benchmark_comparison_of_leveraged_returns() {
+    let benchmark = calculate_buy_and_hold_returns(&[100.0, 102.0, 99.0, 104.0, 103.0]);
+    assert_eq!(benchmark.len(), 4);
+    assert!((benchmark[0] - 0.02).abs() < 1e-12);
+
+    let returns: Vec<f64> = benchmark.iter().map(|b| 2.0 * b).collect();
+    let comparison = calculate_benchmark_comparison(&returns, &benchmark, 8760.0);
+
+    assert!((comparison.benchmark_return - 0.03).abs() < 1e-12);
+    assert!((comparison.beta - 2.0).abs() < 1e-12);
+    assert!(comparison.alpha.abs() < 1e-9);
+    assert!((comparison.up_capture - 2.0).abs() < 1e-12);
+    assert!((comparison.down_capture - 2.0).abs() < 1e-12);
+
+    // El retorno activo es el propio benchmark
+    let mean = benchmark.iter().sum::<f64>() / 4.0;
+    let std = (benchmark.iter().map(|b| (b - mean).powi(2)).sum::<f64>() / 4.0).sqrt();
+    assert!((comparison.tracking_error - std).abs() < 1e-12);
+    assert!((comparison.information_ratio - mean / std).abs() < 1e-9);
+
+    // Un retorno constante por encima del benchmark es alpha puro
+    let shifted: Vec<f64> = benchmark.iter().map(|b| b + 0.001).collect();
+    let comparison = calculate_benchmark_comparison(&shifted, &benchmark, 8760.0);
+    assert!((comparison.beta - 1.0).abs() < 1e-9);
+    assert!((comparison.alpha - 0.001 * 8760.0).abs() < 1e-6);
+    assert!(comparison.tracking_error < 1e-12);
+    assert_eq!(comparison.information_ratio, 0.0);
+}
+
+#[tokio::test]
+async fn test_massive_metrics_include_buy_and_hold() {
+    let closes = [95.0, 101.0, 105.0, 99.0, 120.0];
+    let result = run(&closes, strategy(100.0)).await;
+    let m = &result.metrics;
+
+    assert!((m.benchmark_return - (120.0 / 95.0 - 1.0)).abs() < 1e-12);
+    assert!((m.excess_return - (m.total_return - m.benchmark_return)).abs() < 1e-12);
+
+    // Totalmente invertida desde la vela 1: captura el 100% de las caídas
+    // y nada de la subida de la primera vela
+    let b = calculate_buy_and_hold_returns(&closes);
+    assert!((m.up_capture - (b[1] + b[3]) / (b[0] + b[1] + b[3])).abs() < 1e-9);
+    assert!((m.down_capture - 1.0).abs() < 1e-9);
+    assert!(m.beta > 0.0 && m.beta < 1.5);
+}
+
+#[tokio::test]
+async fn test_strategy_without_trades_trails_benchmark() {
+    let closes = [95.0, 96.0, 98.0, 97.0, 99.0];
+    let result = run(&closes, strategy(150.0)).await;
+    let m = &result.metrics;
+
+    assert_eq!(m.total_trades, 0);
+    assert!((m.excess_return + (99.0 / 95.0 - 1.0)).abs() < 1e-12);
+    assert_eq!(m.beta, 0.0);
+    assert_eq!(m.alpha, 0.0);
+    assert_eq!(m.up_capture, 0.0);
+}
+
+#[test]
+fn test_benchmark_metrics_serde_defaults() {
+    let mut value = serde_json::to_value(BacktestMetrics::default()).unwrap();
+    for field in ["benchmark_return", "excess_return", "alpha", "beta", "up_capture"] {
+        value.as_object_mut().unwrap().remove(field);
+    }
+    let metrics: BacktestMetrics = serde_json::from_value(value).unwrap();
+    assert_eq!(metrics.excess_return, 0.0);
+    assert_eq!(metrics.beta, 0.0);
+}
@@ -0,0 +1,243 @@
+//! Tests de integración de los modelos de costes (comisiones, slippage y funding)
+
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, PositionSide, TimeFrame};
+use darwinx_generator::StrategyAST;
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+use polars::prelude::*;
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01 00:00 UTC
+const HOUR_MS: i64 = 3_600_000;
+
+fn candles_from_closes(closes: &[f64]) -> Vec<Candle> {
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| Candle::new(BASE_TIMESTAMP + i as i64 * HOUR_MS, close, close + 0.5, close - 0.5, close, 1000.0))
+        .collect()
+}
+
+/// Entra al cruzar 100 hacia arriba y sale al cruzarlo hacia abajo
+fn around_hundred() -> StrategyAST {
+    StrategyBuilder::new("AroundHundred".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above_value("sma", vec![1.0], 100.0))
+        .add_exit_condition(ConditionBuilder::crosses_below_value("sma", vec![1.0], 100.0))
+        .build()
+}
+
+fn no_cost_config() -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.5)
+}
+
+async fn run_both(candles: Vec<Candle>, config: &BacktestConfig) -> (BacktestResult, BacktestResult) {
+    let polars = PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(vec![around_hundred()], candles.clone(), config)
+        .await
+        .unwrap()
+        .remove(0);
+    let event = EventDrivenBacktestEngine::new()
+        .run_ast(around_hundred(), &SingleTimeFrameProvider::new(candles, TimeFrame::H1), config)
+        .await
+        .unwrap();
+    (polars, event)
+}
+
+fn assert_same_trades(polars: &BacktestResult, event: &BacktestResult) {
+    assert_eq!(polars.trades.len(), event.trades.len());
+    for (p, e) in polars.trades.iter().zip(&event.trades) {
+        assert_eq!(p.exit_reason, e.exit_reason);
+        assert!((p.entry_price - e.entry_price).abs() < 1e-9);
+        assert!((p.exit_price - e.exit_price).abs() < 1e-9);
+        assert!((p.entry_commission - e.entry_commission).abs() < 1e-9);
+        assert!((p.commission - e.commission).abs() < 1e-9);
+        assert!((p.funding - e.funding).abs() < 1e-9);
+        assert!((p.pnl - e.pnl).abs() < 1e-9);
+    }
+    assert!((polars.metadata.final_balance - event.metadata.final_balance).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_maker_taker_fees_by_exit_type() {
+    let candles = candles_from_closes(&[95.0, 101.0, 104.0, 107.0, 108.0]);
+    let fees = FeeModel::MakerTaker { maker_rate: 0.0002, taker_rate: 0.0005 };
+    let mut config = no_cost_config().with_cost_model(CostModel::default().with_fees(fees));
+    config.take_profit_percent = Some(0.05);
+
+    let (polars, event) = run_both(candles, &config).await;
+    assert_same_trades(&polars, &event);
+
+    // Entrada a mercado (taker) y take profit con orden limitada (maker)
+    let trade = &polars.trades[0];
+    assert_eq!(trade.exit_reason, ExitReason::TakeProfit);
+    assert!((trade.entry_commission - 5000.0 * 0.0005).abs() < 1e-9);
+    assert!((trade.commission - trade.exit_price * trade.size * 0.0002).abs() < 1e-9);
+    let expected_balance = 10000.0 + (trade.exit_price - 101.0) * trade.size - trade.commission - trade.entry_commission;
+    assert!((polars.metadata.final_balance - expected_balance).abs() < 1e-9);
+    assert!((polars.equity_curve.last().unwrap().balance - expected_balance).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_total_return_includes_entry_commission() {
+    let candles = candles_from_closes(&[95.0, 101.0, 104.0, 99.0, 103.0, 98.0]);
+    let config = BacktestConfig::with_position_size(10000.0, 0.001, 0.0, 1, 0.02, None, None, 0.5);
+    let (polars, event) = run_both(candles, &config).await;
+    assert_same_trades(&polars, &event);
+
+    for result in [&polars, &event] {
+        assert!(result.trades.iter().all(|t| t.entry_commission > 0.0));
+        let metadata = &result.metadata;
+        let expected = metadata.final_balance / metadata.initial_balance - 1.0;
+        assert!((result.metrics.total_return - expected).abs() < 1e-12);
+        let last_equity = result.equity_curve.last().unwrap().balance;
+        assert!((last_equity - metadata.final_balance).abs() < 1e-9);
+    }
+}
+
+#[tokio::test]
+async fn test_tiered_fees_follow_traded_volume() {
+    let tiers = vec![
+        FeeTier { min_volume: 5000.0, maker_rate: 0.0004, taker_rate: 0.0004 },
+        FeeTier { min_volume: 0.0, maker_rate: 0.001, taker_rate: 0.001 },
+    ];
+    // Sale 3 horas después de entrar: la entrada sigue dentro de la ventana
+    let candles = candles_from_closes(&[95.0, 101.0, 102.0, 103.0, 99.0]);
+    let config = no_cost_config().with_cost_model(CostModel::default().with_fees(FeeModel::tiered(tiers.clone(), 30)));
+    let (polars, event) = run_both(candles, &config).await;
+    assert_same_trades(&polars, &event);
+
+    let trade = &polars.trades[0];
+    assert!((trade.entry_commission - 5000.0 * 0.001).abs() < 1e-9);
+    assert!((trade.commission - trade.exit_price * trade.size * 0.0004).abs() < 1e-9);
+
+    // Con una ventana de un día la entrada ya no cuenta al salir 30 horas después
+    let mut closes = vec![95.0, 101.0];
+    closes.extend(std::iter::repeat_n(102.0, 29));
+    closes.push(99.0);
+    let candles = candles_from_closes(&closes);
+    let config = no_cost_config().with_cost_model(CostModel::default().with_fees(FeeModel::tiered(tiers, 1)));
+    let (polars, event) = run_both(candles, &config).await;
+    assert_same_trades(&polars, &event);
+    let trade = &polars.trades[0];
+    assert!((trade.commission - trade.exit_price * trade.size * 0.001).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_volume_impact_slippage_scales_with_participation() {
+    let candles = candles_from_closes(&[95.0, 101.0, 104.0, 99.0]);
+    let slippage = SlippageModel::VolumeImpact { base_bps: 0.0, impact_bps: 100.0, max_bps: None };
+    let config = no_cost_config().with_cost_model(CostModel::default().with_slippage(slippage));
+    let (polars, event) = run_both(candles.clone(), &config).await;
+    assert_same_trades(&polars, &event);
+
+    // 49.5 unidades sobre un volumen de 1000: sqrt(0.0495) * 100 bps
+    let trade = &polars.trades[0];
+    let size = 5000.0 / 101.0;
+    assert!((trade.size - size).abs() < 1e-9);
+    let bps = 100.0 * (size / 1000.0_f64).sqrt();
+    assert!((trade.entry_price - 101.0 * (1.0 + bps / 10000.0)).abs() < 1e-9);
+    assert!((trade.exit_price - 99.0 * (1.0 - bps / 10000.0)).abs() < 1e-9);
+    assert!((trade.slippage - 99.0 * bps / 10000.0 * size).abs() < 1e-9);
+
+    // El tope limita el impacto
+    let capped = SlippageModel::VolumeImpact { base_bps: 0.0, impact_bps: 100.0, max_bps: Some(10.0) };
+    let config = no_cost_config().with_cost_model(CostModel::default().with_slippage(capped));
+    let (polars, event) = run_both(candles, &config).await;
+    assert_same_trades(&polars, &event);
+    assert!((polars.trades[0].entry_price - 101.0 * 1.001).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_atr_slippage_matches_across_engines() {
+    let closes = [95.0, 96.0, 98.0, 97.0, 101.0, 103.0, 106.0, 104.0, 99.0, 97.0];
+    let candles = candles_from_closes(&closes);
+    let slippage = SlippageModel::Atr { multiple: 0.5, period: 3 };
+    let config = no_cost_config().with_cost_model(CostModel::default().with_slippage(slippage));
+    let (polars, event) = run_both(candles.clone(), &config).await;
+    assert_same_trades(&polars, &event);
+
+    // Fills al cierre: medio ATR de la propia vela (entrada en la 4, salida en la 8)
+    let highs: Vec<f64> = candles.iter().map(|c| c.high).collect();
+    let lows: Vec<f64> = candles.iter().map(|c| c.low).collect();
+    let atr = sizing::atr_series(&highs, &lows, &closes, 3);
+    let trade = &polars.trades[0];
+    assert!((trade.entry_price - (101.0 + 0.5 * atr[4].unwrap())).abs() < 1e-9);
+    assert!((trade.exit_price - (99.0 - 0.5 * atr[8].unwrap())).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_funding_payments_over_holding_period() {
+    let funding = FundingRates::new(vec![
+        FundingRate { timestamp: BASE_TIMESTAMP + 2 * HOUR_MS, rate: 0.001 },
+        FundingRate { timestamp: BASE_TIMESTAMP + 3 * HOUR_MS, rate: -0.0005 },
+        // Posterior a la salida: no se paga
+        FundingRate { timestamp: BASE_TIMESTAMP + 10 * HOUR_MS, rate: 0.01 },
+        // Anterior a la entrada: no se paga
+        FundingRate { timestamp: BASE_TIMESTAMP, rate: 0.01 },
+    ]);
+    let cost_model = CostModel::default().with_funding(funding.clone());
+    let candles = candles_from_closes(&[95.0, 101.0, 102.0, 103.0, 99.0]);
+    let (polars, event) = run_both(candles, &no_cost_config().with_cost_model(cost_model.clone())).await;
+    assert_same_trades(&polars, &event);
+
+    let trade = &polars.trades[0];
+    assert!((trade.funding - 5000.0 * 0.0005).abs() < 1e-9);
+    assert!((trade.pnl - ((99.0 - 101.0) * trade.size - trade.funding)).abs() < 1e-9);
+
+    // Los cortos cobran cuando la tasa es positiva
+    let received = cost_model.funding_payment(PositionSide::Short, 1000.0, BASE_TIMESTAMP, BASE_TIMESTAMP + 2 * HOUR_MS);
+    assert!((received + 1.0).abs() < 1e-12);
+    assert_eq!(funding.cumulative_rate(BASE_TIMESTAMP + 3 * HOUR_MS, BASE_TIMESTAMP + 9 * HOUR_MS), 0.0);
+}
+
+#[test]
+fn test_funding_rates_load_from_csv_and_parquet() {
+    let dir = std::env::temp_dir();
+    let csv_path = dir.join(format!("darwinx_funding_{}.csv", std::process::id()));
+    std::fs::write(&csv_path, "timestamp,funding_rate\n1609488000000,-0.0002\n1609459200000,0.0001\n").unwrap();
+    let from_csv = FundingRates::load_csv(csv_path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&csv_path).unwrap();
+
+    assert_eq!(from_csv.len(), 2);
+    assert_eq!(from_csv.rates()[0], FundingRate { timestamp: 1609459200000, rate: 0.0001 });
+
+    let mut df = df!(
+        "timestamp" => [1609459200000i64, 1609488000000],
+        "funding_rate" => [0.0001, -0.0002],
+    )
+    .unwrap();
+    let parquet_path = dir.join(format!("darwinx_funding_{}.parquet", std::process::id()));
+    ParquetWriter::new(std::fs::File::create(&parquet_path).unwrap()).finish(&mut df).unwrap();
+    let from_parquet = FundingRates::load_parquet(parquet_path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&parquet_path).unwrap();
+
+    assert_eq!(from_parquet, from_csv);
+    assert!(FundingRates::load_csv("/nonexistent/funding.csv").is_err());
+}
+
+#[test]
+fn test_cost_model_serde_defaults() {
+    let mut value = serde_json::to_value(BacktestConfig::default()).unwrap();
+    value.as_object_mut().unwrap().remove("cost_model");
+    let config: BacktestConfig = serde_json::from_value(value).unwrap();
+    assert_eq!(config.cost_model, CostModel::default());
+
+    let cost_model: CostModel = serde_json::from_str(
+        r#"{"fees":{"model":"tiered","tiers":[{"min_volume":0.0,"maker_rate":0.001,"taker_rate":0.002}]},
+            "slippage":{"model":"volume_impact","base_bps":1.0,"impact_bps":50.0}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        cost_model.fees,
+        FeeModel::Tiered { tiers: vec![FeeTier { min_volume: 0.0, maker_rate: 0.001, taker_rate: 0.002 }], window_days: 30 }
+    );
+    assert_eq!(cost_model.slippage, SlippageModel::VolumeImpact { base_bps: 1.0, impact_bps: 50.0, max_bps: None });
+    assert!(cost_model.funding.is_none());
+
+    // Trades guardados antes de los campos de costes
+    let trade: Trade = serde_json::from_str(
+        r#"{"entry_timestamp":0,"exit_timestamp":1,"entry_price":100.0,"exit_price":101.0,"size":1.0,
+            "is_long":true,"pnl":1.0,"commission":0.0,"slippage":0.0,"exit_reason":"Signal"}"#,
+    )
+    .unwrap();
+    assert_eq!((trade.entry_commission, trade.funding), (0.0, 0.0));
+}
@@ -0,0 +1,180 @@
+//! Tests de regresión para la semántica de cruces (CrossesAbove/CrossesBelow)
+//! en el motor masivo de Polars
+
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, TimeFrame};
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+
+/// Velas en forma de V: 30 velas bajando y 30 subiendo
+fn create_v_shaped_candles() -> Vec<Candle> {
+    let mut candles = Vec::new();
+
+    for i in 0..60 {
+        let timestamp = BASE_TIMESTAMP + (i as i64 * 60_000);
+        let close = if i < 30 {
+            200.0 - (i as f64 * 2.0)
+        } else {
+            142.0 + ((i - 29) as f64 * 2.0)
+        };
+        candles.push(Candle::new(timestamp, close, close + 1.0, close - 1.0, close, 1000.0));
+    }
+
+    candles
+}
+
+/// Velas en forma de V invertida: 30 velas subiendo y 30 bajando
+fn create_inverted_v_candles() -> Vec<Candle> {
+    create_v_shaped_candles()
+        .into_iter()
+        .map(|c| {
+            let close = 342.0 - c.close;
+            Candle::new(c.timestamp, close, close + 1.0, close - 1.0, close, c.volume)
+        })
+        .collect()
+}
+
+/// SMA de referencia calculada vela a vela (None durante el warmup)
+fn reference_sma(closes: &[f64], period: usize) -> Vec<Option<f64>> {
+    (0..closes.len())
+        .map(|i| {
+            if i + 1 < period {
+                None
+            } else {
+                Some(closes[i + 1 - period..=i].iter().sum::<f64>() / period as f64)
+            }
+        })
+        .collect()
+}
+
+/// Índices donde `a` cruza por encima de `b` (anterior <= y actual >)
+fn reference_crosses_above(a: &[Option<f64>], b: &[Option<f64>]) -> Vec<usize> {
+    (1..a.len())
+        .filter(|&i| match (a[i - 1], b[i - 1], a[i], b[i]) {
+            (Some(pa), Some(pb), Some(ca), Some(cb)) => pa <= pb && ca > cb,
+            _ => false,
+        })
+        .collect()
+}
+
+fn no_risk_config() -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.1)
+}
+
+async fn run_single(strategy: darwinx_generator::StrategyAST, candles: Vec<Candle>) -> BacktestResult {
+    let engine = PolarsVectorizedBacktestEngine::new();
+    let mut results = engine
+        .run_massive_backtest(vec![strategy], candles, &no_risk_config())
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    results.remove(0)
+}
+
+#[tokio::test]
+async fn test_golden_cross_fires_only_on_crossing_bar() {
+    let candles = create_v_shaped_candles();
+    let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+
+    let expected = reference_crosses_above(&reference_sma(&closes, 3), &reference_sma(&closes, 10));
+    assert_eq!(expected.len(), 1, "la serie de prueba debe tener un único golden cross");
+
+    let strategy = StrategyBuilder::new("GoldenCross".to_string(), TimeFrame::M1)
+        .add_entry_condition(ConditionBuilder::crosses_above("sma", vec![3.0], "sma", vec![10.0]))
+        .build();
+
+    let result = run_single(strategy, candles).await;
+
+    // Con la semántica anterior (sma_3 > sma_10) habría señal en todas las velas tras el cruce
+    assert_eq!(result.metrics.entry_signals_count, 1);
+    assert_eq!(result.trades.len(), 1);
+    assert_eq!(
+        result.trades[0].entry_timestamp,
+        BASE_TIMESTAMP + expected[0] as i64 * 60_000
+    );
+}
+
+#[tokio::test]
+async fn test_death_cross_fires_only_on_crossing_bar() {
+    let candles = create_inverted_v_candles();
+    let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+
+    // Un cruce por debajo de sma_3 sobre sma_10 equivale a sma_10 cruzando por encima de sma_3
+    let expected = reference_crosses_above(&reference_sma(&closes, 10), &reference_sma(&closes, 3));
+    assert_eq!(expected.len(), 1, "la serie de prueba debe tener un único death cross");
+
+    let strategy = StrategyBuilder::new("DeathCross".to_string(), TimeFrame::M1)
+        .add_entry_condition(ConditionBuilder::crosses_below("sma", vec![3.0], "sma", vec![10.0]))
+        .build();
+
+    let result = run_single(strategy, candles).await;
+
+    // Con la semántica anterior (sma_3 < sma_10) habría señal en todas las velas tras el cruce
+    assert_eq!(result.metrics.entry_signals_count, 1);
+    assert_eq!(result.trades.len(), 1);
+    assert_eq!(
+        result.trades[0].entry_timestamp,
+        BASE_TIMESTAMP + expected[0] as i64 * 60_000
+    );
+}
+
+#[tokio::test]
+async fn test_death_cross_absent_on_falling_series() {
+    let candles = create_v_shaped_candles();
+
+    let strategy = StrategyBuilder::new("DeathCross".to_string(), TimeFrame::M1)
+        .add_entry_condition(ConditionBuilder::crosses_below("sma", vec![3.0], "sma", vec![10.0]))
+        .build();
+
+    let result = run_single(strategy, candles).await;
+
+    // La serie baja desde el inicio: sma_3 ya está por debajo al terminar el warmup
+    assert_eq!(result.metrics.entry_signals_count, 0);
+    assert!(result.trades.is_empty());
+}
+
+#[tokio::test]
+async fn test_crosses_value_fires_only_on_crossing_bar() {
+    let candles = create_v_shaped_candles();
+    let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+
+    let sma = reference_sma(&closes, 3);
+    let threshold = vec![Some(160.0); sma.len()];
+    let expected = reference_crosses_above(&sma, &threshold);
+    assert_eq!(expected.len(), 1);
+
+    let strategy = StrategyBuilder::new("CrossValue".to_string(), TimeFrame::M1)
+        .add_entry_condition(ConditionBuilder::crosses_above_value("sma", vec![3.0], 160.0))
+        .build();
+
+    let result = run_single(strategy, candles).await;
+
+    assert_eq!(result.metrics.entry_signals_count, 1);
+    assert_eq!(
+        result.trades[0].entry_timestamp,
+        BASE_TIMESTAMP + expected[0] as i64 * 60_000
+    );
+}
+
+#[tokio::test]
+async fn test_price_crosses_above_indicator_fires_only_on_crossing_bar() {
+    let candles = create_v_shaped_candles();
+    let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+
+    let price: Vec<Option<f64>> = closes.iter().map(|c| Some(*c)).collect();
+    let expected = reference_crosses_above(&price, &reference_sma(&closes, 5));
+    assert_eq!(expected.len(), 1);
+
+    let strategy = StrategyBuilder::new("PriceCross".to_string(), TimeFrame::M1)
+        .add_entry_condition(ConditionBuilder::price_crosses_above("sma", vec![5.0]))
+        .build();
+
+    let result = run_single(strategy, candles).await;
+
+    assert_eq!(result.metrics.entry_signals_count, 1);
+    assert_eq!(
+        result.trades[0].entry_timestamp,
+        BASE_TIMESTAMP + expected[0] as i64 * 60_000
+    );
+}
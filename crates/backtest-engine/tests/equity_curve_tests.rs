@@ -0,0 +1,150 @@
+//! Tests de integración de la curva de equity mark-to-market del motor masivo
+
+use darwinx_backtest_engine::*;
+use darwinx_backtest_engine::metrics::{calculate_mark_to_market_equity, downsample_equity_curve};
+use darwinx_core::{Candle, TimeFrame};
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+const HOUR_MS: i64 = 3_600_000;
+
+fn candles_from_closes(closes: &[f64]) -> Vec<Candle> {
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| Candle::new(BASE_TIMESTAMP + i as i64 * HOUR_MS, close, close + 0.5, close - 0.5, close, 1000.0))
+        .collect()
+}
+
+fn no_cost_config() -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.5)
+}
+
+fn trade(entry: usize, exit: usize, entry_price: f64, exit_price: f64, is_long: bool) -> Trade {
+    let size = 10.0;
+    let price_move = (exit_price - entry_price) * size;
+    Trade {
+        entry_timestamp: BASE_TIMESTAMP + entry as i64 * HOUR_MS,
+        exit_timestamp: BASE_TIMESTAMP + exit as i64 * HOUR_MS,
+        entry_price,
+        exit_price,
+        size,
+        is_long,
+        pnl: if is_long { price_move } else { -price_move },
+        commission: 0.0,
+        slippage: 0.0,
+        borrow_cost: 0.0,
+        exit_reason: ExitReason::Signal,
+        position_id: 0,
+        entry_commission: 0.0,
+        funding: 0.0,
+    }
+}
+
+#[test]
+fn test_mark_to_market_includes_unrealized_pnl() {
+    let closes = [100.0, 100.0, 80.0, 120.0, 120.0, 90.0, 100.0];
+    let timestamps: Vec<i64> = (0..closes.len()).map(|i| BASE_TIMESTAMP + i as i64 * HOUR_MS).collect();
+    let trades = [trade(1, 3, 100.0, 120.0, true), trade(4, 6, 120.0, 100.0, false)];
+
+    let curve = calculate_mark_to_market_equity(&trades, &timestamps, &closes, &no_cost_config());
+    let balances: Vec<f64> = curve.iter().map(|p| p.balance).collect();
+
+    assert_eq!(balances, vec![10000.0, 10000.0, 9800.0, 10200.0, 10200.0, 10500.0, 10400.0]);
+    // El drawdown dentro del primer trade es visible aunque cierre ganando
+    assert!((curve[2].drawdown - 0.02).abs() < 1e-12);
+    assert_eq!(curve[3].drawdown, 0.0);
+}
+
+#[test]
+fn test_mark_to_market_deducts_entry_commission() {
+    let config = BacktestConfig::with_position_size(10000.0, 0.001, 0.0, 1, 0.02, None, None, 0.5);
+    let timestamps = [BASE_TIMESTAMP, BASE_TIMESTAMP + HOUR_MS];
+    let mut long = trade(0, 1, 100.0, 100.0, true);
+    long.pnl = -1.0; // comisión de salida
+    long.commission = 1.0;
+    long.entry_commission = 1.0;
+
+    let curve = calculate_mark_to_market_equity(&[long], &timestamps, &[100.0, 100.0], &config);
+
+    assert!((curve[0].balance - 9999.0).abs() < 1e-9);
+    assert!((curve[1].balance - 9998.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_massive_metrics_use_intratrade_drawdown() {
+    // Entra al cruzar 100 hacia arriba, cae fuerte sin cruzar de vuelta y recupera
+    let mut closes = vec![95.0, 96.0, 97.0, 98.0, 99.0, 101.0];
+    closes.extend([103.0, 104.0, 150.0, 160.0, 104.0, 150.0, 200.0]);
+    let candles = candles_from_closes(&closes);
+    let strategy = StrategyBuilder::new("AboveHundred".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above_value("sma", vec![1.0], 100.0))
+        .build();
+
+    let result = PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(vec![strategy], candles.clone(), &no_cost_config())
+        .await
+        .unwrap()
+        .remove(0);
+
+    assert_eq!(result.trades.len(), 1);
+    assert_eq!(result.equity_curve.len(), candles.len());
+    // Un único trade ganador: la curva por trades no tendría drawdown
+    let size = result.trades[0].size;
+    let peak = 10000.0 + size * (160.0 - 101.0);
+    let trough = 10000.0 + size * (104.0 - 101.0);
+    assert!((result.metrics.max_drawdown - (peak - trough) / peak).abs() < 1e-9);
+    assert!((result.metrics.max_drawdown - result.equity_curve.iter().map(|p| p.drawdown).fold(0.0, f64::max)).abs() < 1e-12);
+    assert_eq!(result.metrics.sharpe_observations, candles.len() - 1);
+}
+
+#[test]
+fn test_downsample_keeps_extremes_and_bounds() {
+    let closes: Vec<f64> = (0..1000).map(|i| 100.0 + (i as f64 * 0.05).sin() * 10.0).collect();
+    let timestamps: Vec<i64> = (0..closes.len()).map(|i| BASE_TIMESTAMP + i as i64 * HOUR_MS).collect();
+    let trades = [trade(0, 999, closes[0], closes[999], true)];
+    let curve = calculate_mark_to_market_equity(&trades, &timestamps, &closes, &no_cost_config());
+
+    let sampled = downsample_equity_curve(&curve, 100);
+
+    assert!(sampled.len() <= 100);
+    assert_eq!(sampled.first().unwrap().timestamp, curve[0].timestamp);
+    assert_eq!(sampled.last().unwrap().timestamp, curve[999].timestamp);
+    assert!(sampled.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+    let min = |c: &[EquityPoint]| c.iter().map(|p| p.balance).fold(f64::INFINITY, f64::min);
+    let max = |c: &[EquityPoint]| c.iter().map(|p| p.balance).fold(f64::NEG_INFINITY, f64::max);
+    assert_eq!(min(&sampled), min(&curve));
+    assert_eq!(max(&sampled), max(&curve));
+
+    // Curvas cortas no se modifican
+    assert_eq!(downsample_equity_curve(&curve[..50], 100).len(), 50);
+}
+
+#[test]
+fn test_parallel_equity_curve_output_options() {
+    let closes: Vec<f64> = (0..300).map(|i| 100.0 + (i as f64 * 0.1).sin() * 10.0).collect();
+    let candles = candles_from_closes(&closes);
+    let strategy = StrategyBuilder::new("Cross".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above("sma", vec![5.0], "sma", vec![20.0]))
+        .add_exit_condition(ConditionBuilder::crosses_below("sma", vec![5.0], "sma", vec![20.0]))
+        .build();
+    let engine = PolarsVectorizedBacktestEngine::new();
+    let config = no_cost_config();
+    let run = |options: ParallelConfig| {
+        engine
+            .run_massive_backtest_parallel(vec![strategy.clone()], &candles, &config, options)
+            .unwrap()
+            .remove(0)
+    };
+
+    let full = run(ParallelConfig::default());
+    let downsampled = run(ParallelConfig::default().with_equity_curve_points(40));
+    let skipped = run(ParallelConfig::default().without_equity_curve());
+
+    assert_eq!(full.equity_curve.len(), candles.len());
+    assert!(downsampled.equity_curve.len() <= 40 && !downsampled.equity_curve.is_empty());
+    assert!(skipped.equity_curve.is_empty());
+    // Las métricas no dependen de lo que se conserve de la curva
+    assert_eq!(full.metrics.max_drawdown, skipped.metrics.max_drawdown);
+    assert_eq!(full.metrics.sharpe_ratio, downsampled.metrics.sharpe_ratio);
+}
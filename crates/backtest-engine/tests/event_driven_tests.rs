@@ -0,0 +1,359 @@
+//! Tests de integración para el motor event-driven
+//!
+//! Incluyen el contraste con el motor masivo de Polars sobre el mismo AST.
+
+use std::collections::HashMap;
+use darwinx_backtest_engine::*;
+use darwinx_backtest_engine::event_driven::{
+    BarContext, BarStrategy, CoreStrategyAdapter, EventDrivenConfig, OrderRequest,
+};
+use darwinx_core::{Candle, MarketData, PositionSide, Signal, TimeFrame};
+use darwinx_generator::StrategyAST;
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+const HOUR_MS: i64 = 3_600_000;
+
+/// Genera velas horarias a partir de tramos de 30 velas con pendiente ±2
+fn create_zigzag_candles(start: f64, slopes: &[f64]) -> Vec<Candle> {
+    let mut candles = Vec::new();
+    let mut close = start;
+
+    for slope in slopes {
+        for _ in 0..30 {
+            let timestamp = BASE_TIMESTAMP + candles.len() as i64 * HOUR_MS;
+            candles.push(Candle::new(timestamp, close, close + 1.0, close - 1.0, close, 1000.0));
+            close += slope;
+        }
+    }
+
+    candles
+}
+
+/// Velas a partir de tuplas (open, high, low, close)
+fn create_ohlc_candles(bars: &[(f64, f64, f64, f64)]) -> Vec<Candle> {
+    bars.iter()
+        .enumerate()
+        .map(|(i, &(open, high, low, close))| {
+            Candle::new(BASE_TIMESTAMP + i as i64 * HOUR_MS, open, high, low, close, 1000.0)
+        })
+        .collect()
+}
+
+fn provider(candles: Vec<Candle>) -> SingleTimeFrameProvider {
+    SingleTimeFrameProvider::new(candles, TimeFrame::H1)
+}
+
+fn no_cost_config() -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.1)
+}
+
+/// Estrategia que envía órdenes predefinidas en velas concretas
+struct ScriptedStrategy {
+    orders: HashMap<usize, Vec<OrderRequest>>,
+}
+
+impl ScriptedStrategy {
+    fn new(orders: Vec<(usize, OrderRequest)>) -> Self {
+        let mut by_bar: HashMap<usize, Vec<OrderRequest>> = HashMap::new();
+        for (bar, order) in orders {
+            by_bar.entry(bar).or_default().push(order);
+        }
+        Self { orders: by_bar }
+    }
+}
+
+impl BarStrategy for ScriptedStrategy {
+    fn name(&self) -> &str {
+        "Scripted"
+    }
+
+    fn on_bar(&mut self, ctx: &BarContext<'_>) -> Result<Vec<OrderRequest>, BacktestError> {
+        Ok(self.orders.remove(&ctx.index).unwrap_or_default())
+    }
+}
+
+async fn run_both(strategy: StrategyAST, candles: Vec<Candle>, config: &BacktestConfig) -> (BacktestResult, BacktestResult) {
+    let polars = PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(vec![strategy.clone()], candles.clone(), config)
+        .await
+        .unwrap()
+        .remove(0);
+    let event = EventDrivenBacktestEngine::new()
+        .run_ast(strategy, &provider(candles), config)
+        .await
+        .unwrap();
+    (polars, event)
+}
+
+fn assert_same_trades(polars: &BacktestResult, event: &BacktestResult) {
+    assert_eq!(polars.trades.len(), event.trades.len());
+    for (p, e) in polars.trades.iter().zip(&event.trades) {
+        assert_eq!(p.is_long, e.is_long);
+        assert_eq!(p.entry_timestamp, e.entry_timestamp);
+        assert_eq!(p.exit_timestamp, e.exit_timestamp);
+        assert_eq!(p.exit_reason, e.exit_reason);
+        assert!((p.entry_price - e.entry_price).abs() < 1e-9);
+        assert!((p.exit_price - e.exit_price).abs() < 1e-9);
+        assert!((p.pnl - e.pnl).abs() < 1e-9);
+    }
+    assert_eq!(polars.metrics.entry_signals_count, event.metrics.entry_signals_count);
+    assert!((polars.metrics.total_return - event.metrics.total_return).abs() < 1e-12);
+    assert!((polars.metadata.final_balance - event.metadata.final_balance).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_cross_check_long_short_strategy_with_polars() {
+    let candles = create_zigzag_candles(200.0, &[-2.0, 2.0, -2.0]);
+    let strategy = StrategyBuilder::new("LongShort".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above("sma", vec![3.0], "sma", vec![10.0]))
+        .add_short_entry_condition(ConditionBuilder::crosses_below("sma", vec![3.0], "sma", vec![10.0]))
+        .build();
+    let config = BacktestConfig::with_position_size(10000.0, 0.001, 0.0005, 1, 0.02, None, None, 0.1);
+
+    let (polars, event) = run_both(strategy, candles.clone(), &config).await;
+
+    assert_eq!(event.trades.len(), 2);
+    assert_same_trades(&polars, &event);
+    assert_eq!(event.equity_curve.len(), candles.len());
+    assert_eq!(event.equity_curve[0].balance, config.initial_balance);
+    let last = event.equity_curve.last().unwrap();
+    assert!(last.balance > config.initial_balance);
+
+    // Ambos motores marcan a mercado igual; solo la última vela difiere porque el
+    // masivo incluye el cierre por fin de datos
+    assert_eq!(polars.equity_curve.len(), event.equity_curve.len());
+    for (p, e) in polars.equity_curve.iter().zip(&event.equity_curve).take(candles.len() - 1) {
+        assert_eq!(p.timestamp, e.timestamp);
+        assert!((p.balance - e.balance).abs() < 1e-6, "{} vs {}", p.balance, e.balance);
+    }
+    assert!((polars.equity_curve.last().unwrap().balance - polars.metadata.final_balance).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn test_cross_check_stop_loss_with_polars() {
+    let candles = create_zigzag_candles(200.0, &[-2.0, 2.0]);
+    let strategy = StrategyBuilder::new("ShortBelowValue".to_string(), TimeFrame::H1)
+        .add_short_entry_condition(ConditionBuilder::crosses_below_value("sma", vec![3.0], 190.0))
+        .build();
+    let config = BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, Some(0.05), None, 0.1);
+
+    let (polars, event) = run_both(strategy, candles, &config).await;
+
+    assert_eq!(event.trades.len(), 1);
+    assert_eq!(event.trades[0].exit_reason, ExitReason::StopLoss);
+    assert_same_trades(&polars, &event);
+}
+
+#[tokio::test]
+async fn test_latency_delays_market_fill_to_next_open() {
+    let candles = create_zigzag_candles(200.0, &[-2.0, 2.0]);
+    let strategy = StrategyBuilder::new("PriceCross".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above_value("sma", vec![3.0], 160.0))
+        .build();
+    let config = no_cost_config();
+
+    let immediate = EventDrivenBacktestEngine::new()
+        .run_ast(strategy.clone(), &provider(candles.clone()), &config)
+        .await
+        .unwrap();
+    let delayed = EventDrivenBacktestEngine::with_config(EventDrivenConfig {
+        latency_bars: 2,
+        ..Default::default()
+    })
+    .run_ast(strategy, &provider(candles.clone()), &config)
+    .await
+    .unwrap();
+
+    let signal_trade = &immediate.trades[0];
+    let delayed_trade = &delayed.trades[0];
+    assert_eq!(delayed_trade.entry_timestamp, signal_trade.entry_timestamp + 2 * HOUR_MS);
+
+    let fill_bar = candles
+        .iter()
+        .find(|c| c.timestamp == delayed_trade.entry_timestamp)
+        .unwrap();
+    assert_eq!(delayed_trade.entry_price, fill_bar.open);
+}
+
+#[tokio::test]
+async fn test_limit_order_fills_at_limit_or_better_on_gap() {
+    let candles = create_ohlc_candles(&[
+        (100.0, 101.0, 99.0, 100.0),
+        (100.0, 101.0, 98.0, 99.0), // toca el límite 98
+        (99.0, 100.0, 98.5, 99.5),
+        (95.0, 96.0, 94.0, 95.0), // gap por debajo del límite 97
+        (95.0, 96.0, 94.0, 95.0),
+    ]);
+    let mut strategy = ScriptedStrategy::new(vec![
+        (0, OrderRequest::limit_open(PositionSide::Long, 98.0)),
+        (1, OrderRequest::market_close()),
+        (2, OrderRequest::limit_open(PositionSide::Long, 97.0)),
+    ]);
+
+    let result = EventDrivenBacktestEngine::new()
+        .run_backtest(&mut strategy, &provider(candles), &no_cost_config())
+        .await
+        .unwrap();
+
+    assert_eq!(result.trades.len(), 2);
+    assert_eq!(result.trades[0].entry_price, 98.0);
+    assert_eq!(result.trades[0].entry_timestamp, BASE_TIMESTAMP + HOUR_MS);
+    assert_eq!(result.trades[1].entry_price, 95.0);
+    assert_eq!(result.trades[1].entry_timestamp, BASE_TIMESTAMP + 3 * HOUR_MS);
+}
+
+#[tokio::test]
+async fn test_stop_order_triggers_on_breakout_and_expires() {
+    let candles = create_ohlc_candles(&[
+        (100.0, 101.0, 99.0, 100.0),
+        (100.0, 101.5, 99.0, 101.0),
+        (101.0, 103.0, 100.5, 102.5), // rompe el stop 102
+        (102.5, 103.0, 102.0, 102.5),
+        (102.5, 103.0, 102.0, 102.5),
+        (102.5, 110.0, 102.0, 109.0), // tocaría el stop 105 pero ya expiró
+    ]);
+    let mut strategy = ScriptedStrategy::new(vec![
+        (0, OrderRequest::stop_open(PositionSide::Long, 102.0)),
+        (2, OrderRequest::market_close()),
+        (2, OrderRequest::stop_open(PositionSide::Long, 105.0)),
+    ]);
+    let engine = EventDrivenBacktestEngine::with_config(EventDrivenConfig {
+        order_ttl_bars: Some(2),
+        ..Default::default()
+    });
+
+    let result = engine
+        .run_backtest(&mut strategy, &provider(candles), &no_cost_config())
+        .await
+        .unwrap();
+
+    assert_eq!(result.trades.len(), 1);
+    assert_eq!(result.trades[0].entry_price, 102.0);
+    assert_eq!(result.trades[0].exit_price, 102.5);
+}
+
+#[tokio::test]
+async fn test_partial_fills_limited_by_volume() {
+    // Tamaño objetivo: 10000 * 0.1 / 100 = 10 unidades; 4 por vela como máximo
+    let candles = create_ohlc_candles(&[(100.0, 100.0, 100.0, 100.0); 6]);
+    let mut strategy = ScriptedStrategy::new(vec![(0, OrderRequest::market_open(PositionSide::Long))]);
+    let engine = EventDrivenBacktestEngine::with_config(EventDrivenConfig {
+        max_volume_participation: Some(0.004),
+        ..Default::default()
+    });
+
+    let result = engine
+        .run_backtest(&mut strategy, &provider(candles), &no_cost_config())
+        .await
+        .unwrap();
+
+    assert_eq!(result.trades.len(), 1);
+    let trade = &result.trades[0];
+    assert!((trade.size - 10.0).abs() < 1e-9);
+    assert_eq!(trade.exit_reason, ExitReason::EndOfData);
+    // Se empezó a ejecutar al cierre de la vela 0 y se completó en la vela 2
+    assert_eq!(trade.entry_timestamp, BASE_TIMESTAMP);
+}
+
+/// Estrategia de `darwinx_core`: compra si el cierre sube, vende si baja
+struct MomentumStrategy;
+
+impl darwinx_core::Strategy for MomentumStrategy {
+    fn name(&self) -> &str {
+        "Momentum"
+    }
+
+    fn evaluate(&mut self, data: &dyn MarketData) -> Signal {
+        let closes = data.close(2);
+        if closes[1] > closes[0] {
+            Signal::Buy { price: closes[1], confidence: 1.0 }
+        } else if closes[1] < closes[0] {
+            Signal::Sell { price: closes[1], confidence: 1.0 }
+        } else {
+            Signal::Hold
+        }
+    }
+
+    fn min_periods(&self) -> usize {
+        2
+    }
+
+    fn reset(&mut self) {}
+}
+
+#[tokio::test]
+async fn test_core_strategy_adapter_reverses_positions() {
+    let candles = create_zigzag_candles(100.0, &[2.0, -2.0]);
+    let mut strategy = CoreStrategyAdapter::new(MomentumStrategy).with_short(true);
+
+    let result = EventDrivenBacktestEngine::new()
+        .run_backtest(&mut strategy, &provider(candles), &no_cost_config())
+        .await
+        .unwrap();
+
+    assert_eq!(result.strategy_name, "Momentum");
+    assert_eq!(result.trades.len(), 2);
+    assert!(result.trades[0].is_long);
+    assert!(!result.trades[1].is_long);
+    assert_eq!(result.metrics.entry_signals_count, 2);
+}
+
+#[tokio::test]
+async fn test_latency_does_not_resubmit_queued_entries() {
+    // Condición de nivel (no cruce): sigue siendo cierta mientras la orden está en cola
+    let candles = create_zigzag_candles(100.0, &[2.0]);
+    let strategy = StrategyBuilder::new("AboveLevel".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::above("sma", vec![3.0], 110.0))
+        .build();
+    let config = no_cost_config();
+
+    let immediate = EventDrivenBacktestEngine::new()
+        .run_ast(strategy.clone(), &provider(candles.clone()), &config)
+        .await
+        .unwrap();
+    let delayed = EventDrivenBacktestEngine::with_config(EventDrivenConfig {
+        latency_bars: 2,
+        ..Default::default()
+    })
+    .run_ast(strategy, &provider(candles), &config)
+    .await
+    .unwrap();
+
+    assert_eq!(immediate.trades.len(), 1);
+    assert_eq!(delayed.trades.len(), 1);
+    // Tamaño de una sola orden: 10000 * 0.1 / precio de entrada
+    let trade = &delayed.trades[0];
+    assert!((trade.size - 1000.0 / trade.entry_price).abs() < 1e-9);
+    assert_eq!(trade.entry_timestamp, immediate.trades[0].entry_timestamp + 2 * HOUR_MS);
+}
+
+#[tokio::test]
+async fn test_equity_includes_entry_commission() {
+    // Tamaño: 10000 * 0.1 / 100 = 10 unidades; comisión 0.1% de 1000 = 1 en entrada y salida
+    let candles = create_ohlc_candles(&[(100.0, 100.0, 100.0, 100.0); 3]);
+    let mut strategy = ScriptedStrategy::new(vec![(0, OrderRequest::market_open(PositionSide::Long))]);
+    let config = BacktestConfig::with_position_size(10000.0, 0.001, 0.0, 1, 0.02, None, None, 0.1);
+
+    let result = EventDrivenBacktestEngine::new()
+        .run_backtest(&mut strategy, &provider(candles), &config)
+        .await
+        .unwrap();
+
+    assert!((result.equity_curve[0].balance - 9999.0).abs() < 1e-9);
+    assert!((result.metadata.final_balance - 9998.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_rejects_unsupported_position_settings() {
+    let candles = create_ohlc_candles(&[(100.0, 100.0, 100.0, 100.0); 3]);
+    let engine = EventDrivenBacktestEngine::new();
+    let multiple = BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 3, 0.02, None, None, 0.1);
+    let leveraged = no_cost_config().with_margin(MarginConfig::isolated(5.0));
+
+    for config in [multiple, leveraged] {
+        let mut strategy = ScriptedStrategy::new(vec![(0, OrderRequest::market_open(PositionSide::Long))]);
+        let result = engine.run_backtest(&mut strategy, &provider(candles.clone()), &config).await;
+        assert!(matches!(result, Err(BacktestError::ConfigError(_))));
+    }
+}
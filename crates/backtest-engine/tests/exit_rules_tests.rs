@@ -0,0 +1,198 @@
+//! Tests de integración de trailing stops, stops por ATR y salidas por tiempo
+//!
+//! Cada regla se comprueba en el motor masivo y en el event-driven, que deben
+//! cerrar el trade en la misma vela, al mismo precio y por la misma razón.
+
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, TimeFrame};
+use darwinx_generator::StrategyAST;
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01 00:00 UTC
+const HOUR_MS: i64 = 3_600_000;
+
+fn candles_from_closes(closes: &[f64]) -> Vec<Candle> {
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| Candle::new(BASE_TIMESTAMP + i as i64 * HOUR_MS, close, close + 0.5, close - 0.5, close, 1000.0))
+        .collect()
+}
+
+/// Entra al cruzar 100 hacia arriba y no tiene salida por señal
+fn above_hundred() -> StrategyAST {
+    StrategyBuilder::new("AboveHundred".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above_value("sma", vec![1.0], 100.0))
+        .build()
+}
+
+fn no_cost_config() -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.5)
+}
+
+async fn run_both(candles: Vec<Candle>, config: &BacktestConfig) -> (BacktestResult, BacktestResult) {
+    let polars = PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(vec![above_hundred()], candles.clone(), config)
+        .await
+        .unwrap()
+        .remove(0);
+    let event = EventDrivenBacktestEngine::new()
+        .run_ast(above_hundred(), &SingleTimeFrameProvider::new(candles, TimeFrame::H1), config)
+        .await
+        .unwrap();
+    (polars, event)
+}
+
+/// Comprueba que ambos motores producen un único trade con la salida esperada
+fn assert_single_exit(polars: &BacktestResult, event: &BacktestResult, exit_bar: usize, exit_price: f64, reason: ExitReason) {
+    for result in [polars, event] {
+        assert_eq!(result.trades.len(), 1, "{:?}", result.trades);
+        let trade = &result.trades[0];
+        assert_eq!(trade.exit_reason, reason);
+        assert_eq!(trade.exit_timestamp, BASE_TIMESTAMP + exit_bar as i64 * HOUR_MS);
+        assert!((trade.exit_price - exit_price).abs() < 1e-9, "exit {} expected {}", trade.exit_price, exit_price);
+    }
+}
+
+#[tokio::test]
+async fn test_trailing_stop_percent_follows_highest_high() {
+    let candles = candles_from_closes(&[95.0, 99.0, 101.0, 110.0, 120.0, 115.0, 110.0, 112.0]);
+    let config = no_cost_config().with_trailing_stop(0.05);
+
+    let (polars, event) = run_both(candles, &config).await;
+
+    // Máximo 120.5 en la vela 4: stop en 114.475, tocado por el mínimo 109.5 de la vela 6
+    assert_single_exit(&polars, &event, 6, 120.5 * 0.95, ExitReason::TrailingStop);
+    assert_eq!(polars.metrics.trailing_stop_exits, 1);
+    assert_eq!(polars.metrics.stop_loss_exits, 0);
+}
+
+#[tokio::test]
+async fn test_break_even_moves_stop_to_entry() {
+    let candles = candles_from_closes(&[95.0, 99.0, 101.0, 108.0, 104.0, 100.0, 99.0]);
+    // El stop loss fijo del 5% quedaría lejos; tras +5% el stop pasa a la entrada
+    let config = no_cost_config().with_break_even(0.05);
+    let config = BacktestConfig { stop_loss_percent: Some(0.05), ..config };
+
+    let (polars, event) = run_both(candles, &config).await;
+
+    assert_single_exit(&polars, &event, 5, 101.0, ExitReason::BreakEven);
+    assert!(polars.trades[0].pnl.abs() < 1e-9);
+    assert_eq!(polars.metrics.break_even_exits, 1);
+}
+
+#[tokio::test]
+async fn test_atr_stops() {
+    let mut closes: Vec<f64> = (90..100).map(f64::from).collect();
+    closes.extend([101.0, 102.0, 103.0, 104.0, 105.0, 106.0, 98.0, 99.0]);
+    let candles = candles_from_closes(&closes);
+    let highs: Vec<f64> = candles.iter().map(|c| c.high).collect();
+    let lows: Vec<f64> = candles.iter().map(|c| c.low).collect();
+    let atr_at = |bar: usize| darwinx_indicators::atr(&highs[..=bar], &lows[..=bar], &closes[..=bar], 3).unwrap();
+
+    // Trailing por ATR: 2 ATRs bajo el cierre de la vela 15, tocado por la caída de la 16
+    let config = no_cost_config().with_exit_atr_period(3).with_atr_trailing_stop(2.0);
+    let (polars, event) = run_both(candles.clone(), &config).await;
+    assert_single_exit(&polars, &event, 16, 106.0 - 2.0 * atr_at(15), ExitReason::TrailingStop);
+
+    // Chandelier: 2 ATRs bajo el máximo de las últimas 5 velas (106.5), más ceñido que el trailing
+    let config = config.with_chandelier_exit(5, 2.0);
+    let (polars, event) = run_both(candles, &config).await;
+    assert_single_exit(&polars, &event, 16, 106.5 - 2.0 * atr_at(15), ExitReason::ChandelierExit);
+    assert_eq!(polars.metrics.chandelier_exits, 1);
+}
+
+#[tokio::test]
+async fn test_max_bars_in_trade() {
+    let candles = candles_from_closes(&[95.0, 99.0, 101.0, 102.0, 103.0, 104.0, 105.0, 106.0]);
+    let config = no_cost_config().with_max_bars_in_trade(3);
+
+    let (polars, event) = run_both(candles, &config).await;
+
+    // Entrada en la vela 2, salida al cierre de la vela 5
+    assert_single_exit(&polars, &event, 5, 104.0, ExitReason::MaxBars);
+    assert_eq!(polars.metrics.max_bars_exits, 1);
+}
+
+#[tokio::test]
+async fn test_session_close_exits_and_blocks_entries() {
+    // Sesión hasta las 05:00 UTC: la vela de las 04:00 es la última
+    let candles = candles_from_closes(&[95.0, 99.0, 101.0, 102.0, 103.0, 104.0, 105.0]);
+    let config = no_cost_config().with_session_close(5 * 60);
+
+    let (polars, event) = run_both(candles, &config).await;
+    assert_single_exit(&polars, &event, 4, 103.0, ExitReason::SessionClose);
+    assert_eq!(polars.metrics.session_close_exits, 1);
+
+    // El cruce en la última vela de la sesión no abre posición
+    let candles = candles_from_closes(&[95.0, 96.0, 97.0, 98.0, 101.0, 102.0, 103.0]);
+    let (polars, event) = run_both(candles, &config).await;
+    assert!(polars.trades.is_empty());
+    assert!(event.trades.is_empty());
+}
+
+#[tokio::test]
+async fn test_synthetic_bars_block_entries() {
+    // El cruce de la vela 2 cae en una vela sintética (hueco rellenado)
+    let candles = candles_from_closes(&[95.0, 99.0, 101.0, 102.0, 98.0, 103.0, 104.0]);
+    let config = no_cost_config().with_synthetic_bars(vec![BASE_TIMESTAMP + 2 * HOUR_MS]);
+    assert!(config.is_synthetic_bar(BASE_TIMESTAMP + 2 * HOUR_MS));
+    assert!(!config.is_synthetic_bar(BASE_TIMESTAMP + 3 * HOUR_MS));
+
+    // Solo entra en el siguiente cruce, sobre una vela real
+    let (polars, event) = run_both(candles, &config).await;
+    for result in [&polars, &event] {
+        assert_eq!(result.trades.len(), 1);
+        assert_eq!(result.trades[0].entry_timestamp, BASE_TIMESTAMP + 5 * HOUR_MS);
+    }
+}
+
+#[test]
+fn test_exit_rules_deserialize_with_defaults() {
+    // Configuraciones guardadas antes de las nuevas reglas
+    let mut value = serde_json::to_value(BacktestConfig::default()).unwrap();
+    let object = value.as_object_mut().unwrap();
+    for field in ["trailing_stop_percent", "chandelier_period", "exit_atr_period", "session_close_minute"] {
+        object.remove(field);
+    }
+
+    let config: BacktestConfig = serde_json::from_value(value).unwrap();
+    assert_eq!(config.trailing_stop_percent, None);
+    assert_eq!(config.chandelier_period, 22);
+    assert_eq!(config.exit_atr_period, 14);
+    assert_eq!(config.session_close_minute, None);
+}
+
+#[test]
+fn test_exit_reason_keeps_legacy_serialization() {
+    assert_eq!(serde_json::to_string(&ExitReason::EndOfData).unwrap(), "\"End of data\"");
+    assert_eq!(serde_json::to_string(&ExitReason::TrailingStop).unwrap(), "\"TrailingStop\"");
+    // Textos que escribían versiones anteriores de los motores
+    for (legacy, reason) in [("\"Strategy signal\"", ExitReason::Signal), ("\"EndOfData\"", ExitReason::EndOfData)] {
+        assert_eq!(serde_json::from_str::<ExitReason>(legacy).unwrap(), reason);
+    }
+    for reason in ExitReason::ALL {
+        assert_eq!(reason.as_str().parse::<ExitReason>().unwrap(), reason);
+    }
+    assert!("Unknown".parse::<ExitReason>().is_err());
+}
+
+#[tokio::test]
+async fn test_exit_reason_breakdown() {
+    // Dos trades: uno cerrado por stop loss y otro al final de los datos
+    let candles = candles_from_closes(&[95.0, 99.0, 101.0, 90.0, 95.0, 99.0, 102.0, 104.0]);
+    let config = BacktestConfig { stop_loss_percent: Some(0.05), ..no_cost_config() };
+
+    let (polars, event) = run_both(candles, &config).await;
+
+    for result in [&polars, &event] {
+        let breakdown = &result.metrics.exit_reasons;
+        let reasons: Vec<ExitReason> = breakdown.iter().map(|s| s.reason).collect();
+        assert_eq!(reasons, vec![ExitReason::StopLoss, ExitReason::EndOfData]);
+        assert_eq!(breakdown[0].trades, 1);
+        assert_eq!(breakdown[0].win_rate, 0.0);
+        assert!((breakdown[0].pnl - result.trades[0].pnl).abs() < 1e-9);
+        assert_eq!(breakdown[1].win_rate, 1.0);
+        assert_eq!(breakdown.iter().map(|s| s.trades).sum::<usize>(), result.metrics.total_trades);
+    }
+}
@@ -0,0 +1,217 @@
+//! Tests de integración de la caché de indicadores compartida del motor masivo
+
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, TimeFrame};
+use darwinx_generator::StrategyAST;
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+const HOUR_MS: i64 = 3_600_000;
+
+fn create_wave_candles(count: usize, start: i64) -> Vec<Candle> {
+    create_scaled_candles(count, start, 1.0)
+}
+
+/// Misma forma de onda con precios escalados (otro símbolo sobre la misma ventana)
+fn create_scaled_candles(count: usize, start: i64, scale: f64) -> Vec<Candle> {
+    (0..count)
+        .map(|i| {
+            let close = (100.0 + (i as f64 * 0.2).sin() * 8.0) * scale;
+            Candle::new(start + i as i64 * HOUR_MS, close, close + 1.0, close - 1.0, close, 1000.0)
+        })
+        .collect()
+}
+
+/// Tres estrategias que comparten rsi(14); sma(20) y sma(50) aparecen una vez cada una
+fn create_strategies() -> Vec<StrategyAST> {
+    vec![
+        StrategyBuilder::new("RsiOnly".to_string(), TimeFrame::H1)
+            .add_entry_condition(ConditionBuilder::below("rsi", vec![14.0], 30.0))
+            .add_exit_condition(ConditionBuilder::above("rsi", vec![14.0], 70.0))
+            .build(),
+        StrategyBuilder::new("RsiSma".to_string(), TimeFrame::H1)
+            .add_entry_condition(ConditionBuilder::below("rsi", vec![14.0], 40.0))
+            .add_exit_condition(ConditionBuilder::below_price("sma", vec![20.0]))
+            .build(),
+        StrategyBuilder::new("RsiSmaSlow".to_string(), TimeFrame::H1)
+            .add_entry_condition(ConditionBuilder::above_price("sma", vec![50.0]))
+            .add_exit_condition(ConditionBuilder::above("rsi", vec![14.0], 60.0))
+            .build(),
+    ]
+}
+
+fn no_risk_config() -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.1)
+}
+
+#[tokio::test]
+async fn test_shared_indicators_computed_once() {
+    let engine = PolarsVectorizedBacktestEngine::new();
+    let candles = create_wave_candles(300, BASE_TIMESTAMP);
+
+    engine
+        .run_massive_backtest(create_strategies(), candles, &no_risk_config())
+        .await
+        .unwrap();
+
+    let stats = engine.indicator_cache_stats();
+    // rsi(14), sma(20) y sma(50) se calculan una vez; rsi(14) se reutiliza dos veces
+    assert_eq!(stats.misses, 3);
+    assert_eq!(stats.hits, 2);
+    assert_eq!(stats.entries, 3);
+    // La memoria estimada incluye los valores y la sobrecarga de cada entrada
+    assert!(stats.memory_bytes > 3 * 300 * std::mem::size_of::<f64>());
+    assert_eq!(stats.evictions, 0);
+}
+
+#[tokio::test]
+async fn test_cache_reused_across_batches_of_same_dataset() {
+    let engine = PolarsVectorizedBacktestEngine::new();
+    let candles = create_wave_candles(300, BASE_TIMESTAMP);
+
+    engine
+        .run_massive_backtest(create_strategies(), candles.clone(), &no_risk_config())
+        .await
+        .unwrap();
+    engine
+        .run_massive_backtest(create_strategies(), candles, &no_risk_config())
+        .await
+        .unwrap();
+
+    let stats = engine.indicator_cache_stats();
+    assert_eq!(stats.misses, 3);
+    assert_eq!(stats.hits, 7);
+}
+
+#[tokio::test]
+async fn test_cache_invalidated_on_new_dataset() {
+    let engine = PolarsVectorizedBacktestEngine::new();
+
+    engine
+        .run_massive_backtest(create_strategies(), create_wave_candles(300, BASE_TIMESTAMP), &no_risk_config())
+        .await
+        .unwrap();
+    engine
+        .run_massive_backtest(
+            create_strategies(),
+            create_wave_candles(300, BASE_TIMESTAMP + 1000 * HOUR_MS),
+            &no_risk_config(),
+        )
+        .await
+        .unwrap();
+
+    // Las series de ambos datasets conviven en la caché sin reutilizarse entre ellos
+    let stats = engine.indicator_cache_stats();
+    assert_eq!(stats.misses, 6);
+    assert_eq!(stats.entries, 6);
+}
+
+#[tokio::test]
+async fn test_cache_invalidated_for_other_symbol_on_same_window() {
+    let engine = PolarsVectorizedBacktestEngine::new();
+    let uncached = PolarsVectorizedBacktestEngine::with_indicator_cache_budget(0);
+    let first_symbol = create_scaled_candles(300, BASE_TIMESTAMP, 1.0);
+    let second_symbol = create_scaled_candles(300, BASE_TIMESTAMP, 3.0);
+
+    engine
+        .run_massive_backtest(create_strategies(), first_symbol, &no_risk_config())
+        .await
+        .unwrap();
+    let cached = engine
+        .run_massive_backtest(create_strategies(), second_symbol.clone(), &no_risk_config())
+        .await
+        .unwrap();
+    let expected = uncached
+        .run_massive_backtest(create_strategies(), second_symbol, &no_risk_config())
+        .await
+        .unwrap();
+
+    // Mismos timestamps pero distintos precios: no se reutilizan las series del primer símbolo
+    assert_eq!(engine.indicator_cache_stats().misses, 6);
+    for (a, b) in cached.iter().zip(&expected) {
+        assert_eq!(a.trades.len(), b.trades.len());
+        assert_eq!(a.metrics.total_return, b.metrics.total_return);
+    }
+}
+
+#[tokio::test]
+async fn test_interleaved_datasets_on_shared_engine() {
+    let engine = PolarsVectorizedBacktestEngine::new();
+    let uncached = PolarsVectorizedBacktestEngine::with_indicator_cache_budget(0);
+    let datasets = [
+        create_scaled_candles(300, BASE_TIMESTAMP, 1.0),
+        create_scaled_candles(300, BASE_TIMESTAMP, 3.0),
+        create_scaled_candles(300, BASE_TIMESTAMP, 1.0),
+    ];
+
+    for candles in datasets {
+        let cached = engine
+            .run_massive_backtest(create_strategies(), candles.clone(), &no_risk_config())
+            .await
+            .unwrap();
+        let expected = uncached
+            .run_massive_backtest(create_strategies(), candles, &no_risk_config())
+            .await
+            .unwrap();
+        for (a, b) in cached.iter().zip(&expected) {
+            assert_eq!(a.trades.len(), b.trades.len());
+            assert_eq!(a.metrics.total_return, b.metrics.total_return);
+        }
+    }
+
+    // Al volver al primer dataset sus series siguen en la caché
+    let stats = engine.indicator_cache_stats();
+    assert_eq!(stats.misses, 6);
+    assert_eq!(stats.hits, 2 + 2 + 5);
+}
+
+#[tokio::test]
+async fn test_cached_results_match_uncached() {
+    let candles = create_wave_candles(300, BASE_TIMESTAMP);
+    let cached = PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(create_strategies(), candles.clone(), &no_risk_config())
+        .await
+        .unwrap();
+    let uncached_engine = PolarsVectorizedBacktestEngine::with_indicator_cache_budget(0);
+    let uncached = uncached_engine
+        .run_massive_backtest(create_strategies(), candles, &no_risk_config())
+        .await
+        .unwrap();
+
+    assert_eq!(uncached_engine.indicator_cache_stats().entries, 0);
+    assert_eq!(uncached_engine.indicator_cache_stats().hits, 0);
+    for (a, b) in cached.iter().zip(&uncached) {
+        assert_eq!(a.strategy_name, b.strategy_name);
+        assert_eq!(a.trades.len(), b.trades.len());
+        assert_eq!(a.metrics.total_return, b.metrics.total_return);
+    }
+}
+
+#[tokio::test]
+async fn test_memory_budget_evicts_least_recently_used() {
+    // Presupuesto para dos series de 300 velas (más la sobrecarga de cada entrada), no tres
+    let budget = 2 * 300 * std::mem::size_of::<f64>() + 400;
+    let engine = PolarsVectorizedBacktestEngine::with_indicator_cache_budget(budget);
+    let candles = create_wave_candles(300, BASE_TIMESTAMP);
+
+    engine
+        .run_massive_backtest(create_strategies(), candles, &no_risk_config())
+        .await
+        .unwrap();
+
+    let stats = engine.indicator_cache_stats();
+    assert_eq!(stats.entries, 2);
+    assert!(stats.memory_bytes <= budget);
+    // sma(50) desaloja a rsi(14) (usado antes que sma(20)) y al volver a
+    // calcular rsi(14) se desaloja sma(20)
+    assert_eq!(stats.hits, 1);
+    assert_eq!(stats.misses, 4);
+    assert_eq!(stats.evictions, 2);
+}
+
+#[test]
+fn test_cache_stats_hit_rate() {
+    let stats = IndicatorCacheStats { hits: 3, misses: 1, ..Default::default() };
+    assert_eq!(stats.hit_rate(), 0.75);
+    assert_eq!(IndicatorCacheStats::default().hit_rate(), 0.0);
+}
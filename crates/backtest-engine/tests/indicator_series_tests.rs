@@ -0,0 +1,53 @@
+//! Tests de integración: el motor masivo usa las series incrementales
+//! de `darwinx_indicators` (EMA con estado arrastrado, no re-sembrada por ventana)
+
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, TimeFrame};
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+use darwinx_indicators::streaming::PriceIndicator;
+use darwinx_indicators::trend::ema::Ema;
+use darwinx_indicators::trend::sma::Sma;
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+const HOUR_MS: i64 = 3_600_000;
+
+/// Serie oscilante con varios cruces de medias
+fn create_wave_candles() -> Vec<Candle> {
+    (0..200)
+        .map(|i| {
+            let close = 100.0 + (i as f64 * 0.15).sin() * 10.0 + (i as f64 * 0.05).cos() * 3.0;
+            Candle::new(BASE_TIMESTAMP + i as i64 * HOUR_MS, close, close + 1.0, close - 1.0, close, 1000.0)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_ema_crossover_matches_streaming_reference() {
+    let candles = create_wave_candles();
+    let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+
+    // Referencia: primer cruce de EMA(12) por encima de SMA(30)
+    let ema = Ema::new(12).compute_series(&closes);
+    let sma = Sma::new(30).compute_series(&closes);
+    let first_cross = (1..closes.len())
+        .find(|&i| {
+            let valid = [ema[i - 1], sma[i - 1], ema[i], sma[i]].iter().all(|v| !v.is_nan());
+            valid && ema[i - 1] <= sma[i - 1] && ema[i] > sma[i]
+        })
+        .expect("la serie de prueba debe tener un cruce");
+
+    let strategy = StrategyBuilder::new("EmaCross".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above("ema", vec![12.0], "sma", vec![30.0]))
+        .add_exit_condition(ConditionBuilder::crosses_below("ema", vec![12.0], "sma", vec![30.0]))
+        .build();
+    let config = BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.1);
+
+    let result = PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(vec![strategy], candles.clone(), &config)
+        .await
+        .unwrap()
+        .remove(0);
+
+    assert!(!result.trades.is_empty());
+    assert_eq!(result.trades[0].entry_timestamp, candles[first_cross].timestamp);
+}
@@ -0,0 +1,147 @@
+//! Tests de integración de apalancamiento, margen y liquidaciones del motor masivo
+
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, PositionSide, TimeFrame};
+use darwinx_generator::StrategyAST;
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+const HOUR_MS: i64 = 3_600_000;
+
+fn candles_from_closes(closes: &[f64]) -> Vec<Candle> {
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| Candle::new(BASE_TIMESTAMP + i as i64 * HOUR_MS, close, close + 0.5, close - 0.5, close, 1000.0))
+        .collect()
+}
+
+/// Entra cuando el precio cruza 100 hacia arriba y no tiene salida por señal
+fn above_hundred() -> StrategyAST {
+    StrategyBuilder::new("AboveHundred".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above_value("sma", vec![1.0], 100.0))
+        .build()
+}
+
+fn no_cost_config(position_size_percent: f64) -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, position_size_percent)
+}
+
+async fn run(closes: &[f64], config: &BacktestConfig) -> BacktestResult {
+    PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(vec![above_hundred()], candles_from_closes(closes), config)
+        .await
+        .unwrap()
+        .remove(0)
+}
+
+#[test]
+fn test_liquidation_prices() {
+    let isolated = MarginConfig::isolated(10.0);
+    let long = isolated.isolated_liquidation_price(PositionSide::Long, 100.0);
+    let short = isolated.isolated_liquidation_price(PositionSide::Short, 100.0);
+    assert!((long - 90.0 / 0.995).abs() < 1e-9);
+    assert!((short - 110.0 / 1.005).abs() < 1e-9);
+    // En el precio de liquidación el margen más el P&L iguala el mantenimiento
+    assert!((10.0 + (long - 100.0) - 0.005 * long).abs() < 1e-9);
+
+    // Sin apalancamiento un largo aislado no puede liquidarse
+    assert_eq!(MarginConfig::isolated(1.0).isolated_liquidation_price(PositionSide::Long, 100.0), 0.0);
+
+    let cross = MarginConfig::cross(10.0);
+    assert_eq!(cross.cross_liquidation_price(PositionSide::Long, 10000.0, 50.0, 5000.0), None);
+    let price = cross.cross_liquidation_price(PositionSide::Long, 10000.0, 300.0, 30000.0).unwrap();
+    assert!((10000.0 + (price - 100.0) * 300.0 - 0.005 * price * 300.0).abs() < 1e-6);
+    let price = cross.cross_liquidation_price(PositionSide::Short, 1000.0, 100.0, 10000.0).unwrap();
+    assert!((1000.0 + (100.0 - price) * 100.0 - 0.005 * price * 100.0).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn test_isolated_liquidation_exit_and_fee() {
+    let margin = MarginConfig::isolated(10.0).with_liquidation_fee_rate(0.01);
+    let config = no_cost_config(0.5).with_margin(margin);
+    let result = run(&[95.0, 101.0, 101.0, 95.0, 80.0, 80.0], &config).await;
+
+    assert_eq!(result.trades.len(), 1);
+    let trade = &result.trades[0];
+    let liquidation_price = 101.0 * 0.9 / 0.995;
+    assert_eq!(trade.exit_reason, ExitReason::Liquidation);
+    assert_eq!(trade.exit_timestamp, BASE_TIMESTAMP + 4 * HOUR_MS);
+    assert!((trade.exit_price - liquidation_price).abs() < 1e-9);
+    assert!((trade.commission - 0.01 * liquidation_price * trade.size).abs() < 1e-9);
+    assert!((trade.pnl - ((liquidation_price - 101.0) * trade.size - trade.commission)).abs() < 1e-9);
+    assert_eq!(result.metrics.liquidation_exits, 1);
+
+    // Margen de 500 sobre el equity al cierre de las velas 1 a 3
+    let size = 5000.0 / 101.0;
+    let usages = [0.05, 0.05, 500.0 / (10000.0 - 6.0 * size)];
+    assert!((result.metrics.max_margin_usage - usages[2]).abs() < 1e-12);
+    assert!((result.metrics.average_margin_usage - usages.iter().sum::<f64>() / 3.0).abs() < 1e-12);
+    assert_eq!(result.metrics.min_liquidation_distance, Some(0.0));
+}
+
+#[tokio::test]
+async fn test_cross_margin_uses_account_balance() {
+    let closes = [95.0, 101.0, 101.0, 95.0, 80.0, 60.0];
+
+    // Con el mismo apalancamiento, el margen aislado se liquida y el cruzado no
+    let isolated = run(&closes, &no_cost_config(0.5).with_margin(MarginConfig::isolated(10.0))).await;
+    assert_eq!(isolated.trades[0].exit_reason, ExitReason::Liquidation);
+    let cross = run(&closes, &no_cost_config(0.5).with_margin(MarginConfig::cross(10.0))).await;
+    assert_eq!(cross.trades[0].exit_reason, ExitReason::EndOfData);
+    assert!(cross.metrics.min_liquidation_distance.is_none());
+
+    // Una exposición de 3x el balance sí se liquida en margen cruzado
+    let cross = run(&closes, &no_cost_config(3.0).with_margin(MarginConfig::cross(10.0))).await;
+    let trade = &cross.trades[0];
+    let expected = MarginConfig::cross(10.0)
+        .cross_liquidation_price(PositionSide::Long, 10000.0, trade.size, 30000.0)
+        .unwrap();
+    assert_eq!(trade.exit_reason, ExitReason::Liquidation);
+    assert_eq!(trade.exit_timestamp, BASE_TIMESTAMP + 5 * HOUR_MS);
+    assert!((trade.exit_price - expected).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_leverage_allows_larger_positions() {
+    let closes = [95.0, 101.0, 102.0, 103.0];
+
+    let unlevered = run(&closes, &no_cost_config(3.0)).await;
+    assert!(unlevered.trades.is_empty());
+
+    let levered = run(&closes, &no_cost_config(3.0).with_margin(MarginConfig::isolated(5.0))).await;
+    assert_eq!(levered.trades.len(), 1);
+    assert!((levered.trades[0].size * levered.trades[0].entry_price - 30000.0).abs() < 1e-9);
+    assert!((levered.metrics.max_margin_usage - 6000.0 / 10000.0).abs() < 1e-12);
+}
+
+#[tokio::test]
+async fn test_stop_ahead_of_liquidation_exits_first() {
+    let closes = [95.0, 101.0, 101.0, 80.0];
+    let margin = MarginConfig::isolated(10.0);
+
+    let mut config = no_cost_config(0.5).with_margin(margin);
+    config.stop_loss_percent = Some(0.05);
+    let result = run(&closes, &config).await;
+    assert_eq!(result.trades[0].exit_reason, ExitReason::StopLoss);
+    assert!((result.trades[0].exit_price - 101.0 * 0.95).abs() < 1e-9);
+
+    // Un stop más allá del precio de liquidación no llega a ejecutarse
+    config.stop_loss_percent = Some(0.15);
+    let result = run(&closes, &config).await;
+    assert_eq!(result.trades[0].exit_reason, ExitReason::Liquidation);
+}
+
+#[test]
+fn test_margin_config_serde_defaults() {
+    let mut value = serde_json::to_value(BacktestConfig::default()).unwrap();
+    value.as_object_mut().unwrap().remove("margin");
+    let config: BacktestConfig = serde_json::from_value(value).unwrap();
+    assert!(config.margin.is_none());
+    assert_eq!(config.initial_margin(1000.0), 1000.0);
+
+    let margin: MarginConfig = serde_json::from_str(r#"{"leverage":3.0,"mode":"cross"}"#).unwrap();
+    assert_eq!(margin, MarginConfig::cross(3.0));
+    assert_eq!(margin.maintenance_margin_rate, 0.005);
+    assert!((BacktestConfig::default().with_margin(margin).initial_margin(900.0) - 300.0).abs() < 1e-12);
+}
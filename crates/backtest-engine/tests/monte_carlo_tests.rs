@@ -0,0 +1,134 @@
+//! Tests de integración del análisis Monte Carlo
+
+use darwinx_backtest_engine::*;
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+const HOUR_MS: i64 = 3_600_000;
+
+fn trade(index: usize, pnl: f64) -> Trade {
+    Trade {
+        entry_timestamp: BASE_TIMESTAMP + index as i64 * 2 * HOUR_MS,
+        exit_timestamp: BASE_TIMESTAMP + (index as i64 * 2 + 1) * HOUR_MS,
+        entry_price: 100.0,
+        exit_price: 100.0 + pnl,
+        size: 1.0,
+        is_long: true,
+        pnl,
+        commission: 0.0,
+        slippage: 0.0,
+        borrow_cost: 0.0,
+        exit_reason: ExitReason::Signal,
+        position_id: 0,
+        entry_commission: 0.0,
+        funding: 0.0,
+    }
+}
+
+fn result_from_pnls(pnls: &[f64]) -> BacktestResult {
+    let config = BacktestConfig::with_position_size(1000.0, 0.0, 0.0, 1, 0.02, None, None, 0.5);
+    let trades: Vec<Trade> = pnls.iter().enumerate().map(|(i, pnl)| trade(i, *pnl)).collect();
+    BacktestResult {
+        strategy_name: "MC".to_string(),
+        metrics: BacktestMetrics::default(),
+        equity_curve: Vec::new(),
+        metadata: BacktestMetadata {
+            start_date: BASE_TIMESTAMP,
+            end_date: trades.last().map(|t| t.exit_timestamp).unwrap_or(BASE_TIMESTAMP),
+            total_candles: pnls.len() * 2,
+            initial_balance: config.initial_balance,
+            final_balance: config.initial_balance + pnls.iter().sum::<f64>(),
+            config,
+        },
+        trades,
+    }
+}
+
+fn mixed_pnls() -> Vec<f64> {
+    (0..60).map(|i| if i % 3 == 0 { -40.0 } else { 30.0 }).collect()
+}
+
+#[test]
+fn test_shuffle_preserves_final_balance() {
+    let result = result_from_pnls(&mixed_pnls());
+    let config = MonteCarloConfig::new(200)
+        .with_seed(7)
+        .with_methods(vec![MonteCarloMethod::Shuffle]);
+
+    let report = run_monte_carlo(&result, &config).unwrap();
+    let shuffle = report.method(MonteCarloMethod::Shuffle).unwrap();
+
+    // Permutar no cambia el P&L total, solo el camino
+    assert!((shuffle.final_balance.lower - result.metadata.final_balance).abs() < 1e-6);
+    assert!((shuffle.final_balance.upper - result.metadata.final_balance).abs() < 1e-6);
+    assert!(shuffle.max_drawdown.lower <= shuffle.max_drawdown.upper);
+    assert!(shuffle.max_drawdown.upper > 0.0);
+    assert_eq!(shuffle.iterations, 200);
+}
+
+#[test]
+fn test_seed_makes_results_reproducible() {
+    let result = result_from_pnls(&mixed_pnls());
+    let config = MonteCarloConfig::new(300).with_seed(42);
+
+    let first = run_monte_carlo(&result, &config).unwrap();
+    let second = run_monte_carlo(&result, &config).unwrap();
+
+    assert_eq!(first.methods.len(), MonteCarloMethod::ALL.len());
+    assert_eq!(first.combined.iterations, 300 * MonteCarloMethod::ALL.len());
+    for (a, b) in first.methods.iter().zip(&second.methods) {
+        assert_eq!(a.summary.final_balance, b.summary.final_balance);
+        assert_eq!(a.summary.sharpe_ratio, b.summary.sharpe_ratio);
+        assert_eq!(a.summary.probability_of_ruin, b.summary.probability_of_ruin);
+    }
+}
+
+#[test]
+fn test_bootstrap_and_skip_spread_final_balance() {
+    let result = result_from_pnls(&mixed_pnls());
+    let config = MonteCarloConfig::new(500).with_seed(1);
+    let report = run_monte_carlo(&result, &config).unwrap();
+
+    let bootstrap = report.method(MonteCarloMethod::Bootstrap).unwrap();
+    assert!(bootstrap.final_balance.lower < result.metadata.final_balance);
+    assert!(bootstrap.final_balance.upper > result.metadata.final_balance);
+
+    let skip = report.method(MonteCarloMethod::SkipTrades).unwrap();
+    assert!(skip.final_balance.lower < skip.final_balance.upper);
+}
+
+#[test]
+fn test_cost_perturbation_only_reduces_balance() {
+    let result = result_from_pnls(&mixed_pnls());
+    let config = MonteCarloConfig::new(200)
+        .with_seed(3)
+        .with_methods(vec![MonteCarloMethod::CostPerturbation]);
+    let report = run_monte_carlo(&result, &config).unwrap();
+
+    let costs = report.method(MonteCarloMethod::CostPerturbation).unwrap();
+    assert!(costs.final_balance.upper <= result.metadata.final_balance);
+    assert!(costs.final_balance.lower < costs.final_balance.upper);
+}
+
+#[test]
+fn test_probability_of_ruin() {
+    // Estrategia perdedora: siempre arruina con umbral del 20%
+    let losing = result_from_pnls(&[-50.0; 10]);
+    let config = MonteCarloConfig::new(100).with_seed(5).with_ruin_threshold(0.2);
+    let report = run_monte_carlo(&losing, &config).unwrap();
+    assert_eq!(report.method(MonteCarloMethod::Shuffle).unwrap().probability_of_ruin, 1.0);
+
+    // Estrategia que solo gana: nunca tiene drawdown
+    let winning = result_from_pnls(&[10.0; 10]);
+    let report = run_monte_carlo(&winning, &config).unwrap();
+    assert_eq!(report.combined.probability_of_ruin, 0.0);
+    assert_eq!(report.combined.max_drawdown.upper, 0.0);
+}
+
+#[test]
+fn test_invalid_monte_carlo_input() {
+    let result = result_from_pnls(&mixed_pnls());
+    assert!(run_monte_carlo(&result, &MonteCarloConfig::new(0)).is_err());
+    assert!(run_monte_carlo(&result, &MonteCarloConfig::new(10).with_methods(Vec::new())).is_err());
+    assert!(run_monte_carlo(&result, &MonteCarloConfig::new(10).with_ruin_threshold(0.0)).is_err());
+    assert!(run_monte_carlo(&result_from_pnls(&[]), &MonteCarloConfig::new(10)).is_err());
+}
@@ -0,0 +1,166 @@
+//! Tests de integración de las condiciones en timeframes superiores
+//!
+//! Los valores de un timeframe superior solo deben ser visibles una vez cerrada
+//! la barra correspondiente (sin look-ahead).
+
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, TimeFrame};
+use darwinx_generator::{ConditionTimeframe, StrategyAST};
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01 (múltiplo de 4h)
+const HOUR_MS: i64 = 3_600_000;
+
+/// Velas horarias en 100 con un escalón a 200 entre las velas 13 y 24
+fn create_step_candles() -> Vec<Candle> {
+    (0..40)
+        .map(|i| {
+            let close = if (13..25).contains(&i) { 200.0 } else { 100.0 };
+            Candle::new(BASE_TIMESTAMP + i as i64 * HOUR_MS, close, close + 1.0, close - 1.0, close, 1000.0)
+        })
+        .collect()
+}
+
+/// Genera velas horarias a partir de tramos de 30 velas con pendiente ±2
+fn create_zigzag_candles(start: f64, slopes: &[f64]) -> Vec<Candle> {
+    let mut candles = Vec::new();
+    let mut close = start;
+
+    for slope in slopes {
+        for _ in 0..30 {
+            let timestamp = BASE_TIMESTAMP + candles.len() as i64 * HOUR_MS;
+            candles.push(Candle::new(timestamp, close, close + 1.0, close - 1.0, close, 1000.0));
+            close += slope;
+        }
+    }
+
+    candles
+}
+
+fn no_cost_config() -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.1)
+}
+
+/// Estrategia que entra con el cierre por encima de 150 y sale por debajo
+fn step_strategy(timeframe: Option<ConditionTimeframe>) -> StrategyAST {
+    let with_tf = |condition: darwinx_generator::Condition| match timeframe {
+        Some(tf) => condition.with_timeframe(tf),
+        None => condition,
+    };
+    StrategyBuilder::new("Step".to_string(), TimeFrame::H1)
+        .add_entry_condition(with_tf(ConditionBuilder::above("sma", vec![1.0], 150.0)))
+        .add_exit_condition(with_tf(ConditionBuilder::below("sma", vec![1.0], 150.0)))
+        .build()
+}
+
+async fn run_polars(strategies: Vec<StrategyAST>, candles: Vec<Candle>) -> Vec<BacktestResult> {
+    PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(strategies, candles, &no_cost_config())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_higher_timeframe_values_visible_only_after_bar_close() {
+    let results = run_polars(
+        vec![step_strategy(None), step_strategy(Some(ConditionTimeframe::Relative(4)))],
+        create_step_candles(),
+    )
+    .await;
+    let (base, higher) = (&results[0], &results[1]);
+
+    assert_eq!(base.trades.len(), 1);
+    assert_eq!(higher.trades.len(), 1);
+
+    // La barra de 4h que contiene el escalón (velas 12-15) cierra con la vela 15 y la
+    // que contiene la bajada (velas 24-27) con la vela 27: ambas señales llegan 2h tarde.
+    // Antes de la primera barra cerrada los valores son NaN y no generan señal.
+    let (base_trade, higher_trade) = (&base.trades[0], &higher.trades[0]);
+    assert_eq!(higher_trade.entry_timestamp - base_trade.entry_timestamp, 2 * HOUR_MS);
+    assert_eq!(higher_trade.exit_timestamp - base_trade.exit_timestamp, 2 * HOUR_MS);
+}
+
+#[tokio::test]
+async fn test_absolute_and_relative_timeframes_are_equivalent() {
+    let results = run_polars(
+        vec![
+            step_strategy(Some(ConditionTimeframe::Relative(4))),
+            step_strategy(Some(ConditionTimeframe::Absolute(TimeFrame::H4))),
+            step_strategy(None),
+            // Un timeframe que no supera el de las velas se evalúa en la serie principal
+            step_strategy(Some(ConditionTimeframe::Absolute(TimeFrame::H1))),
+        ],
+        create_step_candles(),
+    )
+    .await;
+
+    for (a, b) in [(&results[0], &results[1]), (&results[2], &results[3])] {
+        assert_eq!(a.trades.len(), b.trades.len());
+        for (x, y) in a.trades.iter().zip(&b.trades) {
+            assert_eq!(x.entry_timestamp, y.entry_timestamp);
+            assert_eq!(x.exit_timestamp, y.exit_timestamp);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_event_driven_interpreter_matches_polars_on_higher_timeframes() {
+    let candles = create_zigzag_candles(200.0, &[-2.0, 2.0, -2.0, 2.0]);
+    let strategy = StrategyBuilder::new("MultiTimeframe".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above("sma", vec![3.0], "sma", vec![10.0]))
+        .add_entry_condition(
+            ConditionBuilder::below_price("sma", vec![2.0]).with_timeframe(ConditionTimeframe::Relative(3)),
+        )
+        .add_exit_condition(
+            ConditionBuilder::crosses_below("sma", vec![2.0], "sma", vec![5.0])
+                .with_timeframe(ConditionTimeframe::Absolute(TimeFrame::H4)),
+        )
+        .build();
+    let config = no_cost_config();
+
+    let polars = run_polars(vec![strategy.clone()], candles.clone()).await.remove(0);
+    let event = EventDrivenBacktestEngine::new()
+        .run_ast(strategy, &SingleTimeFrameProvider::new(candles, TimeFrame::H1), &config)
+        .await
+        .unwrap();
+
+    assert!(!polars.trades.is_empty());
+    assert_eq!(polars.trades.len(), event.trades.len());
+    for (p, e) in polars.trades.iter().zip(&event.trades) {
+        assert_eq!(p.entry_timestamp, e.entry_timestamp);
+        assert_eq!(p.exit_timestamp, e.exit_timestamp);
+        assert!((p.pnl - e.pnl).abs() < 1e-9);
+    }
+    assert_eq!(polars.metrics.entry_signals_count, event.metrics.entry_signals_count);
+}
+
+#[tokio::test]
+async fn test_weekly_bars_follow_calendar_weeks() {
+    // Velas diarias desde el lunes 2021-01-04 con un escalón a 200 del miércoles
+    // 13 al domingo 24 de enero
+    const MONDAY: i64 = 1609718400000;
+    const DAY_MS: i64 = 24 * HOUR_MS;
+    let candles: Vec<Candle> = (0..35)
+        .map(|i| {
+            let close = if (9..21).contains(&i) { 200.0 } else { 100.0 };
+            Candle::new(MONDAY + i as i64 * DAY_MS, close, close + 1.0, close - 1.0, close, 1000.0)
+        })
+        .collect();
+    let weekly = step_strategy(Some(ConditionTimeframe::Absolute(TimeFrame::W1)));
+
+    let results = run_polars(vec![step_strategy(None), weekly.clone()], candles.clone()).await;
+    let event = EventDrivenBacktestEngine::new()
+        .run_ast(weekly, &SingleTimeFrameProvider::new(candles, TimeFrame::D1), &no_cost_config())
+        .await
+        .unwrap();
+    let (base, higher) = (&results[0], &results[1]);
+
+    // Las semanas van de lunes a domingo: la del escalón cierra el domingo 17 y la
+    // de la bajada (lunes 25) el domingo 31
+    assert_eq!(base.trades.len(), 1);
+    for result in [higher, &event] {
+        assert_eq!(result.trades.len(), 1);
+        assert_eq!(result.trades[0].entry_timestamp - base.trades[0].entry_timestamp, 4 * DAY_MS);
+        assert_eq!(result.trades[0].exit_timestamp - base.trades[0].exit_timestamp, 6 * DAY_MS);
+    }
+}
@@ -0,0 +1,167 @@
+//! Tests de integración del modo paralelo (rayon) del motor masivo
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, TimeFrame};
+use darwinx_generator::StrategyAST;
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+const HOUR_MS: i64 = 3_600_000;
+
+fn create_wave_candles() -> Vec<Candle> {
+    (0..400)
+        .map(|i| {
+            let close = 100.0 + (i as f64 * 0.1).sin() * 10.0 + (i as f64 * 0.37).cos() * 2.0;
+            Candle::new(BASE_TIMESTAMP + i as i64 * HOUR_MS, close, close + 1.0, close - 1.0, close, 1000.0)
+        })
+        .collect()
+}
+
+/// Estrategias con periodos distintos para que cada resultado sea distinguible
+fn create_strategies(count: usize) -> Vec<StrategyAST> {
+    (0..count)
+        .map(|i| {
+            let fast = 3.0 + (i % 7) as f64;
+            let slow = 12.0 + (i % 11) as f64;
+            StrategyBuilder::new(format!("Strategy_{}", i), TimeFrame::H1)
+                .add_entry_condition(ConditionBuilder::crosses_above("sma", vec![fast], "sma", vec![slow]))
+                .add_exit_condition(ConditionBuilder::crosses_below("sma", vec![fast], "sma", vec![slow]))
+                .build()
+        })
+        .collect()
+}
+
+fn no_risk_config() -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.1)
+}
+
+#[tokio::test]
+async fn test_parallel_matches_sequential_in_order() {
+    let candles = create_wave_candles();
+    let config = no_risk_config();
+
+    let sequential = PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(create_strategies(40), candles.clone(), &config)
+        .await
+        .unwrap();
+
+    for threads in [1, 2, 4] {
+        let parallel = PolarsVectorizedBacktestEngine::new()
+            .run_massive_backtest_parallel(
+                create_strategies(40),
+                &candles,
+                &config,
+                ParallelConfig::default().with_threads(threads).with_chunk_size(7),
+            )
+            .unwrap();
+
+        assert_eq!(parallel.len(), sequential.len());
+        for (p, s) in parallel.iter().zip(&sequential) {
+            assert_eq!(p.strategy_name, s.strategy_name);
+            assert_eq!(p.trades.len(), s.trades.len());
+            assert_eq!(p.metrics.total_return, s.metrics.total_return);
+        }
+    }
+}
+
+#[test]
+fn test_stream_yields_results_lazily() {
+    let candles = create_wave_candles();
+    let config = no_risk_config();
+    let engine = PolarsVectorizedBacktestEngine::new();
+
+    let mut stream = engine
+        .stream_massive_backtest(create_strategies(10), &candles, &config, ParallelConfig::default().with_chunk_size(4))
+        .unwrap();
+    assert_eq!(stream.total(), 10);
+    assert_eq!(stream.len(), 10);
+
+    let first = stream.next().unwrap();
+    assert_eq!(first.strategy_name, "Strategy_0");
+    assert_eq!(stream.len(), 9);
+
+    let names: Vec<String> = stream.map(|r| r.strategy_name).collect();
+    let expected: Vec<String> = (1..10).map(|i| format!("Strategy_{}", i)).collect();
+    assert_eq!(names, expected);
+}
+
+#[tokio::test]
+async fn test_open_stream_unaffected_by_other_datasets_on_engine() {
+    let candles = create_wave_candles();
+    let other: Vec<Candle> = candles
+        .iter()
+        .map(|c| Candle::new(c.timestamp, c.open * 3.0, c.high * 3.0, c.low * 3.0, c.close * 3.0, c.volume))
+        .collect();
+    let config = no_risk_config();
+    let engine = PolarsVectorizedBacktestEngine::new();
+
+    let mut stream = engine
+        .stream_massive_backtest(create_strategies(12), &candles, &config, ParallelConfig::default().with_chunk_size(3))
+        .unwrap();
+    let mut streamed = vec![stream.next().unwrap()];
+
+    // Entre bloques del stream el motor procesa otro dataset y vacía la caché
+    engine
+        .run_massive_backtest(create_strategies(12), other, &config)
+        .await
+        .unwrap();
+    streamed.extend(stream.by_ref().take(4));
+    engine.clear_indicator_cache();
+    streamed.extend(stream);
+
+    let expected = PolarsVectorizedBacktestEngine::with_indicator_cache_budget(0)
+        .run_massive_backtest(create_strategies(12), candles, &config)
+        .await
+        .unwrap();
+    assert_eq!(streamed.len(), expected.len());
+    for (s, e) in streamed.iter().zip(&expected) {
+        assert_eq!(s.strategy_name, e.strategy_name);
+        assert_eq!(s.trades.len(), e.trades.len());
+        assert_eq!(s.metrics.total_return, e.metrics.total_return);
+    }
+}
+
+#[test]
+fn test_progress_callback_and_without_trades() {
+    let candles = create_wave_candles();
+    let config = no_risk_config();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let max_completed = Arc::new(AtomicUsize::new(0));
+
+    let options = {
+        let calls = calls.clone();
+        let max_completed = max_completed.clone();
+        ParallelConfig::default()
+            .with_threads(2)
+            .without_trades()
+            .with_progress(move |progress| {
+                assert_eq!(progress.total, 12);
+                calls.fetch_add(1, Ordering::SeqCst);
+                max_completed.fetch_max(progress.completed, Ordering::SeqCst);
+            })
+    };
+
+    let results = PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest_parallel(create_strategies(12), &candles, &config, options)
+        .unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 12);
+    assert_eq!(max_completed.load(Ordering::SeqCst), 12);
+    assert!(results.iter().all(|r| r.trades.is_empty()));
+    // Las métricas se calculan antes de descartar los trades
+    assert!(results.iter().any(|r| r.metrics.total_trades > 0));
+}
+
+#[test]
+fn test_parallel_rejects_empty_candles() {
+    let config = no_risk_config();
+    let result = PolarsVectorizedBacktestEngine::new().run_massive_backtest_parallel(
+        create_strategies(2),
+        &[],
+        &config,
+        ParallelConfig::default(),
+    );
+    assert!(result.is_err());
+}
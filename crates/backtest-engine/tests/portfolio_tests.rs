@@ -0,0 +1,162 @@
+//! Tests de integración del backtest de cartera con capital compartido
+
+use std::collections::HashMap;
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, TimeFrame};
+use darwinx_generator::StrategyAST;
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01 00:00 UTC
+const HOUR_MS: i64 = 3_600_000;
+
+fn candles_from_closes(closes: &[f64]) -> Vec<Candle> {
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| Candle::new(BASE_TIMESTAMP + i as i64 * HOUR_MS, close, close + 0.5, close - 0.5, close, 1000.0))
+        .collect()
+}
+
+/// Entra cuando el precio cruza 100 hacia arriba y no tiene salida por señal
+fn above_hundred() -> StrategyAST {
+    StrategyBuilder::new("AboveHundred".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above_value("sma", vec![1.0], 100.0))
+        .build()
+}
+
+fn no_cost_config(position_size_percent: f64) -> PortfolioConfig {
+    PortfolioConfig::new(BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, position_size_percent))
+}
+
+fn two_symbols(btc: &[f64], eth: &[f64]) -> (HashMap<String, Vec<Candle>>, Vec<PortfolioSleeve>) {
+    let data = HashMap::from([
+        ("BTC".to_string(), candles_from_closes(btc)),
+        ("ETH".to_string(), candles_from_closes(eth)),
+    ]);
+    let sleeves = vec![PortfolioSleeve::new("BTC", above_hundred()), PortfolioSleeve::new("ETH", above_hundred())];
+    (data, sleeves)
+}
+
+fn run(data: &HashMap<String, Vec<Candle>>, sleeves: &[PortfolioSleeve], config: &PortfolioConfig) -> PortfolioReport {
+    PolarsVectorizedBacktestEngine::new().run_portfolio_backtest(data, sleeves, config).unwrap()
+}
+
+#[test]
+fn test_shared_capital_is_split_across_sleeves() {
+    let (data, sleeves) = two_symbols(&[95.0, 101.0, 103.0, 105.0], &[90.0, 90.0, 102.0, 99.0]);
+    let report = run(&data, &sleeves, &no_cost_config(0.8));
+
+    // Cada estrategia recibe el 40% del equity en el momento de su entrada
+    let btc = &report.sleeves[0].trades[0];
+    assert!((btc.size * btc.entry_price - 4000.0).abs() < 1e-9);
+    let equity_at_eth_entry = 10000.0 + 4000.0 / 101.0 * 2.0;
+    let eth = &report.sleeves[1].trades[0];
+    assert_eq!(eth.entry_price, 102.0);
+    assert!((eth.size * eth.entry_price - equity_at_eth_entry * 0.4).abs() < 1e-6);
+
+    let pnl: f64 = report.sleeves.iter().map(|s| s.pnl).sum();
+    assert!((report.final_balance - (10000.0 + pnl)).abs() < 1e-9);
+    assert!((report.equity_curve.last().unwrap().balance - report.final_balance).abs() < 1e-9);
+    assert_eq!(report.equity_curve.len(), 4);
+    assert_eq!(report.metrics.total_trades, 2);
+}
+
+#[test]
+fn test_exposure_limits_cap_entries() {
+    let (data, sleeves) = two_symbols(&[95.0, 101.0, 101.0], &[95.0, 101.0, 101.0]);
+    let config = no_cost_config(1.0).with_symbol_exposure(0.3).with_total_exposure(0.4);
+    let report = run(&data, &sleeves, &config);
+
+    // El primero queda limitado por el símbolo y el segundo por lo que resta del total
+    let values: Vec<f64> = report.sleeves.iter().map(|s| s.trades[0].size * s.trades[0].entry_price).collect();
+    assert!((values[0] - 3000.0).abs() < 1e-9);
+    assert!((values[1] - 1000.0).abs() < 1e-9);
+    assert!((report.symbols[0].max_exposure - 0.3).abs() < 1e-9);
+    assert!((report.symbols[1].max_exposure - 0.1).abs() < 1e-9);
+}
+
+#[test]
+fn test_rebalance_rules() {
+    let data = HashMap::from([("BTC".to_string(), candles_from_closes(&[95.0, 101.0, 150.0, 150.0]))]);
+    let sleeves = vec![PortfolioSleeve::new("BTC", above_hundred())];
+
+    let never = run(&data, &sleeves, &no_cost_config(0.5));
+    assert_eq!(never.rebalances, 0);
+    assert_eq!(never.sleeves[0].trades.len(), 1);
+
+    // En la vela 2 la posición vale un 19.5% más que su objetivo
+    let size = 5000.0 / 101.0;
+    let target = (10000.0 + size * 49.0) * 0.5;
+    for rule in [RebalanceRule::Periodic { bars: 2 }, RebalanceRule::Drift { threshold: 0.1 }] {
+        let report = run(&data, &sleeves, &no_cost_config(0.5).with_rebalance(rule));
+        assert_eq!(report.rebalances, 1, "{:?}", rule);
+
+        let trades = &report.sleeves[0].trades;
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].exit_reason, ExitReason::Rebalance);
+        assert_eq!(trades[0].exit_timestamp, BASE_TIMESTAMP + 2 * HOUR_MS);
+        assert!(((size - trades[0].size) * 150.0 - target).abs() < 1e-6);
+        assert_eq!(trades[1].exit_reason, ExitReason::EndOfData);
+        assert_eq!(trades[0].position_id, trades[1].position_id);
+    }
+
+    let report = run(&data, &sleeves, &no_cost_config(0.5).with_rebalance(RebalanceRule::Drift { threshold: 0.25 }));
+    assert_eq!(report.rebalances, 0);
+}
+
+#[test]
+fn test_correlation_and_attribution() {
+    let closes = [95.0, 101.0, 104.0, 102.0, 107.0, 103.0];
+    let data = HashMap::from([
+        ("BTC".to_string(), candles_from_closes(&closes)),
+        ("ETH".to_string(), candles_from_closes(&closes)),
+        ("SOL".to_string(), candles_from_closes(&[95.0, 101.0, 101.0, 101.0, 101.0, 101.0])),
+    ]);
+    let sleeves = vec![
+        PortfolioSleeve::new("BTC", above_hundred()),
+        PortfolioSleeve::new("ETH", above_hundred()),
+        PortfolioSleeve::new("ETH", above_hundred()),
+        PortfolioSleeve::new("SOL", above_hundred()),
+    ];
+    let report = run(&data, &sleeves, &no_cost_config(0.8));
+
+    let correlation = &report.correlation;
+    assert_eq!(correlation.labels[0], "BTC:AboveHundred");
+    assert!((correlation.get(0, 1) - 1.0).abs() < 1e-9);
+    assert!((correlation.get(1, 2) - 1.0).abs() < 1e-9);
+    // Una estrategia sin variación no está correlacionada con las demás
+    assert_eq!(correlation.get(0, 3), 0.0);
+    assert_eq!(correlation.get(3, 3), 1.0);
+
+    // Atribución por símbolo: ETH agrupa dos estrategias
+    assert_eq!(report.symbols.len(), 3);
+    let eth = &report.symbols[1];
+    assert_eq!(eth.symbol, "ETH");
+    assert_eq!(eth.trades, 2);
+    assert!((eth.pnl - (report.sleeves[1].pnl + report.sleeves[2].pnl)).abs() < 1e-9);
+    assert!((eth.contribution - eth.pnl / 10000.0).abs() < 1e-12);
+    assert!((eth.max_exposure - 2.0 * report.symbols[0].max_exposure).abs() < 1e-3);
+    assert!(report.symbols[2].pnl.abs() < 1e-9);
+
+    let total: f64 = report.symbols.iter().map(|s| s.pnl).sum();
+    assert!((report.final_balance - 10000.0 - total).abs() < 1e-9);
+}
+
+#[test]
+fn test_portfolio_config_errors_and_defaults() {
+    let engine = PolarsVectorizedBacktestEngine::new();
+    let (data, _) = two_symbols(&[95.0, 101.0], &[95.0, 101.0]);
+    let config = PortfolioConfig::default();
+
+    let missing = engine.run_portfolio_backtest(&data, &[PortfolioSleeve::new("SOL", above_hundred())], &config);
+    assert!(matches!(missing, Err(BacktestError::ConfigError(_))));
+    assert!(matches!(engine.run_portfolio_backtest(&data, &[], &config), Err(BacktestError::ConfigError(_))));
+
+    let mut value = serde_json::to_value(&config).unwrap();
+    value.as_object_mut().unwrap().remove("rebalance");
+    let config: PortfolioConfig = serde_json::from_value(value).unwrap();
+    assert_eq!(config.rebalance, RebalanceRule::Never);
+
+    let rule: RebalanceRule = serde_json::from_str(r#"{"rule":"drift","threshold":0.1}"#).unwrap();
+    assert_eq!(rule, RebalanceRule::Drift { threshold: 0.1 });
+}
@@ -0,0 +1,157 @@
+//! Tests de integración de posiciones múltiples, pyramiding y cierres parciales
+
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, TimeFrame};
+use darwinx_generator::StrategyAST;
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01 00:00 UTC
+const HOUR_MS: i64 = 3_600_000;
+
+fn candles_from_closes(closes: &[f64]) -> Vec<Candle> {
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, &close)| Candle::new(BASE_TIMESTAMP + i as i64 * HOUR_MS, close, close + 0.5, close - 0.5, close, 1000.0))
+        .collect()
+}
+
+/// Entra cada vez que el precio cruza 100 hacia arriba y no tiene salida por señal
+fn above_hundred() -> StrategyAST {
+    StrategyBuilder::new("AboveHundred".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above_value("sma", vec![1.0], 100.0))
+        .build()
+}
+
+/// Señales de entrada repetidas en las velas 1, 3, 5 y 7 sin salida intermedia
+fn repeated_signals() -> Vec<Candle> {
+    candles_from_closes(&[95.0, 101.0, 99.0, 102.0, 98.0, 103.0, 97.0, 104.0, 105.0])
+}
+
+fn no_cost_config(position_size_percent: f64, max_positions: usize) -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, max_positions, 0.02, None, None, position_size_percent)
+}
+
+async fn run(candles: Vec<Candle>, config: &BacktestConfig) -> BacktestResult {
+    PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(vec![above_hundred()], candles, config)
+        .await
+        .unwrap()
+        .remove(0)
+}
+
+#[tokio::test]
+async fn test_single_position_ignores_repeated_signals() {
+    let result = run(repeated_signals(), &no_cost_config(0.5, 1)).await;
+
+    assert_eq!(result.trades.len(), 1);
+    let trade = &result.trades[0];
+    assert_eq!(trade.entry_price, 101.0);
+    assert_eq!(trade.position_id, 0);
+    assert!((trade.size - 5000.0 / 101.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_max_positions_opens_independent_positions() {
+    let result = run(repeated_signals(), &no_cost_config(0.5, 3)).await;
+
+    // La cuarta señal no cabe en `max_positions`
+    let entries: Vec<f64> = result.trades.iter().map(|t| t.entry_price).collect();
+    assert_eq!(entries, vec![101.0, 102.0, 103.0]);
+    let ids: Vec<usize> = result.trades.iter().map(|t| t.position_id).collect();
+    assert_eq!(ids, vec![0, 1, 2]);
+    for trade in &result.trades {
+        assert!((trade.size * trade.entry_price - 10000.0 * 0.5 / 3.0).abs() < 1e-9);
+        assert_eq!(trade.exit_reason, ExitReason::EndOfData);
+    }
+
+    // La equity incluye el P&L no realizado de todas las posiciones abiertas
+    let last = result.equity_curve.last().unwrap();
+    assert!((last.balance - result.metadata.final_balance).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_pyramiding_adds_decaying_lots() {
+    let config = no_cost_config(0.5, 1).with_pyramiding(2, 0.5);
+    let result = run(repeated_signals(), &config).await;
+
+    assert_eq!(result.trades.len(), 3);
+    let base = 5000.0 / 101.0;
+    for (k, (trade, price)) in result.trades.iter().zip([101.0, 102.0, 103.0]).enumerate() {
+        assert_eq!(trade.entry_price, price);
+        assert!((trade.size - base * 0.5f64.powi(k as i32)).abs() < 1e-9);
+        // Todos los lotes pertenecen a la misma posición y se cierran juntos
+        assert_eq!(trade.position_id, 0);
+        assert_eq!(trade.exit_timestamp, BASE_TIMESTAMP + 8 * HOUR_MS);
+    }
+}
+
+#[tokio::test]
+async fn test_scale_out_closes_lots_in_order() {
+    // Lotes a 101 y 102 (precio medio 101.5); el nivel del 3% se alcanza en la vela 4
+    let candles = candles_from_closes(&[95.0, 101.0, 99.0, 102.0, 106.0, 107.0]);
+    let level = ScaleOutLevel { profit_percent: 0.03, fraction: 0.5 };
+    let partial_price = 101.5 * 1.03;
+
+    for (order, closed_entry, remaining_entry) in [(CloseOrder::Fifo, 101.0, 102.0), (CloseOrder::Lifo, 102.0, 101.0)] {
+        let config = no_cost_config(0.4, 1)
+            .with_pyramiding(1, 1.0)
+            .with_scale_out(vec![level])
+            .with_close_order(order);
+        let result = run(candles.clone(), &config).await;
+
+        assert_eq!(result.trades.len(), 2, "{:?}", order);
+        let partial = &result.trades[0];
+        assert_eq!(partial.exit_reason, ExitReason::PartialTakeProfit);
+        assert_eq!(partial.entry_price, closed_entry);
+        assert!((partial.exit_price - partial_price).abs() < 1e-9);
+        assert_eq!(partial.exit_timestamp, BASE_TIMESTAMP + 4 * HOUR_MS);
+
+        let rest = &result.trades[1];
+        assert_eq!(rest.exit_reason, ExitReason::EndOfData);
+        assert_eq!(rest.entry_price, remaining_entry);
+        assert_eq!(rest.position_id, partial.position_id);
+        assert!((partial.size - rest.size).abs() < 1e-9);
+        assert_eq!(result.metrics.exit_reasons[0].reason, ExitReason::PartialTakeProfit);
+    }
+}
+
+#[test]
+fn test_position_rules_deserialize_with_defaults() {
+    let mut value = serde_json::to_value(BacktestConfig::default()).unwrap();
+    let object = value.as_object_mut().unwrap();
+    for field in ["pyramiding", "scale_out", "close_order"] {
+        object.remove(field);
+    }
+    let config: BacktestConfig = serde_json::from_value(value).unwrap();
+    assert_eq!(config.pyramiding, PyramidingConfig::default());
+    assert!(config.scale_out.is_empty());
+    assert_eq!(config.close_order, CloseOrder::Fifo);
+
+    let config = BacktestConfig::default().with_close_order(CloseOrder::Lifo);
+    assert_eq!(serde_json::to_value(&config).unwrap()["close_order"], "lifo");
+
+    // Trades guardados antes de `position_id`
+    let mut trade = serde_json::to_value(saved_trade()).unwrap();
+    trade.as_object_mut().unwrap().remove("position_id");
+    assert_eq!(serde_json::from_value::<Trade>(trade).unwrap().position_id, 0);
+}
+
+fn saved_trade() -> Trade {
+    Trade {
+        entry_timestamp: 0,
+        exit_timestamp: 1,
+        entry_price: 100.0,
+        exit_price: 101.0,
+        size: 1.0,
+        is_long: true,
+        pnl: 1.0,
+        commission: 0.0,
+        slippage: 0.0,
+        borrow_cost: 0.0,
+        exit_reason: ExitReason::Signal,
+        position_id: 3,
+        entry_commission: 0.0,
+        funding: 0.0,
+    }
+}
@@ -0,0 +1,206 @@
+//! Tests de integración del scoring y ranking de resultados
+
+use darwinx_backtest_engine::*;
+
+fn metrics(sharpe_ratio: f64, max_drawdown: f64, total_trades: usize) -> BacktestMetrics {
+    BacktestMetrics { sharpe_ratio, max_drawdown, total_trades, ..BacktestMetrics::default() }
+}
+
+fn spec(terms: Vec<ScoreTerm>) -> ScoreSpec {
+    ScoreSpec { terms, constraints: Vec::new(), ranking: RankingMethod::Weighted }
+}
+
+#[test]
+fn test_default_spec_matches_classic_composite_score() {
+    let m = BacktestMetrics {
+        sharpe_ratio: 1.0,
+        sortino_ratio: 3.0,
+        profit_factor: 2.0,
+        total_return: 0.2,
+        max_drawdown: 0.1,
+        ..BacktestMetrics::default()
+    };
+    let score = ScoreSpec::default().score_batch(&[&m])[0].unwrap();
+
+    // Sortino se recorta a 1; el resto es lineal en su rango
+    let expected = 0.3 * 0.75 + 0.2 * 1.0 + 0.2 * 0.4 + 0.15 * 0.6 + 0.15 * 0.9;
+    assert!((score - expected).abs() < 1e-12);
+
+    // Los pesos se normalizan y la métrica de retorno se puede sustituir
+    let doubled = ScoreSpec::from_weights(&[0.6, 0.4, 0.4, 0.3, 0.3])
+        .with_metric_replaced(ScoreMetric::TotalReturn, ScoreMetric::ExcessReturn);
+    let m = BacktestMetrics { excess_return: -0.2, ..m };
+    let score = doubled.score_batch(&[&m])[0].unwrap();
+    assert!((score - (expected - 0.15 * 0.2)).abs() < 1e-12);
+}
+
+#[test]
+fn test_batch_normalizations() {
+    let batch = [metrics(0.5, 0.3, 10), metrics(1.5, 0.1, 10), metrics(1.0, 0.2, 10), metrics(1.0, 0.4, 10)];
+    let refs: Vec<&BacktestMetrics> = batch.iter().collect();
+
+    let percentile = spec(vec![ScoreTerm::new(ScoreMetric::SharpeRatio, 1.0, Normalization::Percentile)]);
+    let scores: Vec<f64> = percentile.score_batch(&refs).into_iter().map(Option::unwrap).collect();
+    assert_eq!(scores, vec![0.125, 0.875, 0.5, 0.5]);
+
+    // En el drawdown el menor es el mejor
+    let rank = spec(vec![ScoreTerm::new(ScoreMetric::MaxDrawdown, 1.0, Normalization::Rank)]);
+    let scores: Vec<f64> = rank.score_batch(&refs).into_iter().map(Option::unwrap).collect();
+    assert_eq!(scores, vec![1.0 / 3.0, 1.0, 2.0 / 3.0, 0.0]);
+
+    let ranked = rank.rank(&refs);
+    assert_eq!(ranked.iter().map(|r| r.index).collect::<Vec<_>>(), vec![1, 2, 0, 3]);
+    assert!(ranked.iter().all(|r| r.front.is_none()));
+}
+
+#[test]
+fn test_constraints_exclude_results_before_normalizing() {
+    let batch = [metrics(0.5, 0.3, 50), metrics(3.0, 0.1, 5), metrics(1.0, 0.2, 40)];
+    let refs: Vec<&BacktestMetrics> = batch.iter().collect();
+    let spec = spec(vec![ScoreTerm::new(ScoreMetric::SharpeRatio, 1.0, Normalization::Percentile)])
+        .with_constraint(ScoreConstraint { metric: ScoreMetric::TotalTrades, min: Some(30.0), max: None });
+
+    let scores = spec.score_batch(&refs);
+    assert_eq!(scores, vec![Some(0.25), None, Some(0.75)]);
+    let ranked = spec.rank(&refs);
+    assert_eq!(ranked.len(), 2);
+    assert_eq!(ranked[0].index, 2);
+    assert!(!spec.passes(&batch[1]));
+}
+
+#[test]
+fn test_score_against_fixed_reference_is_batch_independent() {
+    let reference = [metrics(0.5, 0.3, 10), metrics(1.0, 0.2, 10), metrics(1.5, 0.1, 10), metrics(2.0, 0.4, 10)];
+    let reference: Vec<&BacktestMetrics> = reference.iter().collect();
+    let spec = spec(vec![
+        ScoreTerm::new(ScoreMetric::SharpeRatio, 0.5, Normalization::Percentile),
+        ScoreTerm::new(ScoreMetric::MaxDrawdown, 0.5, Normalization::Rank),
+    ]);
+
+    let candidate = metrics(1.2, 0.2, 10);
+    let weak = [metrics(0.1, 0.9, 10), metrics(0.2, 0.8, 10)];
+    let strong = [metrics(5.0, 0.01, 10), metrics(4.0, 0.02, 10)];
+    let score_in = |others: &[BacktestMetrics]| {
+        let mut batch: Vec<&BacktestMetrics> = others.iter().collect();
+        batch.push(&candidate);
+        spec.score_against(&batch, &reference).pop().unwrap().unwrap()
+    };
+
+    // Percentil 0.5 en sharpe y rango 2/3 en drawdown, sea cual sea el resto del batch
+    let expected = 0.5 * 0.5 + 0.5 * (2.0 / 3.0);
+    assert!((score_in(&weak) - expected).abs() < 1e-12);
+    assert!((score_in(&strong) - expected).abs() < 1e-12);
+
+    // Fuera del rango de la referencia los valores se recortan a [0, 1]
+    let best = spec.score_against(&[&strong[0]], &reference)[0].unwrap();
+    assert!((best - (0.5 * 1.0 + 0.5 * 1.0)).abs() < 1e-12);
+}
+
+#[test]
+fn test_pareto_ranking_orders_by_front_then_score() {
+    // (sharpe, drawdown): 0 y 1 no están dominados, 2 está dominado por 1 y 3 por todos
+    let batch = [metrics(2.0, 0.4, 10), metrics(1.0, 0.1, 10), metrics(0.9, 0.2, 10), metrics(0.5, 0.5, 10)];
+    let refs: Vec<&BacktestMetrics> = batch.iter().collect();
+    let spec = spec(vec![
+        ScoreTerm::new(ScoreMetric::SharpeRatio, 0.9, Normalization::Rank),
+        ScoreTerm::new(ScoreMetric::MaxDrawdown, 0.1, Normalization::Rank),
+    ])
+    .with_ranking(RankingMethod::Pareto);
+
+    let ranked = spec.rank(&refs);
+    let order: Vec<(usize, Option<usize>)> = ranked.iter().map(|r| (r.index, r.front)).collect();
+    assert_eq!(order, vec![(0, Some(0)), (1, Some(0)), (2, Some(1)), (3, Some(2))]);
+    assert!(ranked[0].score > ranked[1].score);
+}
+
+#[test]
+fn test_pareto_fronts_match_pairwise_definition() {
+    // Valores en una rejilla pequeña para que haya empates y puntos repetidos
+    let batch: Vec<BacktestMetrics> = (0..300)
+        .map(|i: usize| BacktestMetrics {
+            sharpe_ratio: ((i * 37) % 11) as f64 * 0.25,
+            max_drawdown: ((i * 53) % 7) as f64 * 0.05,
+            total_return: ((i * 71) % 13) as f64 * 0.1,
+            ..BacktestMetrics::default()
+        })
+        .collect();
+    let refs: Vec<&BacktestMetrics> = batch.iter().collect();
+    let spec = spec(vec![
+        ScoreTerm::new(ScoreMetric::SharpeRatio, 1.0, Normalization::Percentile),
+        ScoreTerm::new(ScoreMetric::MaxDrawdown, 1.0, Normalization::Percentile),
+        ScoreTerm::new(ScoreMetric::TotalReturn, 1.0, Normalization::Percentile),
+    ])
+    .with_ranking(RankingMethod::Pareto);
+
+    let objectives = |m: &BacktestMetrics| [m.sharpe_ratio, -m.max_drawdown, m.total_return];
+    let dominates = |a: [f64; 3], b: [f64; 3]| a.iter().zip(&b).all(|(x, y)| x >= y) && a != b;
+
+    let mut fronts = vec![usize::MAX; batch.len()];
+    for r in spec.rank(&refs) {
+        fronts[r.index] = r.front.unwrap();
+    }
+    for i in 0..batch.len() {
+        let dominators: Vec<usize> =
+            (0..batch.len()).filter(|&j| dominates(objectives(&batch[j]), objectives(&batch[i]))).collect();
+        // El frente de un punto es uno más que el peor frente de quienes lo dominan
+        let expected = dominators.iter().map(|&j| fronts[j] + 1).max().unwrap_or(0);
+        assert_eq!(fronts[i], expected, "punto {}", i);
+    }
+}
+
+#[test]
+fn test_nan_metrics_rank_last() {
+    let batch = [metrics(f64::NAN, 0.1, 10), metrics(1.0, 0.2, 10), metrics(0.5, 0.2, 10)];
+    let refs: Vec<&BacktestMetrics> = batch.iter().collect();
+
+    for normalization in [Normalization::Percentile, Normalization::Rank, Normalization::MinMax { min: 0.0, max: 2.0 }] {
+        let spec = spec(vec![ScoreTerm::new(ScoreMetric::SharpeRatio, 1.0, normalization)]);
+        let scores = spec.score_batch(&refs);
+        assert_eq!(scores[0], Some(0.0), "{:?}", normalization);
+        assert!(scores.iter().all(|s| s.unwrap().is_finite()));
+        let order: Vec<usize> = spec.rank(&refs).iter().map(|r| r.index).collect();
+        assert_eq!(order, vec![1, 2, 0], "{:?}", normalization);
+    }
+
+    // El NaN cuenta como el peor valor del batch
+    let percentile = spec(vec![ScoreTerm::new(ScoreMetric::SharpeRatio, 1.0, Normalization::Percentile)]);
+    assert_eq!(percentile.score_batch(&refs), vec![Some(0.0), Some(5.0 / 6.0), Some(0.5)]);
+}
+
+#[test]
+fn test_score_spec_from_toml() {
+    let spec = ScoreSpec::from_toml_str(
+        r#"
+        ranking = "pareto"
+
+        [[terms]]
+        metric = "sharpe_ratio"
+        weight = 0.6
+
+        [[terms]]
+        metric = "max_drawdown"
+        weight = 0.4
+        normalization = { method = "min_max", min = 0.0, max = 0.5 }
+
+        [[constraints]]
+        metric = "total_trades"
+        min = 30
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(spec.ranking, RankingMethod::Pareto);
+    assert_eq!(spec.terms[0].normalization, Normalization::Percentile);
+    assert_eq!(spec.terms[1].normalization, Normalization::MinMax { min: 0.0, max: 0.5 });
+    assert_eq!(spec.constraints[0].min, Some(30.0));
+
+    let invalid = [
+        "terms = []",
+        "[[terms]]\nmetric = \"sharpe_ratio\"\nweight = -1.0",
+        "[[terms]]\nmetric = \"sharpe_ratio\"\nweight = 1.0\nnormalization = { method = \"min_max\", min = 1.0, max = 1.0 }",
+        "[[terms]]\nmetric = \"luck\"\nweight = 1.0",
+    ];
+    for toml in invalid {
+        assert!(matches!(ScoreSpec::from_toml_str(toml), Err(BacktestError::ConfigError(_))), "{}", toml);
+    }
+}
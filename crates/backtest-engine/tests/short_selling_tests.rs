@@ -0,0 +1,149 @@
+//! Tests de integración para posiciones cortas y estrategias long/short
+//! en el motor masivo de Polars
+
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, TimeFrame};
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+const HOUR_MS: i64 = 3_600_000;
+
+/// Genera velas horarias a partir de tramos de 30 velas con pendiente ±2
+fn create_zigzag_candles(start: f64, slopes: &[f64]) -> Vec<Candle> {
+    let mut candles = Vec::new();
+    let mut close = start;
+
+    for slope in slopes {
+        for _ in 0..30 {
+            let timestamp = BASE_TIMESTAMP + candles.len() as i64 * HOUR_MS;
+            candles.push(Candle::new(timestamp, close, close + 1.0, close - 1.0, close, 1000.0));
+            close += slope;
+        }
+    }
+
+    candles
+}
+
+fn no_cost_config() -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.1)
+}
+
+async fn run_single(strategy: darwinx_generator::StrategyAST, candles: Vec<Candle>, config: &BacktestConfig) -> BacktestResult {
+    let engine = PolarsVectorizedBacktestEngine::new();
+    let mut results = engine
+        .run_massive_backtest(vec![strategy], candles, config)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    results.remove(0)
+}
+
+fn death_cross_short_strategy() -> darwinx_generator::StrategyAST {
+    StrategyBuilder::new("DeathCrossShort".to_string(), TimeFrame::H1)
+        .add_short_entry_condition(ConditionBuilder::crosses_below("sma", vec![3.0], "sma", vec![10.0]))
+        .build()
+}
+
+#[tokio::test]
+async fn test_short_profits_on_falling_market() {
+    // Sube y luego baja: un único death cross en el techo
+    let candles = create_zigzag_candles(100.0, &[2.0, -2.0]);
+
+    let result = run_single(death_cross_short_strategy(), candles, &no_cost_config()).await;
+
+    assert_eq!(result.trades.len(), 1);
+    let trade = &result.trades[0];
+    assert!(!trade.is_long);
+    assert_eq!(trade.exit_reason, ExitReason::EndOfData);
+    assert!(trade.exit_price < trade.entry_price);
+    assert!((trade.pnl - (trade.entry_price - trade.exit_price) * trade.size).abs() < 1e-9);
+
+    assert_eq!(result.metrics.short_trades, 1);
+    assert_eq!(result.metrics.long_trades, 0);
+    assert_eq!(result.metrics.short_win_rate, 1.0);
+    assert!((result.metrics.short_pnl - trade.pnl).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_short_stop_loss_is_above_entry() {
+    // Baja y luego sube por encima del precio de entrada
+    let candles = create_zigzag_candles(200.0, &[-2.0, 2.0]);
+    let strategy = StrategyBuilder::new("ShortBelowValue".to_string(), TimeFrame::H1)
+        .add_short_entry_condition(ConditionBuilder::crosses_below_value("sma", vec![3.0], 190.0))
+        .build();
+    let config = BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, Some(0.05), None, 0.1);
+
+    let result = run_single(strategy, candles, &config).await;
+
+    assert_eq!(result.trades.len(), 1);
+    let trade = &result.trades[0];
+    assert!(!trade.is_long);
+    assert_eq!(trade.exit_reason, ExitReason::StopLoss);
+    assert!((trade.exit_price - trade.entry_price * 1.05).abs() < 1e-9);
+    assert!(trade.pnl < 0.0);
+}
+
+#[tokio::test]
+async fn test_short_take_profit_is_below_entry() {
+    let candles = create_zigzag_candles(200.0, &[-2.0, 2.0]);
+    let strategy = StrategyBuilder::new("ShortBelowValue".to_string(), TimeFrame::H1)
+        .add_short_entry_condition(ConditionBuilder::crosses_below_value("sma", vec![3.0], 190.0))
+        .build();
+    let config = BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, Some(0.1), 0.1);
+
+    let result = run_single(strategy, candles, &config).await;
+
+    assert_eq!(result.trades.len(), 1);
+    let trade = &result.trades[0];
+    assert_eq!(trade.exit_reason, ExitReason::TakeProfit);
+    assert!((trade.exit_price - trade.entry_price * 0.9).abs() < 1e-9);
+    assert!(trade.pnl > 0.0);
+}
+
+#[tokio::test]
+async fn test_short_borrow_cost_reduces_pnl() {
+    let candles = create_zigzag_candles(100.0, &[2.0, -2.0]);
+
+    let free = run_single(death_cross_short_strategy(), candles.clone(), &no_cost_config()).await;
+    let borrowed_config = no_cost_config().with_short_borrow_rate(0.01);
+    let borrowed = run_single(death_cross_short_strategy(), candles, &borrowed_config).await;
+
+    let trade = &borrowed.trades[0];
+    let days = (trade.exit_timestamp - trade.entry_timestamp) as f64 / (24.0 * HOUR_MS as f64);
+    let expected_cost = trade.entry_price * trade.size * 0.01 * days;
+
+    assert!(expected_cost > 0.0);
+    assert!((trade.borrow_cost - expected_cost).abs() < 1e-9);
+    assert!((free.trades[0].pnl - trade.pnl - expected_cost).abs() < 1e-9);
+    assert_eq!(free.trades[0].borrow_cost, 0.0);
+}
+
+#[tokio::test]
+async fn test_long_short_strategy_reverses_on_opposite_signal() {
+    // Baja, sube y vuelve a bajar: golden cross en el valle, death cross en el techo
+    let candles = create_zigzag_candles(200.0, &[-2.0, 2.0, -2.0]);
+    let strategy = StrategyBuilder::new("LongShort".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above("sma", vec![3.0], "sma", vec![10.0]))
+        .add_short_entry_condition(ConditionBuilder::crosses_below("sma", vec![3.0], "sma", vec![10.0]))
+        .build();
+
+    let result = run_single(strategy, candles, &no_cost_config()).await;
+
+    assert_eq!(result.trades.len(), 2);
+    let (long, short) = (&result.trades[0], &result.trades[1]);
+
+    assert!(long.is_long);
+    assert_eq!(long.exit_reason, ExitReason::Signal);
+    assert!(long.pnl > 0.0);
+
+    assert!(!short.is_long);
+    assert_eq!(short.entry_timestamp, long.exit_timestamp);
+    assert_eq!(short.exit_reason, ExitReason::EndOfData);
+    assert!(short.pnl > 0.0);
+
+    assert_eq!(result.metrics.entry_signals_count, 2);
+    assert_eq!(result.metrics.long_trades, 1);
+    assert_eq!(result.metrics.short_trades, 1);
+    assert!((result.metrics.long_pnl - long.pnl).abs() < 1e-9);
+    assert!((result.metrics.short_pnl - short.pnl).abs() < 1e-9);
+}
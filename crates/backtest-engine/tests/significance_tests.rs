@@ -0,0 +1,128 @@
+//! Tests de integración de PSR, DSR y Reality Check/SPA
+
+use darwinx_backtest_engine::metrics::*;
+use darwinx_backtest_engine::{ExitReason, Trade};
+
+fn trade(exit_timestamp: i64, pnl: f64) -> Trade {
+    Trade {
+        entry_timestamp: exit_timestamp - 1,
+        exit_timestamp,
+        entry_price: 100.0,
+        exit_price: 100.0,
+        size: 1.0,
+        is_long: true,
+        pnl,
+        commission: 0.0,
+        slippage: 0.0,
+        borrow_cost: 0.0,
+        exit_reason: ExitReason::Signal,
+        position_id: 0,
+        entry_commission: 0.0,
+        funding: 0.0,
+    }
+}
+
+/// Serie determinista con media `mean` y ruido acotado
+fn noisy_returns(n: usize, mean: f64, phase: f64) -> Vec<f64> {
+    (0..n).map(|i| mean + ((i as f64 * 1.7 + phase).sin()) * 0.01).collect()
+}
+
+#[test]
+fn test_skewness_and_kurtosis() {
+    let symmetric = [-2.0, -1.0, 0.0, 1.0, 2.0];
+    assert!(calculate_skewness(&symmetric).abs() < 1e-12);
+    assert!((calculate_kurtosis(&symmetric) - 1.7).abs() < 1e-12);
+
+    let right_tail = [0.0, 0.0, 0.0, 0.0, 10.0];
+    assert!(calculate_skewness(&right_tail) > 1.0);
+
+    // Serie constante: sin dispersión
+    assert_eq!(calculate_skewness(&[1.0; 4]), 0.0);
+    assert_eq!(calculate_kurtosis(&[1.0; 4]), 3.0);
+}
+
+#[test]
+fn test_probabilistic_sharpe_ratio() {
+    // Sharpe igual al de referencia: 50%
+    assert!((calculate_probabilistic_sharpe_ratio(0.1, 0.1, 100, 0.0, 3.0) - 0.5).abs() < 1e-9);
+
+    // Más observaciones aumentan la confianza en el mismo Sharpe
+    let short = calculate_probabilistic_sharpe_ratio(0.1, 0.0, 50, 0.0, 3.0);
+    let long = calculate_probabilistic_sharpe_ratio(0.1, 0.0, 1000, 0.0, 3.0);
+    assert!(long > short);
+    assert!(long > 0.99);
+
+    // Colas gruesas y asimetría negativa reducen el PSR
+    let fat_tails = calculate_probabilistic_sharpe_ratio(0.1, 0.0, 200, -1.0, 10.0);
+    let normal = calculate_probabilistic_sharpe_ratio(0.1, 0.0, 200, 0.0, 3.0);
+    assert!(fat_tails < normal);
+
+    assert_eq!(calculate_probabilistic_sharpe_ratio(0.1, 0.0, 1, 0.0, 3.0), 0.0);
+}
+
+#[test]
+fn test_deflated_sharpe_ratio_penalizes_trials() {
+    assert_eq!(calculate_expected_max_sharpe(1, 0.01), 0.0);
+    let few = calculate_expected_max_sharpe(10, 0.01);
+    let many = calculate_expected_max_sharpe(10_000, 0.01);
+    assert!(few > 0.0 && many > few);
+
+    let psr = calculate_probabilistic_sharpe_ratio(0.2, 0.0, 500, 0.0, 3.0);
+    let dsr = calculate_deflated_sharpe_ratio(0.2, 500, 0.0, 3.0, 10_000, 0.01);
+    assert!(dsr < psr);
+    // Con un solo intento el DSR coincide con el PSR
+    assert!((calculate_deflated_sharpe_ratio(0.2, 500, 0.0, 3.0, 1, 0.01) - psr).abs() < 1e-12);
+}
+
+#[test]
+fn test_period_returns_align_with_timestamps() {
+    let timestamps = [10, 20, 30, 40];
+    let trades = [trade(20, 100.0), trade(25, -50.0), trade(40, 20.0)];
+
+    let returns = calculate_period_returns(&trades, 1000.0, &timestamps);
+
+    assert_eq!(returns.len(), 4);
+    assert_eq!(returns[0], 0.0);
+    assert!((returns[1] - 0.1).abs() < 1e-12);
+    // El trade que cierra a las 25 se asigna al período 30
+    assert!((returns[2] + 50.0 / 1100.0).abs() < 1e-12);
+    assert!((returns[3] - 20.0 / 1050.0).abs() < 1e-12);
+}
+
+#[test]
+fn test_reality_check_detects_real_edge() {
+    let benchmark = vec![0.0; 500];
+    let strategies = vec![
+        noisy_returns(500, 0.0, 0.0),
+        noisy_returns(500, 0.005, 1.0),
+        noisy_returns(500, -0.002, 2.0),
+    ];
+
+    for studentized in [false, true] {
+        let config = RealityCheckConfig { studentized, seed: Some(11), ..Default::default() };
+        let result = calculate_reality_check(&strategies, &benchmark, &config).unwrap();
+        assert_eq!(result.best_index, 1);
+        assert!(result.p_value < 0.05, "p-value {}", result.p_value);
+    }
+}
+
+#[test]
+fn test_reality_check_without_edge() {
+    let benchmark = vec![0.0; 400];
+    let strategies: Vec<Vec<f64>> = (0..20).map(|k| noisy_returns(400, 0.0, k as f64)).collect();
+
+    let config = RealityCheckConfig { seed: Some(3), ..Default::default() };
+    let result = calculate_reality_check(&strategies, &benchmark, &config).unwrap();
+    assert!(result.p_value > 0.05, "p-value {}", result.p_value);
+
+    let again = calculate_reality_check(&strategies, &benchmark, &config).unwrap();
+    assert_eq!(result.p_value, again.p_value);
+}
+
+#[test]
+fn test_reality_check_invalid_input() {
+    let config = RealityCheckConfig::default();
+    assert!(calculate_reality_check(&[], &[0.0; 10], &config).is_err());
+    assert!(calculate_reality_check(&[vec![0.0; 5]], &[0.0; 10], &config).is_err());
+    assert!(calculate_reality_check(&[vec![0.0; 1]], &[0.0; 1], &config).is_err());
+}
@@ -0,0 +1,181 @@
+//! Tests de integración de los modelos de tamaño de posición
+
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, PositionSide, TimeFrame};
+use darwinx_generator::StrategyAST;
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+const HOUR_MS: i64 = 3_600_000;
+
+fn trade(pnl: f64) -> Trade {
+    Trade {
+        entry_timestamp: 0,
+        exit_timestamp: 1,
+        entry_price: 100.0,
+        exit_price: 100.0 + pnl,
+        size: 1.0,
+        is_long: true,
+        pnl,
+        commission: 0.0,
+        slippage: 0.0,
+        borrow_cost: 0.0,
+        exit_reason: ExitReason::Signal,
+        position_id: 0,
+        entry_commission: 0.0,
+        funding: 0.0,
+    }
+}
+
+fn context<'a>(config: &'a BacktestConfig, balance: f64, atr: Option<f64>, trades: &'a [Trade]) -> SizingContext<'a> {
+    SizingContext {
+        side: PositionSide::Long,
+        entry_price: 100.0,
+        balance,
+        atr,
+        closed_trades: trades,
+        config,
+    }
+}
+
+/// Velas horarias a partir de tramos de 30 velas con pendiente ±2
+fn create_zigzag_candles(start: f64, slopes: &[f64]) -> Vec<Candle> {
+    let mut candles = Vec::new();
+    let mut close = start;
+    for slope in slopes {
+        for _ in 0..30 {
+            let timestamp = BASE_TIMESTAMP + candles.len() as i64 * HOUR_MS;
+            candles.push(Candle::new(timestamp, close, close + 1.0, close - 1.0, close, 1000.0));
+            close += slope;
+        }
+    }
+    candles
+}
+
+fn crossover_strategy() -> StrategyAST {
+    StrategyBuilder::new("Cross".to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above("sma", vec![5.0], "sma", vec![20.0]))
+        .add_exit_condition(ConditionBuilder::crosses_below("sma", vec![5.0], "sma", vec![20.0]))
+        .build()
+}
+
+fn no_cost_config() -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.5)
+}
+
+#[test]
+fn test_fixed_fractional_uses_initial_balance_unless_compounding() {
+    let config = BacktestConfig::default();
+    let ctx = context(&config, 20000.0, None, &[]);
+
+    assert!((FixedFractional::default().position_size(&ctx) - 50.0).abs() < 1e-9);
+    assert!((FixedFractional { compound: true }.position_size(&ctx) - 100.0).abs() < 1e-9);
+    assert_eq!(FixedQuantity { quantity: 3.0 }.position_size(&ctx), 3.0);
+}
+
+#[test]
+fn test_risk_based_sizers() {
+    let config = BacktestConfig { stop_loss_percent: Some(0.05), ..Default::default() };
+    let ctx = context(&config, 10000.0, Some(2.0), &[]);
+
+    // 2% de 10000 = 200 arriesgados a 5 por unidad
+    assert!((RiskPerTrade.position_size(&ctx) - 40.0).abs() < 1e-9);
+    // 200 arriesgados a 2 ATR de 2.0
+    let volatility = VolatilityTarget { atr_period: 14, atr_multiple: 2.0 };
+    assert!((volatility.position_size(&ctx) - 50.0).abs() < 1e-9);
+    assert_eq!(volatility.position_size(&context(&config, 10000.0, None, &[])), 0.0);
+
+    // Un ATR diminuto no puede superar el balance
+    assert!((volatility.position_size(&context(&config, 10000.0, Some(0.001), &[])) - 100.0).abs() < 1e-9);
+
+    // Sin stop loss, riesgo por trade usa la fracción fija
+    let no_stop = BacktestConfig::default();
+    assert!((RiskPerTrade.position_size(&context(&no_stop, 10000.0, None, &[])) - 50.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_kelly_fraction() {
+    let config = BacktestConfig::default();
+    // Win rate 60%, payoff 1 -> Kelly 0.2
+    let trades: Vec<Trade> = [10.0, 10.0, 10.0, -10.0, -10.0].iter().map(|p| trade(*p)).collect();
+    assert!((KellyFraction::kelly(&trades).unwrap() - 0.2).abs() < 1e-9);
+
+    let sizer = KellyFraction { fraction: 0.5, lookback: 0, min_trades: 5 };
+    // Medio Kelly = 10% del balance
+    assert!((sizer.position_size(&context(&config, 10000.0, None, &trades)) - 10.0).abs() < 1e-9);
+    // Pocos trades: fracción fija
+    assert!((sizer.position_size(&context(&config, 10000.0, None, &trades[..2])) - 50.0).abs() < 1e-9);
+
+    // Sin ventaja estimada no se opera
+    let losing: Vec<Trade> = [10.0, -10.0, -10.0, -10.0, -10.0].iter().map(|p| trade(*p)).collect();
+    assert_eq!(sizer.position_size(&context(&config, 10000.0, None, &losing)), 0.0);
+}
+
+#[test]
+fn test_position_sizing_serialization() {
+    let sizing = PositionSizing::VolatilityTarget(VolatilityTarget { atr_period: 14, atr_multiple: 2.0 });
+    let json = serde_json::to_string(&sizing).unwrap();
+    assert!(json.contains("\"model\":\"volatility_target\""));
+    assert_eq!(serde_json::from_str::<PositionSizing>(&json).unwrap(), sizing);
+    assert_eq!(sizing.atr_period(), Some(14));
+
+    let risk = PositionSizing::RiskPerTrade(RiskPerTrade);
+    let json = serde_json::to_string(&risk).unwrap();
+    assert_eq!(serde_json::from_str::<PositionSizing>(&json).unwrap(), risk);
+
+    // Las configuraciones guardadas sin modelo usan la fracción fija
+    let mut value = serde_json::to_value(BacktestConfig::default()).unwrap();
+    value.as_object_mut().unwrap().remove("position_sizing");
+    let config: BacktestConfig = serde_json::from_value(value).unwrap();
+    assert_eq!(config.position_sizing, PositionSizing::default());
+}
+
+#[tokio::test]
+async fn test_engines_apply_sizing_model() {
+    let candles = create_zigzag_candles(100.0, &[2.0, -2.0, 2.0, -2.0, 2.0, -2.0]);
+    let config = no_cost_config()
+        .with_position_sizing(PositionSizing::FixedQuantity(FixedQuantity { quantity: 7.0 }));
+
+    let polars = PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(vec![crossover_strategy()], candles.clone(), &config)
+        .await
+        .unwrap()
+        .remove(0);
+    let event = EventDrivenBacktestEngine::new()
+        .run_ast(crossover_strategy(), &SingleTimeFrameProvider::new(candles, TimeFrame::H1), &config)
+        .await
+        .unwrap();
+
+    assert!(!polars.trades.is_empty());
+    assert_eq!(polars.trades.len(), event.trades.len());
+    assert!(polars.trades.iter().chain(&event.trades).all(|t| t.size == 7.0));
+    assert!(matches!(polars.metadata.config.position_sizing, PositionSizing::FixedQuantity(_)));
+}
+
+#[tokio::test]
+async fn test_volatility_target_sizes_from_atr() {
+    // Rango de 2.0 y saltos de 2.0 entre cierres: true range y ATR = 3.0
+    let candles = create_zigzag_candles(100.0, &[2.0, -2.0, 2.0, -2.0]);
+    let config = no_cost_config()
+        .with_position_sizing(PositionSizing::VolatilityTarget(VolatilityTarget { atr_period: 5, atr_multiple: 2.0 }));
+
+    let polars = PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(vec![crossover_strategy()], candles.clone(), &config)
+        .await
+        .unwrap()
+        .remove(0);
+    let event = EventDrivenBacktestEngine::new()
+        .run_ast(crossover_strategy(), &SingleTimeFrameProvider::new(candles, TimeFrame::H1), &config)
+        .await
+        .unwrap();
+
+    assert!(!polars.trades.is_empty());
+    let mut balance = config.initial_balance;
+    for (p, e) in polars.trades.iter().zip(&event.trades) {
+        // 2% del balance arriesgado a 2 ATR
+        let expected = balance * 0.02 / (3.0 * 2.0);
+        assert!((p.size - expected).abs() < 1e-6, "size {} expected {}", p.size, expected);
+        assert!((p.size - e.size).abs() < 1e-9);
+        balance += p.pnl;
+    }
+}
@@ -0,0 +1,209 @@
+//! Tests de integración del análisis walk-forward
+
+use darwinx_backtest_engine::*;
+use darwinx_core::{Candle, TimeFrame};
+use darwinx_generator::StrategyAST;
+use darwinx_generator::ast::builder::{ConditionBuilder, StrategyBuilder};
+
+const BASE_TIMESTAMP: i64 = 1609459200000; // 2021-01-01
+const HOUR_MS: i64 = 3_600_000;
+
+fn create_wave_candles(count: usize) -> Vec<Candle> {
+    (0..count)
+        .map(|i| {
+            let close = 100.0 + (i as f64 * 0.1).sin() * 10.0 + i as f64 * 0.02;
+            Candle::new(BASE_TIMESTAMP + i as i64 * HOUR_MS, close, close + 1.0, close - 1.0, close, 1000.0)
+        })
+        .collect()
+}
+
+fn create_crossover_strategy(name: &str) -> StrategyAST {
+    StrategyBuilder::new(name.to_string(), TimeFrame::H1)
+        .add_entry_condition(ConditionBuilder::crosses_above("sma", vec![5.0], "sma", vec![20.0]))
+        .add_exit_condition(ConditionBuilder::crosses_below("sma", vec![5.0], "sma", vec![20.0]))
+        .build()
+}
+
+fn no_risk_config() -> BacktestConfig {
+    BacktestConfig::with_position_size(10000.0, 0.0, 0.0, 1, 0.02, None, None, 0.5)
+}
+
+#[test]
+fn test_rolling_windows_split() {
+    let windows = WalkForwardConfig::new(4, 0.6).split(1000).unwrap();
+
+    assert_eq!(windows.len(), 4);
+    assert_eq!(windows[0].in_sample, 0..271);
+    assert_eq!(windows[0].out_of_sample, 271..452);
+    for pair in windows.windows(2) {
+        // OOS consecutivos y la IS avanza lo mismo que la OOS
+        assert_eq!(pair[0].out_of_sample.end, pair[1].out_of_sample.start);
+        assert_eq!(pair[1].in_sample.start - pair[0].in_sample.start, 181);
+        assert_eq!(pair[1].in_sample.len(), 271);
+    }
+    for window in &windows {
+        assert_eq!(window.in_sample.end, window.out_of_sample.start);
+    }
+    // La última ventana absorbe las velas sobrantes
+    assert_eq!(windows[3].out_of_sample.end, 1000);
+}
+
+#[test]
+fn test_anchored_windows_split() {
+    let windows = WalkForwardConfig::new(3, 0.5).anchored().split(400).unwrap();
+
+    assert_eq!(windows.len(), 3);
+    assert!(windows.iter().all(|w| w.in_sample.start == 0));
+    assert_eq!(windows[0].in_sample, 0..100);
+    assert_eq!(windows[1].in_sample, 0..200);
+    assert_eq!(windows[2].out_of_sample, 300..400);
+}
+
+#[test]
+fn test_invalid_walk_forward_config() {
+    assert!(WalkForwardConfig::new(0, 0.7).split(1000).is_err());
+    assert!(WalkForwardConfig::new(3, 1.0).split(1000).is_err());
+    assert!(WalkForwardConfig::new(3, 0.0).split(1000).is_err());
+    assert!(WalkForwardConfig::new(10, 0.7).split(5).is_err());
+}
+
+#[test]
+fn test_walk_forward_efficiency() {
+    // Mismo retorno por vela dentro y fuera de muestra
+    assert!((walk_forward_efficiency(0.1, 100, 0.05, 50).unwrap() - 1.0).abs() < 1e-12);
+    assert!((walk_forward_efficiency(0.1, 100, -0.01, 50).unwrap() + 0.2).abs() < 1e-12);
+    assert_eq!(walk_forward_efficiency(-0.1, 100, 0.05, 50), None);
+    assert_eq!(walk_forward_efficiency(0.0, 100, 0.05, 50), None);
+}
+
+#[test]
+fn test_run_walk_forward_selects_on_in_sample_only() {
+    let candles = create_wave_candles(900);
+    let config = no_risk_config();
+    let engine = PolarsVectorizedBacktestEngine::new();
+    let walk_forward = WalkForwardConfig::new(3, 0.7);
+    let mut seen_in_sample = Vec::new();
+
+    let report = engine
+        .run_walk_forward(&candles, &walk_forward, &config, ParallelConfig::default(), |window, is_candles| {
+            seen_in_sample.push((window.index, is_candles.len(), is_candles.last().unwrap().timestamp));
+            Ok(vec![
+                create_crossover_strategy(&format!("W{}_A", window.index)),
+                create_crossover_strategy(&format!("W{}_B", window.index)),
+            ])
+        })
+        .unwrap();
+
+    assert_eq!(report.windows.len(), 3);
+    assert_eq!(seen_in_sample.len(), 3);
+    for (window, (index, is_len, is_last)) in report.windows.iter().zip(&seen_in_sample) {
+        assert_eq!(window.window.index, *index);
+        assert_eq!(window.window.in_sample.len(), *is_len);
+        assert_eq!(window.in_sample_end, *is_last);
+        // La selección no ve ninguna vela OOS
+        assert!(window.out_of_sample_start > *is_last);
+        assert_eq!(window.strategies.len(), 2);
+        assert_eq!(window.strategies[0].strategy_name, format!("W{}_A", index));
+        assert!(window.strategies.iter().all(|s| s.window == *index));
+    }
+    assert_eq!(report.strategy_results().count(), 6);
+
+    // La equity OOS encadenada termina en el retorno total OOS
+    let last = report.oos_equity.last().unwrap();
+    assert_eq!(last.timestamp, candles.last().unwrap().timestamp);
+    assert!((last.balance - config.initial_balance * (1.0 + report.oos_total_return)).abs() < 1e-6);
+    let compounded = report
+        .windows
+        .iter()
+        .map(|w| 1.0 + w.strategies[0].out_of_sample_return)
+        .product::<f64>()
+        - 1.0;
+    assert!((report.oos_total_return - compounded).abs() < 1e-9);
+    assert!(report.oos_max_drawdown >= 0.0 && report.oos_max_drawdown <= 1.0);
+}
+
+#[test]
+fn test_out_of_sample_starts_with_warm_indicators() {
+    let candles = create_wave_candles(900);
+    let config = no_risk_config();
+    let engine = PolarsVectorizedBacktestEngine::new();
+    let report = engine
+        .run_walk_forward(&candles, &WalkForwardConfig::new(3, 0.7), &config, ParallelConfig::default(), |window, _| {
+            Ok(vec![create_crossover_strategy(&format!("W{}", window.index))])
+        })
+        .unwrap();
+
+    let mut early_entries = 0;
+    for window in &report.windows {
+        let oos = &window.window.out_of_sample;
+        // Misma simulación que operando la serie completa desde el inicio OOS
+        let expected = engine
+            .run_massive_backtest_parallel(
+                vec![create_crossover_strategy("Reference")],
+                &candles[..oos.end],
+                &config.clone().with_trading_start(window.out_of_sample_start),
+                ParallelConfig::default(),
+            )
+            .unwrap()
+            .remove(0);
+        let result = &window.strategies[0];
+        assert_eq!(result.out_of_sample.total_trades, expected.trades.len());
+        assert_eq!(result.out_of_sample.total_return, expected.metrics.total_return);
+        assert!(expected.trades.iter().all(|t| t.entry_timestamp >= window.out_of_sample_start));
+        assert_eq!(expected.equity_curve[0].timestamp, window.out_of_sample_start);
+        assert_eq!(expected.metadata.total_candles, oos.len());
+
+        // En frío la SMA de 20 no permite entrar en las primeras 20 velas OOS
+        let cold_limit = candles[oos.start + 20].timestamp;
+        early_entries += expected.trades.iter().filter(|t| t.entry_timestamp < cold_limit).count();
+    }
+    assert!(early_entries > 0);
+    assert_eq!(report.oos_equity[0].timestamp, report.windows[0].out_of_sample_start);
+}
+
+#[tokio::test]
+async fn test_trading_start_matches_across_engines() {
+    let candles = create_wave_candles(300);
+    let config = no_risk_config().with_trading_start(candles[120].timestamp);
+
+    let polars = PolarsVectorizedBacktestEngine::new()
+        .run_massive_backtest(vec![create_crossover_strategy("Warm")], candles.clone(), &config)
+        .await
+        .unwrap()
+        .remove(0);
+    let event = EventDrivenBacktestEngine::new()
+        .run_ast(create_crossover_strategy("Warm"), &SingleTimeFrameProvider::new(candles.clone(), TimeFrame::H1), &config)
+        .await
+        .unwrap();
+
+    assert!(!polars.trades.is_empty());
+    assert_eq!(polars.trades.len(), event.trades.len());
+    for (p, e) in polars.trades.iter().zip(&event.trades) {
+        assert!(p.entry_timestamp >= candles[120].timestamp);
+        assert_eq!(p.entry_timestamp, e.entry_timestamp);
+        assert_eq!(p.exit_timestamp, e.exit_timestamp);
+    }
+    assert_eq!(polars.equity_curve.len(), 180);
+    assert_eq!(event.equity_curve.len(), 180);
+    assert_eq!(polars.metadata.start_date, event.metadata.start_date);
+
+    // Sin velas tras el inicio no hay nada que operar
+    let late = no_risk_config().with_trading_start(candles[299].timestamp + 1);
+    assert!(EventDrivenBacktestEngine::new()
+        .run_ast(create_crossover_strategy("Late"), &SingleTimeFrameProvider::new(candles, TimeFrame::H1), &late)
+        .await
+        .is_err());
+}
+
+#[test]
+fn test_run_walk_forward_propagates_selection_error() {
+    let candles = create_wave_candles(300);
+    let result = PolarsVectorizedBacktestEngine::new().run_walk_forward(
+        &candles,
+        &WalkForwardConfig::new(2, 0.5),
+        &no_risk_config(),
+        ParallelConfig::default(),
+        |_, _| Err(BacktestError::StrategyError("no strategies".to_string())),
+    );
+    assert!(result.is_err());
+}
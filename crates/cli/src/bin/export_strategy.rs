@@ -3,7 +3,6 @@
 //!   cargo run --bin export_strategy -- --id 123 --out-dir /tmp/exports
 
 use clap::Parser;
-use darwinx_core::TimeFrame;
 use darwinx_generator::ast::nodes::StrategyAST;
 use darwinx_store::{init_sqlite, StrategyRepository};
 use serde_json::Value;
@@ -28,20 +27,6 @@ struct Config {
     out_dir: Option<String>,
 }
 
-fn timeframe_to_str(tf: TimeFrame) -> &'static str {
-    match tf {
-        TimeFrame::M1 => "1m",
-        TimeFrame::M5 => "5m",
-        TimeFrame::M15 => "15m",
-        TimeFrame::M30 => "30m",
-        TimeFrame::H1 => "1h",
-        TimeFrame::H4 => "4h",
-        TimeFrame::D1 => "1d",
-        TimeFrame::W1 => "1w",
-        TimeFrame::MN1 => "1mo",
-    }
-}
-
 fn extract_pair(strategy_meta: Option<&str>) -> String {
     if let Some(meta_str) = strategy_meta {
         if let Ok(v) = serde_json::from_str::<Value>(meta_str) {
@@ -82,7 +67,7 @@ async fn main() -> anyhow::Result<()> {
 
     let ast: StrategyAST = darwinx_store::helpers::model_to_strategy_ast(&strategy)?;
     let ast_value: Value = serde_json::from_str(ast_json)?;
-    let timeframe_str = timeframe_to_str(ast.timeframe);
+    let timeframe_str = ast.timeframe.to_string();
     let pair = extract_pair(strategy.execution_metadata.as_deref());
 
     let out_dir = config
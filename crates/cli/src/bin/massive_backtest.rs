@@ -11,31 +11,69 @@
 //!     --min-win-rate 0.4 \
 //!     --min-sharpe 0.0
 
-use clap::Parser;
-use darwinx_generator::{RandomGenerator, GeneticGenerator, GeneticConfig};
-use darwinx_core::TimeFrame;
-use darwinx_data::{CsvLoader, ParquetLoader};
+use clap::{Parser, ValueEnum};
+use darwinx_generator::{RandomGenerator, GeneticGenerator, GeneticConfig, TradeDirection};
+use darwinx_core::{BarType, Candle, TimeFrame};
+use darwinx_data::{infer_timeframe, BarBuilder, CsvLoader, DataValidator, ParquetLoader, RepairPolicy, TradeLoader};
 use darwinx_backtest_engine::{
     PolarsVectorizedBacktestEngine,
     BacktestConfig,
     BacktestResult,
+    BacktestError,
+    ParallelConfig,
+    WalkForwardConfig,
+    WalkForwardReport,
+    MonteCarloConfig,
+    MonteCarloReport,
+    run_monte_carlo,
+    PositionSizing,
+    FixedFractional,
+    FixedQuantity,
+    VolatilityTarget,
+    RiskPerTrade,
+    KellyFraction,
+    ScaleOutLevel,
+    CloseOrder,
+    CostModel,
+    MarginConfig,
+    FeeModel,
+    SlippageModel,
+    FundingRates,
+    ScoreSpec,
+    ScoreMetric,
+    RankingMethod,
 };
+use darwinx_backtest_engine::metrics::{
+    RealityCheckConfig,
+    RealityCheckResult,
+    calculate_reality_check,
+    calculate_period_returns,
+    calculate_deflated_sharpe_ratio,
+    calculate_expected_max_sharpe,
+};
+use indicatif::{ProgressBar, ProgressStyle};
 use darwinx_store::{
     init_sqlite,
     StrategyRepository,
     BacktestRepository,
     strategy_ast_to_model,
+    calculate_strategy_hash,
     load_best_strategies_for_genetics,
+    WalkForwardRepository,
+    walk_forward_run_model,
+    walk_forward_result_models,
 };
 use serde_json;
+use std::collections::{HashMap, HashSet};
 use std::fs::{File, create_dir_all};
 use std::io::Write;
 use std::path::Path;
-use chrono::NaiveDate;
+use std::sync::Arc;
+use chrono::{NaiveDate, NaiveTime, Timelike};
 use tokio;
 
 /// Configuración para el pipeline de backtest masivo
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(name = "massive_backtest")]
 #[command(about = "Genera estrategias masivamente, las backtestea y filtra las mejores", long_about = None)]
 struct Config {
@@ -71,6 +109,42 @@ struct Config {
     #[arg(long, default_value_t = 5.0)]
     slippage_bps: f64,
 
+    /// Comisión de las órdenes maker (requiere --taker-fee, ej: 0.0002 = 0.02%)
+    #[arg(long, requires = "taker_fee")]
+    maker_fee: Option<f64>,
+
+    /// Comisión de las órdenes taker (requiere --maker-fee, ej: 0.0005 = 0.05%)
+    #[arg(long, requires = "maker_fee")]
+    taker_fee: Option<f64>,
+
+    /// Slippage adicional en bps por raíz de la participación en el volumen de la vela
+    #[arg(long, conflicts_with = "atr_slippage")]
+    volume_impact_bps: Option<f64>,
+
+    /// Slippage como fracción del ATR (ej: 0.1 = 10% del ATR, usa --exit-atr-period)
+    #[arg(long)]
+    atr_slippage: Option<f64>,
+
+    /// Fichero CSV o Parquet con columnas timestamp y funding_rate de un perpetuo
+    #[arg(long)]
+    funding_file: Option<String>,
+
+    /// Apalancamiento: las posiciones solo bloquean valor/apalancamiento y pueden liquidarse
+    #[arg(long)]
+    leverage: Option<f64>,
+
+    /// Modo de margen con --leverage
+    #[arg(long, value_enum, default_value_t = MarginModeArg::Isolated)]
+    margin_mode: MarginModeArg,
+
+    /// Margen de mantenimiento sobre el valor de la posición (ej: 0.005 = 0.5%)
+    #[arg(long, default_value_t = 0.005)]
+    maintenance_margin: f64,
+
+    /// Comisión de liquidación sobre el valor liquidado (ej: 0.005 = 0.5%)
+    #[arg(long, default_value_t = 0.0)]
+    liquidation_fee: f64,
+
     /// Riesgo por trade como porcentaje del balance (ej: 0.02 = 2%)
     #[arg(long, default_value_t = 0.02)]
     risk_per_trade: f64,
@@ -79,6 +153,38 @@ struct Config {
     #[arg(long, default_value_t = 0.5)]
     position_size: f64,
 
+    /// Modelo de tamaño de posición
+    #[arg(long, value_enum, default_value_t = SizingArg::FixedFractional)]
+    sizing: SizingArg,
+
+    /// Dimensionar sobre el balance actual en lugar del inicial (fixed-fractional)
+    #[arg(long)]
+    sizing_compound: bool,
+
+    /// Unidades por posición (sizing fixed-quantity)
+    #[arg(long, default_value_t = 1.0)]
+    fixed_quantity: f64,
+
+    /// Periodo del ATR (sizing volatility-target)
+    #[arg(long, default_value_t = 14)]
+    atr_period: usize,
+
+    /// Distancia de riesgo en múltiplos de ATR (sizing volatility-target)
+    #[arg(long, default_value_t = 2.0)]
+    atr_multiple: f64,
+
+    /// Fracción de Kelly a usar (sizing kelly, ej: 0.5 = medio Kelly)
+    #[arg(long, default_value_t = 0.5)]
+    kelly_fraction: f64,
+
+    /// Trades recientes para estimar Kelly (0 = todos)
+    #[arg(long, default_value_t = 50)]
+    kelly_lookback: usize,
+
+    /// Trades necesarios antes de aplicar Kelly (antes se usa fracción fija)
+    #[arg(long, default_value_t = 20)]
+    kelly_min_trades: usize,
+
     /// Stop loss como porcentaje del precio de entrada (ej: 0.02 = 2%, 0 = deshabilitado)
     #[arg(long)]
     stop_loss: Option<f64>,
@@ -87,6 +193,96 @@ struct Config {
     #[arg(long)]
     take_profit: Option<f64>,
 
+    /// Trailing stop como porcentaje desde el extremo favorable (ej: 0.03 = 3%)
+    #[arg(long)]
+    trailing_stop: Option<f64>,
+
+    /// Trailing stop a N ATRs del cierre (ej: 3.0)
+    #[arg(long)]
+    atr_trailing_stop: Option<f64>,
+
+    /// Mover el stop a la entrada tras este beneficio (ej: 0.02 = 2%)
+    #[arg(long)]
+    break_even: Option<f64>,
+
+    /// Chandelier exit a N ATRs del máximo/mínimo reciente (ej: 3.0)
+    #[arg(long)]
+    chandelier: Option<f64>,
+
+    /// Velas del máximo/mínimo del chandelier exit
+    #[arg(long, default_value_t = 22)]
+    chandelier_period: usize,
+
+    /// Periodo del ATR de los stops basados en ATR
+    #[arg(long, default_value_t = 14)]
+    exit_atr_period: usize,
+
+    /// Máximo de velas en un trade
+    #[arg(long)]
+    max_bars: Option<usize>,
+
+    /// Cerrar posiciones antes del cierre de sesión (HH:MM en UTC, ej: 21:00)
+    #[arg(long, value_parser = parse_session_close)]
+    session_close: Option<u32>,
+
+    /// Máximo de posiciones simultáneas (cada señal de entrada repetida abre otra)
+    #[arg(long, default_value_t = 1)]
+    max_positions: usize,
+
+    /// Máximo de lotes añadidos a una posición cuando se repite la señal (pyramiding)
+    #[arg(long, default_value_t = 0)]
+    max_adds: usize,
+
+    /// Tamaño de cada añadido respecto al anterior (ej: 0.5 = la mitad)
+    #[arg(long, default_value_t = 1.0)]
+    add_size_decay: f64,
+
+    /// Tomas de beneficio parciales BENEFICIO:FRACCIÓN separadas por comas (ej: 0.02:0.5,0.04:0.5)
+    #[arg(long, value_parser = parse_scale_out_level, value_delimiter = ',')]
+    scale_out: Vec<ScaleOutLevel>,
+
+    /// Orden de cierre de los lotes en las tomas parciales
+    #[arg(long, value_enum, default_value_t = CloseOrderArg::Fifo)]
+    close_order: CloseOrderArg,
+
+    /// Dirección de las estrategias generadas
+    #[arg(long, value_enum, default_value_t = DirectionArg::Long)]
+    direction: DirectionArg,
+
+    /// Número máximo de timeframes por estrategia (1 = solo el del dataset)
+    ///
+    /// Con valores mayores las condiciones pueden evaluarse en múltiplos del timeframe base.
+    /// Requiere velas temporales (no se admite con `--bars` no temporales).
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    max_timeframes: u32,
+
+    /// Construye las velas a partir de trades (timestamp,price,size[,side]) en `--data`
+    ///
+    /// Formato TIPO:UMBRAL: tick:1000, volume:500, dollar:1000000, imbalance:200, range:50,
+    /// renko:25, o un timeframe (ej: 5m) para velas temporales.
+    #[arg(long, value_name = "BAR_TYPE")]
+    bars: Option<BarType>,
+
+    /// Repara los datos antes del backtest (sin esta opción solo se informan los problemas)
+    #[arg(long, value_enum)]
+    repair_data: Option<RepairPolicyArg>,
+
+    /// Costo diario de préstamo/funding para posiciones cortas (ej: 0.0001 = 0.01%/día)
+    #[arg(long, default_value_t = 0.0)]
+    short_borrow_rate: f64,
+
+    /// Memoria máxima para la caché de indicadores compartida entre estrategias (MB, 0 = deshabilitada)
+    #[arg(long, default_value_t = 512)]
+    indicator_cache_mb: usize,
+
+    /// Ejecutar el backtest en paralelo (rayon) con barra de progreso
+    #[arg(long)]
+    parallel: bool,
+
+    /// Número de hilos para el modo paralelo (default: todos los núcleos)
+    #[arg(long)]
+    threads: Option<usize>,
+
     /// Filtros de calidad
     /// Mínimo número de trades requeridos
     #[arg(long, default_value_t = 10)]
@@ -108,10 +304,34 @@ struct Config {
     #[arg(long, default_value_t = 0.5)]
     max_drawdown: f64,
 
+    /// Retorno mínimo por encima de comprar y mantener (ej: 0 = batir al benchmark)
+    #[arg(long)]
+    min_excess_return: Option<f64>,
+
+    /// Alpha anualizado mínimo frente a comprar y mantener
+    #[arg(long)]
+    min_alpha: Option<f64>,
+
+    /// Beta máxima frente a comprar y mantener (ej: 0.5)
+    #[arg(long)]
+    max_beta: Option<f64>,
+
     /// Pesos para el score compuesto (Sharpe, Sortino, Profit Factor, Return, Drawdown)
     #[arg(long, value_delimiter = ',', num_args = 5, default_values_t = vec![0.3, 0.2, 0.2, 0.15, 0.15])]
     score_weights: Vec<f64>,
 
+    /// Usar el retorno en exceso sobre comprar y mantener en el término Return del score
+    #[arg(long)]
+    score_excess_return: bool,
+
+    /// Fichero TOML con la especificación del score: métricas, normalización, pesos y restricciones (reemplaza --score-weights)
+    #[arg(long, conflicts_with = "score_weights")]
+    score_spec: Option<String>,
+
+    /// Rankear por frentes de Pareto sobre las métricas del score (el score desempata dentro de cada frente)
+    #[arg(long)]
+    pareto: bool,
+
     /// Guardar resultados en archivo JSON (opcional, solo para consulta rápida)
     #[arg(short, long)]
     output: Option<String>,
@@ -144,6 +364,54 @@ struct Config {
     #[arg(long, default_value_t = 10)]
     evolve_elite_size: usize,
 
+    /// Generaciones sin mejora antes de detener la evolución (0 = sin parada anticipada)
+    #[arg(long, default_value_t = 25)]
+    evolve_patience: usize,
+
+    /// Análisis walk-forward: número de ventanas in-sample/out-of-sample (reemplaza el ranking sobre todo el período)
+    #[arg(long)]
+    walk_forward: Option<usize>,
+
+    /// Proporción in-sample de cada ventana walk-forward (ej: 0.7 = 70% IS, 30% OOS)
+    #[arg(long, default_value_t = 0.7)]
+    wf_in_sample_ratio: f64,
+
+    /// Ventanas in-sample ancladas al inicio de los datos (por defecto: rolling)
+    #[arg(long)]
+    wf_anchored: bool,
+
+    /// Deflated Sharpe Ratio mínimo de las top estrategias (ej: 0.95; corrige por el número de estrategias probadas)
+    #[arg(long)]
+    min_dsr: Option<f64>,
+
+    /// Test Reality Check/SPA de las top estrategias contra el benchmark: remuestreos bootstrap
+    #[arg(long)]
+    reality_check: Option<usize>,
+
+    /// Benchmark del test Reality Check/SPA
+    #[arg(long, value_enum, default_value_t = BenchmarkArg::BuyAndHold)]
+    rc_benchmark: BenchmarkArg,
+
+    /// Análisis Monte Carlo de las top estrategias: simulaciones por método (descarta las no robustas)
+    #[arg(long)]
+    monte_carlo: Option<usize>,
+
+    /// Drawdown que se considera ruina en el análisis Monte Carlo (ej: 0.5 = 50%)
+    #[arg(long, default_value_t = 0.5)]
+    mc_ruin_threshold: f64,
+
+    /// Probabilidad de ruina máxima permitida en el análisis Monte Carlo (ej: 0.05 = 5%)
+    #[arg(long, default_value_t = 0.05)]
+    mc_max_ruin: f64,
+
+    /// Retorno mínimo del percentil inferior (5%) del balance final Monte Carlo
+    #[arg(long, default_value_t = 0.0)]
+    mc_min_return: f64,
+
+    /// Semilla del análisis Monte Carlo (resultados reproducibles)
+    #[arg(long)]
+    mc_seed: Option<u64>,
+
     /// Mostrar top N estrategias en consola
     #[arg(long, default_value_t = 10)]
     show_top: usize,
@@ -153,6 +421,108 @@ struct Config {
     verbose: bool,
 }
 
+impl Config {
+    /// Filtros de calidad sobre las métricas de un backtest
+    fn passes_filters(&self, m: &darwinx_backtest_engine::BacktestMetrics) -> bool {
+        m.total_trades >= self.min_trades &&
+        m.win_rate >= self.min_win_rate &&
+        m.sharpe_ratio >= self.min_sharpe &&
+        m.total_return >= self.min_return &&
+        m.max_drawdown <= self.max_drawdown &&
+        self.min_excess_return.is_none_or(|min| m.excess_return >= min) &&
+        self.min_alpha.is_none_or(|min| m.alpha >= min) &&
+        self.max_beta.is_none_or(|max| m.beta <= max)
+    }
+
+    /// Score según `--score-spec` o `--score-weights`, con `--score-excess-return` y `--pareto`
+    fn score(&self) -> anyhow::Result<ScoreSpec> {
+        let mut spec = match &self.score_spec {
+            Some(path) => ScoreSpec::load(path)?,
+            None => {
+                let weights: [f64; 5] = self.score_weights.as_slice().try_into()?;
+                let spec = ScoreSpec::from_weights(&weights);
+                spec.validate()?;
+                spec
+            }
+        };
+        if self.score_excess_return {
+            spec = spec.with_metric_replaced(ScoreMetric::TotalReturn, ScoreMetric::ExcessReturn);
+        }
+        if self.pareto {
+            spec = spec.with_ranking(RankingMethod::Pareto);
+        }
+        Ok(spec)
+    }
+
+    /// Filtro de robustez sobre el resultado del análisis Monte Carlo
+    fn passes_monte_carlo(&self, report: &MonteCarloReport) -> bool {
+        let p5_return = report.combined.final_balance.lower / report.initial_balance - 1.0;
+        report.combined.probability_of_ruin <= self.mc_max_ruin &&
+        p5_return >= self.mc_min_return
+    }
+
+    /// Modelo de tamaño de posición seleccionado con `--sizing` y sus parámetros
+    fn position_sizing(&self) -> PositionSizing {
+        match self.sizing {
+            SizingArg::FixedFractional => PositionSizing::FixedFractional(FixedFractional {
+                compound: self.sizing_compound,
+            }),
+            SizingArg::FixedQuantity => PositionSizing::FixedQuantity(FixedQuantity {
+                quantity: self.fixed_quantity,
+            }),
+            SizingArg::VolatilityTarget => PositionSizing::VolatilityTarget(VolatilityTarget {
+                atr_period: self.atr_period,
+                atr_multiple: self.atr_multiple,
+            }),
+            SizingArg::RiskPerTrade => PositionSizing::RiskPerTrade(RiskPerTrade),
+            SizingArg::Kelly => PositionSizing::Kelly(KellyFraction {
+                fraction: self.kelly_fraction,
+                lookback: self.kelly_lookback,
+                min_trades: self.kelly_min_trades,
+            }),
+        }
+    }
+
+    /// Margen según `--leverage`, `--margin-mode`, `--maintenance-margin` y `--liquidation-fee`
+    fn margin(&self) -> Option<MarginConfig> {
+        self.leverage.map(|leverage| {
+            let margin = match self.margin_mode {
+                MarginModeArg::Isolated => MarginConfig::isolated(leverage),
+                MarginModeArg::Cross => MarginConfig::cross(leverage),
+            };
+            margin
+                .with_maintenance_margin_rate(self.maintenance_margin)
+                .with_liquidation_fee_rate(self.liquidation_fee)
+        })
+    }
+
+    /// Modelo de costes según `--maker-fee`/`--taker-fee`, el slippage elegido y `--funding-file`
+    fn cost_model(&self) -> anyhow::Result<CostModel> {
+        let mut model = CostModel::default();
+        if let (Some(maker_rate), Some(taker_rate)) = (self.maker_fee, self.taker_fee) {
+            model = model.with_fees(FeeModel::MakerTaker { maker_rate, taker_rate });
+        }
+        if let Some(impact_bps) = self.volume_impact_bps {
+            model = model.with_slippage(SlippageModel::VolumeImpact {
+                base_bps: self.slippage_bps,
+                impact_bps,
+                max_bps: None,
+            });
+        } else if let Some(multiple) = self.atr_slippage {
+            model = model.with_slippage(SlippageModel::Atr { multiple, period: self.exit_atr_period });
+        }
+        if let Some(path) = &self.funding_file {
+            let funding = if path.ends_with(".parquet") {
+                FundingRates::load_parquet(path)?
+            } else {
+                FundingRates::load_csv(path)?
+            };
+            model = model.with_funding(funding);
+        }
+        Ok(model)
+    }
+}
+
 /// Parsea una fecha en formato YYYY-MM-DD a timestamp en milisegundos
 fn parse_date(date_str: &str) -> anyhow::Result<i64> {
     let dt = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
@@ -162,6 +532,120 @@ fn parse_date(date_str: &str) -> anyhow::Result<i64> {
     Ok(datetime.and_utc().timestamp_millis())
 }
 
+/// Parsea una hora HH:MM (UTC) a minutos desde medianoche
+fn parse_session_close(time_str: &str) -> Result<u32, String> {
+    let time = NaiveTime::parse_from_str(time_str, "%H:%M")
+        .map_err(|e| format!("Hora inválida: {}. Use HH:MM (ej: 21:00)", e))?;
+    Ok(time.hour() * 60 + time.minute())
+}
+
+/// Parsea un nivel de toma parcial BENEFICIO:FRACCIÓN (ej: 0.02:0.5)
+fn parse_scale_out_level(level_str: &str) -> Result<ScaleOutLevel, String> {
+    let (profit, fraction) = level_str
+        .split_once(':')
+        .ok_or_else(|| format!("Nivel inválido: {}. Use BENEFICIO:FRACCIÓN (ej: 0.02:0.5)", level_str))?;
+    let profit_percent: f64 = profit.trim().parse().map_err(|e| format!("Beneficio inválido '{}': {}", profit, e))?;
+    let fraction: f64 = fraction.trim().parse().map_err(|e| format!("Fracción inválida '{}': {}", fraction, e))?;
+    if profit_percent <= 0.0 || !(0.0..=1.0).contains(&fraction) || fraction == 0.0 {
+        return Err(format!("Nivel inválido: {}. El beneficio debe ser > 0 y la fracción estar en (0, 1]", level_str));
+    }
+    Ok(ScaleOutLevel { profit_percent, fraction })
+}
+
+/// Dirección de trading aceptada en la línea de comandos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DirectionArg {
+    /// Solo posiciones largas
+    Long,
+    /// Solo posiciones cortas
+    Short,
+    /// Posiciones largas y cortas
+    #[value(alias = "long-short")]
+    Both,
+}
+
+impl From<DirectionArg> for TradeDirection {
+    fn from(direction: DirectionArg) -> Self {
+        match direction {
+            DirectionArg::Long => TradeDirection::LongOnly,
+            DirectionArg::Short => TradeDirection::ShortOnly,
+            DirectionArg::Both => TradeDirection::LongShort,
+        }
+    }
+}
+
+/// Modelo de tamaño de posición aceptado en la línea de comandos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SizingArg {
+    /// Fracción fija del balance (--position-size)
+    FixedFractional,
+    /// Cantidad fija de unidades (--fixed-quantity)
+    FixedQuantity,
+    /// Riesgo --risk-per-trade a --atr-multiple ATRs
+    VolatilityTarget,
+    /// Riesgo --risk-per-trade hasta el --stop-loss
+    RiskPerTrade,
+    /// Fracción de Kelly estimada sobre los trades cerrados
+    Kelly,
+}
+
+/// Orden de cierre de lotes aceptado en la línea de comandos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CloseOrderArg {
+    /// Primero los lotes más antiguos
+    Fifo,
+    /// Primero los lotes más recientes
+    Lifo,
+}
+
+impl From<CloseOrderArg> for CloseOrder {
+    fn from(order: CloseOrderArg) -> Self {
+        match order {
+            CloseOrderArg::Fifo => CloseOrder::Fifo,
+            CloseOrderArg::Lifo => CloseOrder::Lifo,
+        }
+    }
+}
+
+/// Modo de margen aceptado en la línea de comandos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MarginModeArg {
+    /// Margen aislado por posición
+    Isolated,
+    /// Margen cruzado sobre el balance de la cuenta
+    Cross,
+}
+
+/// Política de reparación de datos aceptada en la línea de comandos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum RepairPolicyArg {
+    /// Descarta las velas inválidas (los huecos se mantienen)
+    Drop,
+    /// Rellena huecos y velas inválidas con el cierre anterior
+    ForwardFill,
+    /// Rellena huecos y velas inválidas interpolando el cierre
+    Interpolate,
+}
+
+impl From<RepairPolicyArg> for RepairPolicy {
+    fn from(policy: RepairPolicyArg) -> Self {
+        match policy {
+            RepairPolicyArg::Drop => RepairPolicy::Drop,
+            RepairPolicyArg::ForwardFill => RepairPolicy::ForwardFill,
+            RepairPolicyArg::Interpolate => RepairPolicy::Interpolate,
+        }
+    }
+}
+
+/// Benchmark contra el que se comparan las estrategias
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BenchmarkArg {
+    /// Sin exposición (retorno 0)
+    Cash,
+    /// Comprar y mantener el activo
+    BuyAndHold,
+}
+
 /// Formatea un timestamp en milisegundos a string legible
 fn format_timestamp(ts: i64) -> String {
     if let Some(dt) = chrono::DateTime::from_timestamp_millis(ts) {
@@ -172,35 +656,30 @@ fn format_timestamp(ts: i64) -> String {
 }
 
 /// Detecta timeframe del archivo de datos
-fn detect_timeframe_from_data(path: &str) -> TimeFrame {
-    let lower = path.to_lowercase();
-    if lower.contains("_5m") {
-        TimeFrame::M5
-    } else if lower.contains("_15m") {
-        TimeFrame::M15
-    } else if lower.contains("_30m") {
-        TimeFrame::M30
-    } else if lower.contains("_4h") {
-        TimeFrame::H4
-    } else if lower.contains("_1d") {
-        TimeFrame::D1
-    } else {
-        TimeFrame::H1
-    }
+///
+/// Busca en el nombre un token separado por `_` que sea un timeframe (ej: `btc_6h.csv`);
+/// None si no lo hay.
+fn detect_timeframe_from_data(path: &str) -> Option<TimeFrame> {
+    Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.split('_').skip(1).find_map(|token| token.parse().ok()))
 }
 
-fn timeframe_to_str(tf: TimeFrame) -> &'static str {
-    match tf {
-        TimeFrame::M1 => "1m",
-        TimeFrame::M5 => "5m",
-        TimeFrame::M15 => "15m",
-        TimeFrame::M30 => "30m",
-        TimeFrame::H1 => "1h",
-        TimeFrame::H4 => "4h",
-        TimeFrame::D1 => "1d",
-        TimeFrame::W1 => "1w",
-        TimeFrame::MN1 => "1mo",
+/// Carga trades (CSV o Parquet) y construye velas del tipo de barra indicado
+fn load_trade_bars(path: &str, bar_type: BarType, verbose: bool) -> anyhow::Result<Vec<Candle>> {
+    let trades = TradeLoader::load(path).inspect_err(|e| {
+        eprintln!("   ❌ Error al cargar trades: {}", e);
+        eprintln!("   💡 Columnas: timestamp, price, size y opcionalmente side (buy/sell)");
+    })?;
+    let candles = BarBuilder::new(bar_type).build(&trades)?;
+
+    if verbose {
+        println!("   ✅ Cargados {} trades desde {}", trades.len(), path);
+        println!("   🧱 Construidas {} barras {}", candles.len(), bar_type);
     }
+
+    Ok(candles)
 }
 
 /// Formatea milisegundos a DD:HH:MM:SS (redondeando al segundo más cercano)
@@ -213,6 +692,431 @@ fn format_duration_ms(ms: f64) -> String {
     format!("{:02}:{:02}:{:02}:{:02}", days, hours, minutes, seconds)
 }
 
+/// Muestra las estadísticas de la caché de indicadores del motor
+fn print_indicator_cache_stats(engine: &PolarsVectorizedBacktestEngine) {
+    let stats = engine.indicator_cache_stats();
+    println!("   🗃️  Caché de indicadores: {} hits, {} misses ({:.1}% aciertos), {} series ({:.1} MB), {} desalojadas",
+        stats.hits,
+        stats.misses,
+        stats.hit_rate() * 100.0,
+        stats.entries,
+        stats.memory_bytes as f64 / (1024.0 * 1024.0),
+        stats.evictions);
+}
+
+/// Ejecuta el backtest masivo en paralelo mostrando una barra de progreso
+///
+/// El trabajo de rayon es bloqueante, por lo que se ejecuta fuera del runtime
+/// de tokio con `spawn_blocking`. Ni los trades ni la curva de equity se
+/// conservan: el pipeline solo usa las métricas.
+async fn run_parallel_backtest(
+    engine: Arc<PolarsVectorizedBacktestEngine>,
+    strategies: Vec<darwinx_generator::StrategyAST>,
+    candles: Vec<darwinx_core::Candle>,
+    backtest_config: BacktestConfig,
+    threads: Option<usize>,
+) -> Result<Vec<BacktestResult>, BacktestError> {
+    let progress_bar = ProgressBar::new(strategies.len() as u64);
+    progress_bar.set_style(
+        ProgressStyle::with_template("   {bar:40.cyan/blue} {pos}/{len} estrategias ({per_sec}, ETA {eta})")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+    );
+
+    let bar = progress_bar.clone();
+    let mut options = ParallelConfig::default()
+        .without_trades()
+        .without_equity_curve()
+        .with_progress(move |_| bar.inc(1));
+    if let Some(threads) = threads {
+        options = options.with_threads(threads);
+    }
+
+    let results = tokio::task::spawn_blocking(move || {
+        engine.run_massive_backtest_parallel(strategies, &candles, &backtest_config, options)
+    })
+    .await
+    .map_err(|e| BacktestError::ExecutionError(format!("Parallel backtest task failed: {}", e)))?;
+    progress_bar.finish_and_clear();
+    results
+}
+
+/// Re-ejecuta el backtest de las estrategias conservando sus trades
+///
+/// El backtest masivo descarta los trades; los análisis de robustez de las top
+/// estrategias los necesitan.
+async fn run_detailed_backtest(
+    engine: Arc<PolarsVectorizedBacktestEngine>,
+    strategies: Vec<darwinx_generator::StrategyAST>,
+    candles: Vec<darwinx_core::Candle>,
+    backtest_config: BacktestConfig,
+    threads: Option<usize>,
+) -> Result<Vec<BacktestResult>, BacktestError> {
+    tokio::task::spawn_blocking(move || {
+        let mut options = ParallelConfig::default().without_equity_curve();
+        if let Some(threads) = threads {
+            options = options.with_threads(threads);
+        }
+        engine.run_massive_backtest_parallel(strategies, &candles, &backtest_config, options)
+    })
+    .await
+    .map_err(|e| BacktestError::ExecutionError(format!("Detailed backtest task failed: {}", e)))?
+}
+
+/// Test Reality Check/SPA de las estrategias contra el benchmark, con retornos por vela
+fn run_reality_check(
+    results: &[BacktestResult],
+    candles: &[darwinx_core::Candle],
+    benchmark: BenchmarkArg,
+    iterations: usize,
+) -> Result<RealityCheckResult, BacktestError> {
+    let timestamps: Vec<i64> = candles.iter().map(|c| c.timestamp).collect();
+    let strategy_returns: Vec<Vec<f64>> = results
+        .iter()
+        .map(|r| calculate_period_returns(&r.trades, r.metadata.initial_balance, &timestamps))
+        .collect();
+    let benchmark_returns: Vec<f64> = match benchmark {
+        BenchmarkArg::Cash => vec![0.0; candles.len()],
+        BenchmarkArg::BuyAndHold => std::iter::once(0.0)
+            .chain(candles.windows(2).map(|w| w[1].close / w[0].close - 1.0))
+            .collect(),
+    };
+
+    let rc_config = RealityCheckConfig {
+        bootstrap_iterations: iterations,
+        ..Default::default()
+    };
+    calculate_reality_check(&strategy_returns, &benchmark_returns, &rc_config)
+}
+
+/// Análisis Monte Carlo de cada resultado con trades
+async fn run_monte_carlo_analysis(
+    results: Vec<BacktestResult>,
+    mc_config: MonteCarloConfig,
+) -> Result<HashMap<String, MonteCarloReport>, BacktestError> {
+    tokio::task::spawn_blocking(move || {
+        let mut reports = HashMap::new();
+        for result in results.iter().filter(|r| !r.trades.is_empty()) {
+            reports.insert(result.strategy_name.clone(), run_monte_carlo(result, &mc_config)?);
+        }
+        Ok(reports)
+    })
+    .await
+    .map_err(|e| BacktestError::ExecutionError(format!("Monte Carlo task failed: {}", e)))?
+}
+
+/// Fitness de la evolución genética: backtestea los individuos nuevos de cada generación
+///
+/// Los resultados se cachean por el hash de la estructura de la estrategia
+/// (sin el nombre), así que los elites y los hijos repetidos no se vuelven a backtestear.
+struct EvolutionFitness {
+    engine: Arc<PolarsVectorizedBacktestEngine>,
+    candles: Vec<darwinx_core::Candle>,
+    backtest_config: BacktestConfig,
+    score: ScoreSpec,
+    threads: Option<usize>,
+    cache: HashMap<String, BacktestResult>,
+    /// Métricas de la población inicial: normalizan el score de todas las generaciones
+    reference: Option<Vec<darwinx_backtest_engine::BacktestMetrics>>,
+    cache_hits: usize,
+    backtests: usize,
+}
+
+impl EvolutionFitness {
+    fn new(
+        engine: Arc<PolarsVectorizedBacktestEngine>,
+        candles: Vec<darwinx_core::Candle>,
+        backtest_config: BacktestConfig,
+        score: ScoreSpec,
+        threads: Option<usize>,
+    ) -> Self {
+        Self {
+            engine,
+            candles,
+            backtest_config,
+            score,
+            threads,
+            cache: HashMap::new(),
+            reference: None,
+            cache_hits: 0,
+            backtests: 0,
+        }
+    }
+
+    fn strategy_key(strategy: &darwinx_generator::StrategyAST) -> String {
+        let mut anonymous = strategy.clone();
+        anonymous.name.clear();
+        calculate_strategy_hash(&anonymous)
+    }
+
+    /// Calcula el fitness de una generación (mismo orden que `population`)
+    ///
+    /// El score se normaliza contra la primera generación evaluada con trades, de
+    /// modo que una estrategia conserva su fitness de una generación a otra (el
+    /// elitismo y la parada anticipada comparan fitness entre generaciones). Las
+    /// estrategias sin trades o que no cumplen las restricciones quedan a -inf.
+    fn evaluate(&mut self, population: &[darwinx_generator::StrategyAST]) -> Vec<f64> {
+        let keys: Vec<String> = population.iter().map(Self::strategy_key).collect();
+
+        let mut pending_keys: Vec<String> = Vec::new();
+        let mut pending: Vec<darwinx_generator::StrategyAST> = Vec::new();
+        for (key, strategy) in keys.iter().zip(population) {
+            if self.cache.contains_key(key) || pending_keys.contains(key) {
+                self.cache_hits += 1;
+            } else {
+                pending_keys.push(key.clone());
+                pending.push(strategy.clone());
+            }
+        }
+
+        if !pending.is_empty() {
+            self.backtests += pending.len();
+            let mut options = ParallelConfig::default().without_trades().without_equity_curve();
+            if let Some(threads) = self.threads {
+                options = options.with_threads(threads);
+            }
+            match self.engine.run_massive_backtest_parallel(pending, &self.candles, &self.backtest_config, options) {
+                Ok(results) => self.cache.extend(pending_keys.into_iter().zip(results)),
+                Err(e) => eprintln!("   ⚠️  Error al backtestear generación: {}", e),
+            }
+        }
+
+        // Sin trades no hay métricas que comparar
+        let scored: Vec<(usize, &darwinx_backtest_engine::BacktestMetrics)> = keys
+            .iter()
+            .enumerate()
+            .filter_map(|(i, key)| self.cache.get(key).map(|result| (i, &result.metrics)))
+            .filter(|(_, m)| m.total_trades > 0)
+            .collect();
+        let metrics: Vec<&darwinx_backtest_engine::BacktestMetrics> = scored.iter().map(|(_, m)| *m).collect();
+        if self.reference.is_none() && !metrics.is_empty() {
+            self.reference = Some(metrics.iter().map(|&m| m.clone()).collect());
+        }
+        let reference: Vec<&darwinx_backtest_engine::BacktestMetrics> =
+            self.reference.iter().flatten().collect();
+        let mut fitness = vec![f64::NEG_INFINITY; population.len()];
+        for ((i, _), score) in scored.iter().zip(self.score.score_against(&metrics, &reference)) {
+            if let Some(score) = score {
+                fitness[*i] = score;
+            }
+        }
+        fitness
+    }
+
+    /// Métricas de cada estrategia distinta backtesteada, salvo las de `exclude`
+    fn evaluated_metrics<'a>(
+        &'a self,
+        exclude: &[darwinx_generator::StrategyAST],
+    ) -> impl Iterator<Item = &'a darwinx_backtest_engine::BacktestMetrics> {
+        let excluded: HashSet<String> = exclude.iter().map(Self::strategy_key).collect();
+        self.cache
+            .iter()
+            .filter(move |(key, _)| !excluded.contains(*key))
+            .map(|(_, result)| &result.metrics)
+    }
+
+    /// Resultado cacheado de una estrategia, con su nombre
+    fn result_for(&self, strategy: &darwinx_generator::StrategyAST) -> Option<BacktestResult> {
+        self.cache.get(&Self::strategy_key(strategy)).map(|result| BacktestResult {
+            strategy_name: strategy.name.clone(),
+            ..result.clone()
+        })
+    }
+}
+
+/// Selecciona estrategias sobre las velas in-sample de una ventana walk-forward
+///
+/// Backtestea los candidatos, aplica los filtros de calidad, rankea con `score`
+/// y, si se pidió evolución, evoluciona las seleccionadas sobre las mismas velas. Retorna como máximo `config.top` estrategias, la mejor primero.
+fn select_in_sample(
+    config: &Config,
+    score: &ScoreSpec,
+    engine: &Arc<PolarsVectorizedBacktestEngine>,
+    candidates: &[darwinx_generator::StrategyAST],
+    is_candles: &[darwinx_core::Candle],
+    backtest_config: &BacktestConfig,
+    direction: TradeDirection,
+) -> Result<Vec<darwinx_generator::StrategyAST>, BacktestError> {
+    let threads = if config.parallel { config.threads } else { Some(1) };
+    let mut options = ParallelConfig::default().without_trades().without_equity_curve();
+    if let Some(threads) = threads {
+        options = options.with_threads(threads);
+    }
+    let results = engine.run_massive_backtest_parallel(candidates.to_vec(), is_candles, backtest_config, options)?;
+
+    // Los resultados salen en el mismo orden que los candidatos
+    let filtered: Vec<usize> = (0..results.len()).filter(|&i| config.passes_filters(&results[i].metrics)).collect();
+    let metrics: Vec<&darwinx_backtest_engine::BacktestMetrics> = filtered.iter().map(|&i| &results[i].metrics).collect();
+    let selected: Vec<darwinx_generator::StrategyAST> = score
+        .rank(&metrics)
+        .iter()
+        .take(config.top)
+        .map(|ranked| candidates[filtered[ranked.index]].clone())
+        .collect();
+
+    let Some(generations) = config.evolve.filter(|_| !selected.is_empty()) else {
+        return Ok(selected);
+    };
+
+    let genetic_gen = GeneticGenerator::new(GeneticConfig {
+        population_size: config.evolve_population,
+        generations,
+        mutation_rate: config.evolve_mutation_rate,
+        elite_size: config.evolve_elite_size,
+        tournament_size: 3,
+        patience: config.evolve_patience,
+        min_improvement: 0.0,
+    })
+    .with_direction(direction)
+    .with_max_timeframes(config.max_timeframes as usize);
+    let mut fitness = EvolutionFitness::new(
+        engine.clone(),
+        is_candles.to_vec(),
+        backtest_config.clone(),
+        score.clone(),
+        threads,
+    );
+    let evolution = genetic_gen.evolve_batch(selected, |population| fitness.evaluate(population));
+
+    Ok(evolution
+        .population
+        .into_iter()
+        .zip(evolution.fitness)
+        .filter(|(strategy, score)| {
+            score.is_finite() && fitness.result_for(strategy).is_some_and(|r| config.passes_filters(&r.metrics))
+        })
+        .take(config.top)
+        .map(|(strategy, _)| strategy)
+        .collect())
+}
+
+/// Ejecuta el pipeline en modo walk-forward y guarda el informe
+#[allow(clippy::too_many_arguments)]
+async fn run_walk_forward_mode(
+    config: &Config,
+    score: &ScoreSpec,
+    engine: Arc<PolarsVectorizedBacktestEngine>,
+    strategies: Vec<darwinx_generator::StrategyAST>,
+    candles: Vec<darwinx_core::Candle>,
+    backtest_config: BacktestConfig,
+    direction: TradeDirection,
+    dataset_label: &str,
+    windows: usize,
+) -> anyhow::Result<()> {
+    let mut walk_forward = WalkForwardConfig::new(windows, config.wf_in_sample_ratio);
+    if config.wf_anchored {
+        walk_forward = walk_forward.anchored();
+    }
+
+    if config.verbose {
+        println!("🔁 FASE 4: Análisis walk-forward ({} ventanas {}, {:.0}% in-sample)...",
+            windows,
+            walk_forward.mode.as_str(),
+            walk_forward.in_sample_ratio * 100.0);
+    }
+
+    let bar_type = backtest_config.bar_type;
+    let start_time = std::time::Instant::now();
+    let (report, selected_asts): (WalkForwardReport, HashMap<String, darwinx_generator::StrategyAST>) = {
+        let config = config.clone();
+        let score = score.clone();
+        let walk_forward = walk_forward.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut selected_asts = HashMap::new();
+            let mut options = ParallelConfig::default();
+            if let Some(threads) = if config.parallel { config.threads } else { Some(1) } {
+                options = options.with_threads(threads);
+            }
+            let report = engine.run_walk_forward(&candles, &walk_forward, &backtest_config, options, |window, is_candles| {
+                let selected = select_in_sample(&config, &score, &engine, &strategies, is_candles, &backtest_config, direction)?;
+                if config.verbose {
+                    println!("   🪟 Ventana {}: {} velas IS, {} velas OOS, {} estrategias seleccionadas",
+                        window.index + 1,
+                        window.in_sample.len(),
+                        window.out_of_sample.len(),
+                        selected.len());
+                }
+                for strategy in &selected {
+                    selected_asts.insert(strategy.name.clone(), strategy.clone());
+                }
+                Ok(selected)
+            })?;
+            Ok::<_, BacktestError>((report, selected_asts))
+        })
+        .await??
+    };
+
+    println!("✅ Walk-forward completado en {:.2}s", start_time.elapsed().as_secs_f64());
+    for window in &report.windows {
+        match window.strategies.first() {
+            Some(traded) => println!(
+                "   Ventana {} | IS {} - {} | OOS {} - {} | {} | IS: {:.2}% | OOS: {:.2}% | WFE: {}",
+                window.window.index + 1,
+                format_timestamp(window.in_sample_start),
+                format_timestamp(window.in_sample_end),
+                format_timestamp(window.out_of_sample_start),
+                format_timestamp(window.out_of_sample_end),
+                traded.strategy_name,
+                traded.in_sample_return * 100.0,
+                traded.out_of_sample_return * 100.0,
+                traded.efficiency.map(|e| format!("{:.2}", e)).unwrap_or_else(|| "N/A".to_string())),
+            None => println!("   Ventana {} | sin estrategias que pasen los filtros in-sample", window.window.index + 1),
+        }
+    }
+    println!("\n📊 Resumen walk-forward:");
+    println!("   Retorno OOS encadenado: {:.2}%", report.oos_total_return * 100.0);
+    println!("   Max drawdown OOS:       {:.2}%", report.oos_max_drawdown * 100.0);
+    println!("   Eficiencia media (WFE): {}",
+        report.efficiency.map(|e| format!("{:.2}", e)).unwrap_or_else(|| "N/A".to_string()));
+
+    if !config.no_db {
+        let pool = init_sqlite(&config.db_path).await?;
+        let strategy_repo = StrategyRepository::new(pool.clone());
+        let walk_forward_repo = WalkForwardRepository::new(pool);
+
+        let execution_metadata = serde_json::json!({
+            "data_file": config.data,
+            "start_date": config.start_date,
+            "end_date": config.end_date,
+            "strategies_generated": config.strategies,
+            "top_n": config.top,
+            "evolve_generations": config.evolve,
+            "max_timeframes": config.max_timeframes,
+            "bar_type": bar_type,
+            "score": score,
+            "executed_at": chrono::Utc::now().to_rfc3339(),
+        });
+        let run = walk_forward_run_model(&report, &config.data, dataset_label, Some(execution_metadata.clone()));
+        let run_id = walk_forward_repo.create_run(&run).await?;
+
+        // Las estrategias operadas OOS se guardan para poder enlazar sus resultados
+        let mut traded_ids: HashMap<String, i64> = HashMap::new();
+        for window in &report.windows {
+            let Some(traded) = window.strategies.first() else { continue };
+            let Some(strategy_ast) = selected_asts.get(&traded.strategy_name) else { continue };
+            if traded_ids.contains_key(&traded.strategy_name) {
+                continue;
+            }
+            let mut strategy_model = strategy_ast_to_model(strategy_ast, Some(&traded.out_of_sample), Some(execution_metadata.clone()));
+            strategy_model.is_best = Some(0);
+            match strategy_repo.create_or_update_best(&strategy_model).await {
+                Ok(strategy_id) => {
+                    traded_ids.insert(traded.strategy_name.clone(), strategy_id);
+                }
+                Err(e) => eprintln!("   ⚠️  Error al guardar estrategia {}: {}", traded.strategy_name, e),
+            }
+        }
+
+        for mut result in walk_forward_result_models(&report, run_id) {
+            result.strategy_id = traded_ids.get(&result.strategy_name).copied();
+            walk_forward_repo.create_result(&result).await?;
+        }
+
+        println!("\n💾 Walk-forward guardado en SQLite (run {}): {}", run_id, config.db_path);
+    }
+
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let config = Config::parse();
@@ -236,17 +1140,87 @@ async fn main() -> anyhow::Result<()> {
         println!("   Balance inicial:      ${:.2}", config.initial_balance);
         println!("   Comisión:            {:.4}%", config.commission_rate * 100.0);
         println!("   Slippage:            {:.2} bps", config.slippage_bps);
+        if let (Some(maker), Some(taker)) = (config.maker_fee, config.taker_fee) {
+            println!("   Comisión maker/taker: {:.4}% / {:.4}%", maker * 100.0, taker * 100.0);
+        }
+        if let Some(impact) = config.volume_impact_bps {
+            println!("   Impacto de volumen:  {:.2} bps x sqrt(participación)", impact);
+        }
+        if let Some(multiple) = config.atr_slippage {
+            println!("   Slippage ATR:        {:.2} x ATR({})", multiple, config.exit_atr_period);
+        }
+        if let Some(path) = &config.funding_file {
+            println!("   Funding:             {}", path);
+        }
+        if let Some(margin) = config.margin() {
+            println!("   Apalancamiento:      {:.1}x ({}, mantenimiento {:.2}%, liquidación {:.2}%)",
+                margin.leverage, margin.mode.as_str(), margin.maintenance_margin_rate * 100.0, margin.liquidation_fee_rate * 100.0);
+        }
         println!("   Tamaño posición:     {:.0}% del balance", config.position_size * 100.0);
+        println!("   Modelo de tamaño:    {}", config.position_sizing().as_str());
         if let Some(sl) = config.stop_loss {
             println!("   Stop Loss:           {:.2}%", sl * 100.0);
         }
         if let Some(tp) = config.take_profit {
             println!("   Take Profit:         {:.2}%", tp * 100.0);
         }
+        if let Some(trailing) = config.trailing_stop {
+            println!("   Trailing Stop:       {:.2}%", trailing * 100.0);
+        }
+        if let Some(multiple) = config.atr_trailing_stop {
+            println!("   Trailing Stop ATR:   {:.1} x ATR({})", multiple, config.exit_atr_period);
+        }
+        if let Some(trigger) = config.break_even {
+            println!("   Break-even tras:     {:.2}%", trigger * 100.0);
+        }
+        if let Some(multiple) = config.chandelier {
+            println!("   Chandelier Exit:     {:.1} x ATR({}) desde {} velas", multiple, config.exit_atr_period, config.chandelier_period);
+        }
+        if let Some(max_bars) = config.max_bars {
+            println!("   Máximo de velas:     {}", max_bars);
+        }
+        if let Some(minute) = config.session_close {
+            println!("   Cierre de sesión:    {:02}:{:02} UTC", minute / 60, minute % 60);
+        }
+        if config.max_positions > 1 {
+            println!("   Posiciones máx.:     {}", config.max_positions);
+        }
+        if config.max_adds > 0 {
+            println!("   Pyramiding:          {} añadidos (x{:.2} cada uno)", config.max_adds, config.add_size_decay);
+        }
+        for level in &config.scale_out {
+            println!("   Toma parcial:        {:.0}% a +{:.2}% ({})", level.fraction * 100.0, level.profit_percent * 100.0, CloseOrder::from(config.close_order).as_str());
+        }
+        println!("   Dirección:           {:?}", TradeDirection::from(config.direction));
+        if config.max_timeframes > 1 {
+            println!("   Timeframes máx.:     {}", config.max_timeframes);
+        }
+        if let Some(bar_type) = config.bars {
+            println!("   Barras:              {} (desde trades)", bar_type);
+        }
+        if config.short_borrow_rate > 0.0 {
+            println!("   Préstamo en corto:   {:.4}%/día", config.short_borrow_rate * 100.0);
+        }
         println!();
     }
 
-    let dataset_timeframe = detect_timeframe_from_data(&config.data);
+    // Timeframe conocido del dataset: el de --bars o el del nombre del archivo.
+    // Si no se conoce se infiere de las velas al cargarlas (1h hasta entonces).
+    // Las barras no temporales no tienen timeframe: 1h es solo nominal en el AST.
+    let time_based = config.bars.is_none_or(|bar_type| bar_type.is_time_based());
+    if let Some(bar_type) = config.bars.filter(|_| !time_based) && config.max_timeframes > 1 {
+        return Err(anyhow::anyhow!(
+            "--max-timeframes > 1 requiere velas temporales: las barras {} no tienen timeframes superiores",
+            bar_type
+        ));
+    }
+    let mut known_timeframe = match config.bars {
+        Some(BarType::Time { timeframe }) => Some(timeframe),
+        Some(_) => None,
+        None => detect_timeframe_from_data(&config.data),
+    };
+    let mut dataset_timeframe = known_timeframe.unwrap_or(TimeFrame::H1);
+    let direction = TradeDirection::from(config.direction);
 
     // ==========================================
     // FASE 1: Generación Masiva de Estrategias
@@ -255,7 +1229,10 @@ async fn main() -> anyhow::Result<()> {
         println!("📝 FASE 1: Generando estrategias masivamente...");
     }
     
-    let generator = RandomGenerator::new();
+    let generator = RandomGenerator::new()
+        .with_timeframes(vec![dataset_timeframe])
+        .with_direction(direction)
+        .with_max_timeframes(config.max_timeframes as usize);
     let mut strategies = Vec::new();
     
     // Cargar mejores estrategias desde SQLite si se especifica
@@ -288,11 +1265,7 @@ async fn main() -> anyhow::Result<()> {
                 println!("   🎲 Generando {} estrategias aleatorias...", remaining);
             }
         }
-        let mut random_strategies = generator.generate_batch(remaining);
-        for s in random_strategies.iter_mut() {
-            s.timeframe = dataset_timeframe;
-        }
-        strategies.extend(random_strategies);
+        strategies.extend(generator.generate_batch(remaining));
     }
 
     // Alinear timeframe principal de estrategias cargadas/generadas al dataset actual
@@ -321,50 +1294,54 @@ async fn main() -> anyhow::Result<()> {
         .unwrap_or("")
         .to_lowercase();
     
-    let mut candles = match extension.as_str() {
-        "parquet" => {
-            if config.verbose {
-                println!("   📦 Detectado formato Parquet");
-            }
-            match ParquetLoader::load(&config.data) {
-                Ok(candles) => {
-                    if config.verbose {
-                        println!("   ✅ Cargadas {} velas desde {}", candles.len(), config.data);
-                    }
-                    candles
+    let mut candles = if let Some(bar_type) = config.bars {
+        load_trade_bars(&config.data, bar_type, config.verbose)?
+    } else {
+        match extension.as_str() {
+            "parquet" => {
+                if config.verbose {
+                    println!("   📦 Detectado formato Parquet");
                 }
-                Err(e) => {
-                    eprintln!("   ❌ Error al cargar archivo Parquet: {}", e);
-                    eprintln!("   💡 Asegúrate de que el archivo existe y tiene el formato correcto:");
-                    eprintln!("      Columnas: timestamp, open, high, low, close, volume");
-                    return Err(e);
+                match ParquetLoader::load(&config.data) {
+                    Ok(candles) => {
+                        if config.verbose {
+                            println!("   ✅ Cargadas {} velas desde {}", candles.len(), config.data);
+                        }
+                        candles
+                    }
+                    Err(e) => {
+                        eprintln!("   ❌ Error al cargar archivo Parquet: {}", e);
+                        eprintln!("   💡 Asegúrate de que el archivo existe y tiene el formato correcto:");
+                        eprintln!("      Columnas: timestamp, open, high, low, close, volume");
+                        return Err(e);
+                    }
                 }
             }
-        }
-        "csv" => {
-            if config.verbose {
-                println!("   📄 Detectado formato CSV");
-            }
-            match CsvLoader::load(&config.data) {
-                Ok(candles) => {
-                    if config.verbose {
-                        println!("   ✅ Cargadas {} velas desde {}", candles.len(), config.data);
-                    }
-                    candles
+            "csv" => {
+                if config.verbose {
+                    println!("   📄 Detectado formato CSV");
                 }
-                Err(e) => {
-                    eprintln!("   ❌ Error al cargar archivo CSV: {}", e);
-                    eprintln!("   💡 Asegúrate de que el archivo existe y tiene el formato correcto:");
-                    eprintln!("      timestamp,open,high,low,close,volume");
-                    return Err(e);
+                match CsvLoader::load(&config.data) {
+                    Ok(candles) => {
+                        if config.verbose {
+                            println!("   ✅ Cargadas {} velas desde {}", candles.len(), config.data);
+                        }
+                        candles
+                    }
+                    Err(e) => {
+                        eprintln!("   ❌ Error al cargar archivo CSV: {}", e);
+                        eprintln!("   💡 Asegúrate de que el archivo existe y tiene el formato correcto:");
+                        eprintln!("      timestamp,open,high,low,close,volume");
+                        return Err(e);
+                    }
                 }
             }
-        }
-        _ => {
-            eprintln!("   ❌ Formato de archivo no soportado: {}", extension);
-            eprintln!("   💡 Formatos soportados: .csv, .parquet");
-            eprintln!("   💡 Archivo especificado: {}", config.data);
-            return Err(anyhow::anyhow!("Formato de archivo no soportado: {}", extension));
+            _ => {
+                eprintln!("   ❌ Formato de archivo no soportado: {}", extension);
+                eprintln!("   💡 Formatos soportados: .csv, .parquet");
+                eprintln!("   💡 Archivo especificado: {}", config.data);
+                return Err(anyhow::anyhow!("Formato de archivo no soportado: {}", extension));
+            }
         }
     };
     
@@ -389,6 +1366,66 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    // Sin timeframe explícito se infiere de la separación mediana entre velas
+    if known_timeframe.is_none() && time_based {
+        known_timeframe = infer_timeframe(&candles);
+        if let Some(timeframe) = known_timeframe {
+            dataset_timeframe = timeframe;
+            for s in strategies.iter_mut() {
+                s.timeframe = timeframe;
+            }
+            if config.verbose {
+                println!("   ⏱️  Timeframe inferido de los datos: {}", timeframe);
+            }
+        }
+    }
+
+    // Validar la calidad de los datos y repararlos si se pidió.
+    // Los huecos solo se comprueban (y rellenan) con un timeframe conocido.
+    let validator = match known_timeframe {
+        Some(timeframe) if time_based => DataValidator::new().with_timeframe(timeframe),
+        _ => DataValidator::new(),
+    };
+    if time_based && known_timeframe.is_none() {
+        eprintln!("   ⚠️  No se pudo determinar el timeframe de los datos: no se comprueban huecos");
+    }
+    let mut synthetic_timestamps = Vec::new();
+    let data_quality = match config.repair_data {
+        Some(policy) => {
+            let policy = RepairPolicy::from(policy);
+            let outcome = validator.repair(&candles, policy);
+            if config.verbose || !outcome.report.is_clean() {
+                println!("   🩺 Calidad de datos: {}", outcome.report);
+                println!("   🔧 Reparación ({}): {} velas descartadas, {} sintéticas",
+                    policy.as_str(), outcome.dropped, outcome.synthetic_count());
+            }
+            synthetic_timestamps = outcome.candles.iter()
+                .zip(&outcome.synthetic)
+                .filter(|&(_, &synthetic)| synthetic)
+                .map(|(candle, _)| candle.timestamp)
+                .collect();
+            candles = outcome.candles;
+            outcome.report
+        }
+        None => {
+            let report = validator.validate(&candles);
+            if !report.is_clean() {
+                eprintln!("   ⚠️  Calidad de datos: {}", report);
+                eprintln!("   💡 Usa --repair-data drop|forward-fill|interpolate para repararlos");
+            } else if config.verbose {
+                println!("   🩺 Calidad de datos: {}", report);
+            }
+            report
+        }
+    };
+    let data_quality = serde_json::json!({
+        "counts": data_quality.counts(),
+        "missing_candles": data_quality.missing_candles(),
+        "repair": config.repair_data.map(|policy| RepairPolicy::from(policy).as_str()),
+        "synthetic_candles": synthetic_timestamps.len(),
+        "gaps_checked": time_based && known_timeframe.is_some(),
+    });
+
     // Validar que haya velas después del filtrado
     if candles.is_empty() {
         return Err(anyhow::anyhow!(
@@ -417,16 +1454,50 @@ async fn main() -> anyhow::Result<()> {
         config.initial_balance,
         config.commission_rate,
         config.slippage_bps,
-        1, // max_positions
+        config.max_positions,
         config.risk_per_trade,
         config.stop_loss,
         config.take_profit,
         config.position_size,
-    );
+    )
+    .with_short_borrow_rate(config.short_borrow_rate)
+    .with_position_sizing(config.position_sizing())
+    .with_pyramiding(config.max_adds, config.add_size_decay)
+    .with_scale_out(config.scale_out.clone())
+    .with_close_order(config.close_order.into())
+    .with_cost_model(config.cost_model()?);
+    // En las velas sintéticas de la reparación no se abren posiciones
+    let backtest_config = if synthetic_timestamps.is_empty() {
+        backtest_config
+    } else {
+        backtest_config.with_synthetic_bars(synthetic_timestamps)
+    };
+    let score = config.score()?;
+    let backtest_config = BacktestConfig {
+        trailing_stop_percent: config.trailing_stop,
+        trailing_stop_atr_multiple: config.atr_trailing_stop,
+        break_even_trigger_percent: config.break_even,
+        chandelier_atr_multiple: config.chandelier,
+        chandelier_period: config.chandelier_period,
+        exit_atr_period: config.exit_atr_period,
+        max_bars_in_trade: config.max_bars,
+        session_close_minute: config.session_close,
+        margin: config.margin(),
+        bar_type: Some(config.bars.unwrap_or(dataset_timeframe.into())),
+        ..backtest_config
+    };
+    // Etiqueta del dataset en los resultados: el timeframe o el tipo de barra no temporal
+    let dataset_label = config.bars.unwrap_or(dataset_timeframe.into()).to_string();
     if config.verbose {
         println!("   ✅ Configuración lista\n");
     }
 
+    // Modo walk-forward: selección in-sample y evaluación out-of-sample por ventana
+    if let Some(windows) = config.walk_forward {
+        let engine = Arc::new(PolarsVectorizedBacktestEngine::with_indicator_cache_budget(config.indicator_cache_mb * 1024 * 1024));
+        return run_walk_forward_mode(&config, &score, engine, strategies, candles, backtest_config, direction, &dataset_label, windows).await;
+    }
+
     // ==========================================
     // FASE 4: Backtest Masivo con Polars
     // ==========================================
@@ -435,10 +1506,9 @@ async fn main() -> anyhow::Result<()> {
         println!("   (Esto puede tardar varios minutos para {} estrategias)", config.strategies);
     }
     
-    let engine = PolarsVectorizedBacktestEngine::new();
+    let engine = Arc::new(PolarsVectorizedBacktestEngine::with_indicator_cache_budget(config.indicator_cache_mb * 1024 * 1024));
     
     // Crear un mapa de nombre de estrategia -> AST para guardar las definiciones completas
-    use std::collections::HashMap;
     let strategies_map: HashMap<String, darwinx_generator::StrategyAST> = strategies
         .iter()
         .map(|s| (s.name.clone(), s.clone()))
@@ -447,12 +1517,19 @@ async fn main() -> anyhow::Result<()> {
     // Clonar candles para poder usarlo después si hay evolución
     let candles_for_backtest = candles.clone();
     let start_time = std::time::Instant::now();
-    let results = match engine.run_massive_backtest(strategies, candles_for_backtest, &backtest_config).await {
+    let backtest_outcome = if config.parallel {
+        run_parallel_backtest(engine.clone(), strategies, candles_for_backtest, backtest_config.clone(), config.threads).await
+    } else {
+        engine.run_massive_backtest(strategies, candles_for_backtest, &backtest_config).await
+    };
+    let results = match backtest_outcome {
         Ok(results) => {
             let elapsed = start_time.elapsed();
             if config.verbose {
                 println!("   ✅ Backtest completado en {:.2} segundos", elapsed.as_secs_f64());
-                println!("   ✅ Resultados: {} estrategias backtesteadas\n", results.len());
+                println!("   ✅ Resultados: {} estrategias backtesteadas", results.len());
+                print_indicator_cache_stats(&engine);
+                println!();
             } else {
                 println!("✅ Backtest completado: {} estrategias en {:.2}s", results.len(), elapsed.as_secs_f64());
             }
@@ -533,6 +1610,18 @@ async fn main() -> anyhow::Result<()> {
             println!("      Return bajo ({}):          {} estrategias", config.min_return, fail_return);
             println!("      Drawdown alto ({}):        {} estrategias", config.max_drawdown, fail_drawdown);
             println!("      Sharpe bajo ({}):          {} estrategias", config.min_sharpe, fail_sharpe);
+            if let Some(min) = config.min_excess_return {
+                let fail = results.iter().filter(|r| r.metrics.excess_return < min).count();
+                println!("      Exceso s/ benchmark ({}):  {} estrategias", min, fail);
+            }
+            if let Some(min) = config.min_alpha {
+                let fail = results.iter().filter(|r| r.metrics.alpha < min).count();
+                println!("      Alpha bajo ({}):           {} estrategias", min, fail);
+            }
+            if let Some(max) = config.max_beta {
+                let fail = results.iter().filter(|r| r.metrics.beta > max).count();
+                println!("      Beta alta ({}):            {} estrategias", max, fail);
+            }
         } else {
             println!("   ⚠️  Ninguna estrategia generó trades!");
             
@@ -560,6 +1649,11 @@ async fn main() -> anyhow::Result<()> {
                             first_cond.comparison,
                             first_cond.value);
                     }
+                    if let Some(short_rules) = &ast.short_entry_rules {
+                        println!("         - Short entry rules: {} condiciones, operador: {:?}",
+                            short_rules.conditions.len(),
+                            short_rules.operator);
+                    }
                     println!("         - Exit rules: {} condiciones, operador: {:?}", 
                         ast.exit_rules.conditions.len(), 
                         ast.exit_rules.operator);
@@ -572,7 +1666,7 @@ async fn main() -> anyhow::Result<()> {
             let empty_entry_rules = results.iter()
                 .filter(|r| {
                     strategies_map.get(&r.strategy_name)
-                        .map(|ast| ast.entry_rules.conditions.is_empty())
+                        .map(|ast| !(ast.has_long_rules() || ast.has_short_rules()))
                         .unwrap_or(true)
                 })
                 .count();
@@ -585,7 +1679,7 @@ async fn main() -> anyhow::Result<()> {
             let mut condition_types: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
             for result in results.iter().take(100) { // Muestra de 100 estrategias
                 if let Some(ast) = strategies_map.get(&result.strategy_name) {
-                    for cond in &ast.entry_rules.conditions {
+                    for cond in ast.all_conditions() {
                         let key = format!("{:?}", cond.comparison);
                         *condition_types.entry(key).or_insert(0) += 1;
                     }
@@ -605,72 +1699,33 @@ async fn main() -> anyhow::Result<()> {
             println!("      - Las condiciones pueden ser demasiado específicas (ej: Equals con valores exactos)");
             println!("      - Las condiciones con operador 'And' requieren que TODAS se cumplan simultáneamente");
             println!("      - Los indicadores pueden no estar calculándose correctamente");
-            println!("      - Las condiciones 'CrossesAbove/Below' están simplificadas a comparaciones directas");
+            println!("      - Las condiciones 'CrossesAbove/Below' solo se activan en la vela exacta del cruce");
         }
     }
     
     // Filtrar estrategias con métricas mínimas
     let filtered: Vec<&BacktestResult> = results
         .iter()
-        .filter(|r| {
-            let m = &r.metrics;
-            m.total_trades >= config.min_trades &&
-            m.win_rate >= config.min_win_rate &&
-            m.sharpe_ratio >= config.min_sharpe &&
-            m.total_return >= config.min_return &&
-            m.max_drawdown <= config.max_drawdown
-        })
+        .filter(|r| config.passes_filters(&r.metrics))
         .collect();
     
     if config.verbose {
         println!("   📊 Estrategias que pasan filtros: {}/{}", filtered.len(), results.len());
     }
 
-    // Validar y normalizar pesos del score
-    let weights = if config.score_weights.len() == 5 {
-        let sum: f64 = config.score_weights.iter().sum();
-        if sum > 0.0 {
-            config.score_weights.iter().map(|w| w / sum).collect::<Vec<f64>>()
-        } else {
-            vec![0.3, 0.2, 0.2, 0.15, 0.15] // Default
-        }
-    } else {
-        vec![0.3, 0.2, 0.2, 0.15, 0.15] // Default
-    };
-
-    // Rankear por score compuesto
-    let mut ranked: Vec<(f64, &BacktestResult)> = filtered
+    // Rankear con el score configurado (los percentiles y rangos se calculan sobre las filtradas)
+    let metrics: Vec<&darwinx_backtest_engine::BacktestMetrics> = filtered.iter().map(|r| &r.metrics).collect();
+    let ranked = score.rank(&metrics);
+    let mut final_scores: HashMap<String, f64> = ranked
         .iter()
-        .map(|r| {
-            let m = &r.metrics;
-            
-            // Normalizar métricas (0-1)
-            let sharpe_norm = (m.sharpe_ratio / 5.0).max(0.0).min(1.0); // Sharpe típico 0-5
-            let sortino_norm = (m.sortino_ratio / 5.0).max(0.0).min(1.0);
-            let pf_norm = (m.profit_factor / 5.0).max(0.0).min(1.0); // PF típico 0-5
-            let return_norm = (m.total_return * 2.0).max(0.0).min(1.0); // Return 0-50%
-            let dd_norm = 1.0 - (m.max_drawdown * 2.0).max(0.0).min(1.0); // Drawdown inverso
-            
-            // Score ponderado
-            let score = 
-                weights[0] * sharpe_norm +
-                weights[1] * sortino_norm +
-                weights[2] * pf_norm +
-                weights[3] * return_norm +
-                weights[4] * dd_norm;
-            
-            (score, *r)
-        })
+        .map(|ranked| (filtered[ranked.index].strategy_name.clone(), ranked.score))
         .collect();
-    
-    // Ordenar por score descendente
-    ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
-    
+
     // Seleccionar top N
     let top_strategies: Vec<&BacktestResult> = ranked
         .iter()
         .take(config.top)
-        .map(|(_, r)| *r)
+        .map(|ranked| filtered[ranked.index])
         .collect();
     
     if config.verbose {
@@ -682,48 +1737,17 @@ async fn main() -> anyhow::Result<()> {
     // ==========================================
     let mut all_results = results.clone();
     let mut all_strategies_map = strategies_map.clone();
+    // Sharpe de cada estrategia probada (con o sin trades), incluida la descendencia de la evolución
+    let mut tested_sharpes: Vec<f64> = results.iter().map(|r| r.metrics.sharpe_ratio).collect();
     
     // Clonar datos necesarios antes del bloque de evolución
     let top_strategy_names: Vec<String> = top_strategies.iter().map(|r| r.strategy_name.clone()).collect();
-    let top_strategies_metrics: Vec<(String, darwinx_backtest_engine::BacktestMetrics)> = top_strategies
-        .iter()
-        .map(|r| (r.strategy_name.clone(), r.metrics.clone()))
-        .collect();
     
     if let Some(generations) = config.evolve {
         if config.verbose {
             println!("🧬 FASE 6: Evolución Genética ({} generaciones)...", generations);
         }
 
-        // Crear mapa de estrategia -> métricas para función de fitness
-        let fitness_map: HashMap<String, darwinx_backtest_engine::BacktestMetrics> = top_strategies_metrics
-            .into_iter()
-            .collect();
-
-        // Función de fitness basada en métricas de backtest
-        let weights = config.score_weights.clone();
-        let fitness_map_clone = fitness_map.clone();
-        let fitness_fn = move |strategy: &darwinx_generator::StrategyAST| -> f64 {
-            if let Some(metrics) = fitness_map_clone.get(&strategy.name) {
-                // Normalizar métricas (similar al scoring)
-                let sharpe_norm = (metrics.sharpe_ratio + 2.0) / 4.0; // -2 a 2 -> 0 a 1
-                let sortino_norm = (metrics.sortino_ratio + 2.0) / 4.0;
-                let pf_norm = (metrics.profit_factor.min(5.0)) / 5.0; // 0 a 5 -> 0 a 1
-                let return_norm = (metrics.total_return + 1.0) / 2.0; // -1 a 1 -> 0 a 1
-                let dd_norm = 1.0 - metrics.max_drawdown_percent.min(1.0); // Invertir (menor es mejor)
-
-                // Score compuesto
-                weights[0] * sharpe_norm +
-                weights[1] * sortino_norm +
-                weights[2] * pf_norm +
-                weights[3] * return_norm +
-                weights[4] * dd_norm
-            } else {
-                // Si no tiene métricas, usar complejidad como proxy
-                -(strategy.complexity() as f64) * 0.1 // Penalizar complejidad
-            }
-        };
-
         // Obtener ASTs de las top estrategias usando nombres clonados
         let top_asts: Vec<darwinx_generator::StrategyAST> = top_strategy_names
             .iter()
@@ -742,8 +1766,13 @@ async fn main() -> anyhow::Result<()> {
                 mutation_rate: config.evolve_mutation_rate,
                 elite_size: config.evolve_elite_size,
                 tournament_size: 3,
+                patience: config.evolve_patience,
+                min_improvement: 0.0,
             };
-            let genetic_gen = GeneticGenerator::new(genetic_config);
+            let genetic_gen = GeneticGenerator::new(genetic_config)
+                .with_timeframes(vec![dataset_timeframe])
+                .with_direction(direction)
+                .with_max_timeframes(config.max_timeframes as usize);
 
             if config.verbose {
                 println!("   🧬 Población inicial: {} estrategias", top_asts.len());
@@ -751,35 +1780,54 @@ async fn main() -> anyhow::Result<()> {
                 println!("   🧬 Generaciones: {}", generations);
             }
 
-            // Evolucionar
-            let evolved_strategies = genetic_gen.evolve(top_asts, fitness_fn);
+            // Evolucionar: cada generación se backtestea con el motor (fuera del runtime de tokio)
+            let mut fitness = EvolutionFitness::new(
+                engine.clone(),
+                candles.clone(),
+                backtest_config.clone(),
+                score.clone(),
+                if config.parallel { config.threads } else { Some(1) },
+            );
+            let initial_population = top_asts.clone();
+            let (evolution, fitness) = tokio::task::spawn_blocking(move || {
+                let evolution = genetic_gen.evolve_batch(top_asts, |population| fitness.evaluate(population));
+                (evolution, fitness)
+            })
+            .await?;
 
             if config.verbose {
-                println!("   ✅ Evolución completada: {} estrategias evolucionadas", evolved_strategies.len());
-            }
-
-            // Agregar estrategias evolucionadas al mapa
-            for strategy in &evolved_strategies {
-                all_strategies_map.insert(strategy.name.clone(), strategy.clone());
+                for stats in &evolution.stats {
+                    println!("   📈 Generación {:>3}: best {:.4} | avg {:.4} | worst {:.4} | {} estrategias",
+                        stats.generation,
+                        stats.best_fitness,
+                        stats.average_fitness,
+                        stats.worst_fitness,
+                        stats.population_size);
+                }
+                if evolution.converged {
+                    println!("   ⏹️  Parada anticipada: {} generaciones sin mejora", config.evolve_patience);
+                }
+                println!("   🗃️  Caché de fitness: {} backtests, {} reutilizados",
+                    fitness.backtests,
+                    fitness.cache_hits);
+                print_indicator_cache_stats(&engine);
+                println!("   ✅ Evolución completada: {} estrategias evolucionadas", evolution.population.len());
             }
 
-            // Backtestear estrategias evolucionadas
-            if config.verbose {
-                println!("   🔄 Backtesteando estrategias evolucionadas...");
-            }
+            // La población inicial ya cuenta como probada en el backtest inicial
+            tested_sharpes.extend(fitness.evaluated_metrics(&initial_population).map(|m| m.sharpe_ratio));
 
-            let evolved_results = match engine.run_massive_backtest(evolved_strategies.clone(), candles.clone(), &backtest_config).await {
-                Ok(results) => {
-                    if config.verbose {
-                        println!("   ✅ {} estrategias evolucionadas backtesteadas", results.len());
-                    }
-                    results
+            // Agregar las estrategias evolucionadas nuevas con sus resultados ya calculados
+            let mut evolved_results = Vec::new();
+            for strategy in &evolution.population {
+                if all_strategies_map.contains_key(&strategy.name) {
+                    continue;
                 }
-                Err(e) => {
-                    eprintln!("   ⚠️  Error al backtestear estrategias evolucionadas: {}", e);
-                    Vec::new()
+                if let Some(result) = fitness.result_for(strategy) {
+                    evolved_results.push(result);
+                    all_strategies_map.insert(strategy.name.clone(), strategy.clone());
                 }
-            };
+            }
 
             // Combinar resultados originales y evolucionados
             all_results.extend(evolved_results);
@@ -801,46 +1849,21 @@ async fn main() -> anyhow::Result<()> {
         // Re-filtrar
         let re_filtered: Vec<&BacktestResult> = all_results
             .iter()
-            .filter(|r| {
-                let m = &r.metrics;
-                m.total_trades >= config.min_trades &&
-                m.win_rate >= config.min_win_rate &&
-                m.sharpe_ratio >= config.min_sharpe &&
-                m.total_return >= config.min_return &&
-                m.max_drawdown <= config.max_drawdown
-            })
+            .filter(|r| config.passes_filters(&r.metrics))
             .collect();
 
         // Re-ranquear
-        let mut re_ranked: Vec<(f64, &BacktestResult)> = re_filtered
+        let metrics: Vec<&darwinx_backtest_engine::BacktestMetrics> = re_filtered.iter().map(|r| &r.metrics).collect();
+        let re_ranked = score.rank(&metrics);
+        final_scores = re_ranked
             .iter()
-            .map(|r| {
-                let m = &r.metrics;
-                let weights = &config.score_weights;
-                
-                // Normalizar métricas
-                let sharpe_norm = (m.sharpe_ratio + 2.0) / 4.0;
-                let sortino_norm = (m.sortino_ratio + 2.0) / 4.0;
-                let pf_norm = (m.profit_factor.min(5.0)) / 5.0;
-                let return_norm = (m.total_return + 1.0) / 2.0;
-                let dd_norm = 1.0 - m.max_drawdown_percent.min(1.0);
-                
-                let score = weights[0] * sharpe_norm +
-                    weights[1] * sortino_norm +
-                    weights[2] * pf_norm +
-                    weights[3] * return_norm +
-                    weights[4] * dd_norm;
-                
-                (score, *r)
-            })
+            .map(|ranked| (re_filtered[ranked.index].strategy_name.clone(), ranked.score))
             .collect();
 
-        re_ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
-        
         let final_top: Vec<&BacktestResult> = re_ranked
             .iter()
             .take(config.top)
-            .map(|(_, r)| *r)
+            .map(|ranked| re_filtered[ranked.index])
             .collect();
 
         if config.verbose {
@@ -852,6 +1875,138 @@ async fn main() -> anyhow::Result<()> {
         top_strategies
     };
 
+    // ==========================================
+    // FASE 7b: Significancia estadística (corrección por pruebas múltiples)
+    // ==========================================
+    // El DSR compara cada Sharpe con el máximo esperado entre todas las estrategias probadas:
+    // las del backtest inicial y las evaluadas durante la evolución, tengan o no trades
+    let trials = tested_sharpes.len();
+    let finite_sharpes: Vec<f64> = tested_sharpes.iter().copied().filter(|s| s.is_finite()).collect();
+    let sharpe_variance = if finite_sharpes.len() > 1 {
+        let mean = finite_sharpes.iter().sum::<f64>() / finite_sharpes.len() as f64;
+        finite_sharpes.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (finite_sharpes.len() - 1) as f64
+    } else {
+        0.0
+    };
+    let deflated_sharpe = |m: &darwinx_backtest_engine::BacktestMetrics| {
+        calculate_deflated_sharpe_ratio(
+            m.sharpe_ratio,
+            m.sharpe_observations,
+            m.returns_skewness,
+            m.returns_kurtosis,
+            trials,
+            sharpe_variance,
+        )
+    };
+
+    if config.verbose {
+        println!("📐 FASE 7b: Significancia estadística ({} estrategias probadas, Sharpe máximo esperado por azar: {:.3})...",
+            trials,
+            calculate_expected_max_sharpe(trials, sharpe_variance));
+        for result in final_top_strategies.iter().take(config.show_top) {
+            println!("   {} | Sharpe: {:.3} | PSR: {:.3} | DSR: {:.3}",
+                result.strategy_name,
+                result.metrics.sharpe_ratio,
+                result.metrics.probabilistic_sharpe_ratio,
+                deflated_sharpe(&result.metrics));
+        }
+    }
+
+    let final_top_strategies: Vec<&BacktestResult> = match config.min_dsr {
+        Some(min_dsr) => {
+            let significant: Vec<&BacktestResult> = final_top_strategies
+                .into_iter()
+                .filter(|r| deflated_sharpe(&r.metrics) >= min_dsr)
+                .collect();
+            if config.verbose {
+                println!("   ✅ {} estrategias con DSR >= {:.2}", significant.len(), min_dsr);
+            }
+            significant
+        }
+        None => final_top_strategies,
+    };
+    if config.verbose {
+        println!();
+    }
+
+    // Los análisis de robustez necesitan los trades de las top estrategias
+    let detailed_results = if (config.reality_check.is_some() || config.monte_carlo.is_some()) && !final_top_strategies.is_empty() {
+        let top_asts: Vec<darwinx_generator::StrategyAST> = final_top_strategies
+            .iter()
+            .filter_map(|r| all_strategies_map.get(&r.strategy_name).cloned())
+            .collect();
+        run_detailed_backtest(
+            engine.clone(),
+            top_asts,
+            candles.clone(),
+            backtest_config.clone(),
+            if config.parallel { config.threads } else { Some(1) },
+        )
+        .await?
+    } else {
+        Vec::new()
+    };
+
+    let mut reality_check: Option<RealityCheckResult> = None;
+    if let Some(iterations) = config.reality_check && !detailed_results.is_empty() {
+        let result = run_reality_check(&detailed_results, &candles, config.rc_benchmark, iterations)?;
+        // Evaluado solo sobre las top estrategias: subestima el sesgo de selección del
+        // conjunto completo, que sí corrige el DSR
+        println!("🎯 Reality Check/SPA vs {:?}: p-valor {:.4} (mejor: {})",
+            config.rc_benchmark,
+            result.p_value,
+            detailed_results[result.best_index].strategy_name);
+        if result.p_value > 0.05 {
+            println!("   ⚠️  La mejor estrategia no supera al benchmark de forma significativa (p > 0.05)");
+        }
+        reality_check = Some(result);
+    }
+
+    // ==========================================
+    // FASE 7c: Análisis Monte Carlo (opcional)
+    // ==========================================
+    let mut monte_carlo_reports: HashMap<String, MonteCarloReport> = HashMap::new();
+    let final_top_strategies = if let Some(iterations) = config.monte_carlo {
+        if config.verbose {
+            println!("🎲 FASE 7c: Análisis Monte Carlo ({} simulaciones por método)...", iterations);
+        }
+
+        let mut mc_config = MonteCarloConfig::new(iterations).with_ruin_threshold(config.mc_ruin_threshold);
+        mc_config.seed = config.mc_seed;
+        monte_carlo_reports = run_monte_carlo_analysis(detailed_results, mc_config).await?;
+
+        let robust: Vec<&BacktestResult> = final_top_strategies
+            .into_iter()
+            .filter(|r| {
+                let Some(report) = monte_carlo_reports.get(&r.strategy_name) else {
+                    return false;
+                };
+                let passes = config.passes_monte_carlo(report);
+                if config.verbose {
+                    let summary = &report.combined;
+                    println!("   {} {} | Balance P5/P50/P95: ${:.2}/${:.2}/${:.2} | DD P95: {:.2}% | Sharpe P5: {:.3} | Ruina: {:.1}%",
+                        if passes { "✅" } else { "❌" },
+                        r.strategy_name,
+                        summary.final_balance.lower,
+                        summary.final_balance.median,
+                        summary.final_balance.upper,
+                        summary.max_drawdown.upper * 100.0,
+                        summary.sharpe_ratio.lower,
+                        summary.probability_of_ruin * 100.0);
+                }
+                passes
+            })
+            .collect();
+
+        if config.verbose {
+            println!("   ✅ {} estrategias robustas según Monte Carlo\n", robust.len());
+        }
+
+        robust
+    } else {
+        final_top_strategies
+    };
+
     // ==========================================
     // FASE 8: Mostrar Resultados
     // ==========================================
@@ -884,13 +2039,24 @@ async fn main() -> anyhow::Result<()> {
             println!("      Max Consecutive Losses: {}", m.max_consecutive_losses);
             println!("      Trades/Month:     {:.1}", m.trades_per_month);
             println!("      Trades/Year:      {:.1}", m.trades_per_year);
-            if m.stop_loss_exits > 0 || m.take_profit_exits > 0 {
-                println!("      Exits por Stop Loss:  {}", m.stop_loss_exits);
-                println!("      Exits por Take Profit: {}", m.take_profit_exits);
+            for stats in &m.exit_reasons {
+                println!("      Exits {:<19} {} trades | Win Rate: {:.2}% | P&L: ${:.2}",
+                    format!("{}:", stats.reason), stats.trades, stats.win_rate * 100.0, stats.pnl);
             }
-            println!("      Exits por Señal:    {}", m.signal_exits);
-            if m.end_of_data_exits > 0 {
-                println!("      Exits fin de datos:  {}", m.end_of_data_exits);
+            println!("      Benchmark B&H:    {:.2}% | Exceso: {:.2}%", m.benchmark_return * 100.0, m.excess_return * 100.0);
+            println!("      Alpha/Beta:       {:.4} / {:.3} | IR: {:.3} | TE: {:.4}", m.alpha, m.beta, m.information_ratio, m.tracking_error);
+            println!("      Captura:          alcista {:.2} | bajista {:.2}", m.up_capture, m.down_capture);
+            if config.leverage.is_some() {
+                println!("      Margen usado:     máx {:.2}% | medio {:.2}%", m.max_margin_usage * 100.0, m.average_margin_usage * 100.0);
+                if let Some(distance) = m.min_liquidation_distance {
+                    println!("      Dist. liquidación mínima: {:.2}%", distance * 100.0);
+                }
+            }
+            if m.short_trades > 0 {
+                println!("      Long:  {} trades | Win Rate: {:.2}% | P&L: ${:.2}",
+                    m.long_trades, m.long_win_rate * 100.0, m.long_pnl);
+                println!("      Short: {} trades | Win Rate: {:.2}% | P&L: ${:.2}",
+                    m.short_trades, m.short_win_rate * 100.0, m.short_pnl);
             }
         } else {
             println!("{}. {} | Return: {:.2}% | Sharpe: {:.3} | Trades: {} | Win Rate: {:.2}% | DD: {:.2}%",
@@ -957,21 +2123,44 @@ async fn main() -> anyhow::Result<()> {
             "end_date": config.end_date,
             "strategies_generated": config.strategies,
             "top_n": config.top,
+            "max_timeframes": config.max_timeframes,
+            "bar_type": backtest_config.bar_type,
+            "data_quality": data_quality,
             "filters": {
                 "min_trades": config.min_trades,
                 "min_win_rate": config.min_win_rate,
                 "min_sharpe": config.min_sharpe,
                 "min_return": config.min_return,
                 "max_drawdown": config.max_drawdown,
+                "min_excess_return": config.min_excess_return,
+                "min_alpha": config.min_alpha,
+                "max_beta": config.max_beta,
             },
-            "score_weights": weights,
+            "score_excess_return": config.score_excess_return,
+            "score": score,
             "backtest_config": {
                 "initial_balance": config.initial_balance,
                 "commission_rate": config.commission_rate,
                 "slippage_bps": config.slippage_bps,
                 "risk_per_trade": config.risk_per_trade,
+                "position_size": config.position_size,
+                "position_sizing": backtest_config.position_sizing,
                 "stop_loss": config.stop_loss,
                 "take_profit": config.take_profit,
+                "trailing_stop": config.trailing_stop,
+                "atr_trailing_stop": config.atr_trailing_stop,
+                "break_even": config.break_even,
+                "chandelier": config.chandelier,
+                "max_bars": config.max_bars,
+                "session_close_minute": config.session_close,
+                "max_positions": config.max_positions,
+                "pyramiding": backtest_config.pyramiding,
+                "scale_out": backtest_config.scale_out,
+                "close_order": backtest_config.close_order,
+                "fees": backtest_config.cost_model.fees,
+                "slippage": backtest_config.cost_model.slippage,
+                "funding_file": config.funding_file,
+                "margin": backtest_config.margin,
             },
             "executed_at": chrono::Utc::now().to_rfc3339(),
         });
@@ -1016,27 +2205,13 @@ async fn main() -> anyhow::Result<()> {
                             .map(|d| d.clone())
                             .unwrap_or_else(|| "N/A".to_string());
                         
-                        let timeframe = timeframe_to_str(dataset_timeframe);
-
-                        // Calcular score para este resultado
-                        let m = &result.metrics;
-                        let weights = &config.score_weights;
-                        let sharpe_norm = (m.sharpe_ratio + 2.0) / 4.0;
-                        let sortino_norm = (m.sortino_ratio + 2.0) / 4.0;
-                        let pf_norm = (m.profit_factor.min(5.0)) / 5.0;
-                        let return_norm = (m.total_return + 1.0) / 2.0;
-                        let dd_norm = 1.0 - m.max_drawdown_percent.min(1.0);
-                        let composite_score = weights[0] * sharpe_norm +
-                            weights[1] * sortino_norm +
-                            weights[2] * pf_norm +
-                            weights[3] * return_norm +
-                            weights[4] * dd_norm;
+                        let timeframe = dataset_label.clone();
 
                         let backtest_result = darwinx_store::BacktestResult {
                             id: None,
                             strategy_id,
                             dataset: config.data.clone(),
-                            timeframe: timeframe.to_string(),
+                            timeframe,
                             start_date: start_date_str,
                             end_date: end_date_str,
                             total_return: result.metrics.total_return,
@@ -1045,7 +2220,7 @@ async fn main() -> anyhow::Result<()> {
                             max_drawdown: result.metrics.max_drawdown,
                             win_rate: result.metrics.win_rate,
                             profit_factor: Some(result.metrics.profit_factor),
-                            total_trades: result.trades.len() as i32,
+                            total_trades: result.metrics.total_trades as i32,
                             tested_at: Some(chrono::Utc::now().to_rfc3339()),
                             annualized_return: Some(result.metrics.annualized_return),
                             max_drawdown_percent: Some(result.metrics.max_drawdown_percent),
@@ -1059,7 +2234,15 @@ async fn main() -> anyhow::Result<()> {
                             take_profit_exits: Some(result.metrics.take_profit_exits as i32),
                             signal_exits: Some(result.metrics.signal_exits as i32),
                             end_of_data_exits: Some(result.metrics.end_of_data_exits as i32),
-                            composite_score: Some(composite_score),
+                            composite_score: final_scores.get(&result.strategy_name).copied(),
+                            benchmark_return: Some(result.metrics.benchmark_return),
+                            excess_return: Some(result.metrics.excess_return),
+                            alpha: Some(result.metrics.alpha),
+                            beta: Some(result.metrics.beta),
+                            information_ratio: Some(result.metrics.information_ratio),
+                            tracking_error: Some(result.metrics.tracking_error),
+                            up_capture: Some(result.metrics.up_capture),
+                            down_capture: Some(result.metrics.down_capture),
                         };
 
                         match backtest_repo.create_or_update_extended(&backtest_result).await {
@@ -1107,9 +2290,6 @@ async fn main() -> anyhow::Result<()> {
             println!("\n💾 Guardando resultados en {}...", output_path);
         }
         
-        // Determinar timeframe del dataset para corregir el AST en el JSON
-        let dataset_timeframe = detect_timeframe_from_data(&config.data);
-        
         // Crear estructura serializable con resultados y scores
         let output_data = serde_json::json!({
             "config": {
@@ -1123,12 +2303,17 @@ async fn main() -> anyhow::Result<()> {
                     "min_return": config.min_return,
                     "max_drawdown": config.max_drawdown,
                 },
-                "score_weights": weights,
+                "score": score,
+                "position_sizing": backtest_config.position_sizing,
+                "bar_type": backtest_config.bar_type,
+                "data_quality": data_quality,
             },
             "summary": {
                 "total_backtested": results.len(),
                 "passed_filters": filtered.len(),
                 "top_selected": final_top_strategies.len(),
+                "trials": trials,
+                "reality_check": reality_check,
             },
             "top_strategies": final_top_strategies.iter().enumerate().map(|(i, r)| {
                 // Obtener la definición completa de la estrategia (AST)
@@ -1139,19 +2324,7 @@ async fn main() -> anyhow::Result<()> {
                     ast.timeframe = dataset_timeframe;
                 }
                 
-                // Calcular score
                 let m = &r.metrics;
-                let weights = &config.score_weights;
-                let sharpe_norm = (m.sharpe_ratio + 2.0) / 4.0;
-                let sortino_norm = (m.sortino_ratio + 2.0) / 4.0;
-                let pf_norm = (m.profit_factor.min(5.0)) / 5.0;
-                let return_norm = (m.total_return + 1.0) / 2.0;
-                let dd_norm = 1.0 - m.max_drawdown_percent.min(1.0);
-                let score = weights[0] * sharpe_norm +
-                    weights[1] * sortino_norm +
-                    weights[2] * pf_norm +
-                    weights[3] * return_norm +
-                    weights[4] * dd_norm;
 
                 // Métricas con tiempos formateados
                 let mut metrics_value = serde_json::to_value(&r.metrics)
@@ -1174,11 +2347,13 @@ async fn main() -> anyhow::Result<()> {
                 // Solo guardar métricas esenciales, NO trades ni equity_curve para reducir tamaño
                 serde_json::json!({
                     "rank": i + 1,
-                    "score": score,
+                    "score": final_scores.get(&r.strategy_name),
                     "strategy_name": r.strategy_name,
                     "strategy": strategy_ast, // AST necesario para reproducir la estrategia
                     "metrics": metrics_value, // Solo métricas, NO incluye trades ni equity_curve
-                    "total_trades": r.trades.len(),
+                    "total_trades": r.metrics.total_trades,
+                    "deflated_sharpe_ratio": deflated_sharpe(&r.metrics),
+                    "monte_carlo": monte_carlo_reports.get(&r.strategy_name).map(|mc| &mc.combined),
                     // NOTA: trades y equity_curve no se guardan en JSON para reducir tamaño
                     // Los trades completos están disponibles en SQLite si se necesitan
                 })
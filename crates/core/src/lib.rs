@@ -2,5 +2,5 @@ pub mod types;
 pub mod traits;
 
 // Re-exports para conveniencia
-pub use types::{Candle, Order, OrderSide, Position, PositionSide, Signal, TimeFrame};
+pub use types::{BarType, Candle, Order, OrderSide, Position, PositionSide, Signal, TimeFrame, TimeUnit};
 pub use traits::{Exchange, MarketData, RiskManager, Strategy};
\ No newline at end of file
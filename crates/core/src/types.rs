@@ -1,5 +1,6 @@
 //! Tipos fundamentales del sistema
 
+pub mod bar_type;
 pub mod candle;
 pub mod order;
 pub mod position;
@@ -7,8 +8,9 @@ pub mod signal;
 pub mod timeframe;
 
 // Re-exports
+pub use bar_type::BarType;
 pub use candle::Candle;
 pub use order::{Order, OrderSide};
 pub use position::{Position, PositionSide};
 pub use signal::Signal;
-pub use timeframe::TimeFrame;
\ No newline at end of file
+pub use timeframe::{TimeFrame, TimeUnit};
\ No newline at end of file
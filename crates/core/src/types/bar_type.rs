@@ -0,0 +1,132 @@
+use super::timeframe::TimeFrame;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Error de parsing de tipo de barra
+#[derive(Debug, Error)]
+pub enum BarTypeError {
+    #[error("Tipo de barra inválido: {0}")]
+    Invalid(String),
+}
+
+/// Tipo de barra de los datos: temporal o construida a partir de trades
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BarType {
+    /// Velas de duración fija
+    Time { timeframe: TimeFrame },
+    /// Una barra cada N trades
+    Tick { trades: usize },
+    /// Una barra cada N unidades negociadas
+    Volume { threshold: f64 },
+    /// Una barra cada N de valor negociado (precio × tamaño)
+    Dollar { threshold: f64 },
+    /// Una barra cuando el volumen comprador menos el vendedor acumulado alcanza ±N
+    Imbalance { threshold: f64 },
+    /// Una barra cuando el rango high-low alcanza N en precio
+    Range { range: f64 },
+    /// Ladrillos de N en precio; un cambio de dirección necesita dos ladrillos
+    Renko { brick_size: f64 },
+}
+
+impl BarType {
+    /// Indica si las barras tienen duración fija
+    pub fn is_time_based(&self) -> bool {
+        matches!(self, BarType::Time { .. })
+    }
+
+    /// Nombre corto del tipo ("time", "tick", "volume", ...)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BarType::Time { .. } => "time",
+            BarType::Tick { .. } => "tick",
+            BarType::Volume { .. } => "volume",
+            BarType::Dollar { .. } => "dollar",
+            BarType::Imbalance { .. } => "imbalance",
+            BarType::Range { .. } => "range",
+            BarType::Renko { .. } => "renko",
+        }
+    }
+}
+
+impl From<TimeFrame> for BarType {
+    fn from(timeframe: TimeFrame) -> Self {
+        BarType::Time { timeframe }
+    }
+}
+
+impl FromStr for BarType {
+    type Err = BarTypeError;
+
+    /// Parsea "TIPO:UMBRAL" (ej: "tick:1000", "volume:500", "renko:25") o un timeframe ("1h")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || BarTypeError::Invalid(s.to_string());
+        let Some((kind, value)) = s.trim().split_once(':') else {
+            let timeframe: TimeFrame = s.parse().map_err(|_| invalid())?;
+            return Ok(timeframe.into());
+        };
+
+        if kind.eq_ignore_ascii_case("tick") {
+            let trades: usize = value.parse().map_err(|_| invalid())?;
+            return if trades > 0 { Ok(BarType::Tick { trades }) } else { Err(invalid()) };
+        }
+
+        let threshold: f64 = value.parse().map_err(|_| invalid())?;
+        if !(threshold.is_finite() && threshold > 0.0) {
+            return Err(invalid());
+        }
+        match kind.to_lowercase().as_str() {
+            "volume" => Ok(BarType::Volume { threshold }),
+            "dollar" => Ok(BarType::Dollar { threshold }),
+            "imbalance" => Ok(BarType::Imbalance { threshold }),
+            "range" => Ok(BarType::Range { range: threshold }),
+            "renko" => Ok(BarType::Renko { brick_size: threshold }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for BarType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BarType::Time { timeframe } => write!(f, "{}", timeframe),
+            BarType::Tick { trades } => write!(f, "tick:{}", trades),
+            BarType::Volume { threshold }
+            | BarType::Dollar { threshold }
+            | BarType::Imbalance { threshold }
+            | BarType::Range { range: threshold }
+            | BarType::Renko { brick_size: threshold } => write!(f, "{}:{}", self.kind(), threshold),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar_type_from_str() {
+        assert_eq!("1h".parse::<BarType>().unwrap(), BarType::Time { timeframe: TimeFrame::H1 });
+        assert_eq!("tick:1000".parse::<BarType>().unwrap(), BarType::Tick { trades: 1000 });
+        assert_eq!("Volume:2.5".parse::<BarType>().unwrap(), BarType::Volume { threshold: 2.5 });
+        assert_eq!("renko:25".parse::<BarType>().unwrap(), BarType::Renko { brick_size: 25.0 });
+
+        for invalid in ["tick:0", "tick:1.5", "volume:-1", "range:abc", "candles:5", "2x"] {
+            assert!(invalid.parse::<BarType>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_bar_type_display_and_serde() {
+        let bar_type = BarType::Dollar { threshold: 1_000_000.0 };
+        assert_eq!(bar_type.to_string(), "dollar:1000000");
+        assert_eq!(bar_type.to_string().parse::<BarType>().unwrap(), bar_type);
+        assert!(!bar_type.is_time_based());
+
+        let json = serde_json::to_string(&BarType::from(TimeFrame::H4)).unwrap();
+        assert_eq!(json, r#"{"type":"time","timeframe":"H4"}"#);
+        assert_eq!(serde_json::from_str::<BarType>(&json).unwrap(), BarType::from(TimeFrame::H4));
+    }
+}
@@ -1,5 +1,7 @@
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Datelike, NaiveDate};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
+use std::str::FromStr;
 use thiserror::Error;
 
 /// Error de parsing de timeframe
@@ -9,43 +11,125 @@ pub enum TimeFrameError {
     Invalid(String),
 }
 
-/// Timeframe de las velas
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub enum TimeFrame {
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+/// Días desde 1970-01-01 (jueves) hasta el primer lunes
+const FIRST_MONDAY_DAYS: i64 = 4;
+
+/// Unidad de duración de un timeframe
+///
+/// No exhaustiva: podrá ampliarse con barras no temporales.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum TimeUnit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl TimeUnit {
+    /// Duración de una unidad en milisegundos
+    pub const fn millis(&self) -> i64 {
+        match self {
+            TimeUnit::Minute => 60 * 1000,
+            TimeUnit::Hour => 60 * 60 * 1000,
+            TimeUnit::Day => 24 * 60 * 60 * 1000,
+            TimeUnit::Week => 7 * 24 * 60 * 60 * 1000,
+            TimeUnit::Month => 30 * 24 * 60 * 60 * 1000, // Aproximado
+        }
+    }
+
+    /// Sufijo en la notación corta ("5m", "6h", "1mo")
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            TimeUnit::Minute => "m",
+            TimeUnit::Hour => "h",
+            TimeUnit::Day => "d",
+            TimeUnit::Week => "w",
+            TimeUnit::Month => "mo",
+        }
+    }
+
+    /// Prefijo en la notación por unidad ("M5", "H6", "MN1"), usada al serializar
+    fn prefix(&self) -> &'static str {
+        match self {
+            TimeUnit::Minute => "M",
+            TimeUnit::Hour => "H",
+            TimeUnit::Day => "D",
+            TimeUnit::Week => "W",
+            TimeUnit::Month => "MN",
+        }
+    }
+}
+
+/// Timeframe de las velas: N minutos, horas, días, semanas o meses
+///
+/// Se normaliza a la mayor unidad exacta (60m = 1h, 24h = 1d, 7d = 1w), de modo
+/// que dos timeframes con la misma duración son iguales.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimeFrame {
+    count: u32,
+    unit: TimeUnit,
+}
+
+impl TimeFrame {
     /// 1 minuto
-    M1,
+    pub const M1: TimeFrame = TimeFrame::preset(1, TimeUnit::Minute);
     /// 5 minutos
-    M5,
+    pub const M5: TimeFrame = TimeFrame::preset(5, TimeUnit::Minute);
     /// 15 minutos
-    M15,
+    pub const M15: TimeFrame = TimeFrame::preset(15, TimeUnit::Minute);
     /// 30 minutos
-    M30,
+    pub const M30: TimeFrame = TimeFrame::preset(30, TimeUnit::Minute);
     /// 1 hora
-    H1,
+    pub const H1: TimeFrame = TimeFrame::preset(1, TimeUnit::Hour);
     /// 4 horas
-    H4,
+    pub const H4: TimeFrame = TimeFrame::preset(4, TimeUnit::Hour);
     /// 1 día
-    D1,
+    pub const D1: TimeFrame = TimeFrame::preset(1, TimeUnit::Day);
     /// 1 semana
-    W1,
+    pub const W1: TimeFrame = TimeFrame::preset(1, TimeUnit::Week);
     /// 1 mes
-    MN1,
-}
+    pub const MN1: TimeFrame = TimeFrame::preset(1, TimeUnit::Month);
 
-impl TimeFrame {
-    /// Retorna la duración en milisegundos
-    pub fn to_millis(&self) -> i64 {
-        match self {
-            TimeFrame::M1 => 60 * 1000,
-            TimeFrame::M5 => 5 * 60 * 1000,
-            TimeFrame::M15 => 15 * 60 * 1000,
-            TimeFrame::M30 => 30 * 60 * 1000,
-            TimeFrame::H1 => 60 * 60 * 1000,
-            TimeFrame::H4 => 4 * 60 * 60 * 1000,
-            TimeFrame::D1 => 24 * 60 * 60 * 1000,
-            TimeFrame::W1 => 7 * 24 * 60 * 60 * 1000,
-            TimeFrame::MN1 => 30 * 24 * 60 * 60 * 1000, // Aproximado
+    const fn preset(count: u32, unit: TimeUnit) -> Self {
+        Self { count, unit }
+    }
+
+    /// Crea un timeframe de `count` unidades (count > 0)
+    pub fn new(count: u32, unit: TimeUnit) -> Result<Self, TimeFrameError> {
+        if count == 0 {
+            return Err(TimeFrameError::Invalid(format!("0{}", unit.suffix())));
+        }
+
+        let (mut count, mut unit) = (count, unit);
+        loop {
+            (count, unit) = match unit {
+                TimeUnit::Minute if count % 60 == 0 => (count / 60, TimeUnit::Hour),
+                TimeUnit::Hour if count % 24 == 0 => (count / 24, TimeUnit::Day),
+                TimeUnit::Day if count % 7 == 0 => (count / 7, TimeUnit::Week),
+                _ => break,
+            };
         }
+
+        Ok(Self { count, unit })
+    }
+
+    /// Número de unidades
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Unidad de duración
+    pub fn unit(&self) -> TimeUnit {
+        self.unit
+    }
+
+    /// Retorna la duración en milisegundos (los meses se aproximan a 30 días)
+    pub fn to_millis(&self) -> i64 {
+        self.count as i64 * self.unit.millis()
     }
 
     /// Retorna la duración en segundos
@@ -53,38 +137,44 @@ impl TimeFrame {
         self.to_millis() / 1000
     }
 
-    /// Convierte a string para APIs
-    pub fn as_str(&self) -> &str {
-        match self {
-            TimeFrame::M1 => "1m",
-            TimeFrame::M5 => "5m",
-            TimeFrame::M15 => "15m",
-            TimeFrame::M30 => "30m",
-            TimeFrame::H1 => "1h",
-            TimeFrame::H4 => "4h",
-            TimeFrame::D1 => "1d",
-            TimeFrame::W1 => "1w",
-            TimeFrame::MN1 => "1M",
+    /// Timeframe de `millis` milisegundos si es un número entero de minutos
+    pub fn from_millis(millis: i64) -> Option<Self> {
+        if millis <= 0 || millis % TimeUnit::Minute.millis() != 0 {
+            return None;
         }
+        let minutes = u32::try_from(millis / TimeUnit::Minute.millis()).ok()?;
+        Self::new(minutes, TimeUnit::Minute).ok()
     }
 
-    /// Parsea desde string
-    pub fn from_str(s: &str) -> Result<Self, TimeFrameError> {
-        match s.to_lowercase().as_str() {
-            "1m" | "m1" => Ok(TimeFrame::M1),
-            "5m" | "m5" => Ok(TimeFrame::M5),
-            "15m" | "m15" => Ok(TimeFrame::M15),
-            "30m" | "m30" => Ok(TimeFrame::M30),
-            "1h" | "h1" => Ok(TimeFrame::H1),
-            "4h" | "h4" => Ok(TimeFrame::H4),
-            "1d" | "d1" => Ok(TimeFrame::D1),
-            "1w" | "w1" => Ok(TimeFrame::W1),
-            "1mo" | "mn1" => Ok(TimeFrame::MN1),
-            _ => Err(TimeFrameError::Invalid(s.to_string())),
+    /// Inicio (UTC) de la barra que contiene `timestamp`
+    ///
+    /// Las barras siguen el calendario: las semanas empiezan el lunes y los meses
+    /// el día 1. Los timeframes de varias semanas o meses se alinean a partir del
+    /// lunes 1970-01-05 y de enero de 1970 (3mo = trimestres naturales).
+    pub fn bar_start(&self, timestamp: i64) -> i64 {
+        match self.unit {
+            TimeUnit::Week => {
+                let days = timestamp.div_euclid(DAY_MILLIS) - FIRST_MONDAY_DAYS;
+                let period = 7 * self.count as i64;
+                (FIRST_MONDAY_DAYS + days.div_euclid(period) * period) * DAY_MILLIS
+            }
+            TimeUnit::Month => {
+                let period = self.count as i64;
+                month_start(month_index(timestamp).div_euclid(period) * period)
+            }
+            _ => timestamp.div_euclid(self.to_millis()) * self.to_millis(),
         }
     }
 
-    /// Retorna todos los timeframes disponibles
+    /// Inicio de la barra siguiente a la que empieza en `start`
+    pub fn next_bar_start(&self, start: i64) -> i64 {
+        match self.unit {
+            TimeUnit::Month => month_start(month_index(start) + self.count as i64),
+            _ => start + self.to_millis(),
+        }
+    }
+
+    /// Retorna los timeframes estándar
     pub fn all() -> Vec<TimeFrame> {
         vec![
             TimeFrame::M1,
@@ -100,9 +190,76 @@ impl TimeFrame {
     }
 }
 
+/// Meses transcurridos desde enero de 1970
+fn month_index(timestamp: i64) -> i64 {
+    let date = DateTime::from_timestamp_millis(timestamp)
+        .map(|dt| dt.date_naive())
+        .unwrap_or_default();
+    (date.year() as i64 - 1970) * 12 + date.month0() as i64
+}
+
+/// Inicio del mes con índice `months` (ver `month_index`)
+fn month_start(months: i64) -> i64 {
+    let year = 1970 + months.div_euclid(12) as i32;
+    let month = months.rem_euclid(12) as u32 + 1;
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc().timestamp_millis())
+        .unwrap_or_default()
+}
+
+impl FromStr for TimeFrame {
+    type Err = TimeFrameError;
+
+    /// Parsea la notación corta ("3m", "6H", "3d", "1w", "1mo") o la notación por
+    /// unidad ("M5", "h4", "MN1")
+    ///
+    /// "M" es siempre minutos ("5M" = 5 minutos): los meses requieren "mo" o "MN".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || TimeFrameError::Invalid(s.to_string());
+        let trimmed = s.trim();
+        let split = trimmed.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+
+        let (count, unit) = if split == 0 {
+            let digits = trimmed.find(|c: char| c.is_ascii_digit()).ok_or_else(invalid)?;
+            let (unit, count) = trimmed.split_at(digits);
+            (count, unit.to_lowercase())
+        } else {
+            let (count, unit) = trimmed.split_at(split);
+            (count, unit.to_lowercase())
+        };
+
+        let unit = match unit.as_str() {
+            "m" | "min" => TimeUnit::Minute,
+            "h" => TimeUnit::Hour,
+            "d" => TimeUnit::Day,
+            "w" => TimeUnit::Week,
+            "mo" | "mn" => TimeUnit::Month,
+            _ => return Err(invalid()),
+        };
+        let count: u32 = count.parse().map_err(|_| invalid())?;
+        TimeFrame::new(count, unit).map_err(|_| invalid())
+    }
+}
+
 impl fmt::Display for TimeFrame {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.as_str())
+        write!(f, "{}{}", self.count, self.unit.suffix())
+    }
+}
+
+// Se serializa en la notación por unidad ("H1", "MN1"), compatible con las
+// estrategias guardadas cuando el timeframe era un enum cerrado
+impl Serialize for TimeFrame {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}{}", self.unit.prefix(), self.count))
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeFrame {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
     }
 }
 
@@ -115,6 +272,7 @@ mod tests {
         assert_eq!(TimeFrame::M1.to_millis(), 60_000);
         assert_eq!(TimeFrame::H1.to_millis(), 3_600_000);
         assert_eq!(TimeFrame::D1.to_millis(), 86_400_000);
+        assert_eq!(TimeFrame::new(6, TimeUnit::Hour).unwrap().to_millis(), 21_600_000);
     }
 
     #[test]
@@ -122,14 +280,71 @@ mod tests {
         assert_eq!(TimeFrame::from_str("1m").unwrap(), TimeFrame::M1);
         assert_eq!(TimeFrame::from_str("1h").unwrap(), TimeFrame::H1);
         assert_eq!(TimeFrame::from_str("1d").unwrap(), TimeFrame::D1);
+        assert_eq!(TimeFrame::from_str("m5").unwrap(), TimeFrame::M5);
+        assert_eq!(TimeFrame::from_str("MN1").unwrap(), TimeFrame::MN1);
+        assert_eq!(TimeFrame::from_str("1mo").unwrap(), TimeFrame::MN1);
+        assert_eq!(TimeFrame::from_str("1MN").unwrap(), TimeFrame::MN1);
+
+        // "M" es minutos en ambas notaciones, nunca meses
+        assert_eq!(TimeFrame::from_str("5M").unwrap(), TimeFrame::M5);
+        assert_eq!(TimeFrame::from_str("M5").unwrap(), TimeFrame::M5);
 
         assert!(TimeFrame::from_str("invalid").is_err());
+        assert!(TimeFrame::from_str("0h").is_err());
+        assert!(TimeFrame::from_str("h").is_err());
+        assert!(TimeFrame::from_str("3x").is_err());
+    }
+
+    #[test]
+    fn test_custom_timeframes() {
+        let six_hours: TimeFrame = "6H".parse().unwrap();
+        assert_eq!((six_hours.count(), six_hours.unit()), (6, TimeUnit::Hour));
+        assert_eq!("3m".parse::<TimeFrame>().unwrap().to_string(), "3m");
+        assert_eq!("3d".parse::<TimeFrame>().unwrap().to_millis(), 3 * 86_400_000);
+
+        // Duraciones equivalentes se normalizan
+        assert_eq!("60m".parse::<TimeFrame>().unwrap(), TimeFrame::H1);
+        assert_eq!("24h".parse::<TimeFrame>().unwrap(), TimeFrame::D1);
+        assert_eq!("14d".parse::<TimeFrame>().unwrap().to_string(), "2w");
+    }
+
+    #[test]
+    fn test_timeframe_from_millis() {
+        assert_eq!(TimeFrame::from_millis(4 * 3_600_000), Some(TimeFrame::H4));
+        assert_eq!(TimeFrame::from_millis(7 * 86_400_000), Some(TimeFrame::W1));
+        assert_eq!(TimeFrame::from_millis(90_000), None);
+        assert_eq!(TimeFrame::from_millis(0), None);
+    }
+
+    #[test]
+    fn test_calendar_bar_start() {
+        // 2024-01-10 (miércoles) 12:00 UTC
+        let timestamp = 1_704_888_000_000;
+        assert_eq!(TimeFrame::H4.bar_start(timestamp), timestamp);
+        // La semana empieza el lunes 2024-01-08 y el mes el 2024-01-01
+        assert_eq!(TimeFrame::W1.bar_start(timestamp), 1_704_672_000_000);
+        assert_eq!(TimeFrame::MN1.bar_start(timestamp), 1_704_067_200_000);
+        // Febrero de 2024 tiene 29 días
+        assert_eq!(TimeFrame::MN1.next_bar_start(1_706_745_600_000), 1_709_251_200_000);
     }
 
     #[test]
     fn test_timeframe_display() {
         assert_eq!(TimeFrame::M1.to_string(), "1m");
         assert_eq!(TimeFrame::H4.to_string(), "4h");
+        assert_eq!(TimeFrame::MN1.to_string(), "1mo");
+    }
+
+    #[test]
+    fn test_timeframe_serde() {
+        assert_eq!(serde_json::to_string(&TimeFrame::H1).unwrap(), "\"H1\"");
+        assert_eq!(serde_json::to_string(&TimeFrame::MN1).unwrap(), "\"MN1\"");
+
+        let twelve_hours = TimeFrame::new(12, TimeUnit::Hour).unwrap();
+        let json = serde_json::to_string(&twelve_hours).unwrap();
+        assert_eq!(json, "\"H12\"");
+        assert_eq!(serde_json::from_str::<TimeFrame>(&json).unwrap(), twelve_hours);
+        assert_eq!(serde_json::from_str::<TimeFrame>("\"W1\"").unwrap(), TimeFrame::W1);
     }
 
     #[test]
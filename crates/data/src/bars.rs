@@ -0,0 +1,336 @@
+//! Barras construidas a partir de trades
+//!
+//! Convierte trades (timestamp, precio, tamaño y lado) en barras temporales, de
+//! ticks, volumen, valor negociado, desequilibrio, rango o Renko, devueltas como
+//! `Candle` para usarlas directamente en los motores de backtest. El timestamp de
+//! cada barra es el de su primer trade (el inicio del periodo en las temporales);
+//! si varias barras empiezan en el mismo milisegundo se desplazan 1 ms para que
+//! los timestamps sean estrictamente crecientes.
+
+use crate::resampler::{PartialBars, Resampler};
+use darwinx_core::{BarType, Candle, OrderSide};
+
+/// Trade individual
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trade {
+    pub timestamp: i64,
+    pub price: f64,
+    pub size: f64,
+    /// Lado del agresor (None = desconocido; se infiere con la regla del tick)
+    pub side: Option<OrderSide>,
+}
+
+impl Trade {
+    pub fn new(timestamp: i64, price: f64, size: f64, side: Option<OrderSide>) -> Self {
+        Self {
+            timestamp,
+            price,
+            size,
+            side,
+        }
+    }
+}
+
+/// Constructor de barras de un tipo dado a partir de trades
+///
+/// # Example
+/// ```rust
+/// use darwinx_data::{BarBuilder, Trade};
+/// use darwinx_core::BarType;
+///
+/// let trades: Vec<Trade> = (0..10).map(|i| Trade::new(i, 100.0 + i as f64, 1.0, None)).collect();
+/// let bars = BarBuilder::new(BarType::Tick { trades: 5 }).build(&trades).unwrap();
+/// assert_eq!(bars.len(), 2);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct BarBuilder {
+    bar_type: BarType,
+    partial_bars: PartialBars,
+}
+
+/// Barra en construcción
+struct OpenBar {
+    timestamp: i64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+}
+
+impl OpenBar {
+    fn new(timestamp: i64, price: f64) -> Self {
+        Self {
+            timestamp,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 0.0,
+        }
+    }
+
+    fn add(&mut self, trade: &Trade) {
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        self.close = trade.price;
+        self.volume += trade.size;
+    }
+}
+
+impl BarBuilder {
+    /// Crea un constructor (por defecto descarta la última barra si no alcanzó su umbral)
+    pub fn new(bar_type: BarType) -> Self {
+        Self {
+            bar_type,
+            partial_bars: PartialBars::default(),
+        }
+    }
+
+    /// Define el tratamiento de la última barra incompleta (no aplica a Renko,
+    /// que solo emite ladrillos completos)
+    pub fn with_partial_bars(mut self, partial_bars: PartialBars) -> Self {
+        self.partial_bars = partial_bars;
+        self
+    }
+
+    /// Tipo de barra construido
+    pub fn bar_type(&self) -> BarType {
+        self.bar_type
+    }
+
+    /// Construye las barras a partir de trades ordenados por timestamp
+    pub fn build(&self, trades: &[Trade]) -> anyhow::Result<Vec<Candle>> {
+        let threshold = match self.bar_type {
+            BarType::Time { .. } => 1.0,
+            BarType::Tick { trades } => trades as f64,
+            BarType::Volume { threshold }
+            | BarType::Dollar { threshold }
+            | BarType::Imbalance { threshold }
+            | BarType::Range { range: threshold }
+            | BarType::Renko { brick_size: threshold } => threshold,
+        };
+        if !(threshold.is_finite() && threshold > 0.0) {
+            anyhow::bail!("Invalid bar type {}: threshold must be positive", self.bar_type);
+        }
+        if let Some(i) = (1..trades.len()).find(|&i| trades[i].timestamp < trades[i - 1].timestamp) {
+            anyhow::bail!("Trades are not sorted by timestamp at index {}", i);
+        }
+
+        Ok(match self.bar_type {
+            BarType::Renko { brick_size } => Self::renko(trades, brick_size),
+            bar_type => self.accumulate(trades, bar_type, threshold),
+        })
+    }
+
+    /// Barras que se cierran al alcanzar el umbral (o al cambiar de periodo en las temporales)
+    fn accumulate(&self, trades: &[Trade], bar_type: BarType, threshold: f64) -> Vec<Candle> {
+        let mut bars = Vec::new();
+        let mut current: Option<OpenBar> = None;
+        // Ticks, volumen, valor o desequilibrio acumulados en la barra actual
+        let mut progress = 0.0;
+        let mut tick_sign = 1.0;
+        let mut last_price: Option<f64> = None;
+
+        for trade in trades {
+            let start = match bar_type {
+                BarType::Time { timeframe } => Resampler::bar_start(trade.timestamp, timeframe),
+                _ => trade.timestamp,
+            };
+            if bar_type.is_time_based() && current.as_ref().is_some_and(|bar| bar.timestamp != start) {
+                Self::push(&mut bars, current.take().unwrap());
+            }
+
+            let bar = current.get_or_insert_with(|| OpenBar::new(start, trade.price));
+            bar.add(trade);
+
+            // Regla del tick para trades sin lado: subida = compra, bajada = venta
+            if let Some(last) = last_price {
+                if trade.price > last {
+                    tick_sign = 1.0;
+                } else if trade.price < last {
+                    tick_sign = -1.0;
+                }
+            }
+            last_price = Some(trade.price);
+
+            let complete = match bar_type {
+                BarType::Time { .. } | BarType::Renko { .. } => false,
+                BarType::Tick { .. } => {
+                    progress += 1.0;
+                    progress >= threshold
+                }
+                BarType::Volume { .. } => {
+                    progress += trade.size;
+                    progress >= threshold
+                }
+                BarType::Dollar { .. } => {
+                    progress += trade.price * trade.size;
+                    progress >= threshold
+                }
+                BarType::Imbalance { .. } => {
+                    let sign = match trade.side {
+                        Some(OrderSide::Buy) => 1.0,
+                        Some(OrderSide::Sell) => -1.0,
+                        None => tick_sign,
+                    };
+                    progress += sign * trade.size;
+                    progress.abs() >= threshold
+                }
+                BarType::Range { .. } => bar.high - bar.low >= threshold,
+            };
+            if complete {
+                Self::push(&mut bars, current.take().unwrap());
+                progress = 0.0;
+            }
+        }
+
+        if let Some(bar) = current.filter(|_| self.partial_bars == PartialBars::Keep) {
+            Self::push(&mut bars, bar);
+        }
+        bars
+    }
+
+    /// Ladrillos Renko: continúan a un ladrillo del extremo y giran a un ladrillo
+    /// del otro extremo del último (dos ladrillos de movimiento)
+    fn renko(trades: &[Trade], brick_size: f64) -> Vec<Candle> {
+        let mut bricks = Vec::new();
+        let Some(first) = trades.first() else {
+            return bricks;
+        };
+
+        // Último ladrillo (open, close); antes del primero ambos son el precio inicial
+        let (mut open, mut close) = (first.price, first.price);
+        let mut volume = 0.0;
+        // Primer trade desde el último ladrillo
+        let mut pending_start: Option<i64> = None;
+
+        for trade in trades {
+            volume += trade.size;
+            let start = *pending_start.get_or_insert(trade.timestamp);
+
+            let mut formed = Vec::new();
+            loop {
+                let (top, bottom) = (open.max(close), open.min(close));
+                if trade.price >= top + brick_size {
+                    (open, close) = (top, top + brick_size);
+                } else if trade.price <= bottom - brick_size {
+                    (open, close) = (bottom, bottom - brick_size);
+                } else {
+                    break;
+                }
+                formed.push((open, close));
+            }
+            if formed.is_empty() {
+                continue;
+            }
+
+            let brick_volume = volume / formed.len() as f64;
+            for (brick_open, brick_close) in formed {
+                let mut brick = OpenBar::new(start, brick_open);
+                brick.add(&Trade::new(start, brick_close, brick_volume, None));
+                Self::push(&mut bricks, brick);
+            }
+            volume = 0.0;
+            pending_start = None;
+        }
+
+        bricks
+    }
+
+    /// Añade una barra manteniendo los timestamps estrictamente crecientes
+    fn push(bars: &mut Vec<Candle>, bar: OpenBar) {
+        let timestamp = match bars.last() {
+            Some(last) if last.timestamp >= bar.timestamp => last.timestamp + 1,
+            _ => bar.timestamp,
+        };
+        bars.push(Candle::new(timestamp, bar.open, bar.high, bar.low, bar.close, bar.volume));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use darwinx_core::TimeFrame;
+
+    /// Trades de tamaño 1 cada segundo con los precios dados
+    fn create_trades(prices: &[f64]) -> Vec<Trade> {
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, &price)| Trade::new(i as i64 * 1000, price, 1.0, None))
+            .collect()
+    }
+
+    #[test]
+    fn test_tick_volume_and_dollar_bars() {
+        let trades = create_trades(&[10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0]);
+
+        let ticks = BarBuilder::new(BarType::Tick { trades: 3 }).build(&trades).unwrap();
+        assert_eq!(ticks.len(), 2);
+        let bar = &ticks[1];
+        assert_eq!((bar.timestamp, bar.open, bar.high, bar.low, bar.close, bar.volume), (3000, 13.0, 15.0, 13.0, 15.0, 3.0));
+
+        let volume = BarBuilder::new(BarType::Volume { threshold: 2.0 })
+            .with_partial_bars(PartialBars::Keep)
+            .build(&trades)
+            .unwrap();
+        assert_eq!(volume.len(), 4);
+        assert_eq!(volume[3].volume, 1.0);
+
+        // 10 + 11 + 12 = 33 >= 30; 13 + 14 + 15 = 42 >= 30; la barra de 16 queda incompleta
+        let dollar = BarBuilder::new(BarType::Dollar { threshold: 30.0 }).build(&trades).unwrap();
+        assert_eq!(dollar.iter().map(|c| c.close).collect::<Vec<_>>(), vec![12.0, 15.0]);
+    }
+
+    #[test]
+    fn test_imbalance_bars_use_side_or_tick_rule() {
+        let mut trades = create_trades(&[10.0, 10.0, 11.0, 12.0, 11.0, 10.0, 9.0]);
+        trades[0].side = Some(OrderSide::Sell);
+        trades[1].side = Some(OrderSide::Buy);
+
+        // -1 +1 | +1 +1 (regla del tick) -> cierra en 12; luego -1 -1 -> cierra en 10
+        let bars = BarBuilder::new(BarType::Imbalance { threshold: 2.0 }).build(&trades).unwrap();
+        assert_eq!(bars.iter().map(|c| c.close).collect::<Vec<_>>(), vec![12.0, 10.0]);
+        assert_eq!(bars[0].volume, 4.0);
+    }
+
+    #[test]
+    fn test_range_and_time_bars() {
+        let trades = create_trades(&[100.0, 101.0, 99.5, 102.0, 102.5, 101.0, 104.0]);
+        let range = BarBuilder::new(BarType::Range { range: 2.0 }).build(&trades).unwrap();
+        assert_eq!(range.len(), 2);
+        assert_eq!((range[0].high, range[0].low, range[0].close), (102.0, 99.5, 102.0));
+        assert_eq!((range[1].open, range[1].close), (102.5, 104.0));
+
+        // Trades cada segundo: barras de 1 minuto con 60 trades
+        let trades: Vec<Trade> = (0..150).map(|i| Trade::new(i * 1000, 100.0 + i as f64, 0.5, None)).collect();
+        let minutes = BarBuilder::new(BarType::from(TimeFrame::M1)).build(&trades).unwrap();
+        assert_eq!(minutes.len(), 2);
+        assert_eq!((minutes[1].timestamp, minutes[1].open, minutes[1].close, minutes[1].volume), (60_000, 160.0, 219.0, 30.0));
+    }
+
+    #[test]
+    fn test_renko_bricks() {
+        let trades = create_trades(&[100.0, 105.0, 121.0, 115.0, 99.0, 89.0]);
+        let bricks = BarBuilder::new(BarType::Renko { brick_size: 10.0 }).build(&trades).unwrap();
+
+        // Dos ladrillos alcistas con el mismo trade, giro bajista a 100 -> 90 y continuación
+        let moves: Vec<(f64, f64)> = bricks.iter().map(|c| (c.open, c.close)).collect();
+        assert_eq!(moves, vec![(100.0, 110.0), (110.0, 120.0), (110.0, 100.0), (100.0, 90.0)]);
+        let timestamps: Vec<i64> = bricks.iter().map(|c| c.timestamp).collect();
+        assert_eq!(timestamps, vec![0, 1, 3000, 5000]);
+        assert_eq!(bricks[0].volume + bricks[1].volume, 3.0);
+        assert_eq!(bricks[2].high, 110.0);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let trades = create_trades(&[1.0, 2.0]);
+        assert!(BarBuilder::new(BarType::Volume { threshold: 0.0 }).build(&trades).is_err());
+        assert!(BarBuilder::new(BarType::Tick { trades: 0 }).build(&trades).is_err());
+
+        let unsorted = vec![trades[1], trades[0]];
+        assert!(BarBuilder::new(BarType::Tick { trades: 1 }).build(&unsorted).is_err());
+    }
+}
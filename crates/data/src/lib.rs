@@ -1,8 +1,20 @@
+pub mod bars;
 pub mod loader;
 pub mod multi_timeframe;
+pub mod resampler;
+pub mod validation;
 
 // Re-exports for loaders
-pub use loader::{CsvLoader, ParquetLoader, MultiTimeframeLoader};
+pub use loader::{CsvLoader, ParquetLoader, MultiTimeframeLoader, TradeLoader};
+
+// Re-exports for trade bars
+pub use bars::{BarBuilder, Trade};
+
+// Re-exports for resampling
+pub use resampler::{Resampler, PartialBars};
+
+// Re-exports for data quality
+pub use validation::{infer_timeframe, DataIssue, DataValidator, IssueKind, RepairOutcome, RepairPolicy, ValidationReport};
 
 // Re-exports for multi-timeframe
 pub use multi_timeframe::{
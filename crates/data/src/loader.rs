@@ -2,12 +2,14 @@
 
 pub mod csv;
 pub mod parquet;
+pub mod trades;
 
 #[cfg(test)]
 mod integration_tests;
 
 pub use csv::CsvLoader;
 pub use parquet::ParquetLoader;
+pub use trades::TradeLoader;
 
 use crate::multi_timeframe::MultiTimeframeContext;
 use darwinx_core::TimeFrame;
@@ -68,6 +70,28 @@ impl MultiTimeframeLoader {
         Ok(context)
     }
 
+    /// Carga un CSV de un timeframe base y agrega sus velas a los timeframes superiores
+    ///
+    /// Evita tener un archivo por timeframe; ver `MultiTimeframeContext::from_base`.
+    pub fn load_resampled_csv(
+        path: &str,
+        base_timeframe: TimeFrame,
+        target_timeframes: &[TimeFrame],
+    ) -> anyhow::Result<MultiTimeframeContext> {
+        let candles = CsvLoader::load(path)?;
+        MultiTimeframeContext::from_base(candles, base_timeframe, target_timeframes)
+    }
+
+    /// Carga un Parquet de un timeframe base y agrega sus velas a los timeframes superiores
+    pub fn load_resampled_parquet(
+        path: &str,
+        base_timeframe: TimeFrame,
+        target_timeframes: &[TimeFrame],
+    ) -> anyhow::Result<MultiTimeframeContext> {
+        let candles = ParquetLoader::load(path)?;
+        MultiTimeframeContext::from_base(candles, base_timeframe, target_timeframes)
+    }
+
     /// Carga un solo timeframe y crea un contexto multi-timeframe simple
     /// 
     /// Útil para casos donde solo se necesita un timeframe pero se quiere usar
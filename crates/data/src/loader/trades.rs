@@ -0,0 +1,122 @@
+//! Loader para archivos de trades (CSV o Parquet)
+//!
+//! Columnas: `timestamp`, `price`, `size` y opcionalmente `side`
+//! ("buy"/"sell" o "b"/"s", sin distinguir mayúsculas).
+
+use crate::bars::Trade;
+use darwinx_core::OrderSide;
+use polars::prelude::*;
+use std::fs::File;
+
+/// Loader para archivos de trades
+pub struct TradeLoader;
+
+impl TradeLoader {
+    /// Carga trades según la extensión del archivo (.csv o .parquet)
+    pub fn load(path: &str) -> anyhow::Result<Vec<Trade>> {
+        if path.ends_with(".parquet") {
+            Self::load_parquet(path)
+        } else {
+            Self::load_csv(path)
+        }
+    }
+
+    pub fn load_csv(path: &str) -> anyhow::Result<Vec<Trade>> {
+        let df = CsvReadOptions::default()
+            .with_has_header(true)
+            .try_into_reader_with_file_path(Some(path.into()))?
+            .finish()?;
+
+        Self::from_dataframe(&df)
+    }
+
+    pub fn load_parquet(path: &str) -> anyhow::Result<Vec<Trade>> {
+        let mut file = File::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open Parquet file: {}", e))?;
+        let df = ParquetReader::new(&mut file)
+            .finish()
+            .map_err(|e| anyhow::anyhow!("Failed to read Parquet file: {}", e))?;
+
+        Self::from_dataframe(&df)
+    }
+
+    fn from_dataframe(df: &DataFrame) -> anyhow::Result<Vec<Trade>> {
+        let timestamps = df.column("timestamp")?.cast(&DataType::Int64)?;
+        let prices = df.column("price")?.cast(&DataType::Float64)?;
+        let sizes = df.column("size")?.cast(&DataType::Float64)?;
+        let sides = df
+            .column("side")
+            .ok()
+            .map(|column| column.cast(&DataType::String))
+            .transpose()?;
+
+        let (timestamps, prices, sizes) = (timestamps.i64()?, prices.f64()?, sizes.f64()?);
+        let sides = sides.as_ref().map(|column| column.str()).transpose()?;
+
+        let mut trades = Vec::with_capacity(df.height());
+        for i in 0..df.height() {
+            let timestamp = timestamps.get(i).ok_or_else(|| {
+                anyhow::anyhow!("Missing timestamp at index {}", i)
+            })?;
+            let price = prices.get(i).ok_or_else(|| {
+                anyhow::anyhow!("Missing price at index {}", i)
+            })?;
+            let size = sizes.get(i).ok_or_else(|| {
+                anyhow::anyhow!("Missing size at index {}", i)
+            })?;
+            let side = match sides.and_then(|sides| sides.get(i)) {
+                Some(value) => Some(parse_side(value).ok_or_else(|| {
+                    anyhow::anyhow!("Invalid side '{}' at index {}", value, i)
+                })?),
+                None => None,
+            };
+
+            trades.push(Trade::new(timestamp, price, size, side));
+        }
+
+        Ok(trades)
+    }
+}
+
+fn parse_side(value: &str) -> Option<OrderSide> {
+    match value.trim().to_lowercase().as_str() {
+        "buy" | "b" => Some(OrderSide::Buy),
+        "sell" | "s" => Some(OrderSide::Sell),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_trade_loader_csv() {
+        let mut file = NamedTempFile::with_suffix(".csv").unwrap();
+        writeln!(
+            file,
+            "timestamp,price,size,side\n\
+             1609459200000,29000.5,2,BUY\n\
+             1609459200100,29001,0.5,s\n\
+             1609459200200,29002,1,"
+        )
+        .unwrap();
+
+        let trades = TradeLoader::load(file.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(trades.len(), 3);
+        assert_eq!(trades[0], Trade::new(1609459200000, 29000.5, 2.0, Some(OrderSide::Buy)));
+        assert_eq!(trades[1].side, Some(OrderSide::Sell));
+        assert_eq!(trades[2].side, None);
+    }
+
+    #[test]
+    fn test_trade_loader_invalid_side() {
+        let mut file = NamedTempFile::with_suffix(".csv").unwrap();
+        writeln!(file, "timestamp,price,size,side\n1,10.0,1.0,hold").unwrap();
+
+        assert!(TradeLoader::load_csv(file.path().to_str().unwrap()).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 //! Multi-timeframe context for trading strategies
 
+use crate::resampler::Resampler;
 use darwinx_core::{Candle, TimeFrame};
 use std::collections::HashMap;
 
@@ -89,6 +90,27 @@ impl MultiTimeframeContext {
         }
     }
 
+    /// Creates a context from base candles, resampling them to each target timeframe
+    ///
+    /// The base timeframe becomes the primary one. Incomplete higher-timeframe bars
+    /// are dropped (see [`Resampler`] to keep them).
+    pub fn from_base(
+        candles: Vec<Candle>,
+        base_timeframe: TimeFrame,
+        target_timeframes: &[TimeFrame],
+    ) -> anyhow::Result<Self> {
+        let resampler = Resampler::new(base_timeframe);
+        let mut context = Self::new(base_timeframe);
+
+        for &timeframe in target_timeframes.iter().filter(|&&tf| tf != base_timeframe) {
+            let resampled = resampler.resample(&candles, timeframe)?;
+            context.add_timeframe(timeframe, resampled);
+        }
+        context.add_timeframe(base_timeframe, candles);
+
+        Ok(context)
+    }
+
     /// Adds data for a timeframe
     pub fn add_timeframe(&mut self, timeframe: TimeFrame, candles: Vec<Candle>) {
         self.data
@@ -197,6 +219,25 @@ mod tests {
         assert!(candle.timestamp <= 300000);
     }
 
+    #[test]
+    fn test_from_base() {
+        let candles = create_test_candles(150, 100.0);
+        let context =
+            MultiTimeframeContext::from_base(candles, TimeFrame::M1, &[TimeFrame::M5, TimeFrame::H1]).unwrap();
+
+        assert_eq!(context.timeframes().len(), 3);
+        assert_eq!(context.primary().unwrap().len(), 150);
+        assert_eq!(context.get(&TimeFrame::M5).unwrap().len(), 30);
+        // The third hour is incomplete and is dropped
+        let hourly = context.get(&TimeFrame::H1).unwrap();
+        assert_eq!(hourly.len(), 2);
+        assert_eq!(hourly.candles[1].open, 160.0);
+        assert_eq!(hourly.candles[1].close, 219.5);
+
+        let invalid = create_test_candles(10, 100.0);
+        assert!(MultiTimeframeContext::from_base(invalid, TimeFrame::H1, &[TimeFrame::M5]).is_err());
+    }
+
     #[test]
     fn test_sync_to_timestamp() {
         let mut context = MultiTimeframeContext::new(TimeFrame::M1);
@@ -0,0 +1,250 @@
+//! Agregación de velas a timeframes superiores
+//!
+//! Construye velas M5/H1/H4/D1/W1/MN1 (o cualquier timeframe compatible) a partir
+//! de datos de un timeframe inferior, sin necesidad de un archivo por timeframe.
+//! Los límites de las barras siguen el calendario UTC de `TimeFrame::bar_start`:
+//! las semanas empiezan el lunes y los meses el día 1 (`TimeFrame::to_millis`
+//! aproxima el mes a 30 días).
+
+use darwinx_core::{Candle, TimeFrame, TimeUnit};
+
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+/// Tratamiento de las barras incompletas al agregar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PartialBars {
+    /// Descarta las barras que no cubren todo su periodo (ej: la última, aún en formación)
+    #[default]
+    Drop,
+    /// Conserva las barras incompletas con los datos disponibles
+    Keep,
+}
+
+/// Agregador de velas desde un timeframe base
+///
+/// # Example
+/// ```rust
+/// use darwinx_data::Resampler;
+/// use darwinx_core::{Candle, TimeFrame};
+///
+/// let candles: Vec<Candle> = (0..120)
+///     .map(|i| Candle::new(i * 60_000, 100.0, 101.0, 99.0, 100.0, 1.0))
+///     .collect();
+/// let hourly = Resampler::new(TimeFrame::M1).resample(&candles, TimeFrame::H1).unwrap();
+/// assert_eq!(hourly.len(), 2);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Resampler {
+    base: TimeFrame,
+    partial_bars: PartialBars,
+}
+
+impl Resampler {
+    /// Crea un agregador para velas de `base` (por defecto descarta barras incompletas)
+    pub fn new(base: TimeFrame) -> Self {
+        Self {
+            base,
+            partial_bars: PartialBars::default(),
+        }
+    }
+
+    /// Define el tratamiento de las barras incompletas
+    pub fn with_partial_bars(mut self, partial_bars: PartialBars) -> Self {
+        self.partial_bars = partial_bars;
+        self
+    }
+
+    /// Timeframe de las velas de entrada
+    pub fn base(&self) -> TimeFrame {
+        self.base
+    }
+
+    /// Indica si `target` puede construirse agregando velas de `base`
+    ///
+    /// Cada barra del timeframe superior debe estar formada por velas completas:
+    /// los timeframes fijos deben ser múltiplos del base, las semanas o meses se
+    /// construyen desde timeframes que dividen el día y desde semanas o meses
+    /// solo se agrega a múltiplos de la misma unidad.
+    pub fn is_compatible(base: TimeFrame, target: TimeFrame) -> bool {
+        let calendar = |tf: TimeFrame| matches!(tf.unit(), TimeUnit::Week | TimeUnit::Month);
+        match (calendar(base), calendar(target)) {
+            (true, _) => base.unit() == target.unit() && target.count().is_multiple_of(base.count()),
+            (false, true) => DAY_MILLIS % base.to_millis() == 0,
+            (false, false) => target.to_millis() % base.to_millis() == 0,
+        }
+    }
+
+    /// Inicio (UTC) de la barra de `timeframe` que contiene `timestamp`
+    pub fn bar_start(timestamp: i64, timeframe: TimeFrame) -> i64 {
+        timeframe.bar_start(timestamp)
+    }
+
+    /// Inicio de la barra siguiente a la que empieza en `start`
+    pub fn next_bar_start(start: i64, timeframe: TimeFrame) -> i64 {
+        timeframe.next_bar_start(start)
+    }
+
+    /// Agrega las velas (ordenadas por timestamp) al timeframe `target`
+    ///
+    /// Cada barra toma el open de la primera vela, el máximo de los high, el mínimo
+    /// de los low, el close de la última vela y la suma del volumen; su timestamp es
+    /// el inicio de la barra. Una barra está completa si tiene la vela que abre su
+    /// periodo y la que lo cierra.
+    pub fn resample(&self, candles: &[Candle], target: TimeFrame) -> anyhow::Result<Vec<Candle>> {
+        if !Self::is_compatible(self.base, target) {
+            anyhow::bail!("Cannot resample {} candles to {}", self.base, target);
+        }
+        if let Some(i) = (1..candles.len()).find(|&i| candles[i].timestamp <= candles[i - 1].timestamp) {
+            anyhow::bail!("Candles are not sorted by timestamp at index {}", i);
+        }
+        if self.base == target {
+            return Ok(candles.to_vec());
+        }
+
+        let base_millis = self.base.to_millis();
+        let mut bars = Vec::new();
+        let mut start_index = 0;
+
+        while start_index < candles.len() {
+            let start = Self::bar_start(candles[start_index].timestamp, target);
+            let end = Self::next_bar_start(start, target);
+            let len = candles[start_index..].iter().take_while(|c| c.timestamp < end).count();
+            let group = &candles[start_index..start_index + len];
+            start_index += len;
+
+            let first = &group[0];
+            let last = &group[len - 1];
+            let complete = first.timestamp == start && last.timestamp + base_millis >= end;
+            if !complete && self.partial_bars == PartialBars::Drop {
+                continue;
+            }
+
+            bars.push(Candle::new(
+                start,
+                first.open,
+                group.iter().map(|c| c.high).fold(f64::NEG_INFINITY, f64::max),
+                group.iter().map(|c| c.low).fold(f64::INFINITY, f64::min),
+                last.close,
+                group.iter().map(|c| c.volume).sum(),
+            ));
+        }
+
+        Ok(bars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    const HOUR: i64 = 3_600_000;
+
+    /// Velas con precio creciente cada `step` ms desde `start`
+    fn create_candles(start: i64, step: i64, count: usize) -> Vec<Candle> {
+        (0..count)
+            .map(|i| {
+                let price = 100.0 + i as f64;
+                Candle::new(start + i as i64 * step, price, price + 2.0, price - 1.0, price + 0.5, 10.0)
+            })
+            .collect()
+    }
+
+    fn timestamp(year: i32, month: u32, day: u32) -> i64 {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp_millis()
+    }
+
+    #[test]
+    fn test_ohlcv_aggregation() {
+        let candles = create_candles(0, 60_000, 10);
+        let bars = Resampler::new(TimeFrame::M1).resample(&candles, TimeFrame::M5).unwrap();
+
+        assert_eq!(bars.len(), 2);
+        let bar = &bars[1];
+        assert_eq!(bar.timestamp, 300_000);
+        assert_eq!(bar.open, 105.0);
+        assert_eq!(bar.high, 111.0);
+        assert_eq!(bar.low, 104.0);
+        assert_eq!(bar.close, 109.5);
+        assert_eq!(bar.volume, 50.0);
+    }
+
+    #[test]
+    fn test_partial_bars() {
+        // Empieza a mitad de una hora y termina antes de completar la última
+        let candles = create_candles(30 * 60_000, 60_000, 120);
+
+        let dropped = Resampler::new(TimeFrame::M1).resample(&candles, TimeFrame::H1).unwrap();
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].timestamp, HOUR);
+
+        let kept = Resampler::new(TimeFrame::M1)
+            .with_partial_bars(PartialBars::Keep)
+            .resample(&candles, TimeFrame::H1)
+            .unwrap();
+        assert_eq!(kept.iter().map(|c| c.timestamp).collect::<Vec<_>>(), vec![0, HOUR, 2 * HOUR]);
+        assert_eq!(kept[0].volume, 300.0);
+    }
+
+    #[test]
+    fn test_calendar_weeks_and_months() {
+        // Lunes 2024-01-01 hasta el 2024-03-31 en velas diarias (2024 es bisiesto)
+        let candles = create_candles(timestamp(2024, 1, 1), 24 * HOUR, 91);
+        let resampler = Resampler::new(TimeFrame::D1);
+
+        let months = resampler.resample(&candles, TimeFrame::MN1).unwrap();
+        let starts: Vec<i64> = months.iter().map(|c| c.timestamp).collect();
+        assert_eq!(starts, vec![timestamp(2024, 1, 1), timestamp(2024, 2, 1), timestamp(2024, 3, 1)]);
+        assert_eq!(months[1].volume, 290.0);
+
+        let weeks = resampler.resample(&candles, TimeFrame::W1).unwrap();
+        assert_eq!(weeks.len(), 13);
+        assert!(weeks.iter().all(|c| c.volume == 70.0));
+        assert_eq!(Resampler::bar_start(timestamp(2024, 1, 10), TimeFrame::W1), timestamp(2024, 1, 8));
+        assert_eq!(Resampler::next_bar_start(timestamp(2023, 12, 1), TimeFrame::MN1), timestamp(2024, 1, 1));
+    }
+
+    #[test]
+    fn test_custom_timeframes() {
+        let quarter: TimeFrame = "3mo".parse().unwrap();
+        assert_eq!(Resampler::bar_start(timestamp(2024, 5, 20), quarter), timestamp(2024, 4, 1));
+        assert_eq!(Resampler::next_bar_start(timestamp(2024, 10, 1), quarter), timestamp(2025, 1, 1));
+
+        let two_weeks: TimeFrame = "2w".parse().unwrap();
+        assert_eq!(Resampler::bar_start(timestamp(2024, 1, 21), two_weeks), timestamp(2024, 1, 8));
+        assert_eq!(Resampler::next_bar_start(timestamp(2024, 1, 8), two_weeks), timestamp(2024, 1, 22));
+
+        // Velas de 2h a partir de velas horarias
+        let candles = create_candles(0, HOUR, 6);
+        let bars = Resampler::new(TimeFrame::H1).resample(&candles, "2h".parse().unwrap()).unwrap();
+        assert_eq!(bars.iter().map(|c| c.timestamp).collect::<Vec<_>>(), vec![0, 2 * HOUR, 4 * HOUR]);
+        assert_eq!(bars[1].open, 102.0);
+        assert_eq!(bars[1].close, 103.5);
+
+        assert!(Resampler::is_compatible(TimeFrame::MN1, quarter));
+        assert!(!Resampler::is_compatible(TimeFrame::W1, quarter));
+        assert!(!Resampler::is_compatible("2d".parse().unwrap(), TimeFrame::W1));
+        assert!(!Resampler::is_compatible("3h".parse().unwrap(), "4h".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_incompatible_or_unsorted_input() {
+        let candles = create_candles(0, HOUR, 10);
+        assert!(Resampler::new(TimeFrame::H4).resample(&candles, TimeFrame::H1).is_err());
+        assert!(Resampler::new(TimeFrame::W1).resample(&candles, TimeFrame::MN1).is_err());
+        assert!(!Resampler::is_compatible(TimeFrame::M30, TimeFrame::M15));
+        assert!(Resampler::is_compatible(TimeFrame::H4, TimeFrame::MN1));
+
+        let mut unsorted = candles.clone();
+        unsorted.swap(2, 3);
+        assert!(Resampler::new(TimeFrame::H1).resample(&unsorted, TimeFrame::H4).is_err());
+
+        // Mismo timeframe: se devuelven las velas tal cual
+        assert_eq!(Resampler::new(TimeFrame::H1).resample(&candles, TimeFrame::H1).unwrap().len(), 10);
+    }
+}
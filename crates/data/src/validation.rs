@@ -0,0 +1,600 @@
+//! Validación y reparación de la calidad de las velas
+//!
+//! Detecta huecos respecto al timeframe esperado, timestamps duplicados o
+//! desordenados, velas OHLC inconsistentes, volumen nulo o negativo y precios
+//! atípicos, y genera un informe estructurado. La reparación ordena las velas,
+//! elimina duplicados (conserva la primera) y trata las velas inválidas y los
+//! huecos según una `RepairPolicy`; las velas generadas se marcan como sintéticas.
+//! Las variantes `*_marked` aceptan esas marcas, de modo que validar o reparar
+//! una serie ya reparada no vuelve a señalar sus velas sintéticas.
+
+use crate::resampler::Resampler;
+use darwinx_core::{Candle, TimeFrame, TimeUnit};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+/// Umbral por defecto de precio atípico (desviaciones robustas del retorno)
+const DEFAULT_OUTLIER_THRESHOLD: f64 = 10.0;
+
+/// Escala de la MAD para que equivalga a la desviación típica en datos normales
+const MAD_SCALE: f64 = 1.4826;
+
+/// Duración mínima y máxima de un mes del calendario
+const MONTH_MS: std::ops::RangeInclusive<i64> = 28 * 86_400_000..=31 * 86_400_000;
+
+/// Tipo de problema detectado en una vela
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IssueKind {
+    /// Faltan `missing` velas antes de esta
+    Gap { missing: usize },
+    /// Timestamp repetido (se conserva la primera aparición)
+    Duplicate,
+    /// Timestamp anterior al de alguna vela previa
+    Unsorted,
+    /// Precios no positivos o no finitos, high < low, u open/close fuera del rango
+    InvalidOhlc,
+    /// Volumen cero, negativo o no finito
+    NonPositiveVolume,
+    /// Pico de precio que revierte en la vela siguiente
+    Outlier,
+}
+
+impl IssueKind {
+    /// Nombre corto del problema ("gap", "duplicate", ...)
+    pub fn name(&self) -> &'static str {
+        match self {
+            IssueKind::Gap { .. } => "gap",
+            IssueKind::Duplicate => "duplicate",
+            IssueKind::Unsorted => "unsorted",
+            IssueKind::InvalidOhlc => "invalid_ohlc",
+            IssueKind::NonPositiveVolume => "non_positive_volume",
+            IssueKind::Outlier => "outlier",
+        }
+    }
+
+    /// Indica si la vela debe descartarse o reemplazarse al reparar
+    fn is_bad_candle(&self) -> bool {
+        matches!(self, IssueKind::InvalidOhlc | IssueKind::NonPositiveVolume | IssueKind::Outlier)
+    }
+}
+
+/// Problema detectado en una vela
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataIssue {
+    /// Posición de la vela en los datos originales
+    pub index: usize,
+    /// Timestamp de la vela (en los huecos, el de la primera vela que falta)
+    pub timestamp: i64,
+    #[serde(flatten)]
+    pub kind: IssueKind,
+}
+
+/// Informe de calidad de un conjunto de velas
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// Número de velas validadas
+    pub total_candles: usize,
+    /// Problemas detectados, ordenados por posición
+    pub issues: Vec<DataIssue>,
+}
+
+impl ValidationReport {
+    /// Indica si no se detectó ningún problema
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Número de problemas por tipo
+    pub fn counts(&self) -> BTreeMap<&'static str, usize> {
+        let mut counts = BTreeMap::new();
+        for issue in &self.issues {
+            *counts.entry(issue.kind.name()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Total de velas que faltan en los huecos
+    pub fn missing_candles(&self) -> usize {
+        self.issues
+            .iter()
+            .map(|issue| match issue.kind {
+                IssueKind::Gap { missing } => missing,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_clean() {
+            return write!(f, "{} velas sin problemas", self.total_candles);
+        }
+
+        let counts: Vec<String> = self.counts().iter().map(|(name, count)| format!("{}={}", name, count)).collect();
+        write!(f, "{} velas: {}", self.total_candles, counts.join(", "))?;
+        if self.missing_candles() > 0 {
+            write!(f, " ({} velas faltantes)", self.missing_candles())?;
+        }
+        Ok(())
+    }
+}
+
+/// Tratamiento de las velas inválidas y de los huecos al reparar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepairPolicy {
+    /// Descarta las velas inválidas; los huecos se mantienen
+    #[default]
+    Drop,
+    /// Rellena huecos y velas inválidas con velas planas al cierre anterior
+    ForwardFill,
+    /// Rellena huecos y velas inválidas interpolando el cierre entre las velas válidas vecinas
+    Interpolate,
+}
+
+impl RepairPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RepairPolicy::Drop => "drop",
+            RepairPolicy::ForwardFill => "forward_fill",
+            RepairPolicy::Interpolate => "interpolate",
+        }
+    }
+}
+
+/// Resultado de una reparación
+#[derive(Debug, Clone)]
+pub struct RepairOutcome {
+    /// Velas reparadas, ordenadas y sin duplicados
+    pub candles: Vec<Candle>,
+    /// Marca de las velas generadas o reemplazadas (paralelo a `candles`; volumen 0)
+    pub synthetic: Vec<bool>,
+    /// Velas originales eliminadas (duplicadas e inválidas, aunque se reemplacen por sintéticas)
+    pub dropped: usize,
+    /// Informe de los datos antes de reparar
+    pub report: ValidationReport,
+}
+
+impl RepairOutcome {
+    /// Número de velas sintéticas
+    pub fn synthetic_count(&self) -> usize {
+        self.synthetic.iter().filter(|&&synthetic| synthetic).count()
+    }
+}
+
+/// Validador de calidad de velas
+///
+/// Sin timeframe no se detectan ni rellenan huecos (ej: barras de volumen o Renko).
+///
+/// # Example
+/// ```rust
+/// use darwinx_data::{DataValidator, RepairPolicy};
+/// use darwinx_core::{Candle, TimeFrame};
+///
+/// let candles: Vec<Candle> = [0, 1, 3]
+///     .iter()
+///     .map(|&i| Candle::new(i * 3_600_000, 100.0, 101.0, 99.0, 100.0, 1.0))
+///     .collect();
+/// let validator = DataValidator::new().with_timeframe(TimeFrame::H1);
+/// assert_eq!(validator.validate(&candles).missing_candles(), 1);
+///
+/// let repaired = validator.repair(&candles, RepairPolicy::ForwardFill);
+/// assert_eq!(repaired.candles.len(), 4);
+/// assert!(repaired.synthetic[2]);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DataValidator {
+    timeframe: Option<TimeFrame>,
+    outlier_threshold: f64,
+}
+
+impl Default for DataValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DataValidator {
+    /// Crea un validador sin detección de huecos
+    pub fn new() -> Self {
+        Self {
+            timeframe: None,
+            outlier_threshold: DEFAULT_OUTLIER_THRESHOLD,
+        }
+    }
+
+    /// Detecta huecos respecto a las barras de `timeframe` (alineadas al calendario UTC)
+    pub fn with_timeframe(mut self, timeframe: TimeFrame) -> Self {
+        self.timeframe = Some(timeframe);
+        self
+    }
+
+    /// Umbral de precio atípico en desviaciones robustas (MAD) del retorno logarítmico
+    pub fn with_outlier_threshold(mut self, threshold: f64) -> Self {
+        self.outlier_threshold = threshold;
+        self
+    }
+
+    /// Valida las velas sin modificarlas
+    pub fn validate(&self, candles: &[Candle]) -> ValidationReport {
+        self.validate_marked(candles, &[])
+    }
+
+    /// Valida velas ya reparadas: las marcadas en `synthetic` (volumen 0 por
+    /// construcción) no cuentan como volumen nulo
+    pub fn validate_marked(&self, candles: &[Candle], synthetic: &[bool]) -> ValidationReport {
+        let mut issues = Vec::new();
+        let issue = |index: usize, kind: IssueKind| DataIssue {
+            index,
+            timestamp: candles[index].timestamp,
+            kind,
+        };
+
+        let mut max_timestamp = i64::MIN;
+        for (i, candle) in candles.iter().enumerate() {
+            if candle.timestamp < max_timestamp {
+                issues.push(issue(i, IssueKind::Unsorted));
+            }
+            max_timestamp = max_timestamp.max(candle.timestamp);
+
+            if !Self::valid_ohlc(candle) {
+                issues.push(issue(i, IssueKind::InvalidOhlc));
+            }
+            let marked = synthetic.get(i).copied().unwrap_or(false);
+            if !marked && (candle.volume.is_nan() || candle.volume <= 0.0 || candle.volume.is_infinite()) {
+                issues.push(issue(i, IssueKind::NonPositiveVolume));
+            }
+        }
+
+        let (rows, duplicates) = Self::sorted_rows(candles);
+        issues.extend(duplicates.into_iter().map(|i| issue(i, IssueKind::Duplicate)));
+        issues.extend(self.outliers(candles, &rows).into_iter().map(|i| issue(i, IssueKind::Outlier)));
+
+        if let Some(timeframe) = self.timeframe {
+            for pair in rows.windows(2) {
+                let (previous, next) = (candles[pair[0]].timestamp, candles[pair[1]].timestamp);
+                let mut missing = Self::missing_starts(previous, next, timeframe);
+                if let Some(first) = missing.next() {
+                    issues.push(DataIssue {
+                        index: pair[1],
+                        timestamp: first,
+                        kind: IssueKind::Gap { missing: 1 + missing.count() },
+                    });
+                }
+            }
+        }
+
+        issues.sort_by_key(|issue| issue.index);
+        ValidationReport {
+            total_candles: candles.len(),
+            issues,
+        }
+    }
+
+    /// Valida y repara las velas según `policy`
+    pub fn repair(&self, candles: &[Candle], policy: RepairPolicy) -> RepairOutcome {
+        self.repair_marked(candles, &[], policy)
+    }
+
+    /// Repara velas con marcas de una reparación anterior (paralelas a `candles`)
+    ///
+    /// Las velas sintéticas válidas se conservan con su marca y no cuentan como
+    /// descartadas, así que reparar dos veces da el mismo resultado.
+    pub fn repair_marked(&self, candles: &[Candle], synthetic: &[bool], policy: RepairPolicy) -> RepairOutcome {
+        let report = self.validate_marked(candles, synthetic);
+        let bad: HashSet<usize> = report
+            .issues
+            .iter()
+            .filter(|issue| issue.kind.is_bad_candle())
+            .map(|issue| issue.index)
+            .collect();
+        let (rows, _) = Self::sorted_rows(candles);
+
+        // Posiciones de la serie reparada: vela original o None si hay que generarla
+        let mut slots: Vec<(i64, Option<(&Candle, bool)>)> = Vec::with_capacity(rows.len());
+        for (k, &i) in rows.iter().enumerate() {
+            let candle = &candles[i];
+            if policy != RepairPolicy::Drop && k > 0 && let Some(timeframe) = self.timeframe {
+                let previous = candles[rows[k - 1]].timestamp;
+                slots.extend(Self::missing_starts(previous, candle.timestamp, timeframe).map(|ts| (ts, None)));
+            }
+            match (bad.contains(&i), policy) {
+                (true, RepairPolicy::Drop) => {}
+                (true, _) => slots.push((candle.timestamp, None)),
+                (false, _) => slots.push((candle.timestamp, Some((candle, synthetic.get(i).copied().unwrap_or(false))))),
+            }
+        }
+
+        // Siguiente vela válida de cada posición, para interpolar
+        let mut next_valid = vec![None; slots.len()];
+        let mut next = None;
+        for (k, (_, candle)) in slots.iter().enumerate().rev() {
+            next_valid[k] = next;
+            if let Some((candle, _)) = candle {
+                next = Some((candle.timestamp, candle.close));
+            }
+        }
+
+        let mut repaired: Vec<Candle> = Vec::with_capacity(slots.len());
+        let mut marks = Vec::with_capacity(slots.len());
+        let mut anchor: Option<(i64, f64)> = None;
+        let mut kept = 0;
+        for (k, (timestamp, candle)) in slots.into_iter().enumerate() {
+            if let Some((candle, marked)) = candle {
+                anchor = Some((candle.timestamp, candle.close));
+                repaired.push(candle.clone());
+                marks.push(marked);
+                kept += 1;
+                continue;
+            }
+
+            // Sin vela válida previa no hay precio de referencia: se descarta
+            let Some((anchor_ts, anchor_close)) = anchor else {
+                continue;
+            };
+            let close = match (policy, next_valid[k]) {
+                (RepairPolicy::Interpolate, Some((next_ts, next_close))) => {
+                    let weight = (timestamp - anchor_ts) as f64 / (next_ts - anchor_ts) as f64;
+                    anchor_close + (next_close - anchor_close) * weight
+                }
+                _ => anchor_close,
+            };
+            let open = repaired.last().map_or(anchor_close, |previous| previous.close);
+            repaired.push(Candle::new(timestamp, open, open.max(close), open.min(close), close, 0.0));
+            marks.push(true);
+        }
+
+        RepairOutcome {
+            candles: repaired,
+            synthetic: marks,
+            dropped: candles.len() - kept,
+            report,
+        }
+    }
+
+    fn valid_ohlc(candle: &Candle) -> bool {
+        let prices = [candle.open, candle.high, candle.low, candle.close];
+        prices.iter().all(|price| price.is_finite() && *price > 0.0)
+            && candle.low <= candle.high
+            && (candle.low..=candle.high).contains(&candle.open)
+            && (candle.low..=candle.high).contains(&candle.close)
+    }
+
+    /// Posiciones ordenadas por timestamp sin duplicados, y posiciones duplicadas
+    fn sorted_rows(candles: &[Candle]) -> (Vec<usize>, Vec<usize>) {
+        let mut order: Vec<usize> = (0..candles.len()).collect();
+        order.sort_by_key(|&i| candles[i].timestamp);
+
+        let mut rows: Vec<usize> = Vec::with_capacity(order.len());
+        let mut duplicates = Vec::new();
+        for i in order {
+            if rows.last().is_some_and(|&last| candles[last].timestamp == candles[i].timestamp) {
+                duplicates.push(i);
+            } else {
+                rows.push(i);
+            }
+        }
+        (rows, duplicates)
+    }
+
+    /// Inicios de las barras de `timeframe` que faltan entre dos velas consecutivas
+    fn missing_starts(previous: i64, next: i64, timeframe: TimeFrame) -> impl Iterator<Item = i64> {
+        let end = Resampler::bar_start(next, timeframe);
+        let mut start = Resampler::next_bar_start(Resampler::bar_start(previous, timeframe), timeframe);
+        std::iter::from_fn(move || {
+            (start < end).then(|| {
+                let current = start;
+                start = Resampler::next_bar_start(start, timeframe);
+                current
+            })
+        })
+    }
+
+    /// Velas cuyo retorno y el siguiente superan el umbral en sentidos opuestos
+    fn outliers(&self, candles: &[Candle], rows: &[usize]) -> Vec<usize> {
+        let valid: Vec<usize> = rows.iter().copied().filter(|&i| Self::valid_ohlc(&candles[i])).collect();
+        let returns: Vec<f64> = valid
+            .windows(2)
+            .map(|pair| (candles[pair[1]].close / candles[pair[0]].close).ln())
+            .collect();
+        if returns.len() < 3 {
+            return Vec::new();
+        }
+
+        let center = median(&returns);
+        let deviations: Vec<f64> = returns.iter().map(|r| (r - center).abs()).collect();
+        let scale = median(&deviations) * MAD_SCALE;
+        if scale <= f64::EPSILON {
+            return Vec::new();
+        }
+
+        let score = |r: f64| (r - center) / scale;
+        returns
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| {
+                let (into, out) = (score(pair[0]), score(pair[1]));
+                into.abs() > self.outlier_threshold && out.abs() > self.outlier_threshold && into.signum() != out.signum()
+            })
+            .map(|(k, _)| valid[k + 1])
+            .collect()
+    }
+}
+
+/// Timeframe de las velas según la mediana de la separación entre ellas
+///
+/// None si hay menos de dos velas o si la separación no es un número entero de
+/// minutos ni un mes del calendario (ej: barras de volumen).
+pub fn infer_timeframe(candles: &[Candle]) -> Option<TimeFrame> {
+    let mut deltas: Vec<i64> = candles
+        .windows(2)
+        .map(|pair| pair[1].timestamp - pair[0].timestamp)
+        .filter(|&delta| delta > 0)
+        .collect();
+    if deltas.is_empty() {
+        return None;
+    }
+    deltas.sort_unstable();
+    let delta = deltas[deltas.len() / 2];
+
+    // 28 días son 4 semanas; 29, 30 y 31 días solo pueden ser meses
+    if MONTH_MS.contains(&delta) && delta != 4 * TimeUnit::Week.millis() && delta % TimeUnit::Day.millis() == 0 {
+        return Some(TimeFrame::MN1);
+    }
+    if delta % TimeUnit::Minute.millis() != 0 {
+        return None;
+    }
+    let minutes = u32::try_from(delta / TimeUnit::Minute.millis()).ok()?;
+    TimeFrame::new(minutes, TimeUnit::Minute).ok()
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: i64 = 3_600_000;
+
+    /// Velas horarias con cierres irregulares entre 98 y 102
+    fn create_candles(count: i64) -> Vec<Candle> {
+        (0..count)
+            .map(|i| {
+                let close = 100.0 + (i as f64 * 1.7).sin() * 2.0;
+                Candle::new(i * HOUR_MS, close, close + 1.0, close - 1.0, close, 10.0)
+            })
+            .collect()
+    }
+
+    fn kinds(report: &ValidationReport) -> Vec<(usize, &'static str)> {
+        report.issues.iter().map(|issue| (issue.index, issue.kind.name())).collect()
+    }
+
+    #[test]
+    fn test_clean_data() {
+        let report = DataValidator::new().with_timeframe(TimeFrame::H1).validate(&create_candles(50));
+        assert!(report.is_clean());
+        assert_eq!(report.to_string(), "50 velas sin problemas");
+    }
+
+    #[test]
+    fn test_detects_issues() {
+        let mut candles = create_candles(30);
+        candles[3].high = candles[3].low - 1.0;
+        candles[5].volume = 0.0;
+        candles[10].close = 1000.0;
+        candles[10].high = 1001.0;
+        candles.remove(20); // hueco de una vela
+        candles.swap(24, 25);
+        let duplicate = candles[7].clone();
+        candles.insert(8, duplicate);
+
+        let report = DataValidator::new().with_timeframe(TimeFrame::H1).validate(&candles);
+        assert_eq!(
+            kinds(&report),
+            vec![(3, "invalid_ohlc"), (5, "non_positive_volume"), (8, "duplicate"), (11, "outlier"), (21, "gap"), (26, "unsorted")]
+        );
+        assert_eq!(report.issues[4].timestamp, 20 * HOUR_MS);
+        assert_eq!(report.missing_candles(), 1);
+        assert_eq!(report.counts()["gap"], 1);
+
+        // Sin timeframe no hay huecos
+        let report = DataValidator::new().validate(&candles);
+        assert_eq!(report.missing_candles(), 0);
+    }
+
+    #[test]
+    fn test_repair_policies() {
+        let mut candles = create_candles(10);
+        candles[4].volume = -1.0;
+        candles.remove(7);
+        let duplicate = candles[1].clone();
+        candles.push(duplicate);
+        let validator = DataValidator::new().with_timeframe(TimeFrame::H1);
+
+        let dropped = validator.repair(&candles, RepairPolicy::Drop);
+        assert_eq!(dropped.candles.len(), 8);
+        assert_eq!(dropped.dropped, 2);
+        assert_eq!(dropped.synthetic_count(), 0);
+        assert!(dropped.candles.windows(2).all(|pair| pair[1].timestamp > pair[0].timestamp));
+
+        let filled = validator.repair(&candles, RepairPolicy::ForwardFill);
+        assert_eq!(filled.candles.len(), 10);
+        assert_eq!(filled.synthetic_count(), 2);
+        assert!(filled.synthetic[4] && filled.synthetic[7]);
+        assert_eq!((filled.candles[4].close, filled.candles[4].volume), (candles[3].close, 0.0));
+
+        // La vela 4 queda a mitad entre los cierres de las velas 3 y 5
+        let interpolated = validator.repair(&candles, RepairPolicy::Interpolate);
+        let (before, after) = (candles[3].close, candles[5].close);
+        assert_eq!(interpolated.candles.len(), 10);
+        assert!((interpolated.candles[4].close - (before + after) / 2.0).abs() < 1e-9);
+        assert_eq!(interpolated.candles[4].open, before);
+    }
+
+    #[test]
+    fn test_repair_is_idempotent_with_marks() {
+        let mut candles = create_candles(12);
+        candles[4].volume = 0.0;
+        candles.remove(8);
+        let validator = DataValidator::new().with_timeframe(TimeFrame::H1);
+        let prices = |candles: &[Candle]| candles.iter().map(|c| (c.timestamp, c.open, c.close, c.volume)).collect::<Vec<_>>();
+
+        for policy in [RepairPolicy::ForwardFill, RepairPolicy::Interpolate] {
+            let first = validator.repair(&candles, policy);
+            assert_eq!(first.synthetic_count(), 2);
+
+            // Sin marcas las velas sintéticas vuelven a parecer de volumen nulo
+            assert_eq!(validator.validate(&first.candles).counts()["non_positive_volume"], 2);
+            assert!(validator.validate_marked(&first.candles, &first.synthetic).is_clean());
+
+            let second = validator.repair_marked(&first.candles, &first.synthetic, policy);
+            assert!(second.report.is_clean());
+            assert_eq!(second.dropped, 0);
+            assert_eq!(second.synthetic, first.synthetic);
+            assert_eq!(prices(&second.candles), prices(&first.candles));
+        }
+    }
+
+    #[test]
+    fn test_infer_timeframe_from_median_spacing() {
+        let mut candles = create_candles(20);
+        candles.remove(5); // un hueco no cambia la mediana
+        assert_eq!(infer_timeframe(&candles), Some(TimeFrame::H1));
+
+        let daily: Vec<Candle> = (0..10).map(|i| Candle::new(i * 24 * HOUR_MS, 1.0, 1.0, 1.0, 1.0, 1.0)).collect();
+        assert_eq!(infer_timeframe(&daily), Some(TimeFrame::D1));
+
+        let monthly = [1609459200000, 1612137600000, 1614556800000, 1617235200000];
+        let monthly: Vec<Candle> = monthly.iter().map(|&ts| Candle::new(ts, 1.0, 1.0, 1.0, 1.0, 1.0)).collect();
+        assert_eq!(infer_timeframe(&monthly), Some(TimeFrame::MN1));
+
+        // Separaciones irregulares de segundos (barras de volumen) o una sola vela
+        let irregular: Vec<Candle> = [0, 1_500, 4_200, 5_100].iter().map(|&ts| Candle::new(ts, 1.0, 1.0, 1.0, 1.0, 1.0)).collect();
+        assert_eq!(infer_timeframe(&irregular), None);
+        assert_eq!(infer_timeframe(&daily[..1]), None);
+    }
+
+    #[test]
+    fn test_monthly_gaps_follow_calendar() {
+        // 2021-01-01, 2021-02-01 y 2021-04-01: falta marzo
+        let starts = [1609459200000, 1612137600000, 1617235200000];
+        let candles: Vec<Candle> = starts.iter().map(|&ts| Candle::new(ts, 1.0, 1.0, 1.0, 1.0, 1.0)).collect();
+
+        let report = DataValidator::new().with_timeframe(TimeFrame::MN1).validate(&candles);
+        assert_eq!(report.missing_candles(), 1);
+        assert_eq!(report.issues[0].timestamp, 1614556800000);
+    }
+}
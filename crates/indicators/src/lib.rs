@@ -4,7 +4,24 @@
 //!
 //! Todos los indicadores son funciones puras que operan sobre slices
 //! de precios, permitiendo máxima eficiencia y reutilización.
+//!
+//! Además, cada indicador expone un struct de estado incremental
+//! (ver [`streaming`]) para calcular series completas en O(n).
+//!
+//! # Cambios de contrato en las funciones escalares
+//!
+//! Las funciones escalares delegan en el estado incremental, por lo que
+//! comparten su warmup:
+//!
+//! - `ema(data, period)` se siembra con la SMA de los primeros `period`
+//!   precios y retorna None con menos de `period` datos (antes se sembraba
+//!   con `data[0]` y aceptaba cualquier serie no vacía).
+//! - `macd(data, fast, slow, signal)` calcula la señal como EMA real de la
+//!   línea MACD y necesita `slow + signal - 1` datos (antes la señal era
+//!   `0.9 * macd_line` y bastaban `slow` datos).
+//! - `rsi` y `atr` usan el suavizado de Wilder.
 pub mod metadata;
+pub mod streaming;
 pub mod registry;
 pub mod macros;
 pub mod trend;
@@ -17,3 +34,4 @@ pub use trend::{ema, sma, wma, vwma};
 pub use momentum::{rsi, macd, stochastic, roc};
 pub use volatility::{atr, bollinger, keltner};
 pub use volume::{obv, mfi, vwap};
+pub use streaming::{PriceIndicator, StreamingIndicator};
@@ -1,5 +1,7 @@
 use crate::register_indicator;
-use crate::trend::ema;
+use crate::streaming::{PriceIndicator, StreamingIndicator};
+use crate::trend::ema::Ema;
+use darwinx_core::Candle;
 
 /// Metadata del indicador MACD
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
@@ -17,16 +19,70 @@ pub fn metadata() -> crate::metadata::IndicatorMetadata {
 
 /// Calcula el MACD
 /// Retorna (macd_line, signal_line, histogram)
-pub fn macd(data: &[f64], fast: usize, slow: usize, _signal: usize) -> Option<(f64, f64, f64)> {
-    let ema_fast = ema(data, fast)?;
-    let ema_slow = ema(data, slow)?;
-    let macd_line = ema_fast - ema_slow;
-    
-    // Simplificado: signal line como EMA del MACD
-    let signal_line = macd_line * 0.9;
-    let histogram = macd_line - signal_line;
-    
-    Some((macd_line, signal_line, histogram))
+///
+/// La línea de señal es la EMA de la línea MACD, por lo que necesita
+/// `slow + signal - 1` precios. Debe recibir toda la historia disponible.
+pub fn macd(data: &[f64], fast: usize, slow: usize, signal: usize) -> Option<(f64, f64, f64)> {
+    let mut state = Macd::new(fast, slow, signal);
+    for &price in data {
+        state.update_price(price);
+    }
+    state.value()
+}
+
+/// Estado incremental del MACD
+///
+/// Como indicador de una sola serie (`update`), retorna la línea MACD;
+/// `value()` da además la señal y el histograma cuando están disponibles.
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+    value: Option<(f64, f64, f64)>,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self {
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+            value: None,
+        }
+    }
+
+    /// Último (macd_line, signal_line, histogram), None hasta que la señal está lista
+    pub fn value(&self) -> Option<(f64, f64, f64)> {
+        self.value
+    }
+}
+
+impl PriceIndicator for Macd {
+    fn update_price(&mut self, price: f64) -> Option<f64> {
+        let fast = self.fast.update_price(price);
+        let slow = self.slow.update_price(price);
+        let macd_line = fast? - slow?;
+
+        self.value = self
+            .signal
+            .update_price(macd_line)
+            .map(|signal_line| (macd_line, signal_line, macd_line - signal_line));
+        Some(macd_line)
+    }
+}
+
+impl StreamingIndicator for Macd {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.update_price(candle.close)
+    }
+
+    fn reset(&mut self) {
+        self.fast.reset();
+        self.slow.reset();
+        self.signal.reset();
+        self.value = None;
+    }
 }
 
 register_indicator!(metadata);
@@ -48,4 +104,28 @@ mod tests {
         let result = macd(&data, 12, 26, 9);
         assert!(result.is_some());
     }
+
+    #[test]
+    fn test_macd_reference() {
+        // En una recta de pendiente 1, cada EMA sembrada con SMA va (period - 1) / 2 por detrás,
+        // así que la línea MACD es constante: (26 - 12) / 2 = 7, y la señal converge a ella
+        let data: Vec<f64> = (1..=60).map(|x| x as f64).collect();
+        let series = Macd::new(12, 26, 9).compute_series(&data);
+
+        assert!(series[..25].iter().all(|v| v.is_nan()));
+        assert!(series[25..].iter().all(|v| (v - 7.0).abs() < 1e-9));
+
+        let (line, signal, histogram) = macd(&data, 12, 26, 9).unwrap();
+        assert!((line - 7.0).abs() < 1e-9);
+        assert!((signal - 7.0).abs() < 1e-9);
+        assert!(histogram.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_macd_signal_warmup() {
+        let data: Vec<f64> = (1..=33).map(|x| x as f64).collect();
+        assert!(macd(&data, 12, 26, 9).is_none());
+        let data: Vec<f64> = (1..=34).map(|x| x as f64).collect();
+        assert!(macd(&data, 12, 26, 9).is_some());
+    }
 }
\ No newline at end of file
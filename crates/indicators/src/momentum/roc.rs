@@ -1,4 +1,6 @@
 use crate::register_indicator;
+use crate::streaming::{PriceIndicator, RollingWindow, StreamingIndicator};
+use darwinx_core::Candle;
 
 /// Metadata del indicador ROC
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
@@ -27,6 +29,45 @@ pub fn roc(data: &[f64], period: usize) -> Option<f64> {
     Some(((current - previous) / previous) * 100.0)
 }
 
+/// Estado incremental del ROC (ventana de `period + 1` precios)
+#[derive(Debug, Clone)]
+pub struct Roc {
+    window: RollingWindow,
+}
+
+impl Roc {
+    pub fn new(period: usize) -> Self {
+        Self {
+            window: RollingWindow::new(period + 1),
+        }
+    }
+}
+
+impl PriceIndicator for Roc {
+    fn update_price(&mut self, price: f64) -> Option<f64> {
+        self.window.push(price);
+        if !self.window.is_full() {
+            return None;
+        }
+
+        let previous = self.window.front()?;
+        if previous == 0.0 {
+            return None;
+        }
+        Some(((price - previous) / previous) * 100.0)
+    }
+}
+
+impl StreamingIndicator for Roc {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.update_price(candle.close)
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+}
+
 register_indicator!(metadata);
 
 #[cfg(test)]
@@ -46,4 +87,14 @@ mod tests {
         let result = roc(&data, 3);
         assert!(result.is_some());
     }
+
+    #[test]
+    fn test_roc_series() {
+        let data = vec![100.0, 105.0, 110.0, 108.0, 112.0];
+        let series = Roc::new(3).compute_series(&data);
+
+        assert!(series[..3].iter().all(|v| v.is_nan()));
+        assert!((series[3] - 8.0).abs() < 1e-9);
+        assert!((series[4] - (112.0 - 105.0) / 105.0 * 100.0).abs() < 1e-9);
+    }
 }
\ No newline at end of file
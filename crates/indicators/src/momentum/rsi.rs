@@ -1,4 +1,6 @@
 use crate::register_indicator;
+use crate::streaming::{last_value, PriceIndicator, StreamingIndicator};
+use darwinx_core::Candle;
 
 /// Metadata del indicador RSI
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
@@ -12,32 +14,82 @@ pub fn metadata() -> crate::metadata::IndicatorMetadata {
         .description("Relative Strength Index")
 }
 
+/// Calcula el RSI de Wilder del último precio de la serie
+///
+/// Necesita al menos `period + 1` precios. Debe recibir toda la historia
+/// disponible: las medias de Wilder dependen de todos los cambios anteriores.
 pub fn rsi(data: &[f64], period: usize) -> Option<f64> {
-    if data.len() < period + 1 {
-        return None;
+    last_value(Rsi::new(period), data)
+}
+
+/// Estado incremental del RSI con suavizado de Wilder
+///
+/// Las medias de ganancias y pérdidas se siembran con la media simple de los
+/// primeros `period` cambios y luego se suavizan: `avg = (avg * (n - 1) + x) / n`.
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    prev_price: Option<f64>,
+    changes: usize,
+    avg_gain: f64,
+    avg_loss: f64,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            prev_price: None,
+            changes: 0,
+            avg_gain: 0.0,
+            avg_loss: 0.0,
+        }
     }
-    
-    let mut gains = 0.0;
-    let mut losses = 0.0;
-    
-    for i in 1..=period {
-        let change = data[data.len() - i] - data[data.len() - i - 1];
-        if change > 0.0 {
-            gains += change;
+}
+
+impl PriceIndicator for Rsi {
+    fn update_price(&mut self, price: f64) -> Option<f64> {
+        let prev = self.prev_price.replace(price)?;
+        if self.period == 0 {
+            return None;
+        }
+
+        let change = price - prev;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        let n = self.period as f64;
+        self.changes += 1;
+
+        if self.changes <= self.period {
+            // Warmup: acumular la media simple de los primeros cambios
+            self.avg_gain += gain / n;
+            self.avg_loss += loss / n;
+            if self.changes < self.period {
+                return None;
+            }
         } else {
-            losses -= change;
+            self.avg_gain = (self.avg_gain * (n - 1.0) + gain) / n;
+            self.avg_loss = (self.avg_loss * (n - 1.0) + loss) / n;
         }
+
+        if self.avg_loss == 0.0 {
+            return Some(100.0);
+        }
+        let rs = self.avg_gain / self.avg_loss;
+        Some(100.0 - (100.0 / (1.0 + rs)))
     }
-    
-    let avg_gain = gains / period as f64;
-    let avg_loss = losses / period as f64;
-    
-    if avg_loss == 0.0 {
-        return Some(100.0);
+}
+
+impl StreamingIndicator for Rsi {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.update_price(candle.close)
+    }
+
+    fn reset(&mut self) {
+        self.prev_price = None;
+        self.changes = 0;
+        self.avg_gain = 0.0;
+        self.avg_loss = 0.0;
     }
-    
-    let rs = avg_gain / avg_loss;
-    Some(100.0 - (100.0 / (1.0 + rs)))
 }
 
 register_indicator!(metadata);
@@ -59,4 +111,30 @@ mod tests {
         let result = rsi(&data, 5);
         assert!(result.is_some());
     }
+
+    #[test]
+    fn test_rsi_wilder_reference() {
+        // Ejemplo clásico de Wilder (StockCharts), RSI(14) redondeado a 2 decimales
+        let data = vec![
+            44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08,
+            45.89, 46.03, 45.61, 46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64,
+            46.21, 46.25, 45.71, 46.45, 45.78, 45.35, 44.03,
+        ];
+        let expected = [70.53, 66.32, 66.55, 69.41, 66.36, 57.97, 62.93, 63.26, 56.06, 62.38, 54.71, 50.42, 39.99];
+
+        let series = Rsi::new(14).compute_series(&data);
+
+        assert!(series[..14].iter().all(|v| v.is_nan()));
+        for (value, expected) in series[14..].iter().zip(expected) {
+            // La referencia redondea los valores intermedios
+            assert!((value - expected).abs() < 0.1, "{} vs {}", value, expected);
+        }
+        assert_eq!(rsi(&data, 14), series.last().copied());
+    }
+
+    #[test]
+    fn test_rsi_only_gains() {
+        let data: Vec<f64> = (1..=10).map(|x| x as f64).collect();
+        assert_eq!(rsi(&data, 5), Some(100.0));
+    }
 }
\ No newline at end of file
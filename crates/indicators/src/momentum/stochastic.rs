@@ -1,4 +1,6 @@
 use crate::register_indicator;
+use crate::streaming::{RollingExtreme, StreamingIndicator};
+use darwinx_core::Candle;
 
 /// Metadata del indicador Stochastic
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
@@ -28,6 +30,40 @@ pub fn stochastic(high: &[f64], low: &[f64], close: &[f64], period: usize) -> Op
     Some(((current - lowest) / (highest - lowest)) * 100.0)
 }
 
+/// Estado incremental del %K estocástico (máximos/mínimos deslizantes en O(1) amortizado)
+#[derive(Debug, Clone)]
+pub struct Stochastic {
+    highest: RollingExtreme,
+    lowest: RollingExtreme,
+}
+
+impl Stochastic {
+    pub fn new(period: usize) -> Self {
+        Self {
+            highest: RollingExtreme::max(period),
+            lowest: RollingExtreme::min(period),
+        }
+    }
+}
+
+impl StreamingIndicator for Stochastic {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        let highest = self.highest.push(candle.high);
+        let lowest = self.lowest.push(candle.low);
+        let (highest, lowest) = (highest?, lowest?);
+
+        if highest == lowest {
+            return Some(50.0);
+        }
+        Some(((candle.close - lowest) / (highest - lowest)) * 100.0)
+    }
+
+    fn reset(&mut self) {
+        self.highest.clear();
+        self.lowest.clear();
+    }
+}
+
 register_indicator!(metadata);
 
 #[cfg(test)]
@@ -50,4 +86,20 @@ mod tests {
         let result = stochastic(&high, &low, &close, 5);
         assert!(result.is_some());
     }
+
+    #[test]
+    fn test_stochastic_series_matches_scalar() {
+        let close: Vec<f64> = (0..40).map(|x| 100.0 + (x as f64 * 0.4).sin() * 8.0).collect();
+        let high: Vec<f64> = close.iter().map(|c| c + 1.5).collect();
+        let low: Vec<f64> = close.iter().map(|c| c - 1.0).collect();
+        let volume = vec![1000.0; close.len()];
+
+        let series = Stochastic::new(14).compute_series_ohlcv(&high, &low, &close, &volume);
+
+        assert!(series[..13].iter().all(|v| v.is_nan()));
+        for i in 13..close.len() {
+            let expected = stochastic(&high[..=i], &low[..=i], &close[..=i], 14).unwrap();
+            assert!((series[i] - expected).abs() < 1e-9);
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,243 @@
+//! API incremental (streaming) de indicadores
+//!
+//! Cada indicador expone un struct de estado que se actualiza vela a vela en
+//! O(1) amortizado, de forma que calcular la serie completa es O(n) y el valor
+//! en la vela `i` solo depende de las velas `<= i`. El mismo estado sirve para
+//! los motores de backtest (serie completa) y para ejecución en vivo (`update`).
+
+use darwinx_core::Candle;
+
+/// Indicador que se actualiza incrementalmente con cada vela
+pub trait StreamingIndicator: Send {
+    /// Procesa la siguiente vela y retorna el valor actual (None durante el warmup)
+    fn update(&mut self, candle: &Candle) -> Option<f64>;
+
+    /// Reinicia el estado interno
+    fn reset(&mut self);
+
+    /// Calcula la serie completa a partir de series OHLCV (NaN durante el warmup)
+    ///
+    /// Reinicia el estado antes de empezar. El open no lo usa ningún indicador,
+    /// por lo que se toma el close.
+    fn compute_series_ohlcv(&mut self, high: &[f64], low: &[f64], close: &[f64], volume: &[f64]) -> Vec<f64> {
+        self.reset();
+        close
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| {
+                let candle = Candle::new(0, c, high[i], low[i], c, volume[i]);
+                self.update(&candle).unwrap_or(f64::NAN)
+            })
+            .collect()
+    }
+}
+
+/// Indicador que solo necesita una serie de precios
+pub trait PriceIndicator: StreamingIndicator {
+    /// Procesa el siguiente precio y retorna el valor actual (None durante el warmup)
+    fn update_price(&mut self, price: f64) -> Option<f64>;
+
+    /// Calcula la serie completa sobre precios (NaN durante el warmup)
+    ///
+    /// Reinicia el estado antes de empezar.
+    fn compute_series(&mut self, data: &[f64]) -> Vec<f64> {
+        self.reset();
+        data.iter()
+            .map(|&price| self.update_price(price).unwrap_or(f64::NAN))
+            .collect()
+    }
+}
+
+/// Crea el indicador incremental registrado con `name` a partir de sus parámetros
+///
+/// Los parámetros siguen el orden de `IndicatorMetadata::parameters`. Retorna
+/// None si el indicador no tiene versión incremental o faltan parámetros.
+pub fn create(name: &str, params: &[f64]) -> Option<Box<dyn StreamingIndicator>> {
+    let period = |i: usize| params.get(i).map(|p| *p as usize);
+
+    let indicator: Box<dyn StreamingIndicator> = match name {
+        "sma" => Box::new(crate::trend::sma::Sma::new(period(0)?)),
+        "ema" => Box::new(crate::trend::ema::Ema::new(period(0)?)),
+        "wma" => Box::new(crate::trend::wma::Wma::new(period(0)?)),
+        "vwma" => Box::new(crate::trend::vwma::Vwma::new(period(0)?)),
+        "rsi" => Box::new(crate::momentum::rsi::Rsi::new(period(0)?)),
+        "macd" => Box::new(crate::momentum::macd::Macd::new(period(0)?, period(1)?, period(2)?)),
+        "stochastic" => Box::new(crate::momentum::stochastic::Stochastic::new(period(0)?)),
+        "roc" => Box::new(crate::momentum::roc::Roc::new(period(0)?)),
+        "atr" => Box::new(crate::volatility::atr::Atr::new(period(0)?)),
+        "bollinger_bands" => Box::new(crate::volatility::bollinger::BollingerBands::new(period(0)?, *params.get(1)?)),
+        "keltner_channels" => Box::new(crate::volatility::keltner::KeltnerChannels::new(period(0)?, *params.get(1)?)),
+        "obv" => Box::new(crate::volume::obv::Obv::new()),
+        "mfi" => Box::new(crate::volume::mfi::Mfi::new(period(0)?)),
+        "vwap" => Box::new(crate::volume::vwap::Vwap::new()),
+        _ => return None,
+    };
+
+    Some(indicator)
+}
+
+/// Ventana deslizante de tamaño fijo con suma acumulada
+#[derive(Debug, Clone)]
+pub(crate) struct RollingWindow {
+    values: std::collections::VecDeque<f64>,
+    capacity: usize,
+    sum: f64,
+}
+
+impl RollingWindow {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            values: std::collections::VecDeque::with_capacity(capacity + 1),
+            capacity,
+            sum: 0.0,
+        }
+    }
+
+    /// Añade un valor y retorna el que sale de la ventana, si lo hay
+    pub(crate) fn push(&mut self, value: f64) -> Option<f64> {
+        self.values.push_back(value);
+        self.sum += value;
+        if self.values.len() > self.capacity {
+            let removed = self.values.pop_front();
+            if let Some(old) = removed {
+                self.sum -= old;
+            }
+            removed
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.capacity > 0 && self.values.len() == self.capacity
+    }
+
+    pub(crate) fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub(crate) fn front(&self) -> Option<f64> {
+        self.values.front().copied()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.values.clear();
+        self.sum = 0.0;
+    }
+}
+
+/// Máximo o mínimo de una ventana deslizante con deque monótona (O(1) amortizado)
+#[derive(Debug, Clone)]
+pub(crate) struct RollingExtreme {
+    /// Pares (índice, valor) candidatos a extremo
+    candidates: std::collections::VecDeque<(usize, f64)>,
+    period: usize,
+    count: usize,
+    is_max: bool,
+}
+
+impl RollingExtreme {
+    pub(crate) fn max(period: usize) -> Self {
+        Self::new(period, true)
+    }
+
+    pub(crate) fn min(period: usize) -> Self {
+        Self::new(period, false)
+    }
+
+    fn new(period: usize, is_max: bool) -> Self {
+        Self {
+            candidates: std::collections::VecDeque::new(),
+            period,
+            count: 0,
+            is_max,
+        }
+    }
+
+    /// Añade un valor y retorna el extremo de la ventana si está completa
+    pub(crate) fn push(&mut self, value: f64) -> Option<f64> {
+        let index = self.count;
+        self.count += 1;
+
+        while let Some(&(_, last)) = self.candidates.back() {
+            let dominated = if self.is_max { last <= value } else { last >= value };
+            if !dominated {
+                break;
+            }
+            self.candidates.pop_back();
+        }
+        self.candidates.push_back((index, value));
+
+        while let Some(&(i, _)) = self.candidates.front() {
+            if i + self.period > index {
+                break;
+            }
+            self.candidates.pop_front();
+        }
+
+        if self.period > 0 && self.count >= self.period {
+            self.candidates.front().map(|&(_, v)| v)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.candidates.clear();
+        self.count = 0;
+    }
+}
+
+/// Valor final de una serie incremental (usado por las funciones escalares)
+pub(crate) fn last_value<I: PriceIndicator>(mut indicator: I, data: &[f64]) -> Option<f64> {
+    let mut last = None;
+    for &price in data {
+        last = indicator.update_price(price);
+    }
+    last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_all_registered() {
+        for meta in crate::registry::all() {
+            let params: Vec<f64> = meta.parameters.iter().map(|p| p.default).collect();
+            assert!(create(meta.name, &params).is_some(), "{} sin versión incremental", meta.name);
+        }
+    }
+
+    #[test]
+    fn test_create_missing_params() {
+        assert!(create("sma", &[]).is_none());
+        assert!(create("unknown", &[14.0]).is_none());
+    }
+
+    #[test]
+    fn test_rolling_extreme() {
+        let mut max = RollingExtreme::max(3);
+        let values: Vec<Option<f64>> = [1.0, 3.0, 2.0, 1.0, 0.5, 4.0].iter().map(|&v| max.push(v)).collect();
+        assert_eq!(values, vec![None, None, Some(3.0), Some(3.0), Some(2.0), Some(4.0)]);
+    }
+
+    #[test]
+    fn test_compute_series_ohlcv_matches_update() {
+        let high = [11.0, 12.0, 13.0, 12.5, 14.0];
+        let low = [9.0, 10.0, 11.0, 11.5, 12.0];
+        let close = [10.0, 11.0, 12.0, 12.0, 13.0];
+        let volume = [1000.0, 1500.0, 1200.0, 1300.0, 1100.0];
+
+        let mut indicator = create("atr", &[2.0]).unwrap();
+        let series = indicator.compute_series_ohlcv(&high, &low, &close, &volume);
+
+        assert_eq!(series.len(), 5);
+        assert!(series[0].is_nan() && series[1].is_nan());
+        assert!(series[2..].iter().all(|v| v.is_finite()));
+    }
+}
@@ -1,4 +1,6 @@
 use crate::register_indicator;
+use crate::streaming::{last_value, PriceIndicator, StreamingIndicator};
+use darwinx_core::Candle;
 
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
     use crate::metadata::*;
@@ -11,19 +13,70 @@ pub fn metadata() -> crate::metadata::IndicatorMetadata {
         .description("Exponential Moving Average")
 }
 
+/// Calcula la media móvil exponencial del último precio de la serie
+///
+/// La EMA se inicializa con la SMA de los primeros `period` precios, por lo que
+/// necesita al menos `period` datos (retorna None con menos). Debe recibir toda
+/// la historia disponible: el valor depende de todos los precios anteriores.
 pub fn ema(data: &[f64], period: usize) -> Option<f64> {
-    if data.is_empty() {
-        return None;
+    last_value(Ema::new(period), data)
+}
+
+/// Estado incremental de la EMA (sembrada con la SMA de los primeros `period` precios)
+#[derive(Debug, Clone)]
+pub struct Ema {
+    period: usize,
+    k: f64,
+    /// Suma de precios durante el warmup
+    seed_sum: f64,
+    count: usize,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            k: 2.0 / (period as f64 + 1.0),
+            seed_sum: 0.0,
+            count: 0,
+            value: None,
+        }
     }
-    
-    let k = 2.0 / (period as f64 + 1.0);
-    let mut ema_val = data[0];
-    
-    for &price in data.iter().skip(1) {
-        ema_val = price * k + ema_val * (1.0 - k);
+
+    /// Valor actual (None durante el warmup)
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+impl PriceIndicator for Ema {
+    fn update_price(&mut self, price: f64) -> Option<f64> {
+        if self.period == 0 {
+            return None;
+        }
+        self.value = match self.value {
+            Some(prev) => Some(price * self.k + prev * (1.0 - self.k)),
+            None => {
+                self.seed_sum += price;
+                self.count += 1;
+                (self.count == self.period).then(|| self.seed_sum / self.period as f64)
+            }
+        };
+        self.value
+    }
+}
+
+impl StreamingIndicator for Ema {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.update_price(candle.close)
+    }
+
+    fn reset(&mut self) {
+        self.seed_sum = 0.0;
+        self.count = 0;
+        self.value = None;
     }
-    
-    Some(ema_val)
 }
 
 register_indicator!(metadata);
@@ -52,4 +105,27 @@ mod tests {
         let data: Vec<f64> = vec![];
         assert!(ema(&data, 5).is_none());
     }
+
+    #[test]
+    fn test_ema_series_reference() {
+        // Sobre una recta con pendiente 1, la EMA(3) sembrada con SMA va una unidad por detrás
+        let data: Vec<f64> = (1..=20).map(|x| x as f64).collect();
+        let series = Ema::new(3).compute_series(&data);
+
+        assert!(series[0].is_nan() && series[1].is_nan());
+        for i in 2..data.len() {
+            assert!((series[i] - (data[i] - 1.0)).abs() < 1e-9);
+        }
+        assert_eq!(ema(&data, 3), Some(19.0));
+    }
+
+    #[test]
+    fn test_ema_carries_state() {
+        // El valor depende de toda la historia, no solo de la última ventana
+        let mut a = Ema::new(3);
+        let mut b = Ema::new(3);
+        a.compute_series(&[10.0, 10.0, 10.0, 50.0, 10.0, 10.0, 10.0]);
+        b.compute_series(&[10.0, 10.0, 10.0, 10.0, 10.0, 10.0, 10.0]);
+        assert!(a.value().unwrap() > b.value().unwrap());
+    }
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
 use crate::register_indicator;
+use crate::streaming::{PriceIndicator, RollingWindow, StreamingIndicator};
+use darwinx_core::Candle;
 
 /// Metadata del indicador SMA
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
@@ -29,6 +31,41 @@ pub fn sma(data: &[f64], period: usize) -> Option<f64> {
     Some(sum / period as f64)
 }
 
+/// Estado incremental de la SMA (suma deslizante, O(1) por vela)
+#[derive(Debug, Clone)]
+pub struct Sma {
+    window: RollingWindow,
+    period: usize,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        Self {
+            window: RollingWindow::new(period),
+            period,
+        }
+    }
+}
+
+impl PriceIndicator for Sma {
+    fn update_price(&mut self, price: f64) -> Option<f64> {
+        self.window.push(price);
+        self.window
+            .is_full()
+            .then(|| self.window.sum() / self.period as f64)
+    }
+}
+
+impl StreamingIndicator for Sma {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.update_price(candle.close)
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+    }
+}
+
 register_indicator!(metadata);
 
 #[cfg(test)]
@@ -55,4 +92,16 @@ mod tests {
         let data = vec![1.0, 2.0];
         assert_eq!(sma(&data, 5), None);
     }
+
+    #[test]
+    fn test_sma_series_matches_scalar() {
+        let data: Vec<f64> = (0..50).map(|x| 100.0 + (x as f64 * 0.7).sin() * 10.0).collect();
+        let series = Sma::new(10).compute_series(&data);
+
+        assert!(series[..9].iter().all(|v| v.is_nan()));
+        for i in 9..data.len() {
+            let expected = sma(&data[..=i], 10).unwrap();
+            assert!((series[i] - expected).abs() < 1e-9);
+        }
+    }
 }
@@ -1,4 +1,6 @@
 use crate::register_indicator;
+use crate::streaming::{RollingWindow, StreamingIndicator};
+use darwinx_core::Candle;
 
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
     use crate::metadata::*;
@@ -33,6 +35,39 @@ pub fn vwma(prices: &[f64], volumes: &[f64], period: usize) -> Option<f64> {
     Some(sum_pv / sum_v)
 }
 
+/// Estado incremental de la VWMA (sumas deslizantes de precio·volumen y volumen)
+#[derive(Debug, Clone)]
+pub struct Vwma {
+    price_volume: RollingWindow,
+    volume: RollingWindow,
+}
+
+impl Vwma {
+    pub fn new(period: usize) -> Self {
+        Self {
+            price_volume: RollingWindow::new(period),
+            volume: RollingWindow::new(period),
+        }
+    }
+}
+
+impl StreamingIndicator for Vwma {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.price_volume.push(candle.close * candle.volume);
+        self.volume.push(candle.volume);
+
+        if !self.volume.is_full() || self.volume.sum() == 0.0 {
+            return None;
+        }
+        Some(self.price_volume.sum() / self.volume.sum())
+    }
+
+    fn reset(&mut self) {
+        self.price_volume.clear();
+        self.volume.clear();
+    }
+}
+
 register_indicator!(metadata);
 
 #[cfg(test)]
@@ -64,6 +99,19 @@ mod tests {
         assert!(vwma(&prices, &volumes, 5).is_none());
     }
 
+    #[test]
+    fn test_vwma_series_matches_scalar() {
+        let prices: Vec<f64> = (0..30).map(|x| 10.0 + x as f64 * 0.5).collect();
+        let volumes: Vec<f64> = (0..30).map(|x| 100.0 + (x % 7) as f64 * 25.0).collect();
+        let series = Vwma::new(5).compute_series_ohlcv(&prices, &prices, &prices, &volumes);
+
+        assert!(series[..4].iter().all(|v| v.is_nan()));
+        for i in 4..prices.len() {
+            let expected = vwma(&prices[..=i], &volumes[..=i], 5).unwrap();
+            assert!((series[i] - expected).abs() < 1e-9);
+        }
+    }
+
     #[test]
     fn test_vwma_zero_volume() {
         let prices = vec![10.0, 11.0, 12.0];
@@ -1,4 +1,6 @@
 use crate::register_indicator;
+use crate::streaming::{PriceIndicator, RollingWindow, StreamingIndicator};
+use darwinx_core::Candle;
 
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
     use crate::metadata::*;
@@ -27,6 +29,53 @@ pub fn wma(data: &[f64], period: usize) -> Option<f64> {
     Some(weighted_sum / weights_sum as f64)
 }
 
+/// Estado incremental de la WMA (O(1) por vela)
+///
+/// Al desplazar la ventana, el peso de cada precio baja en 1: la suma ponderada
+/// nueva es `ponderada - suma + period * precio`.
+#[derive(Debug, Clone)]
+pub struct Wma {
+    window: RollingWindow,
+    period: usize,
+    weighted_sum: f64,
+}
+
+impl Wma {
+    pub fn new(period: usize) -> Self {
+        Self {
+            window: RollingWindow::new(period),
+            period,
+            weighted_sum: 0.0,
+        }
+    }
+}
+
+impl PriceIndicator for Wma {
+    fn update_price(&mut self, price: f64) -> Option<f64> {
+        if self.window.is_full() {
+            self.weighted_sum += self.period as f64 * price - self.window.sum();
+        } else {
+            // Durante el warmup el nuevo precio recibe el peso = posición en la ventana
+            self.weighted_sum += (self.window.len() + 1) as f64 * price;
+        }
+        self.window.push(price);
+
+        let weights_sum = (self.period * (self.period + 1)) as f64 / 2.0;
+        self.window.is_full().then(|| self.weighted_sum / weights_sum)
+    }
+}
+
+impl StreamingIndicator for Wma {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.update_price(candle.close)
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.weighted_sum = 0.0;
+    }
+}
+
 register_indicator!(metadata);
 
 #[cfg(test)]
@@ -52,6 +101,18 @@ mod tests {
         assert!((wma_val - 3.666).abs() < 0.01);
     }
 
+    #[test]
+    fn test_wma_series_matches_scalar() {
+        let data: Vec<f64> = (0..40).map(|x| 50.0 + (x as f64 * 0.3).cos() * 5.0).collect();
+        let series = Wma::new(7).compute_series(&data);
+
+        assert!(series[..6].iter().all(|v| v.is_nan()));
+        for i in 6..data.len() {
+            let expected = wma(&data[..=i], 7).unwrap();
+            assert!((series[i] - expected).abs() < 1e-9);
+        }
+    }
+
     #[test]
     fn test_wma_insufficient_data() {
         let data = vec![1.0, 2.0];
@@ -1,4 +1,6 @@
 use crate::register_indicator;
+use crate::streaming::StreamingIndicator;
+use darwinx_core::Candle;
 
 /// Metadata del indicador ATR
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
@@ -12,22 +14,72 @@ pub fn metadata() -> crate::metadata::IndicatorMetadata {
         .description("Average True Range")
 }
 
+/// Calcula el ATR de Wilder de la última vela de la serie
+///
+/// Necesita al menos `period + 1` velas. Debe recibir toda la historia
+/// disponible: el suavizado de Wilder depende de todos los rangos anteriores.
 pub fn atr(high: &[f64], low: &[f64], close: &[f64], period: usize) -> Option<f64> {
-    if high.len() < period + 1 || low.len() < period + 1 || close.len() < period + 1 {
-        return None;
+    let len = high.len().min(low.len()).min(close.len());
+    let mut state = Atr::new(period);
+    let mut last = None;
+    for i in 0..len {
+        last = state.update(&Candle::new(0, close[i], high[i], low[i], close[i], 0.0));
     }
-    
-    let mut tr_sum = 0.0;
-    
-    for i in 1..=period {
-        let idx = high.len() - i;
-        let h_l = high[idx] - low[idx];
-        let h_c = (high[idx] - close[idx - 1]).abs();
-        let l_c = (low[idx] - close[idx - 1]).abs();
-        tr_sum += h_l.max(h_c).max(l_c);
+    last
+}
+
+/// Estado incremental del ATR con suavizado de Wilder
+///
+/// El true range necesita el cierre anterior, así que el primer valor llega en la
+/// vela `period` como media simple de los primeros `period` rangos.
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+    prev_close: Option<f64>,
+    ranges: usize,
+    value: f64,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            prev_close: None,
+            ranges: 0,
+            value: 0.0,
+        }
+    }
+}
+
+impl StreamingIndicator for Atr {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        let prev_close = self.prev_close.replace(candle.close)?;
+        if self.period == 0 {
+            return None;
+        }
+
+        let true_range = (candle.high - candle.low)
+            .max((candle.high - prev_close).abs())
+            .max((candle.low - prev_close).abs());
+        let n = self.period as f64;
+        self.ranges += 1;
+
+        if self.ranges <= self.period {
+            self.value += true_range / n;
+            if self.ranges < self.period {
+                return None;
+            }
+        } else {
+            self.value = (self.value * (n - 1.0) + true_range) / n;
+        }
+        Some(self.value)
+    }
+
+    fn reset(&mut self) {
+        self.prev_close = None;
+        self.ranges = 0;
+        self.value = 0.0;
     }
-    
-    Some(tr_sum / period as f64)
 }
 
 register_indicator!(metadata);
@@ -52,4 +104,25 @@ mod tests {
         let result = atr(&high, &low, &close, 5);
         assert!(result.is_some());
     }
+
+    #[test]
+    fn test_atr_wilder_reference() {
+        let high = vec![10.0, 11.0, 12.0, 11.5, 13.0, 12.8, 14.0];
+        let low = vec![9.0, 9.5, 10.0, 10.5, 11.0, 11.5, 12.0];
+        let close = vec![9.5, 10.5, 11.0, 11.0, 12.0, 12.5, 13.0];
+        let volume = vec![1000.0; 7];
+
+        // True ranges desde la vela 1: 1.5, 2.0, 1.0, 2.0, 1.3, 2.0
+        let series = Atr::new(3).compute_series_ohlcv(&high, &low, &close, &volume);
+
+        assert!(series[..3].iter().all(|v| v.is_nan()));
+        let first = (1.5 + 2.0 + 1.0) / 3.0;
+        let second = (first * 2.0 + 2.0) / 3.0;
+        let third = (second * 2.0 + 1.3) / 3.0;
+        let fourth = (third * 2.0 + 2.0) / 3.0;
+        for (value, expected) in series[3..].iter().zip([first, second, third, fourth]) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+        assert!((atr(&high, &low, &close, 3).unwrap() - fourth).abs() < 1e-9);
+    }
 }
\ No newline at end of file
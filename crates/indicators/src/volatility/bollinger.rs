@@ -1,5 +1,7 @@
 use crate::register_indicator;
+use crate::streaming::{PriceIndicator, RollingWindow, StreamingIndicator};
 use crate::trend::sma;
+use darwinx_core::Candle;
 
 /// Metadata del indicador Bollinger Bands
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
@@ -34,6 +36,64 @@ pub fn bollinger_bands(data: &[f64], period: usize, std_dev: f64) -> Option<(f64
     ))
 }
 
+/// Estado incremental de las Bandas de Bollinger (sumas deslizantes de x y x²)
+///
+/// Como indicador de una sola serie retorna la banda media;
+/// `value()` da las tres bandas.
+#[derive(Debug, Clone)]
+pub struct BollingerBands {
+    values: RollingWindow,
+    squares: RollingWindow,
+    period: usize,
+    std_dev: f64,
+    value: Option<(f64, f64, f64)>,
+}
+
+impl BollingerBands {
+    pub fn new(period: usize, std_dev: f64) -> Self {
+        Self {
+            values: RollingWindow::new(period),
+            squares: RollingWindow::new(period),
+            period,
+            std_dev,
+            value: None,
+        }
+    }
+
+    /// Últimas bandas (lower, middle, upper)
+    pub fn value(&self) -> Option<(f64, f64, f64)> {
+        self.value
+    }
+}
+
+impl PriceIndicator for BollingerBands {
+    fn update_price(&mut self, price: f64) -> Option<f64> {
+        self.values.push(price);
+        self.squares.push(price * price);
+
+        self.value = self.values.is_full().then(|| {
+            let n = self.period as f64;
+            let mean = self.values.sum() / n;
+            // max(0) evita raíces de varianzas negativas por redondeo
+            let std = (self.squares.sum() / n - mean * mean).max(0.0).sqrt();
+            (mean - self.std_dev * std, mean, mean + self.std_dev * std)
+        });
+        self.value.map(|(_, middle, _)| middle)
+    }
+}
+
+impl StreamingIndicator for BollingerBands {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.update_price(candle.close)
+    }
+
+    fn reset(&mut self) {
+        self.values.clear();
+        self.squares.clear();
+        self.value = None;
+    }
+}
+
 register_indicator!(metadata);
 
 #[cfg(test)]
@@ -57,4 +117,22 @@ mod tests {
         assert!(lower < middle);
         assert!(middle < upper);
     }
+
+    #[test]
+    fn test_bollinger_streaming_matches_scalar() {
+        let data: Vec<f64> = (0..60).map(|x| 100.0 + (x as f64 * 0.25).sin() * 4.0).collect();
+        let mut bands = BollingerBands::new(20, 2.0);
+
+        for i in 0..data.len() {
+            let middle = bands.update_price(data[i]);
+            match bollinger_bands(&data[..=i], 20, 2.0) {
+                Some((lower, expected_middle, upper)) => {
+                    let (l, m, u) = bands.value().unwrap();
+                    assert!((middle.unwrap() - expected_middle).abs() < 1e-9);
+                    assert!((l - lower).abs() < 1e-6 && (m - expected_middle).abs() < 1e-9 && (u - upper).abs() < 1e-6);
+                }
+                None => assert!(middle.is_none()),
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -1,6 +1,10 @@
 use crate::register_indicator;
+use crate::streaming::{PriceIndicator, StreamingIndicator};
 use crate::trend::ema;
+use crate::trend::ema::Ema;
 use crate::volatility::atr;
+use crate::volatility::atr::Atr;
+use darwinx_core::Candle;
 
 /// Metadata del indicador Keltner Channels
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
@@ -34,6 +38,52 @@ pub fn keltner_channels(
     ))
 }
 
+/// Estado incremental de los Keltner Channels (EMA del cierre ± ATR)
+///
+/// Como indicador de una sola serie retorna la banda media;
+/// `value()` da los tres canales.
+#[derive(Debug, Clone)]
+pub struct KeltnerChannels {
+    middle: Ema,
+    atr: Atr,
+    multiplier: f64,
+    value: Option<(f64, f64, f64)>,
+}
+
+impl KeltnerChannels {
+    pub fn new(period: usize, multiplier: f64) -> Self {
+        Self {
+            middle: Ema::new(period),
+            atr: Atr::new(period),
+            multiplier,
+            value: None,
+        }
+    }
+
+    /// Últimos canales (lower, middle, upper)
+    pub fn value(&self) -> Option<(f64, f64, f64)> {
+        self.value
+    }
+}
+
+impl StreamingIndicator for KeltnerChannels {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        let middle = self.middle.update_price(candle.close);
+        let atr_val = self.atr.update(candle);
+
+        self.value = middle
+            .zip(atr_val)
+            .map(|(m, a)| (m - self.multiplier * a, m, m + self.multiplier * a));
+        self.value.map(|(_, middle, _)| middle)
+    }
+
+    fn reset(&mut self) {
+        self.middle.reset();
+        self.atr.reset();
+        self.value = None;
+    }
+}
+
 register_indicator!(metadata);
 
 #[cfg(test)]
@@ -60,4 +110,20 @@ mod tests {
         assert!(lower < middle);
         assert!(middle < upper);
     }
+
+    #[test]
+    fn test_keltner_streaming_matches_scalar() {
+        let high: Vec<f64> = (10..40).map(|x| x as f64).collect();
+        let low: Vec<f64> = (8..38).map(|x| x as f64).collect();
+        let close: Vec<f64> = (9..39).map(|x| x as f64).collect();
+        let volume = vec![1000.0; close.len()];
+
+        let mut channels = KeltnerChannels::new(20, 2.0);
+        let series = channels.compute_series_ohlcv(&high, &low, &close, &volume);
+
+        // La EMA está lista en la vela 19, pero el ATR necesita una vela más
+        assert!(series[..20].iter().all(|v| v.is_nan()));
+        let expected = keltner_channels(&high, &low, &close, 20, 2.0).unwrap();
+        assert_eq!(channels.value(), Some(expected));
+    }
 }
\ No newline at end of file
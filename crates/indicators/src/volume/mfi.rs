@@ -1,4 +1,6 @@
 use crate::register_indicator;
+use crate::streaming::{RollingWindow, StreamingIndicator};
+use darwinx_core::Candle;
 
 /// Metadata del indicador MFI
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
@@ -41,6 +43,56 @@ pub fn mfi(high: &[f64], low: &[f64], close: &[f64], volume: &[f64], period: usi
     Some(100.0 - (100.0 / (1.0 + money_ratio)))
 }
 
+/// Estado incremental del MFI (sumas deslizantes de flujo positivo y negativo)
+#[derive(Debug, Clone)]
+pub struct Mfi {
+    prev_typical: Option<f64>,
+    positive: RollingWindow,
+    negative: RollingWindow,
+}
+
+impl Mfi {
+    pub fn new(period: usize) -> Self {
+        Self {
+            prev_typical: None,
+            positive: RollingWindow::new(period),
+            negative: RollingWindow::new(period),
+        }
+    }
+}
+
+impl StreamingIndicator for Mfi {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        let typical = candle.typical_price();
+        let prev_typical = self.prev_typical.replace(typical)?;
+
+        // Igual que `mfi`: un precio típico sin cambios cuenta como flujo negativo
+        let money_flow = typical * candle.volume;
+        let (positive, negative) = if typical > prev_typical {
+            (money_flow, 0.0)
+        } else {
+            (0.0, money_flow)
+        };
+        self.positive.push(positive);
+        self.negative.push(negative);
+
+        if !self.positive.is_full() {
+            return None;
+        }
+        if self.negative.sum() == 0.0 {
+            return Some(100.0);
+        }
+        let money_ratio = self.positive.sum() / self.negative.sum();
+        Some(100.0 - (100.0 / (1.0 + money_ratio)))
+    }
+
+    fn reset(&mut self) {
+        self.prev_typical = None;
+        self.positive.clear();
+        self.negative.clear();
+    }
+}
+
 register_indicator!(metadata);
 
 #[cfg(test)]
@@ -64,4 +116,20 @@ mod tests {
         let result = mfi(&high, &low, &close, &volume, 14);
         assert!(result.is_some());
     }
+
+    #[test]
+    fn test_mfi_series_matches_scalar() {
+        let close: Vec<f64> = (0..40).map(|x| 50.0 + (x as f64 * 0.5).sin() * 3.0).collect();
+        let high: Vec<f64> = close.iter().map(|c| c + 0.5).collect();
+        let low: Vec<f64> = close.iter().map(|c| c - 0.5).collect();
+        let volume: Vec<f64> = (0..40).map(|x| 1000.0 + (x % 5) as f64 * 100.0).collect();
+
+        let series = Mfi::new(14).compute_series_ohlcv(&high, &low, &close, &volume);
+
+        assert!(series[..14].iter().all(|v| v.is_nan()));
+        for i in 14..close.len() {
+            let expected = mfi(&high[..=i], &low[..=i], &close[..=i], &volume[..=i], 14).unwrap();
+            assert!((series[i] - expected).abs() < 1e-9);
+        }
+    }
 }
\ No newline at end of file
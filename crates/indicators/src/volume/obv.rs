@@ -1,4 +1,6 @@
 use crate::register_indicator;
+use crate::streaming::StreamingIndicator;
+use darwinx_core::Candle;
 
 /// Metadata del indicador OBV
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
@@ -32,6 +34,35 @@ pub fn obv(close: &[f64], volume: &[f64]) -> Option<Vec<f64>> {
     Some(obv_values)
 }
 
+/// Estado incremental del OBV (el primer valor es el volumen de la primera vela)
+#[derive(Debug, Clone, Default)]
+pub struct Obv {
+    prev_close: Option<f64>,
+    value: f64,
+}
+
+impl Obv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StreamingIndicator for Obv {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        match self.prev_close.replace(candle.close) {
+            None => self.value = candle.volume,
+            Some(prev) if candle.close > prev => self.value += candle.volume,
+            Some(prev) if candle.close < prev => self.value -= candle.volume,
+            Some(_) => {}
+        }
+        Some(self.value)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
 register_indicator!(metadata);
 
 #[cfg(test)]
@@ -58,6 +89,16 @@ mod tests {
         assert_eq!(obv_values[0], 100.0);
     }
 
+    #[test]
+    fn test_obv_streaming_matches_series() {
+        let close = vec![10.0, 11.0, 10.5, 12.0, 11.5, 11.5];
+        let volume = vec![100.0, 150.0, 120.0, 200.0, 180.0, 90.0];
+
+        let series = Obv::new().compute_series_ohlcv(&close, &close, &close, &volume);
+
+        assert_eq!(series, obv(&close, &volume).unwrap());
+    }
+
     #[test]
     fn test_obv_mismatched_lengths() {
         let close = vec![10.0, 11.0];
@@ -1,4 +1,6 @@
 use crate::register_indicator;
+use crate::streaming::StreamingIndicator;
+use darwinx_core::Candle;
 
 /// Metadata del indicador VWAP
 pub fn metadata() -> crate::metadata::IndicatorMetadata {
@@ -34,6 +36,35 @@ pub fn vwap(high: &[f64], low: &[f64], close: &[f64], volume: &[f64]) -> Option<
     Some(sum_pv / sum_v)
 }
 
+/// Estado incremental del VWAP acumulado desde la primera vela
+#[derive(Debug, Clone, Default)]
+pub struct Vwap {
+    sum_pv: f64,
+    sum_v: f64,
+}
+
+impl Vwap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StreamingIndicator for Vwap {
+    fn update(&mut self, candle: &Candle) -> Option<f64> {
+        self.sum_pv += candle.typical_price() * candle.volume;
+        self.sum_v += candle.volume;
+
+        if self.sum_v == 0.0 {
+            return None;
+        }
+        Some(self.sum_pv / self.sum_v)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
 register_indicator!(metadata);
 
 #[cfg(test)]
@@ -58,6 +89,21 @@ mod tests {
         assert!(result.is_some());
     }
 
+    #[test]
+    fn test_vwap_series_matches_scalar() {
+        let high = vec![11.0, 12.0, 13.0, 12.5, 14.0];
+        let low = vec![9.0, 10.0, 11.0, 11.5, 12.0];
+        let close = vec![10.0, 11.0, 12.0, 12.0, 13.0];
+        let volume = vec![1000.0, 1500.0, 1200.0, 1300.0, 1100.0];
+
+        let series = Vwap::new().compute_series_ohlcv(&high, &low, &close, &volume);
+
+        for i in 0..close.len() {
+            let expected = vwap(&high[..=i], &low[..=i], &close[..=i], &volume[..=i]).unwrap();
+            assert!((series[i] - expected).abs() < 1e-9);
+        }
+    }
+
     #[test]
     fn test_vwap_empty() {
         let empty: Vec<f64> = vec![];
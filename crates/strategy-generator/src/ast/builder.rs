@@ -39,6 +39,44 @@ impl StrategyBuilder {
         self
     }
 
+    /// Agrega una condición de entrada en corto
+    pub fn add_short_entry_condition(mut self, condition: Condition) -> Self {
+        self.strategy
+            .short_entry_rules
+            .get_or_insert_with(|| RuleSet::new(LogicalOperator::And))
+            .conditions
+            .push(condition);
+        self
+    }
+
+    /// Agrega una condición de salida de posiciones cortas
+    pub fn add_short_exit_condition(mut self, condition: Condition) -> Self {
+        self.strategy
+            .short_exit_rules
+            .get_or_insert_with(|| RuleSet::new(LogicalOperator::And))
+            .conditions
+            .push(condition);
+        self
+    }
+
+    /// Define el operador lógico para las reglas de entrada en corto
+    pub fn short_entry_operator(mut self, operator: LogicalOperator) -> Self {
+        self.strategy
+            .short_entry_rules
+            .get_or_insert_with(|| RuleSet::new(operator))
+            .operator = operator;
+        self
+    }
+
+    /// Define el operador lógico para las reglas de salida en corto
+    pub fn short_exit_operator(mut self, operator: LogicalOperator) -> Self {
+        self.strategy
+            .short_exit_rules
+            .get_or_insert_with(|| RuleSet::new(operator))
+            .operator = operator;
+        self
+    }
+
     /// Construye la estrategia final
     pub fn build(self) -> StrategyAST {
         self.strategy
@@ -70,6 +108,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name, params),
             comparison: Comparison::GreaterThan,
             value: ConditionValue::Number(value),
+            timeframe: None,
         }
     }
     
@@ -79,6 +118,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name, params),
             comparison: Comparison::LessThan,
             value: ConditionValue::Number(value),
+            timeframe: None,
         }
     }
     
@@ -88,6 +128,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name, params),
             comparison: Comparison::Equals,
             value: ConditionValue::Number(value),
+            timeframe: None,
         }
     }
     
@@ -117,6 +158,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name1, params1),
             comparison: Comparison::GreaterThan,
             value: ConditionValue::Indicator(IndicatorType::new(name2, params2)),
+            timeframe: None,
         }
     }
     
@@ -131,6 +173,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name1, params1),
             comparison: Comparison::LessThan,
             value: ConditionValue::Indicator(IndicatorType::new(name2, params2)),
+            timeframe: None,
         }
     }
     
@@ -140,6 +183,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name, params),
             comparison: Comparison::CrossesAbove,
             value: ConditionValue::Number(value),
+            timeframe: None,
         }
     }
     
@@ -149,6 +193,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name, params),
             comparison: Comparison::CrossesBelow,
             value: ConditionValue::Number(value),
+            timeframe: None,
         }
     }
     
@@ -163,6 +208,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name1, params1),
             comparison: Comparison::CrossesAbove,
             value: ConditionValue::Indicator(IndicatorType::new(name2, params2)),
+            timeframe: None,
         }
     }
     
@@ -177,6 +223,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name1, params1),
             comparison: Comparison::CrossesBelow,
             value: ConditionValue::Indicator(IndicatorType::new(name2, params2)),
+            timeframe: None,
         }
     }
     
@@ -186,6 +233,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name, params),
             comparison: Comparison::GreaterThan,
             value: ConditionValue::Price,
+            timeframe: None,
         }
     }
     
@@ -195,6 +243,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name, params),
             comparison: Comparison::LessThan,
             value: ConditionValue::Price,
+            timeframe: None,
         }
     }
     
@@ -204,6 +253,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name, params),
             comparison: Comparison::CrossesBelow,  // Invertido: precio sube = indicador baja relativamente
             value: ConditionValue::Price,
+            timeframe: None,
         }
     }
     
@@ -213,6 +263,7 @@ impl ConditionBuilder {
             indicator: IndicatorType::new(name, params),
             comparison: Comparison::CrossesAbove,  // Invertido
             value: ConditionValue::Price,
+            timeframe: None,
         }
     }
 }
@@ -274,6 +325,23 @@ mod tests {
         assert_eq!(strategy.exit_rules.operator, LogicalOperator::Or);
     }
 
+    #[test]
+    fn test_long_short_strategy() {
+        let strategy = StrategyBuilder::new("Long/Short".to_string(), TimeFrame::H1)
+            .add_entry_condition(ConditionBuilder::crosses_above("sma", vec![10.0], "sma", vec![30.0]))
+            .add_exit_condition(ConditionBuilder::crosses_below("sma", vec![10.0], "sma", vec![30.0]))
+            .add_short_entry_condition(ConditionBuilder::crosses_below("sma", vec![10.0], "sma", vec![30.0]))
+            .add_short_exit_condition(ConditionBuilder::crosses_above("sma", vec![10.0], "sma", vec![30.0]))
+            .short_exit_operator(LogicalOperator::Or)
+            .build();
+
+        assert_eq!(strategy.direction(), TradeDirection::LongShort);
+        let short_entry = strategy.short_entry_rules.as_ref().unwrap();
+        assert_eq!(short_entry.conditions.len(), 1);
+        assert_eq!(short_entry.operator, LogicalOperator::And);
+        assert_eq!(strategy.short_exit_rules.as_ref().unwrap().operator, LogicalOperator::Or);
+    }
+
     #[test]
     fn test_price_based_conditions() {
         let strategy = StrategyBuilder::new("Price Test".to_string(), TimeFrame::H1)
@@ -10,6 +10,23 @@ pub struct StrategyAST {
     pub timeframe: TimeFrame,
     pub entry_rules: RuleSet,
     pub exit_rules: RuleSet,
+    /// Reglas de entrada en corto (None = la estrategia no opera en corto)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub short_entry_rules: Option<RuleSet>,
+    /// Reglas de salida de posiciones cortas
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub short_exit_rules: Option<RuleSet>,
+}
+
+/// Dirección en la que opera una estrategia
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TradeDirection {
+    /// Solo posiciones largas (comportamiento clásico)
+    LongOnly,
+    /// Solo posiciones cortas
+    ShortOnly,
+    /// Posiciones largas y cortas
+    LongShort,
 }
 
 /// Conjunto de reglas
@@ -19,6 +36,16 @@ pub struct RuleSet {
     pub conditions: Vec<Condition>,
 }
 
+impl RuleSet {
+    /// Crea un conjunto de reglas vacío con el operador indicado
+    pub fn new(operator: LogicalOperator) -> Self {
+        Self {
+            operator,
+            conditions: Vec::new(),
+        }
+    }
+}
+
 /// Operador lógico
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LogicalOperator {
@@ -32,6 +59,36 @@ pub struct Condition {
     pub indicator: IndicatorType,
     pub comparison: Comparison,
     pub value: ConditionValue,
+    /// Timeframe superior en el que se evalúan el indicador y el valor (None = serie principal)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeframe: Option<ConditionTimeframe>,
+}
+
+impl Condition {
+    /// Evalúa la condición en un timeframe superior
+    pub fn with_timeframe(mut self, timeframe: ConditionTimeframe) -> Self {
+        self.timeframe = Some(timeframe);
+        self
+    }
+}
+
+/// Timeframe de una condición, relativo a la serie principal o absoluto
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ConditionTimeframe {
+    /// Múltiplo del intervalo de la serie principal (ej: 4 sobre velas de 1h = 4h)
+    Relative(u32),
+    /// Timeframe fijo (ej: H4)
+    Absolute(TimeFrame),
+}
+
+impl ConditionTimeframe {
+    /// Duración en milisegundos dado el intervalo de la serie principal
+    pub fn to_millis(&self, base_millis: i64) -> i64 {
+        match self {
+            ConditionTimeframe::Relative(multiple) => base_millis * i64::from(*multiple),
+            ConditionTimeframe::Absolute(timeframe) => timeframe.to_millis(),
+        }
+    }
 }
 
 /// 🎯 NUEVO: Indicador dinámico (funciona con cualquier indicador del registry)
@@ -121,12 +178,71 @@ impl StrategyAST {
                 operator: LogicalOperator::And,
                 conditions: Vec::new(),
             },
+            short_entry_rules: None,
+            short_exit_rules: None,
         }
     }
 
     /// Retorna la complejidad de la estrategia
     pub fn complexity(&self) -> usize {
-        self.entry_rules.conditions.len() + self.exit_rules.conditions.len()
+        self.all_conditions().count()
+    }
+
+    /// Indica si la estrategia tiene reglas de entrada en largo
+    pub fn has_long_rules(&self) -> bool {
+        !self.entry_rules.conditions.is_empty()
+    }
+
+    /// Indica si la estrategia tiene reglas de entrada en corto
+    pub fn has_short_rules(&self) -> bool {
+        self.short_entry_rules
+            .as_ref()
+            .is_some_and(|rules| !rules.conditions.is_empty())
+    }
+
+    /// Dirección en la que opera la estrategia según sus reglas de entrada
+    pub fn direction(&self) -> TradeDirection {
+        match (self.has_long_rules(), self.has_short_rules()) {
+            (true, true) => TradeDirection::LongShort,
+            (false, true) => TradeDirection::ShortOnly,
+            _ => TradeDirection::LongOnly,
+        }
+    }
+
+    /// Itera sobre todas las condiciones (largo y corto, entrada y salida)
+    pub fn all_conditions(&self) -> impl Iterator<Item = &Condition> {
+        self.entry_rules
+            .conditions
+            .iter()
+            .chain(self.exit_rules.conditions.iter())
+            .chain(self.short_entry_rules.iter().flat_map(|r| r.conditions.iter()))
+            .chain(self.short_exit_rules.iter().flat_map(|r| r.conditions.iter()))
+    }
+
+    /// Timeframes superiores distintos usados por las condiciones, en orden de aparición
+    pub fn higher_timeframes(&self) -> Vec<ConditionTimeframe> {
+        let mut timeframes = Vec::new();
+        for timeframe in self.all_conditions().filter_map(|c| c.timeframe) {
+            if !timeframes.contains(&timeframe) {
+                timeframes.push(timeframe);
+            }
+        }
+        timeframes
+    }
+
+    /// Número de timeframes que usa la estrategia (el principal más los superiores)
+    pub fn timeframe_count(&self) -> usize {
+        1 + self.higher_timeframes().len()
+    }
+
+    /// Itera mutablemente sobre todas las condiciones
+    pub fn all_conditions_mut(&mut self) -> impl Iterator<Item = &mut Condition> {
+        self.entry_rules
+            .conditions
+            .iter_mut()
+            .chain(self.exit_rules.conditions.iter_mut())
+            .chain(self.short_entry_rules.iter_mut().flat_map(|r| r.conditions.iter_mut()))
+            .chain(self.short_exit_rules.iter_mut().flat_map(|r| r.conditions.iter_mut()))
     }
 }
 
@@ -149,11 +265,71 @@ mod tests {
             indicator: IndicatorType::with_period("rsi", 14),
             comparison: Comparison::GreaterThan,
             value: ConditionValue::Number(50.0),
+            timeframe: None,
         });
         
         assert_eq!(strategy.complexity(), 1);
     }
 
+    #[test]
+    fn test_direction() {
+        let mut strategy = StrategyAST::new("Test".to_string(), TimeFrame::H1);
+        let condition = Condition {
+            indicator: IndicatorType::with_period("rsi", 14),
+            comparison: Comparison::GreaterThan,
+            value: ConditionValue::Number(70.0),
+            timeframe: None,
+        };
+
+        strategy.short_entry_rules = Some(RuleSet::new(LogicalOperator::And));
+        strategy.short_entry_rules.as_mut().unwrap().conditions.push(condition.clone());
+        assert_eq!(strategy.direction(), TradeDirection::ShortOnly);
+        assert_eq!(strategy.complexity(), 1);
+
+        strategy.entry_rules.conditions.push(condition);
+        assert_eq!(strategy.direction(), TradeDirection::LongShort);
+        assert_eq!(strategy.complexity(), 2);
+    }
+
+    #[test]
+    fn test_deserialize_without_short_rules() {
+        let strategy = StrategyAST::new("Legacy".to_string(), TimeFrame::H1);
+        let mut json = serde_json::to_value(&strategy).unwrap();
+        assert!(json.get("short_entry_rules").is_none());
+
+        json.as_object_mut().unwrap().remove("short_exit_rules");
+        let parsed: StrategyAST = serde_json::from_value(json).unwrap();
+        assert!(parsed.short_entry_rules.is_none());
+        assert_eq!(parsed.direction(), TradeDirection::LongOnly);
+    }
+
+    #[test]
+    fn test_condition_timeframes() {
+        let rsi = |timeframe: Option<ConditionTimeframe>| Condition {
+            indicator: IndicatorType::with_period("rsi", 14),
+            comparison: Comparison::GreaterThan,
+            value: ConditionValue::Number(50.0),
+            timeframe,
+        };
+        let mut strategy = StrategyAST::new("Test".to_string(), TimeFrame::H1);
+        strategy.entry_rules.conditions.push(rsi(None));
+        assert_eq!(strategy.timeframe_count(), 1);
+
+        strategy.entry_rules.conditions.push(rsi(Some(ConditionTimeframe::Relative(4))));
+        strategy.exit_rules.conditions.push(rsi(Some(ConditionTimeframe::Absolute(TimeFrame::D1))));
+        strategy.exit_rules.conditions.push(rsi(Some(ConditionTimeframe::Relative(4))));
+        assert_eq!(
+            strategy.higher_timeframes(),
+            vec![ConditionTimeframe::Relative(4), ConditionTimeframe::Absolute(TimeFrame::D1)]
+        );
+        assert_eq!(strategy.timeframe_count(), 3);
+        assert_eq!(ConditionTimeframe::Relative(4).to_millis(TimeFrame::H1.to_millis()), TimeFrame::H4.to_millis());
+
+        // Las condiciones sin timeframe no lo serializan
+        let json = serde_json::to_value(&strategy.entry_rules.conditions[0]).unwrap();
+        assert!(json.get("timeframe").is_none());
+    }
+
     #[test]
     fn test_indicator_display() {
         let sma = IndicatorType::with_period("sma", 20);
@@ -35,15 +35,23 @@ impl StrategyValidator {
             ));
         }
 
-        // Validar reglas de entrada
-        if strategy.entry_rules.conditions.is_empty() {
+        // Validar reglas de entrada (en largo o en corto)
+        if !strategy.has_long_rules() && !strategy.has_short_rules() {
             errors.push("Debe tener al menos una condición de entrada".to_string());
         }
 
-        // Validar reglas de salida
-        if strategy.exit_rules.conditions.is_empty() {
+        // Validar reglas de salida (solo se exigen para el lado que opera)
+        if !strategy.has_short_rules() && strategy.exit_rules.conditions.is_empty() {
             errors.push("Debe tener al menos una condición de salida".to_string());
         }
+        if strategy.has_long_rules() && strategy.has_short_rules() && strategy.exit_rules.conditions.is_empty() {
+            errors.push("Debe tener al menos una condición de salida en largo".to_string());
+        }
+        if strategy.has_short_rules()
+            && strategy.short_exit_rules.as_ref().is_none_or(|r| r.conditions.is_empty())
+        {
+            errors.push("Debe tener al menos una condición de salida en corto".to_string());
+        }
 
         // Validar indicadores
         let indicator_count = self.count_unique_indicators(strategy);
@@ -54,9 +62,24 @@ impl StrategyValidator {
             ));
         }
 
+        // Validar timeframes (el principal más los superiores de las condiciones)
+        let timeframe_count = strategy.timeframe_count();
+        if timeframe_count > self.constraints.max_timeframes {
+            errors.push(format!(
+                "Número de timeframes {} excede el máximo {}",
+                timeframe_count, self.constraints.max_timeframes
+            ));
+        }
+
         // Validar cada condición
         self.validate_conditions(&strategy.entry_rules.conditions, "entrada", &mut errors);
         self.validate_conditions(&strategy.exit_rules.conditions, "salida", &mut errors);
+        if let Some(rules) = &strategy.short_entry_rules {
+            self.validate_conditions(&rules.conditions, "entrada en corto", &mut errors);
+        }
+        if let Some(rules) = &strategy.short_exit_rules {
+            self.validate_conditions(&rules.conditions, "salida en corto", &mut errors);
+        }
 
         if errors.is_empty() {
             Ok(())
@@ -68,14 +91,7 @@ impl StrategyValidator {
     fn count_unique_indicators(&self, strategy: &StrategyAST) -> usize {
         let mut indicators = std::collections::HashSet::new();
 
-        for condition in &strategy.entry_rules.conditions {
-            indicators.insert(condition.indicator.name().to_string());
-            if let ConditionValue::Indicator(ind) = &condition.value {
-                indicators.insert(ind.name().to_string());
-            }
-        }
-
-        for condition in &strategy.exit_rules.conditions {
+        for condition in strategy.all_conditions() {
             indicators.insert(condition.indicator.name().to_string());
             if let ConditionValue::Indicator(ind) = &condition.value {
                 indicators.insert(ind.name().to_string());
@@ -168,9 +184,7 @@ impl StrategyValidator {
         let mut seen = std::collections::HashMap::new();
 
         // Contar ocurrencias de cada combinación indicador+params
-        for condition in strategy.entry_rules.conditions.iter()
-            .chain(strategy.exit_rules.conditions.iter())
-        {
+        for condition in strategy.all_conditions() {
             let key = format!("{}({:?})", 
                 condition.indicator.name(), 
                 condition.indicator.params()
@@ -263,6 +277,30 @@ mod tests {
         assert!(errors.iter().any(|e| e.contains("entrada")));
     }
 
+    #[test]
+    fn test_short_only_strategy() {
+        let strategy = StrategyBuilder::new("Short".to_string(), TimeFrame::H1)
+            .add_short_entry_condition(ConditionBuilder::above("rsi", vec![14.0], 70.0))
+            .add_short_exit_condition(ConditionBuilder::below("rsi", vec![14.0], 30.0))
+            .build();
+
+        let validator = StrategyValidator::new(StrategyConstraints::default());
+        assert!(validator.validate(&strategy).is_ok());
+    }
+
+    #[test]
+    fn test_short_entry_without_short_exit() {
+        let strategy = StrategyBuilder::new("Short".to_string(), TimeFrame::H1)
+            .add_entry_condition(ConditionBuilder::below("rsi", vec![14.0], 30.0))
+            .add_exit_condition(ConditionBuilder::above("rsi", vec![14.0], 70.0))
+            .add_short_entry_condition(ConditionBuilder::above("rsi", vec![14.0], 70.0))
+            .build();
+
+        let validator = StrategyValidator::new(StrategyConstraints::default());
+        let errors = validator.validate(&strategy).unwrap_err();
+        assert!(errors.iter().any(|e| e.contains("salida en corto")));
+    }
+
     #[test]
     fn test_unknown_indicator() {
         let mut strategy = StrategyAST::new("Test".to_string(), TimeFrame::H1);
@@ -270,6 +308,7 @@ mod tests {
             indicator: IndicatorType::new("indicador_inexistente", vec![20.0]),
             comparison: Comparison::GreaterThan,
             value: ConditionValue::Number(50.0),
+            timeframe: None,
         });
         strategy.exit_rules.conditions.push(
             ConditionBuilder::below("rsi", vec![14.0], 30.0)
@@ -293,6 +332,7 @@ mod tests {
             indicator: IndicatorType::new("sma", vec![20.0, 30.0]), // Mal: 2 params
             comparison: Comparison::GreaterThan,
             value: ConditionValue::Number(100.0),
+            timeframe: None,
         });
         strategy.exit_rules.conditions.push(
             ConditionBuilder::below("rsi", vec![14.0], 30.0)
@@ -318,6 +358,7 @@ mod tests {
             indicator: IndicatorType::new("rsi", vec![1.0]), // < min
             comparison: Comparison::GreaterThan,
             value: ConditionValue::Number(70.0),
+            timeframe: None,
         });
         strategy.exit_rules.conditions.push(
             ConditionBuilder::below("rsi", vec![14.0], 30.0)
@@ -399,6 +440,21 @@ mod tests {
         assert!(errors.iter().any(|e| e.contains("Complejidad") || e.contains("excede")));
     }
 
+    #[test]
+    fn test_too_many_timeframes() {
+        let strategy = StrategyBuilder::new("Test".to_string(), TimeFrame::H1)
+            .add_entry_condition(ConditionBuilder::above("rsi", vec![14.0], 50.0))
+            .add_entry_condition(
+                ConditionBuilder::above("rsi", vec![14.0], 50.0).with_timeframe(ConditionTimeframe::Relative(4)),
+            )
+            .add_exit_condition(ConditionBuilder::below("rsi", vec![14.0], 30.0))
+            .build();
+
+        assert!(StrategyValidator::new(StrategyConstraints::moderate()).validate(&strategy).is_ok());
+        let errors = StrategyValidator::new(StrategyConstraints::strict()).validate(&strategy).unwrap_err();
+        assert!(errors.iter().any(|e| e.contains("timeframes")));
+    }
+
     #[test]
     fn test_any_valid_indicator() {
         // Debe funcionar con CUALQUIER indicador del registry
@@ -6,6 +6,7 @@
 
 use crate::ast::nodes::*;
 use crate::generator::random::RandomGenerator;
+use darwinx_core::TimeFrame;
 use darwinx_indicators::registry;
 use rand::prelude::*;
 
@@ -29,6 +30,7 @@ use rand::prelude::*;
 //     mutation_rate: 0.2,
 //     elite_size: 3,
 //     tournament_size: 3,
+//     ..Default::default()
 // };
 // ```
 #[derive(Debug, Clone)]
@@ -38,6 +40,10 @@ pub struct GeneticConfig {
     pub mutation_rate: f64,
     pub elite_size: usize,
     pub tournament_size: usize,
+    /// Generaciones sin mejora antes de parar (0 = sin parada anticipada)
+    pub patience: usize,
+    /// Mejora mínima del mejor fitness para reiniciar la paciencia
+    pub min_improvement: f64,
 }
 
 impl Default for GeneticConfig {
@@ -48,10 +54,54 @@ impl Default for GeneticConfig {
             mutation_rate: 0.1,
             elite_size: 10,
             tournament_size: 3,
+            patience: 25,
+            min_improvement: 0.0,
         }
     }
 }
 
+/// Estadísticas de una generación del proceso evolutivo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationStats {
+    /// Número de generación (0 = población inicial)
+    pub generation: usize,
+    pub best_fitness: f64,
+    pub average_fitness: f64,
+    pub worst_fitness: f64,
+    pub population_size: usize,
+}
+
+impl GenerationStats {
+    fn from_fitness(generation: usize, fitness: &[f64]) -> Self {
+        let finite: Vec<f64> = fitness.iter().copied().filter(|f| f.is_finite()).collect();
+        let average_fitness = if finite.is_empty() {
+            f64::NEG_INFINITY
+        } else {
+            finite.iter().sum::<f64>() / finite.len() as f64
+        };
+        Self {
+            generation,
+            best_fitness: fitness.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            average_fitness,
+            worst_fitness: fitness.iter().copied().fold(f64::INFINITY, f64::min),
+            population_size: fitness.len(),
+        }
+    }
+}
+
+/// Resultado de `GeneticGenerator::evolve_batch`
+#[derive(Debug, Clone)]
+pub struct EvolutionResult {
+    /// Población final ordenada por fitness (mejor primero)
+    pub population: Vec<StrategyAST>,
+    /// Fitness de cada estrategia de `population` (mismo orden)
+    pub fitness: Vec<f64>,
+    /// Estadísticas por generación evaluada
+    pub stats: Vec<GenerationStats>,
+    /// true si se detuvo por falta de mejora antes de `generations`
+    pub converged: bool,
+}
+
 // Generador genético de estrategias de trading
 //
 // Implementa un algoritmo genético completo para evolucionar estrategias de trading
@@ -99,6 +149,25 @@ impl GeneticGenerator {
         }
     }
 
+    // Define la dirección (largo, corto o ambas) de las estrategias generadas
+    pub fn with_direction(mut self, direction: TradeDirection) -> Self {
+        self.random_gen = self.random_gen.with_direction(direction);
+        self
+    }
+
+    // Timeframes principales posibles de las estrategias aleatorias
+    pub fn with_timeframes(mut self, timeframes: Vec<TimeFrame>) -> Self {
+        self.random_gen = self.random_gen.with_timeframes(timeframes);
+        self
+    }
+
+    // Máximo de timeframes por estrategia (contando el principal) en la población,
+    // los cruces y las mutaciones
+    pub fn with_max_timeframes(mut self, max_timeframes: usize) -> Self {
+        self.random_gen = self.random_gen.with_max_timeframes(max_timeframes);
+        self
+    }
+
     // Genera una población inicial de estrategias aleatorias
     //
     // Utiliza el generador aleatorio interno para crear estrategias válidas
@@ -169,6 +238,20 @@ impl GeneticGenerator {
             parent2.entry_rules.operator
         };
 
+        // Crossover de reglas en corto: se heredan completas (entrada y salida) de un padre
+        let short_parent = match (parent1.has_short_rules(), parent2.has_short_rules()) {
+            (true, true) => Some(if rng.gen_bool(0.5) { parent1 } else { parent2 }),
+            (true, false) => Some(parent1),
+            (false, true) => Some(parent2),
+            (false, false) => None,
+        };
+        if let Some(parent) = short_parent {
+            child.short_entry_rules = parent.short_entry_rules.clone();
+            child.short_exit_rules = parent.short_exit_rules.clone();
+        }
+
+        // Los padres pueden aportar timeframes distintos
+        self.limit_timeframes(&mut child);
         child
     }
 
@@ -181,7 +264,10 @@ impl GeneticGenerator {
     // 3. **Cambiar operador lógico** (probabilidad: mutation_rate * 0.5)
     // 4. **Ajustar parámetros de indicadores** (probabilidad: mutation_rate * 0.3)
     // 5. **Cambiar comparador** (probabilidad: mutation_rate * 0.2)
+    // 6. **Cambiar timeframe de una condición** (probabilidad: mutation_rate * 0.2,
+    //    solo si se permite más de un timeframe)
     //
+    // Las condiciones reemplazadas conservan su timeframe.
     // # Argumentos
     //
     // * `strategy` - Estrategia a mutar (modificada in-place)
@@ -192,7 +278,8 @@ impl GeneticGenerator {
         if rng.gen_bool(self.config.mutation_rate) {
             if !strategy.entry_rules.conditions.is_empty() {
                 let idx = rng.gen_range(0..strategy.entry_rules.conditions.len());
-                strategy.entry_rules.conditions[idx] = self.random_condition(&mut rng);
+                let timeframe = strategy.entry_rules.conditions[idx].timeframe;
+                strategy.entry_rules.conditions[idx] = self.random_condition(timeframe, &mut rng);
             }
         }
 
@@ -200,17 +287,41 @@ impl GeneticGenerator {
         if rng.gen_bool(self.config.mutation_rate) {
             if !strategy.exit_rules.conditions.is_empty() {
                 let idx = rng.gen_range(0..strategy.exit_rules.conditions.len());
-                strategy.exit_rules.conditions[idx] = self.random_condition(&mut rng);
+                let timeframe = strategy.exit_rules.conditions[idx].timeframe;
+                strategy.exit_rules.conditions[idx] = self.random_condition(timeframe, &mut rng);
+            }
+        }
+
+        // Mutación 2b: Reemplazar condición de entrada/salida en corto
+        if rng.gen_bool(self.config.mutation_rate) {
+            let short_rules = if rng.gen_bool(0.5) {
+                strategy.short_entry_rules.as_mut()
+            } else {
+                strategy.short_exit_rules.as_mut()
+            };
+            if let Some(rules) = short_rules.filter(|r| !r.conditions.is_empty()) {
+                let idx = rng.gen_range(0..rules.conditions.len());
+                let timeframe = rules.conditions[idx].timeframe;
+                rules.conditions[idx] = self.random_condition(timeframe, &mut rng);
             }
         }
 
-        // Mutación 3: Cambiar operador lógico
+        // Mutación 3: Cambiar operador lógico (de la entrada en largo o en corto)
         if rng.gen_bool(self.config.mutation_rate * 0.5) {
-            strategy.entry_rules.operator = if rng.gen_bool(0.5) {
+            let operator = if rng.gen_bool(0.5) {
                 LogicalOperator::And
             } else {
                 LogicalOperator::Or
             };
+            let mutate_short = match (strategy.has_long_rules(), strategy.short_entry_rules.is_some()) {
+                (true, true) => rng.gen_bool(0.5),
+                (false, true) => true,
+                _ => false,
+            };
+            match strategy.short_entry_rules.as_mut().filter(|_| mutate_short) {
+                Some(rules) => rules.operator = operator,
+                None => strategy.entry_rules.operator = operator,
+            }
         }
 
         // Mutación 4: Ajustar parámetros de indicadores existentes
@@ -222,18 +333,18 @@ impl GeneticGenerator {
         if rng.gen_bool(self.config.mutation_rate * 0.2) {
             self.mutate_comparison(strategy, &mut rng);
         }
+
+        // Mutación 6: Cambiar el timeframe de una condición
+        if self.random_gen.max_timeframes() > 1 && rng.gen_bool(self.config.mutation_rate * 0.2) {
+            self.mutate_timeframe(strategy, &mut rng);
+        }
     }
 
     // Mutación de parámetros de indicadores existentes
     // 
     // Ajusta los parámetros dentro de rangos válidos según metadata
     fn mutate_parameters(&self, strategy: &mut StrategyAST, rng: &mut impl Rng) {
-        let mut all_conditions: Vec<&mut Condition> = strategy
-            .entry_rules
-            .conditions
-            .iter_mut()
-            .chain(strategy.exit_rules.conditions.iter_mut())
-            .collect();
+        let mut all_conditions: Vec<&mut Condition> = strategy.all_conditions_mut().collect();
 
         if all_conditions.is_empty() {
             return;
@@ -269,12 +380,7 @@ impl GeneticGenerator {
 
     // Mutación de comparador
     fn mutate_comparison(&self, strategy: &mut StrategyAST, rng: &mut impl Rng) {
-        let mut all_conditions: Vec<&mut Condition> = strategy
-            .entry_rules
-            .conditions
-            .iter_mut()
-            .chain(strategy.exit_rules.conditions.iter_mut())
-            .collect();
+        let mut all_conditions: Vec<&mut Condition> = strategy.all_conditions_mut().collect();
 
         if all_conditions.is_empty() {
             return;
@@ -284,6 +390,37 @@ impl GeneticGenerator {
         all_conditions[idx].comparison = self.random_comparison(rng);
     }
 
+    // Mutación de timeframe: la condición pasa a la serie principal, a otro de los
+    // timeframes de la estrategia o, si quedan libres, a uno nuevo
+    fn mutate_timeframe(&self, strategy: &mut StrategyAST, rng: &mut impl Rng) {
+        let mut options: Vec<Option<ConditionTimeframe>> = vec![None];
+        options.extend(strategy.higher_timeframes().into_iter().map(Some));
+        if strategy.timeframe_count() < self.random_gen.max_timeframes() {
+            options.push(Some(self.random_gen.random_higher_timeframe(rng)));
+        }
+
+        let mut all_conditions: Vec<&mut Condition> = strategy.all_conditions_mut().collect();
+        if all_conditions.is_empty() {
+            return;
+        }
+        let idx = rng.gen_range(0..all_conditions.len());
+        all_conditions[idx].timeframe = *options.choose(rng).unwrap();
+    }
+
+    // Devuelve a la serie principal las condiciones cuyos timeframes exceden el máximo
+    fn limit_timeframes(&self, strategy: &mut StrategyAST) {
+        let allowed: Vec<ConditionTimeframe> = strategy
+            .higher_timeframes()
+            .into_iter()
+            .take(self.random_gen.max_timeframes() - 1)
+            .collect();
+        for condition in strategy.all_conditions_mut() {
+            if condition.timeframe.is_some_and(|tf| !allowed.contains(&tf)) {
+                condition.timeframe = None;
+            }
+        }
+    }
+
     // Selecciona una estrategia mediante torneo (tournament selection)
     //
     // Elige aleatoriamente `tournament_size` candidatos de la población y
@@ -323,8 +460,8 @@ impl GeneticGenerator {
 
     // Evoluciona una población de estrategias usando algoritmo genético
     //
-    // Implementa un loop evolutivo completo que incluye evaluación de fitness,
-    // selección, crossover, mutación y elitismo.
+    // Versión por estrategia de `evolve_batch`: `fitness_fn` se evalúa una vez
+    // por individuo y generación.
     //
     // # Argumentos
     //
@@ -334,90 +471,81 @@ impl GeneticGenerator {
     // # Returns
     //
     // Vector de estrategias ordenado por fitness (mejor primero)
+    pub fn evolve<F>(
+        &self,
+        initial_population: Vec<StrategyAST>,
+        fitness_fn: F,
+    ) -> Vec<StrategyAST>
+    where
+        F: Fn(&StrategyAST) -> f64,
+    {
+        self.evolve_batch(initial_population, |population| {
+            population.iter().map(&fitness_fn).collect()
+        })
+        .population
+    }
+
+    // Evoluciona una población evaluando cada generación completa de una vez
+    //
+    // `fitness_batch` recibe la población de la generación y retorna un fitness
+    // por estrategia (mismo orden). Permite backtestear los hijos de cada
+    // generación en bloque y cachear resultados fuera del generador.
     //
     // # Proceso Evolutivo
     //
-    // Para cada generación:
-    // 1. Evaluar fitness de toda la población
+    // 1. Evaluar la población inicial (generación 0)
     // 2. Preservar elite (mejores estrategias)
     // 3. Generar nueva población mediante selección, crossover y mutación
-    // 4. Reemplazar población anterior
-    // 5. Verificar convergencia
+    // 4. Evaluar la nueva población y registrar sus estadísticas
+    // 5. Repetir hasta `generations` o hasta convergencia
     //
     // # Convergencia
     //
-    // El algoritmo termina antes si no hay mejora por 25 generaciones consecutivas.
-    pub fn evolve<F>(
+    // Termina antes si el mejor fitness no mejora más de `min_improvement`
+    // durante `patience` generaciones consecutivas (0 = sin parada anticipada).
+    pub fn evolve_batch<F>(
         &self,
         initial_population: Vec<StrategyAST>,
-        fitness_fn: F,
-    ) -> Vec<StrategyAST>
+        mut fitness_batch: F,
+    ) -> EvolutionResult
     where
-        F: Fn(&StrategyAST) -> f64,
+        F: FnMut(&[StrategyAST]) -> Vec<f64>,
     {
         let mut population = initial_population;
-        let mut best_fitness = f64::NEG_INFINITY;
+        let mut fitness = Self::evaluate(&mut fitness_batch, &population);
+        let mut stats = vec![GenerationStats::from_fitness(0, &fitness)];
+        let mut best_fitness = stats[0].best_fitness;
         let mut generations_without_improvement = 0;
+        let mut converged = false;
 
-        for generation in 0..self.config.generations {
-            // Calcular fitness de toda la población
-            let mut fitness_scores: Vec<(usize, f64)> = population
-                .iter()
-                .enumerate()
-                .map(|(idx, strategy)| (idx, fitness_fn(strategy)))
-                .collect();
-
-            // Ordenar por fitness (descendente)
-            fitness_scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-
-            // Actualizar mejor fitness
-            let current_best = fitness_scores[0].1;
-            if current_best > best_fitness {
-                best_fitness = current_best;
-                generations_without_improvement = 0;
-            } else {
-                generations_without_improvement += 1;
-            }
-
-            // Logging cada 10 generaciones
-            if generation % 10 == 0 {
-                let avg_fitness = fitness_scores.iter().map(|(_, f)| f).sum::<f64>() / fitness_scores.len() as f64;
-                println!(
-                    "Generación {}/{}, Best: {:.4}, Avg: {:.4}",
-                    generation + 1,
-                    self.config.generations,
-                    current_best,
-                    avg_fitness
-                );
-            }
-
-            // Convergencia anticipada (sin mejora por 25 generaciones)
-            if generations_without_improvement >= 25 {
-                println!("Convergencia alcanzada en generación {}", generation + 1);
+        for generation in 1..=self.config.generations {
+            if population.is_empty() {
                 break;
             }
 
-            // Crear nueva generación
-            let mut new_population = Vec::new();
+            // Índices ordenados por fitness (descendente)
+            let mut ranking: Vec<usize> = (0..population.len()).collect();
+            ranking.sort_by(|&a, &b| fitness[b].partial_cmp(&fitness[a]).unwrap_or(std::cmp::Ordering::Equal));
 
             // Elitismo: preservar las mejores estrategias
-            for i in 0..self.config.elite_size.min(population.len()) {
-                let idx = fitness_scores[i].0;
-                new_population.push(population[idx].clone());
-            }
+            let mut new_population: Vec<StrategyAST> = ranking
+                .iter()
+                .take(self.config.elite_size.min(population.len()))
+                .map(|&idx| population[idx].clone())
+                .collect();
 
             // Generar resto de la población
             let mut child_counter = 0;
             while new_population.len() < self.config.population_size {
                 // Selección de padres
-                let parent1 = self.tournament_selection(&population, &fitness_fn);
-                let parent2 = self.tournament_selection(&population, &fitness_fn);
+                let parent1 = &population[self.tournament_index(&fitness)];
+                let parent2 = &population[self.tournament_index(&fitness)];
 
                 // Crossover
-                let mut child = self.crossover(&parent1, &parent2);
-                
+                let mut child = self.crossover(parent1, parent2);
+
                 // Actualizar nombre con generación y contador para mejor trazabilidad
-                child.name = format!("Evolved_G{}_C{:04}", generation + 1, child_counter);
+                child.name = format!("Evolved_G{}_C{:04}", generation, child_counter);
                 child_counter += 1;
 
                 // Mutación
@@ -426,33 +554,88 @@ impl GeneticGenerator {
                 new_population.push(child);
             }
 
-            // Reemplazar población
+            // Reemplazar población y evaluar la nueva generación
             population = new_population;
+            fitness = Self::evaluate(&mut fitness_batch, &population);
+            let generation_stats = GenerationStats::from_fitness(generation, &fitness);
+
+            // Logging cada 10 generaciones
+            if generation % 10 == 0 {
+                println!(
+                    "Generación {}/{}, Best: {:.4}, Avg: {:.4}",
+                    generation,
+                    self.config.generations,
+                    generation_stats.best_fitness,
+                    generation_stats.average_fitness
+                );
+            }
+
+            // Actualizar mejor fitness
+            if generation_stats.best_fitness > best_fitness + self.config.min_improvement {
+                best_fitness = generation_stats.best_fitness;
+                generations_without_improvement = 0;
+            } else {
+                generations_without_improvement += 1;
+            }
+            stats.push(generation_stats);
+
+            // Convergencia anticipada (sin mejora durante `patience` generaciones)
+            if self.config.patience > 0 && generations_without_improvement >= self.config.patience {
+                println!("Convergencia alcanzada en generación {}", generation);
+                converged = true;
+                break;
+            }
         }
 
         // Ordenar población final por fitness
-        let mut fitness_scores: Vec<(usize, f64)> = population
-            .iter()
-            .enumerate()
-            .map(|(idx, strategy)| (idx, fitness_fn(strategy)))
-            .collect();
-
-        fitness_scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        let mut ranked: Vec<(StrategyAST, f64)> = population.into_iter().zip(fitness).collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        let (population, fitness) = ranked.into_iter().unzip();
+
+        EvolutionResult {
+            population,
+            fitness,
+            stats,
+            converged,
+        }
+    }
 
-        // Retornar población ordenada
-        fitness_scores
-            .iter()
-            .map(|(idx, _)| population[*idx].clone())
+    // Evalúa una población completa (NaN se trata como el peor fitness)
+    fn evaluate<F>(fitness_batch: &mut F, population: &[StrategyAST]) -> Vec<f64>
+    where
+        F: FnMut(&[StrategyAST]) -> Vec<f64>,
+    {
+        let fitness = fitness_batch(population);
+        assert_eq!(fitness.len(), population.len(), "fitness_batch must return one value per strategy");
+        fitness
+            .into_iter()
+            .map(|f| if f.is_nan() { f64::NEG_INFINITY } else { f })
             .collect()
     }
 
+    // Selección por torneo sobre fitness ya calculados; retorna el índice ganador
+    fn tournament_index(&self, fitness: &[f64]) -> usize {
+        let mut rng = rand::thread_rng();
+        let mut best = rng.gen_range(0..fitness.len());
+
+        for _ in 1..self.config.tournament_size {
+            let idx = rng.gen_range(0..fitness.len());
+            if fitness[idx] > fitness[best] {
+                best = idx;
+            }
+        }
+
+        best
+    }
+
     // Funciones auxiliares privadas
 
-    fn random_condition(&self, rng: &mut impl Rng) -> Condition {
+    fn random_condition(&self, timeframe: Option<ConditionTimeframe>, rng: &mut impl Rng) -> Condition {
         Condition {
             indicator: self.random_indicator(rng),
             comparison: self.random_comparison(rng),
             value: self.random_value(rng),
+            timeframe,
         }
     }
 
@@ -521,6 +704,21 @@ mod tests {
         assert!(child.name.contains(&pop[0].name) || child.name.contains(&pop[1].name));
     }
 
+    #[test]
+    fn test_crossover_inherits_short_rules() {
+        let generator = GeneticGenerator::new(GeneticConfig::default());
+        let long_only = generator.generate_population(1).remove(0);
+        let long_short = GeneticGenerator::new(GeneticConfig::default())
+            .with_direction(TradeDirection::LongShort)
+            .generate_population(1)
+            .remove(0);
+
+        let child = generator.crossover(&long_only, &long_short);
+
+        assert!(child.has_short_rules());
+        assert!(child.short_exit_rules.is_some());
+    }
+
     #[test]
     fn test_mutation() {
         let generator = GeneticGenerator::new(GeneticConfig {
@@ -541,6 +739,28 @@ mod tests {
         assert!(!pop[0].exit_rules.conditions.is_empty());
     }
 
+    #[test]
+    fn test_mutation_and_crossover_respect_max_timeframes() {
+        let generator = GeneticGenerator::new(GeneticConfig {
+            mutation_rate: 1.0,
+            ..Default::default()
+        })
+        .with_max_timeframes(2);
+
+        let mut population = generator.generate_population(20);
+        let mut multi_timeframe = false;
+        for _ in 0..100 {
+            let mut child = generator.crossover(&population[0], &population[1]);
+            assert!(child.timeframe_count() <= 2);
+            generator.mutate(&mut child);
+            assert!(child.timeframe_count() <= 2);
+            multi_timeframe |= child.timeframe_count() == 2;
+            population.rotate_left(1);
+            population[0] = child;
+        }
+        assert!(multi_timeframe);
+    }
+
     #[test]
     fn test_parameter_mutation() {
         let generator = GeneticGenerator::new(GeneticConfig {
@@ -593,6 +813,7 @@ mod tests {
             mutation_rate: 0.2,
             elite_size: 2,
             tournament_size: 3,
+            ..Default::default()
         });
 
         let initial_pop = generator.generate_population(20);
@@ -635,6 +856,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_mutation_changes_short_operator() {
+        let generator = GeneticGenerator::new(GeneticConfig {
+            mutation_rate: 1.0,
+            ..Default::default()
+        })
+        .with_direction(TradeDirection::ShortOnly);
+
+        let mut strategy = generator.generate_population(1).remove(0);
+        let mut operators = std::collections::HashSet::new();
+        for _ in 0..50 {
+            generator.mutate(&mut strategy);
+            operators.insert(format!("{:?}", strategy.short_entry_rules.as_ref().unwrap().operator));
+        }
+
+        // Solo opera en corto: el operador mutado es el de las reglas en corto
+        assert_eq!(operators.len(), 2);
+    }
+
     #[test]
     fn test_uses_registry_indicators() {
         let generator = GeneticGenerator::new(GeneticConfig::default());
@@ -669,6 +909,7 @@ mod tests {
             elite_size: 3,
             mutation_rate: 0.5,
             tournament_size: 2,
+            ..Default::default()
         });
 
         let initial_pop = generator.generate_population(10);
@@ -691,4 +932,52 @@ mod tests {
         // El mejor final debe ser al menos tan bueno como el mejor inicial
         assert!(final_scores[0] >= initial_scores[0].1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_evolve_batch_evaluates_each_generation() {
+        let generator = GeneticGenerator::new(GeneticConfig {
+            population_size: 12,
+            generations: 4,
+            elite_size: 2,
+            patience: 0,
+            ..Default::default()
+        });
+        let initial_pop = generator.generate_population(8);
+        let mut batches = Vec::new();
+
+        let result = generator.evolve_batch(initial_pop, |population| {
+            batches.push(population.iter().map(|s| s.name.clone()).collect::<Vec<_>>());
+            population.iter().map(|s| s.complexity() as f64).collect()
+        });
+
+        // Población inicial + una evaluación por generación
+        assert_eq!(batches.len(), 5);
+        assert_eq!(result.stats.len(), 5);
+        assert!(!result.converged);
+        // Los hijos de cada generación se evalúan (no solo la población inicial)
+        assert!(batches[1].iter().any(|name| name.starts_with("Evolved_G1_")));
+        assert_eq!(result.stats[4].population_size, 12);
+        assert_eq!(result.population.len(), result.fitness.len());
+        assert!(result.fitness.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(result.fitness[0], result.stats[4].best_fitness);
+    }
+
+    #[test]
+    fn test_evolve_batch_early_stopping() {
+        let generator = GeneticGenerator::new(GeneticConfig {
+            population_size: 10,
+            generations: 50,
+            elite_size: 2,
+            patience: 3,
+            ..Default::default()
+        });
+        let initial_pop = generator.generate_population(10);
+
+        // Fitness constante: nunca mejora
+        let result = generator.evolve_batch(initial_pop, |population| vec![1.0; population.len()]);
+
+        assert!(result.converged);
+        assert_eq!(result.stats.len(), 4);
+        assert_eq!(result.stats.last().unwrap().generation, 3);
+    }
+}
@@ -6,9 +6,27 @@ use darwinx_indicators::registry;
 use darwinx_indicators::metadata::ParamType;
 use rand::prelude::*;
 
+/// Múltiplos del intervalo principal entre los que se eligen los timeframes superiores
+const HIGHER_TIMEFRAME_MULTIPLES: [u32; 5] = [3, 4, 6, 12, 24];
+
+/// Probabilidad de que una condición se evalúe en un timeframe superior (si la estrategia tiene)
+const HIGHER_TIMEFRAME_PROBABILITY: f64 = 0.3;
+
+/// Timeframes principales entre los que se elige por defecto
+const DEFAULT_TIMEFRAMES: [TimeFrame; 5] = [
+    TimeFrame::M5,
+    TimeFrame::M15,
+    TimeFrame::M30,
+    TimeFrame::H1,
+    TimeFrame::H4,
+];
+
 pub struct RandomGenerator {
     max_conditions: usize,
     max_indicators: usize,
+    max_timeframes: usize,
+    timeframes: Vec<TimeFrame>,
+    direction: TradeDirection,
 }
 
 impl RandomGenerator {
@@ -16,6 +34,9 @@ impl RandomGenerator {
         Self {
             max_conditions: 5,
             max_indicators: 3,
+            max_timeframes: 1,
+            timeframes: DEFAULT_TIMEFRAMES.to_vec(),
+            direction: TradeDirection::LongOnly,
         }
     }
 
@@ -23,9 +44,39 @@ impl RandomGenerator {
         Self {
             max_conditions,
             max_indicators,
+            max_timeframes: 1,
+            timeframes: DEFAULT_TIMEFRAMES.to_vec(),
+            direction: TradeDirection::LongOnly,
         }
     }
 
+    /// Máximo de timeframes por estrategia, contando el principal (1 = sin timeframes superiores)
+    pub fn with_max_timeframes(mut self, max_timeframes: usize) -> Self {
+        self.max_timeframes = max_timeframes.max(1);
+        self
+    }
+
+    /// Máximo de timeframes por estrategia, contando el principal
+    pub fn max_timeframes(&self) -> usize {
+        self.max_timeframes
+    }
+
+    /// Timeframes principales posibles de las estrategias (ej: el del dataset)
+    ///
+    /// Una lista vacía mantiene los timeframes por defecto.
+    pub fn with_timeframes(mut self, timeframes: Vec<TimeFrame>) -> Self {
+        if !timeframes.is_empty() {
+            self.timeframes = timeframes;
+        }
+        self
+    }
+
+    /// Define la dirección de las estrategias generadas (largo, corto o ambas)
+    pub fn with_direction(mut self, direction: TradeDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
     /// Genera una estrategia aleatoria
     pub fn generate(&self, name: String) -> StrategyAST {
         let mut rng = rand::thread_rng();
@@ -33,28 +84,50 @@ impl RandomGenerator {
         let timeframe = self.random_timeframe(&mut rng);
         let mut strategy = StrategyAST::new(name, timeframe);
 
-        // Generar condiciones de entrada
-        let entry_count = rng.gen_range(1..=self.max_conditions.min(3));
-        for _ in 0..entry_count {
-            strategy.entry_rules.conditions.push(self.random_condition(&mut rng));
+        // Timeframes superiores disponibles para las condiciones de esta estrategia
+        let extra = rng.gen_range(0..self.max_timeframes);
+        let higher: Vec<ConditionTimeframe> = HIGHER_TIMEFRAME_MULTIPLES
+            .choose_multiple(&mut rng, extra)
+            .map(|&multiple| ConditionTimeframe::Relative(multiple))
+            .collect();
+
+        if self.direction != TradeDirection::ShortOnly {
+            let (entry_rules, exit_rules) = self.random_rule_sets(&higher, &mut rng);
+            strategy.entry_rules = entry_rules;
+            strategy.exit_rules = exit_rules;
+        }
+
+        if self.direction != TradeDirection::LongOnly {
+            let (short_entry_rules, short_exit_rules) = self.random_rule_sets(&higher, &mut rng);
+            strategy.short_entry_rules = Some(short_entry_rules);
+            strategy.short_exit_rules = Some(short_exit_rules);
         }
 
-        // Operador de entrada
-        strategy.entry_rules.operator = if rng.gen_bool(0.7) {
+        strategy
+    }
+
+    /// Genera un par aleatorio de reglas (entrada, salida) para un lado
+    fn random_rule_sets(&self, higher: &[ConditionTimeframe], rng: &mut impl Rng) -> (RuleSet, RuleSet) {
+        // Generar condiciones de entrada
+        let entry_operator = if rng.gen_bool(0.7) {
             LogicalOperator::And
         } else {
             LogicalOperator::Or
         };
+        let mut entry_rules = RuleSet::new(entry_operator);
+        let entry_count = rng.gen_range(1..=self.max_conditions.min(3));
+        for _ in 0..entry_count {
+            entry_rules.conditions.push(self.random_condition(higher, rng));
+        }
 
         // Generar condiciones de salida
+        let mut exit_rules = RuleSet::new(LogicalOperator::Or);
         let exit_count = rng.gen_range(1..=self.max_conditions.min(2));
         for _ in 0..exit_count {
-            strategy.exit_rules.conditions.push(self.random_condition(&mut rng));
+            exit_rules.conditions.push(self.random_condition(higher, rng));
         }
 
-        strategy.exit_rules.operator = LogicalOperator::Or;
-
-        strategy
+        (entry_rules, exit_rules)
     }
 
     /// Genera múltiples estrategias
@@ -65,26 +138,30 @@ impl RandomGenerator {
     }
 
     fn random_timeframe(&self, rng: &mut impl Rng) -> TimeFrame {
-        let timeframes = [
-            TimeFrame::M5,
-            TimeFrame::M15,
-            TimeFrame::M30,
-            TimeFrame::H1,
-            TimeFrame::H4,
-        ];
-        timeframes[rng.gen_range(0..timeframes.len())]
+        self.timeframes[rng.gen_range(0..self.timeframes.len())]
     }
 
-    fn random_condition(&self, rng: &mut impl Rng) -> Condition {
+    /// Timeframe superior aleatorio (múltiplo del intervalo principal)
+    pub(crate) fn random_higher_timeframe(&self, rng: &mut impl Rng) -> ConditionTimeframe {
+        ConditionTimeframe::Relative(*HIGHER_TIMEFRAME_MULTIPLES.choose(rng).unwrap())
+    }
+
+    fn random_condition(&self, higher: &[ConditionTimeframe], rng: &mut impl Rng) -> Condition {
         let indicator = self.random_indicator(rng);
         let comparison = self.random_comparison(rng);
         // Mejorar: generar valor de comparación apropiado según la categoría del indicador
         let value = self.random_value_for_indicator(&indicator, rng);
+        let timeframe = if rng.gen_bool(HIGHER_TIMEFRAME_PROBABILITY) {
+            higher.choose(rng).copied()
+        } else {
+            None
+        };
 
         Condition {
             indicator,
             comparison,
             value,
+            timeframe,
         }
     }
 
@@ -325,6 +402,46 @@ mod tests {
         assert!(strategy.exit_rules.conditions.len() <= 2);
     }
 
+    #[test]
+    fn test_direction() {
+        let short_only = RandomGenerator::new()
+            .with_direction(TradeDirection::ShortOnly)
+            .generate("Short".to_string());
+        assert!(short_only.entry_rules.conditions.is_empty());
+        assert!(short_only.has_short_rules());
+        assert!(!short_only.short_exit_rules.as_ref().unwrap().conditions.is_empty());
+        assert_eq!(short_only.direction(), TradeDirection::ShortOnly);
+
+        let long_short = RandomGenerator::new()
+            .with_direction(TradeDirection::LongShort)
+            .generate("LongShort".to_string());
+        assert_eq!(long_short.direction(), TradeDirection::LongShort);
+        assert!(!long_short.exit_rules.conditions.is_empty());
+    }
+
+    #[test]
+    fn test_max_timeframes() {
+        let single = RandomGenerator::new().generate_batch(50);
+        assert!(single.iter().all(|s| s.timeframe_count() == 1));
+
+        let generator = RandomGenerator::new().with_max_timeframes(3);
+        let strategies = generator.generate_batch(200);
+        assert!(strategies.iter().all(|s| s.timeframe_count() <= 3));
+        assert!(strategies.iter().any(|s| s.timeframe_count() > 1));
+    }
+
+    #[test]
+    fn test_timeframes() {
+        let six_hours: TimeFrame = "6h".parse().unwrap();
+        let strategies = RandomGenerator::new()
+            .with_timeframes(vec![six_hours])
+            .generate_batch(20);
+        assert!(strategies.iter().all(|s| s.timeframe == six_hours));
+
+        let default = RandomGenerator::new().with_timeframes(Vec::new()).generate_batch(20);
+        assert!(default.iter().all(|s| DEFAULT_TIMEFRAMES.contains(&s.timeframe)));
+    }
+
     #[test]
     fn test_uses_registry() {
         let generator = RandomGenerator::new();
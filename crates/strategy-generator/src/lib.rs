@@ -7,7 +7,7 @@ pub mod generator;
 pub mod constraints;
 
 // Re-exports
-pub use ast::nodes::{StrategyAST, Condition, IndicatorType};
+pub use ast::nodes::{StrategyAST, Condition, ConditionTimeframe, IndicatorType, TradeDirection};
 pub use generator::random::RandomGenerator;
-pub use generator::genetic::{GeneticGenerator, GeneticConfig};
+pub use generator::genetic::{GeneticGenerator, GeneticConfig, GenerationStats, EvolutionResult};
 pub use constraints::StrategyConstraints;
\ No newline at end of file
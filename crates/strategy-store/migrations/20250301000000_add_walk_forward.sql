@@ -0,0 +1,46 @@
+-- Migration: Walk-forward analysis results
+-- Each run stores its configuration and aggregated out-of-sample performance;
+-- each result row is one strategy selected in-sample and evaluated out-of-sample.
+
+CREATE TABLE IF NOT EXISTS walk_forward_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    dataset TEXT NOT NULL,
+    timeframe TEXT NOT NULL,
+    mode TEXT NOT NULL, -- rolling | anchored
+    windows INTEGER NOT NULL,
+    in_sample_ratio REAL NOT NULL,
+    start_date TEXT NOT NULL,
+    end_date TEXT NOT NULL,
+    oos_total_return REAL NOT NULL,
+    oos_max_drawdown REAL NOT NULL,
+    efficiency REAL, -- Mean walk-forward efficiency of traded strategies
+    execution_metadata TEXT, -- JSON with generation/backtest config
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS walk_forward_results (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id INTEGER NOT NULL,
+    strategy_id INTEGER,
+    strategy_name TEXT NOT NULL,
+    window_index INTEGER NOT NULL,
+    selection_rank INTEGER NOT NULL, -- 0 = strategy traded in the out-of-sample window
+    is_start_date TEXT NOT NULL,
+    is_end_date TEXT NOT NULL,
+    oos_start_date TEXT NOT NULL,
+    oos_end_date TEXT NOT NULL,
+    is_total_return REAL NOT NULL,
+    is_sharpe_ratio REAL NOT NULL,
+    is_total_trades INTEGER NOT NULL,
+    oos_total_return REAL NOT NULL,
+    oos_sharpe_ratio REAL NOT NULL,
+    oos_max_drawdown REAL NOT NULL,
+    oos_win_rate REAL NOT NULL,
+    oos_total_trades INTEGER NOT NULL,
+    efficiency REAL,
+    FOREIGN KEY (run_id) REFERENCES walk_forward_runs(id) ON DELETE CASCADE,
+    FOREIGN KEY (strategy_id) REFERENCES strategies(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_walk_forward_results_run ON walk_forward_results(run_id, window_index);
+CREATE INDEX IF NOT EXISTS idx_walk_forward_results_strategy ON walk_forward_results(strategy_id);
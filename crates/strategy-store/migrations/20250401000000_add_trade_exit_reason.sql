@@ -0,0 +1,7 @@
+-- Migration: Exit reason per trade
+-- Stores the serialized ExitReason of the backtest engine ("Signal", "StopLoss",
+-- "End of data", ...). Trades saved before this migration keep NULL.
+
+ALTER TABLE trades ADD COLUMN exit_reason TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_trades_exit_reason ON trades(backtest_result_id, exit_reason);
@@ -0,0 +1,5 @@
+-- Migration: Parent position per trade
+-- Trades from pyramiding lots and partial exits of the same position share
+-- position_id. Trades saved before this migration keep NULL.
+
+ALTER TABLE trades ADD COLUMN position_id INTEGER;
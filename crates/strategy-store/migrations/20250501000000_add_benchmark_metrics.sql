@@ -0,0 +1,14 @@
+-- Migration: Benchmark comparison metrics
+-- Buy-and-hold of the same candles as benchmark. Results saved before this
+-- migration keep NULL.
+
+ALTER TABLE backtest_results ADD COLUMN benchmark_return REAL;
+ALTER TABLE backtest_results ADD COLUMN excess_return REAL;
+ALTER TABLE backtest_results ADD COLUMN alpha REAL;
+ALTER TABLE backtest_results ADD COLUMN beta REAL;
+ALTER TABLE backtest_results ADD COLUMN information_ratio REAL;
+ALTER TABLE backtest_results ADD COLUMN tracking_error REAL;
+ALTER TABLE backtest_results ADD COLUMN up_capture REAL;
+ALTER TABLE backtest_results ADD COLUMN down_capture REAL;
+
+CREATE INDEX IF NOT EXISTS idx_backtest_excess_return ON backtest_results(excess_return DESC);
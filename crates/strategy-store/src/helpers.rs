@@ -1,6 +1,6 @@
 //! Helper functions for strategy store operations
 
-use crate::models::Strategy;
+use crate::models::{Strategy, Trade, WalkForwardResult, WalkForwardRun};
 use darwinx_generator::ast::nodes::StrategyAST;
 use sha2::{Sha256, Digest};
 use serde_json;
@@ -58,3 +58,96 @@ pub fn model_to_strategy_ast(strategy: &Strategy) -> Result<StrategyAST, anyhow:
     }
 }
 
+/// Formatea un timestamp en milisegundos como fecha RFC 3339
+fn timestamp_to_string(ts: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ts)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_else(|| ts.to_string())
+}
+
+/// Convierte un informe walk-forward en el modelo de ejecución para guardar en DB
+pub fn walk_forward_run_model(
+    report: &darwinx_backtest_engine::WalkForwardReport,
+    dataset: &str,
+    timeframe: &str,
+    execution_metadata: Option<serde_json::Value>,
+) -> WalkForwardRun {
+    let start = report.windows.first().map(|w| w.in_sample_start).unwrap_or(0);
+    let end = report.windows.last().map(|w| w.out_of_sample_end).unwrap_or(0);
+
+    WalkForwardRun {
+        id: None,
+        dataset: dataset.to_string(),
+        timeframe: timeframe.to_string(),
+        mode: report.config.mode.as_str().to_string(),
+        windows: report.config.windows as i32,
+        in_sample_ratio: report.config.in_sample_ratio,
+        start_date: timestamp_to_string(start),
+        end_date: timestamp_to_string(end),
+        oos_total_return: report.oos_total_return,
+        oos_max_drawdown: report.oos_max_drawdown,
+        efficiency: report.efficiency,
+        execution_metadata: execution_metadata.and_then(|m| serde_json::to_string(&m).ok()),
+        created_at: None,
+    }
+}
+
+/// Convierte los resultados por estrategia de un informe walk-forward en modelos de DB
+///
+/// `strategy_id` queda vacío; se asigna si la estrategia se guarda en la tabla de estrategias.
+pub fn walk_forward_result_models(
+    report: &darwinx_backtest_engine::WalkForwardReport,
+    run_id: i64,
+) -> Vec<WalkForwardResult> {
+    report
+        .windows
+        .iter()
+        .flat_map(|window| {
+            window.strategies.iter().enumerate().map(move |(rank, result)| WalkForwardResult {
+                id: None,
+                run_id,
+                strategy_id: None,
+                strategy_name: result.strategy_name.clone(),
+                window_index: window.window.index as i32,
+                selection_rank: rank as i32,
+                is_start_date: timestamp_to_string(window.in_sample_start),
+                is_end_date: timestamp_to_string(window.in_sample_end),
+                oos_start_date: timestamp_to_string(window.out_of_sample_start),
+                oos_end_date: timestamp_to_string(window.out_of_sample_end),
+                is_total_return: result.in_sample_return,
+                is_sharpe_ratio: result.in_sample.sharpe_ratio,
+                is_total_trades: result.in_sample.total_trades as i32,
+                oos_total_return: result.out_of_sample_return,
+                oos_sharpe_ratio: result.out_of_sample.sharpe_ratio,
+                oos_max_drawdown: result.out_of_sample.max_drawdown_percent,
+                oos_win_rate: result.out_of_sample.win_rate,
+                oos_total_trades: result.out_of_sample.total_trades as i32,
+                efficiency: result.efficiency,
+            })
+        })
+        .collect()
+}
+
+/// Convierte los trades de un backtest en modelos de DB, con su razón de salida y su posición
+pub fn trade_models(trades: &[darwinx_backtest_engine::Trade], backtest_result_id: i64) -> Vec<Trade> {
+    trades
+        .iter()
+        .map(|trade| {
+            let notional = trade.entry_price * trade.size;
+            Trade {
+                id: None,
+                backtest_result_id,
+                entry_time: timestamp_to_string(trade.entry_timestamp),
+                exit_time: timestamp_to_string(trade.exit_timestamp),
+                side: if trade.is_long { "long" } else { "short" }.to_string(),
+                entry_price: trade.entry_price,
+                exit_price: trade.exit_price,
+                quantity: trade.size,
+                pnl: trade.pnl,
+                pnl_percent: if notional > 0.0 { trade.pnl / notional } else { 0.0 },
+                exit_reason: Some(trade.exit_reason.to_string()),
+                position_id: Some(trade.position_id as i64),
+            }
+        })
+        .collect()
+}
@@ -8,10 +8,13 @@ pub mod database;
 pub mod helpers;
 
 // Re-exports
-pub use models::{BacktestResult, Strategy, Trade};
-pub use repositories::{BacktestRepository, StrategyRepository};
+pub use models::{BacktestResult, Strategy, Trade, WalkForwardRun, WalkForwardResult};
+pub use repositories::{BacktestRepository, StrategyRepository, TradeRepository, WalkForwardRepository};
 pub use database::init_sqlite;
-pub use helpers::{calculate_strategy_hash, strategy_ast_to_model, model_to_strategy_ast};
+pub use helpers::{
+    calculate_strategy_hash, strategy_ast_to_model, model_to_strategy_ast,
+    walk_forward_run_model, walk_forward_result_models, trade_models,
+};
 
 /// Carga mejores estrategias desde SQLite para usar como población inicial en genética
 pub async fn load_best_strategies_for_genetics(
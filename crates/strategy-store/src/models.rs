@@ -1,7 +1,9 @@
 pub mod strategy;
 pub mod backtest_result;
 pub mod trade;
+pub mod walk_forward;
 
 pub use strategy::Strategy;
 pub use backtest_result::BacktestResult;
 pub use trade::Trade;
+pub use walk_forward::{WalkForwardRun, WalkForwardResult};
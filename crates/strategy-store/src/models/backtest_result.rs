@@ -34,4 +34,13 @@ pub struct BacktestResult {
     pub signal_exits: Option<i32>,
     pub end_of_data_exits: Option<i32>,
     pub composite_score: Option<f64>, // Weighted score from ranking
+    // Benchmark (buy-and-hold) metrics
+    pub benchmark_return: Option<f64>,
+    pub excess_return: Option<f64>,
+    pub alpha: Option<f64>,
+    pub beta: Option<f64>,
+    pub information_ratio: Option<f64>,
+    pub tracking_error: Option<f64>,
+    pub up_capture: Option<f64>,
+    pub down_capture: Option<f64>,
 }
@@ -16,4 +16,6 @@ pub struct Trade {
     pub quantity: f64,
     pub pnl: f64,
     pub pnl_percent: f64,
+    pub exit_reason: Option<String>, // ExitReason serializado; NULL en trades antiguos
+    pub position_id: Option<i64>,    // Posición del trade (lotes y cierres parciales)
 }
\ No newline at end of file
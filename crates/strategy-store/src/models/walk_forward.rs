@@ -0,0 +1,47 @@
+// ============================================================================
+// crates/strategy-store/src/models/walk_forward.rs
+// ============================================================================
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Ejecución de un análisis walk-forward
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WalkForwardRun {
+    pub id: Option<i64>,
+    pub dataset: String,
+    pub timeframe: String,
+    pub mode: String, // rolling | anchored
+    pub windows: i32,
+    pub in_sample_ratio: f64,
+    pub start_date: String,
+    pub end_date: String,
+    pub oos_total_return: f64,
+    pub oos_max_drawdown: f64,
+    pub efficiency: Option<f64>,
+    pub execution_metadata: Option<String>, // JSON with generation/backtest config
+    pub created_at: Option<String>,
+}
+
+/// Resultado IS/OOS de una estrategia en una ventana walk-forward
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WalkForwardResult {
+    pub id: Option<i64>,
+    pub run_id: i64,
+    pub strategy_id: Option<i64>,
+    pub strategy_name: String,
+    pub window_index: i32,
+    pub selection_rank: i32, // 0 = strategy traded out-of-sample
+    pub is_start_date: String,
+    pub is_end_date: String,
+    pub oos_start_date: String,
+    pub oos_end_date: String,
+    pub is_total_return: f64,
+    pub is_sharpe_ratio: f64,
+    pub is_total_trades: i32,
+    pub oos_total_return: f64,
+    pub oos_sharpe_ratio: f64,
+    pub oos_max_drawdown: f64,
+    pub oos_win_rate: f64,
+    pub oos_total_trades: i32,
+    pub efficiency: Option<f64>,
+}
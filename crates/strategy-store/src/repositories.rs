@@ -2,6 +2,10 @@
 
 pub mod strategy_repo;
 pub mod backtest_repo;
+pub mod walk_forward_repo;
+pub mod trade_repo;
 
 pub use strategy_repo::StrategyRepository;
-pub use backtest_repo::BacktestRepository;
\ No newline at end of file
+pub use backtest_repo::BacktestRepository;
+pub use walk_forward_repo::WalkForwardRepository;
+pub use trade_repo::TradeRepository;
\ No newline at end of file
@@ -93,8 +93,10 @@ impl BacktestRepository {
              sharpe_ratio, sortino_ratio, max_drawdown, win_rate, profit_factor, total_trades,
              annualized_return, max_drawdown_percent, total_profit, total_loss,
              max_consecutive_wins, max_consecutive_losses, trades_per_month, trades_per_year,
-             stop_loss_exits, take_profit_exits, signal_exits, end_of_data_exits, composite_score)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             stop_loss_exits, take_profit_exits, signal_exits, end_of_data_exits, composite_score,
+             benchmark_return, excess_return, alpha, beta, information_ratio, tracking_error,
+             up_capture, down_capture)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#
         )
         .bind(result.strategy_id)
@@ -122,6 +124,14 @@ impl BacktestRepository {
         .bind(result.signal_exits)
         .bind(result.end_of_data_exits)
         .bind(result.composite_score)
+        .bind(result.benchmark_return)
+        .bind(result.excess_return)
+        .bind(result.alpha)
+        .bind(result.beta)
+        .bind(result.information_ratio)
+        .bind(result.tracking_error)
+        .bind(result.up_capture)
+        .bind(result.down_capture)
         .execute(&self.pool)
         .await?;
 
@@ -173,6 +183,14 @@ impl BacktestRepository {
                     signal_exits = ?,
                     end_of_data_exits = ?,
                     composite_score = ?,
+                    benchmark_return = ?,
+                    excess_return = ?,
+                    alpha = ?,
+                    beta = ?,
+                    information_ratio = ?,
+                    tracking_error = ?,
+                    up_capture = ?,
+                    down_capture = ?,
                     tested_at = CURRENT_TIMESTAMP
                 WHERE id = ?
                 "#
@@ -197,6 +215,14 @@ impl BacktestRepository {
             .bind(result.signal_exits)
             .bind(result.end_of_data_exits)
             .bind(result.composite_score)
+            .bind(result.benchmark_return)
+            .bind(result.excess_return)
+            .bind(result.alpha)
+            .bind(result.beta)
+            .bind(result.information_ratio)
+            .bind(result.tracking_error)
+            .bind(result.up_capture)
+            .bind(result.down_capture)
             .bind(existing_result.id)
             .execute(&self.pool)
             .await?;
@@ -208,6 +234,16 @@ impl BacktestRepository {
         }
     }
 
+    /// Lista los resultados que más superan a comprar y mantener
+    pub async fn top_by_excess_return(&self, limit: i32) -> Result<Vec<BacktestResult>, sqlx::Error> {
+        sqlx::query_as::<_, BacktestResult>(
+            "SELECT * FROM backtest_results WHERE excess_return IS NOT NULL ORDER BY excess_return DESC LIMIT ?"
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
     /// Obtiene mejores resultados por composite_score
     pub async fn top_by_composite_score(&self, limit: i32) -> Result<Vec<BacktestResult>, sqlx::Error> {
         sqlx::query_as::<_, BacktestResult>(
@@ -269,6 +305,14 @@ mod tests {
             signal_exits: None,
             end_of_data_exits: None,
             composite_score: None,
+            benchmark_return: None,
+            excess_return: None,
+            alpha: None,
+            beta: None,
+            information_ratio: None,
+            tracking_error: None,
+            up_capture: None,
+            down_capture: None,
         };
 
         let id = repo.create(&result).await.unwrap();
@@ -276,5 +320,69 @@ mod tests {
 
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].sharpe_ratio, 2.3);
+        assert_eq!(results[0].excess_return, None);
+    }
+
+    #[tokio::test]
+    async fn test_benchmark_metrics_round_trip() {
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let strategy_id = StrategyRepository::new(pool.clone())
+            .create(&Strategy::new("Test".to_string(), "code".to_string(), "rust".to_string()))
+            .await
+            .unwrap();
+
+        let repo = BacktestRepository::new(pool);
+        let base = BacktestResult {
+            id: None,
+            strategy_id,
+            dataset: "BTCUSDT".to_string(),
+            timeframe: "1h".to_string(),
+            start_date: "2024-01-01".to_string(),
+            end_date: "2024-12-31".to_string(),
+            total_return: 0.4,
+            sharpe_ratio: 1.0,
+            sortino_ratio: None,
+            max_drawdown: 0.1,
+            win_rate: 0.5,
+            profit_factor: None,
+            total_trades: 10,
+            tested_at: None,
+            annualized_return: None,
+            max_drawdown_percent: None,
+            total_profit: None,
+            total_loss: None,
+            max_consecutive_wins: None,
+            max_consecutive_losses: None,
+            trades_per_month: None,
+            trades_per_year: None,
+            stop_loss_exits: None,
+            take_profit_exits: None,
+            signal_exits: None,
+            end_of_data_exits: None,
+            composite_score: None,
+            benchmark_return: Some(2.0),
+            excess_return: Some(-1.6),
+            alpha: Some(0.05),
+            beta: Some(0.3),
+            information_ratio: Some(-0.02),
+            tracking_error: Some(0.01),
+            up_capture: Some(0.25),
+            down_capture: Some(0.2),
+        };
+        repo.create_extended(&base).await.unwrap();
+        let better = BacktestResult { end_date: "2025-06-30".to_string(), excess_return: Some(0.3), ..base.clone() };
+        repo.create_extended(&better).await.unwrap();
+
+        // Actualizar conserva la fila y reemplaza las métricas del benchmark
+        let updated = BacktestResult { beta: Some(0.5), ..base };
+        repo.create_or_update_extended(&updated).await.unwrap();
+
+        let top = repo.top_by_excess_return(10).await.unwrap();
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].excess_return, Some(0.3));
+        assert_eq!(top[1].benchmark_return, Some(2.0));
+        assert_eq!(top[1].beta, Some(0.5));
+        assert_eq!(top[1].down_capture, Some(0.2));
     }
 }
\ No newline at end of file
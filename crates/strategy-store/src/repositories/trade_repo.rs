@@ -0,0 +1,164 @@
+//! Repositorio de trades de un backtest
+
+use crate::models::Trade;
+use sqlx::{Pool, Sqlite};
+
+pub struct TradeRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl TradeRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    /// Guarda los trades de un resultado de backtest en una sola transacción
+    pub async fn create_many(&self, trades: &[Trade]) -> Result<usize, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for trade in trades {
+            sqlx::query(
+                r#"
+                INSERT INTO trades
+                (backtest_result_id, entry_time, exit_time, side, entry_price, exit_price,
+                 quantity, pnl, pnl_percent, exit_reason, position_id)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(trade.backtest_result_id)
+            .bind(&trade.entry_time)
+            .bind(&trade.exit_time)
+            .bind(&trade.side)
+            .bind(trade.entry_price)
+            .bind(trade.exit_price)
+            .bind(trade.quantity)
+            .bind(trade.pnl)
+            .bind(trade.pnl_percent)
+            .bind(&trade.exit_reason)
+            .bind(trade.position_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(trades.len())
+    }
+
+    /// Trades de un resultado de backtest, en orden de entrada
+    pub async fn find_by_backtest(&self, backtest_result_id: i64) -> Result<Vec<Trade>, sqlx::Error> {
+        sqlx::query_as::<_, Trade>(
+            "SELECT * FROM trades WHERE backtest_result_id = ? ORDER BY entry_time, id"
+        )
+        .bind(backtest_result_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Número de trades y P&L por razón de salida de un resultado de backtest
+    pub async fn exit_reason_summary(
+        &self,
+        backtest_result_id: i64,
+    ) -> Result<Vec<(Option<String>, i64, f64)>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT exit_reason, COUNT(*), COALESCE(SUM(pnl), 0.0)
+            FROM trades WHERE backtest_result_id = ?
+            GROUP BY exit_reason ORDER BY COUNT(*) DESC
+            "#
+        )
+        .bind(backtest_result_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BacktestResult, Strategy};
+    use crate::repositories::{BacktestRepository, StrategyRepository};
+
+    fn trade(backtest_result_id: i64, hour: u32, pnl: f64, exit_reason: Option<&str>) -> Trade {
+        Trade {
+            id: None,
+            backtest_result_id,
+            entry_time: format!("2024-01-01T{:02}:00:00+00:00", hour),
+            exit_time: format!("2024-01-01T{:02}:30:00+00:00", hour),
+            side: "long".to_string(),
+            entry_price: 100.0,
+            exit_price: 100.0 + pnl,
+            quantity: 1.0,
+            pnl,
+            pnl_percent: pnl / 100.0,
+            exit_reason: exit_reason.map(str::to_string),
+            position_id: Some(i64::from(hour)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_summarize_trades() {
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let strategy_id = StrategyRepository::new(pool.clone())
+            .create(&Strategy::new("Test".to_string(), "code".to_string(), "rust".to_string()))
+            .await
+            .unwrap();
+        let backtest_id = BacktestRepository::new(pool.clone())
+            .create(&BacktestResult {
+                id: None,
+                strategy_id,
+                dataset: "BTCUSDT".to_string(),
+                timeframe: "1h".to_string(),
+                start_date: "2024-01-01".to_string(),
+                end_date: "2024-01-02".to_string(),
+                total_return: 0.1,
+                sharpe_ratio: 1.0,
+                sortino_ratio: None,
+                max_drawdown: 0.05,
+                win_rate: 0.5,
+                profit_factor: None,
+                total_trades: 3,
+                tested_at: None,
+                annualized_return: None,
+                max_drawdown_percent: None,
+                total_profit: None,
+                total_loss: None,
+                max_consecutive_wins: None,
+                max_consecutive_losses: None,
+                trades_per_month: None,
+                trades_per_year: None,
+                stop_loss_exits: None,
+                take_profit_exits: None,
+                signal_exits: None,
+                end_of_data_exits: None,
+                composite_score: None,
+                benchmark_return: None,
+                excess_return: None,
+                alpha: None,
+                beta: None,
+                information_ratio: None,
+                tracking_error: None,
+                up_capture: None,
+                down_capture: None,
+            })
+            .await
+            .unwrap();
+
+        let repo = TradeRepository::new(pool);
+        let trades = [
+            trade(backtest_id, 2, -5.0, Some("StopLoss")),
+            trade(backtest_id, 0, 10.0, Some("Signal")),
+            trade(backtest_id, 1, 4.0, Some("Signal")),
+        ];
+        assert_eq!(repo.create_many(&trades).await.unwrap(), 3);
+
+        let found = repo.find_by_backtest(backtest_id).await.unwrap();
+        let reasons: Vec<Option<&str>> = found.iter().map(|t| t.exit_reason.as_deref()).collect();
+        assert_eq!(reasons, vec![Some("Signal"), Some("Signal"), Some("StopLoss")]);
+        assert_eq!(found[2].position_id, Some(2));
+
+        let summary = repo.exit_reason_summary(backtest_id).await.unwrap();
+        assert_eq!(summary[0], (Some("Signal".to_string()), 2, 14.0));
+        assert_eq!(summary[1], (Some("StopLoss".to_string()), 1, -5.0));
+    }
+}
@@ -0,0 +1,182 @@
+//! Repositorio de análisis walk-forward
+
+use crate::models::{WalkForwardResult, WalkForwardRun};
+use sqlx::{Pool, Sqlite};
+
+pub struct WalkForwardRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl WalkForwardRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    /// Crea una ejecución walk-forward
+    pub async fn create_run(&self, run: &WalkForwardRun) -> Result<i64, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO walk_forward_runs
+            (dataset, timeframe, mode, windows, in_sample_ratio, start_date, end_date,
+             oos_total_return, oos_max_drawdown, efficiency, execution_metadata)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&run.dataset)
+        .bind(&run.timeframe)
+        .bind(&run.mode)
+        .bind(run.windows)
+        .bind(run.in_sample_ratio)
+        .bind(&run.start_date)
+        .bind(&run.end_date)
+        .bind(run.oos_total_return)
+        .bind(run.oos_max_drawdown)
+        .bind(run.efficiency)
+        .bind(&run.execution_metadata)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(row.last_insert_rowid())
+    }
+
+    /// Guarda el resultado de una estrategia en una ventana
+    pub async fn create_result(&self, result: &WalkForwardResult) -> Result<i64, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO walk_forward_results
+            (run_id, strategy_id, strategy_name, window_index, selection_rank,
+             is_start_date, is_end_date, oos_start_date, oos_end_date,
+             is_total_return, is_sharpe_ratio, is_total_trades,
+             oos_total_return, oos_sharpe_ratio, oos_max_drawdown, oos_win_rate, oos_total_trades,
+             efficiency)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(result.run_id)
+        .bind(result.strategy_id)
+        .bind(&result.strategy_name)
+        .bind(result.window_index)
+        .bind(result.selection_rank)
+        .bind(&result.is_start_date)
+        .bind(&result.is_end_date)
+        .bind(&result.oos_start_date)
+        .bind(&result.oos_end_date)
+        .bind(result.is_total_return)
+        .bind(result.is_sharpe_ratio)
+        .bind(result.is_total_trades)
+        .bind(result.oos_total_return)
+        .bind(result.oos_sharpe_ratio)
+        .bind(result.oos_max_drawdown)
+        .bind(result.oos_win_rate)
+        .bind(result.oos_total_trades)
+        .bind(result.efficiency)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(row.last_insert_rowid())
+    }
+
+    /// Busca una ejecución por ID
+    pub async fn find_run(&self, id: i64) -> Result<Option<WalkForwardRun>, sqlx::Error> {
+        sqlx::query_as::<_, WalkForwardRun>("SELECT * FROM walk_forward_runs WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Lista las últimas ejecuciones
+    pub async fn latest_runs(&self, limit: i32) -> Result<Vec<WalkForwardRun>, sqlx::Error> {
+        sqlx::query_as::<_, WalkForwardRun>(
+            "SELECT * FROM walk_forward_runs ORDER BY id DESC LIMIT ?"
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Resultados de una ejecución, por ventana y orden de selección
+    pub async fn find_results_by_run(&self, run_id: i64) -> Result<Vec<WalkForwardResult>, sqlx::Error> {
+        sqlx::query_as::<_, WalkForwardResult>(
+            "SELECT * FROM walk_forward_results WHERE run_id = ? ORDER BY window_index, selection_rank"
+        )
+        .bind(run_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Resultados walk-forward de una estrategia guardada
+    pub async fn find_results_by_strategy(
+        &self,
+        strategy_id: i64,
+    ) -> Result<Vec<WalkForwardResult>, sqlx::Error> {
+        sqlx::query_as::<_, WalkForwardResult>(
+            "SELECT * FROM walk_forward_results WHERE strategy_id = ? ORDER BY run_id DESC, window_index"
+        )
+        .bind(strategy_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_run_and_results() {
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        let repo = WalkForwardRepository::new(pool);
+        let run = WalkForwardRun {
+            id: None,
+            dataset: "BTCUSDT".to_string(),
+            timeframe: "1h".to_string(),
+            mode: "rolling".to_string(),
+            windows: 2,
+            in_sample_ratio: 0.7,
+            start_date: "2024-01-01".to_string(),
+            end_date: "2024-12-31".to_string(),
+            oos_total_return: 0.12,
+            oos_max_drawdown: 0.08,
+            efficiency: Some(0.6),
+            execution_metadata: None,
+            created_at: None,
+        };
+        let run_id = repo.create_run(&run).await.unwrap();
+
+        for (window_index, selection_rank) in [(1, 0), (0, 1), (0, 0)] {
+            let result = WalkForwardResult {
+                id: None,
+                run_id,
+                strategy_id: None,
+                strategy_name: format!("W{}_{}", window_index, selection_rank),
+                window_index,
+                selection_rank,
+                is_start_date: "2024-01-01".to_string(),
+                is_end_date: "2024-06-30".to_string(),
+                oos_start_date: "2024-07-01".to_string(),
+                oos_end_date: "2024-09-30".to_string(),
+                is_total_return: 0.2,
+                is_sharpe_ratio: 1.5,
+                is_total_trades: 20,
+                oos_total_return: 0.05,
+                oos_sharpe_ratio: 0.8,
+                oos_max_drawdown: 0.04,
+                oos_win_rate: 0.55,
+                oos_total_trades: 8,
+                efficiency: None,
+            };
+            repo.create_result(&result).await.unwrap();
+        }
+
+        let found = repo.find_run(run_id).await.unwrap().unwrap();
+        assert_eq!(found.mode, "rolling");
+        assert_eq!(found.efficiency, Some(0.6));
+
+        let results = repo.find_results_by_run(run_id).await.unwrap();
+        let names: Vec<&str> = results.iter().map(|r| r.strategy_name.as_str()).collect();
+        assert_eq!(names, vec!["W0_0", "W0_1", "W1_0"]);
+        assert_eq!(repo.latest_runs(10).await.unwrap().len(), 1);
+    }
+}